@@ -0,0 +1,50 @@
+//! Benchmarks for building the types of large generated modules. These use the unstable
+//! `test` crate, so they only run with `cargo +nightly bench --features nightly,llvmX-Y`.
+
+#![cfg_attr(feature = "nightly", feature(test))]
+
+#[cfg(feature = "nightly")]
+extern crate test;
+
+#[cfg(feature = "nightly")]
+mod benches {
+    use inkwell::context::Context;
+    use inkwell::types::{BasicMetadataTypeEnum, BasicTypeEnum};
+
+    use test::{black_box, Bencher};
+
+    #[bench]
+    fn bench_struct_type(b: &mut Bencher) {
+        let context = Context::create();
+        let fields: Vec<BasicTypeEnum> = vec![context.i64_type().into(), context.f64_type().into(), context.i8_type().into()];
+
+        b.iter(|| black_box(context.struct_type(black_box(&fields), false)));
+    }
+
+    #[bench]
+    fn bench_fn_type(b: &mut Bencher) {
+        let context = Context::create();
+        let i32_type = context.i32_type();
+        let params: Vec<BasicMetadataTypeEnum> = vec![i32_type.into(); 4];
+
+        b.iter(|| black_box(i32_type.fn_type(black_box(&params), false)));
+    }
+
+    #[bench]
+    fn bench_fn_type_many_params(b: &mut Bencher) {
+        let context = Context::create();
+        let i32_type = context.i32_type();
+        let params: Vec<BasicMetadataTypeEnum> = vec![i32_type.into(); 64];
+
+        b.iter(|| black_box(i32_type.fn_type(black_box(&params), false)));
+    }
+
+    #[cfg(any(feature = "llvm12-0", feature = "llvm13-0"))]
+    #[bench]
+    fn bench_get_or_create_named_struct(b: &mut Bencher) {
+        let context = Context::create();
+        let fields: Vec<BasicTypeEnum> = vec![context.i64_type().into(), context.f64_type().into()];
+
+        b.iter(|| black_box(context.get_or_create_named_struct("pair", black_box(&fields), false)));
+    }
+}
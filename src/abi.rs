@@ -0,0 +1,506 @@
+//! Lowers C function signatures to the LLVM function types a C compiler would produce for them.
+//!
+//! LLVM leaves most of a target's C calling convention to the frontend: whether a struct is
+//! passed in registers, split across several of them, copied onto the stack or returned through
+//! a hidden pointer must already be decided by the time the `FunctionType` is created. This
+//! module makes those decisions the way clang does for the most common targets, so that calls
+//! to and from C code built with another compiler agree on where every argument lives.
+//!
+//! The supported targets are x86-64 System V (Linux, macOS, the BSDs), x86-64 Windows and
+//! AArch64 AAPCS. Some rare cases are simplified: vectors inside aggregates and `x86_fp80`
+//! fields are always passed in memory, and scalar arguments are left as they are.
+//!
+//! # Example
+//!
+//! ```
+//! use inkwell::abi::{AbiTarget, ArgAbi};
+//! use inkwell::context::Context;
+//! use inkwell::targets::{TargetData, TargetTriple};
+//!
+//! let context = Context::create();
+//! let target = AbiTarget::from_triple(&TargetTriple::create("x86_64-unknown-linux-gnu")).unwrap();
+//! let target_data = TargetData::create("e-m:e-i64:64-f80:128-n8:16:32:64-S128");
+//! let i64_type = context.i64_type();
+//! let f64_type = context.f64_type();
+//! let pair = context.struct_type(&[i64_type.into(), f64_type.into()], false);
+//!
+//! // struct pair { int64_t a; double b; }; void takes_pair(struct pair p);
+//! let lowered = target.lower_signature(&context, &target_data, None, &[pair.into()]);
+//!
+//! assert_eq!(lowered.get_arg_abis()[0], ArgAbi::Split(vec![i64_type.into(), f64_type.into()]));
+//! assert_eq!(lowered.get_function_type(), context.void_type().fn_type(&[i64_type.into(), f64_type.into()], false));
+//! ```
+
+use llvm_sys::core::{LLVMArrayType, LLVMDoubleTypeInContext, LLVMFloatTypeInContext, LLVMFunctionType, LLVMGetArrayLength, LLVMGetElementType, LLVMGetTypeContext, LLVMGetTypeKind, LLVMIntTypeInContext, LLVMPointerType, LLVMStructTypeInContext, LLVMVectorType, LLVMVoidTypeInContext};
+#[llvm_versions(3.9..=latest)]
+use llvm_sys::core::LLVMCreateEnumAttribute;
+#[llvm_versions(12.0..=latest)]
+use llvm_sys::core::LLVMCreateTypeAttribute;
+use llvm_sys::prelude::{LLVMContextRef, LLVMTypeRef};
+use llvm_sys::LLVMTypeKind;
+
+#[llvm_versions(3.9..=latest)]
+use crate::attributes::{Attribute, AttributeLoc};
+use crate::context::Context;
+use crate::targets::{TargetData, TargetTriple};
+use crate::types::{AsTypeRef, BasicTypeEnum, FunctionType, StructType};
+#[llvm_versions(3.9..=latest)]
+use crate::values::FunctionValue;
+
+/// A C calling convention which signatures can be lowered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbiTarget {
+    /// The System V AMD64 ABI, used by x86-64 targets other than Windows.
+    X86_64SysV,
+    /// The Microsoft x64 calling convention.
+    X86_64Windows,
+    /// The Arm 64-bit procedure call standard.
+    AArch64,
+}
+
+/// How a single argument or return value is passed at the LLVM level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgAbi<'ctx> {
+    /// The value is not passed at all, as happens to empty structs.
+    Ignore,
+    /// The value is passed as a single LLVM value of this type, which is either the original
+    /// type or one of the same size that it should be bitcast to through memory.
+    Direct(BasicTypeEnum<'ctx>),
+    /// The value is passed as several consecutive LLVM params, holding consecutive pieces
+    /// of its memory.
+    Split(Vec<BasicTypeEnum<'ctx>>),
+    /// The value is passed as a pointer to a copy of it. When `byval` is true the copy is made
+    /// on the stack by the call itself and the param should be marked `byval`, otherwise the
+    /// caller must make its own copy. For return values this is a hidden `sret` first param.
+    Indirect {
+        /// Whether or not the param should be marked `byval`.
+        byval: bool,
+    },
+}
+
+/// The result of lowering a C signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoweredSignature<'ctx> {
+    fn_type: FunctionType<'ctx>,
+    return_abi: ArgAbi<'ctx>,
+    arg_abis: Vec<ArgAbi<'ctx>>,
+    // The LLVM param index each argument starts at
+    param_indices: Vec<u32>,
+}
+
+impl<'ctx> LoweredSignature<'ctx> {
+    /// Gets the LLVM function type to declare or define the function with.
+    pub fn get_function_type(&self) -> FunctionType<'ctx> {
+        self.fn_type
+    }
+
+    /// Gets how the return value is passed. `ArgAbi::Indirect` means the function returns
+    /// `void` and takes a pointer to write the result to as its first param.
+    pub fn get_return_abi(&self) -> &ArgAbi<'ctx> {
+        &self.return_abi
+    }
+
+    /// Gets how each of the original arguments is passed.
+    pub fn get_arg_abis(&self) -> &[ArgAbi<'ctx>] {
+        &self.arg_abis
+    }
+
+    /// Gets the index of the first LLVM param holding the original argument `arg`, or `None`
+    /// if it is out of range or ignored.
+    pub fn get_param_index(&self, arg: usize) -> Option<u32> {
+        match self.arg_abis.get(arg)? {
+            ArgAbi::Ignore => None,
+            _ => self.param_indices.get(arg).copied(),
+        }
+    }
+
+    /// Adds the `sret` and `byval` attributes this signature requires to `function`,
+    /// which should have been created with `get_function_type`.
+    #[llvm_versions(3.9..=latest)]
+    pub fn apply_attributes(&self, function: FunctionValue<'ctx>, return_type: Option<BasicTypeEnum<'ctx>>, param_types: &[BasicTypeEnum<'ctx>]) {
+        if let (ArgAbi::Indirect { .. }, Some(return_type)) = (&self.return_abi, return_type) {
+            function.add_attribute(AttributeLoc::Param(0), pointer_attribute("sret", return_type));
+        }
+
+        for (i, (abi, param_type)) in self.arg_abis.iter().zip(param_types).enumerate() {
+            if let ArgAbi::Indirect { byval: true } = abi {
+                function.add_attribute(AttributeLoc::Param(self.param_indices[i]), pointer_attribute("byval", *param_type));
+            }
+        }
+    }
+}
+
+impl AbiTarget {
+    /// Picks the calling convention used by C code on `triple`, if it is supported.
+    pub fn from_triple(triple: &TargetTriple) -> Option<Self> {
+        let triple = triple.as_str().to_string_lossy();
+        let arch = triple.split('-').next().unwrap_or_default();
+
+        match arch {
+            "x86_64" | "amd64" if triple.contains("windows") => Some(AbiTarget::X86_64Windows),
+            "x86_64" | "amd64" => Some(AbiTarget::X86_64SysV),
+            "aarch64" | "arm64" => Some(AbiTarget::AArch64),
+            _ => None,
+        }
+    }
+
+    /// Lowers a C function signature taking `param_types` and returning `return_type`, or `void`
+    /// if it is `None`, with every type belonging to `context`. `target_data` must describe the target this `AbiTarget` was chosen for.
+    pub fn lower_signature<'ctx>(self, context: &'ctx Context, target_data: &TargetData, return_type: Option<BasicTypeEnum<'ctx>>, param_types: &[BasicTypeEnum<'ctx>]) -> LoweredSignature<'ctx> {
+        let mut registers = Registers { int: 6, sse: 8 };
+        let return_abi = match return_type {
+            Some(return_type) => self.lower_return(target_data, return_type),
+            None => ArgAbi::Ignore,
+        };
+
+        if let ArgAbi::Indirect { .. } = return_abi {
+            registers.int -= 1;
+        }
+
+        let arg_abis: Vec<_> = param_types.iter()
+            .map(|param_type| self.lower_arg(target_data, *param_type, &mut registers))
+            .collect();
+
+        let mut params = Vec::new();
+        let mut param_indices = Vec::with_capacity(arg_abis.len());
+
+        if let (ArgAbi::Indirect { .. }, Some(return_type)) = (&return_abi, return_type) {
+            params.push(pointer_to(return_type));
+        }
+
+        for (abi, param_type) in arg_abis.iter().zip(param_types) {
+            param_indices.push(params.len() as u32);
+
+            match abi {
+                ArgAbi::Ignore => {},
+                ArgAbi::Direct(ty) => params.push(ty.as_type_ref()),
+                ArgAbi::Split(types) => params.extend(types.iter().map(|ty| ty.as_type_ref())),
+                ArgAbi::Indirect { .. } => params.push(pointer_to(*param_type)),
+            }
+        }
+
+        let fn_type = unsafe {
+            let return_type = match &return_abi {
+                ArgAbi::Direct(ty) => ty.as_type_ref(),
+                _ => LLVMVoidTypeInContext(context.context),
+            };
+
+            FunctionType::new(LLVMFunctionType(return_type, params.as_mut_ptr(), params.len() as u32, 0))
+        };
+
+        LoweredSignature {
+            fn_type,
+            return_abi,
+            arg_abis,
+            param_indices,
+        }
+    }
+
+    fn lower_arg<'ctx>(self, target_data: &TargetData, ty: BasicTypeEnum<'ctx>, registers: &mut Registers) -> ArgAbi<'ctx> {
+        let size = target_data.get_abi_size(&ty);
+
+        if !is_aggregate(ty) {
+            if let AbiTarget::X86_64SysV = self {
+                registers.take_scalar(ty, size);
+            }
+
+            return ArgAbi::Direct(ty);
+        }
+
+        if size == 0 && self != AbiTarget::X86_64Windows {
+            return ArgAbi::Ignore;
+        }
+
+        match self {
+            AbiTarget::X86_64SysV => {
+                let classes = match classify_sysv(target_data, ty, size) {
+                    Some(classes) => classes,
+                    None => return ArgAbi::Indirect { byval: true },
+                };
+                let needed_int = classes.iter().filter(|class| **class == SysVClass::Integer).count() as u32;
+                let needed_sse = classes.len() as u32 - needed_int;
+
+                if needed_int > registers.int || needed_sse > registers.sse {
+                    return ArgAbi::Indirect { byval: true };
+                }
+
+                registers.int -= needed_int;
+                registers.sse -= needed_sse;
+
+                let mut types = coerce_sysv(target_data, ty, size, &classes);
+
+                if types.len() == 1 {
+                    ArgAbi::Direct(types.remove(0))
+                } else {
+                    ArgAbi::Split(types)
+                }
+            },
+            AbiTarget::X86_64Windows => match size {
+                1 | 2 | 4 | 8 => ArgAbi::Direct(int_type(context_of(ty), size * 8)),
+                _ => ArgAbi::Indirect { byval: false },
+            },
+            AbiTarget::AArch64 => match homogeneous_float_aggregate(target_data, ty) {
+                Some((float_type, count)) => ArgAbi::Direct(array_type(float_type, count)),
+                None => coerce_aarch64(ty, size).unwrap_or(ArgAbi::Indirect { byval: false }),
+            },
+        }
+    }
+
+    fn lower_return<'ctx>(self, target_data: &TargetData, ty: BasicTypeEnum<'ctx>) -> ArgAbi<'ctx> {
+        let size = target_data.get_abi_size(&ty);
+
+        if !is_aggregate(ty) {
+            return ArgAbi::Direct(ty);
+        }
+
+        if size == 0 && self != AbiTarget::X86_64Windows {
+            return ArgAbi::Ignore;
+        }
+
+        match self {
+            AbiTarget::X86_64SysV => match classify_sysv(target_data, ty, size) {
+                Some(classes) => {
+                    let mut types = coerce_sysv(target_data, ty, size, &classes);
+
+                    if types.len() == 1 {
+                        ArgAbi::Direct(types.remove(0))
+                    } else {
+                        let mut fields: Vec<LLVMTypeRef> = types.iter().map(|ty| ty.as_type_ref()).collect();
+
+                        unsafe {
+                            let struct_type = LLVMStructTypeInContext(context_of(ty), fields.as_mut_ptr(), fields.len() as u32, 0);
+
+                            ArgAbi::Direct(BasicTypeEnum::new(struct_type))
+                        }
+                    }
+                },
+                None => ArgAbi::Indirect { byval: false },
+            },
+            AbiTarget::X86_64Windows => match size {
+                1 | 2 | 4 | 8 => ArgAbi::Direct(int_type(context_of(ty), size * 8)),
+                _ => ArgAbi::Indirect { byval: false },
+            },
+            AbiTarget::AArch64 => match homogeneous_float_aggregate(target_data, ty) {
+                Some((float_type, count)) => ArgAbi::Direct(array_type(float_type, count)),
+                None => coerce_aarch64(ty, size).unwrap_or(ArgAbi::Indirect { byval: false }),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Registers {
+    int: u32,
+    sse: u32,
+}
+
+impl Registers {
+    fn take_scalar(&mut self, ty: BasicTypeEnum, size: u64) {
+        let kind = unsafe { LLVMGetTypeKind(ty.as_type_ref()) };
+
+        match kind {
+            LLVMTypeKind::LLVMHalfTypeKind | LLVMTypeKind::LLVMFloatTypeKind | LLVMTypeKind::LLVMDoubleTypeKind |
+            LLVMTypeKind::LLVMFP128TypeKind | LLVMTypeKind::LLVMVectorTypeKind => self.sse = self.sse.saturating_sub(1),
+            LLVMTypeKind::LLVMX86_FP80TypeKind => {},
+            _ => self.int = self.int.saturating_sub(((size + 7) / 8) as u32),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SysVClass {
+    Integer,
+    Sse,
+}
+
+// A scalar found inside an aggregate, along with its offset in bytes.
+#[derive(Debug, Clone, Copy)]
+struct Leaf {
+    offset: u64,
+    size: u64,
+    kind: LLVMTypeKind,
+    ty: LLVMTypeRef,
+}
+
+fn is_aggregate(ty: BasicTypeEnum) -> bool {
+    matches!(ty, BasicTypeEnum::StructType(_) | BasicTypeEnum::ArrayType(_))
+}
+
+fn context_of(ty: BasicTypeEnum) -> LLVMContextRef {
+    unsafe {
+        LLVMGetTypeContext(ty.as_type_ref())
+    }
+}
+
+fn int_type<'ctx>(context: LLVMContextRef, bits: u64) -> BasicTypeEnum<'ctx> {
+    unsafe {
+        BasicTypeEnum::new(LLVMIntTypeInContext(context, bits as u32))
+    }
+}
+
+fn array_type<'ctx>(element_type: LLVMTypeRef, count: u32) -> BasicTypeEnum<'ctx> {
+    unsafe {
+        BasicTypeEnum::new(LLVMArrayType(element_type, count))
+    }
+}
+
+fn pointer_to(ty: BasicTypeEnum) -> LLVMTypeRef {
+    unsafe {
+        LLVMPointerType(ty.as_type_ref(), 0)
+    }
+}
+
+#[llvm_versions(3.9..=11.0)]
+fn pointer_attribute(name: &str, _pointee: BasicTypeEnum) -> Attribute {
+    unsafe {
+        Attribute::new(LLVMCreateEnumAttribute(context_of(_pointee), Attribute::get_named_enum_kind_id(name), 0))
+    }
+}
+
+#[llvm_versions(12.0..=latest)]
+fn pointer_attribute(name: &str, pointee: BasicTypeEnum) -> Attribute {
+    unsafe {
+        Attribute::new(LLVMCreateTypeAttribute(context_of(pointee), Attribute::get_named_enum_kind_id(name), pointee.as_type_ref()))
+    }
+}
+
+// Flattens an aggregate into its scalar leaves, or returns false if it contains
+// something which always needs to be passed in memory.
+fn collect_leaves(target_data: &TargetData, ty: LLVMTypeRef, offset: u64, leaves: &mut Vec<Leaf>) -> bool {
+    let basic_type = unsafe { BasicTypeEnum::new(ty) };
+    let kind = unsafe { LLVMGetTypeKind(ty) };
+
+    match kind {
+        LLVMTypeKind::LLVMStructTypeKind => {
+            let struct_type = unsafe { StructType::new(ty) };
+
+            struct_type.get_field_types().iter().enumerate().all(|(i, field_type)| {
+                let field_offset = target_data.offset_of_element(&struct_type, i as u32).unwrap_or(0);
+
+                collect_leaves(target_data, field_type.as_type_ref(), offset + field_offset, leaves)
+            })
+        },
+        LLVMTypeKind::LLVMArrayTypeKind => {
+            let element_type = unsafe { LLVMGetElementType(ty) };
+            let element_size = target_data.get_abi_size(&unsafe { BasicTypeEnum::new(element_type) });
+            let count = unsafe { LLVMGetArrayLength(ty) } as u64;
+
+            (0..count).all(|i| collect_leaves(target_data, element_type, offset + i * element_size, leaves))
+        },
+        LLVMTypeKind::LLVMVectorTypeKind | LLVMTypeKind::LLVMX86_FP80TypeKind | LLVMTypeKind::LLVMFP128TypeKind |
+        LLVMTypeKind::LLVMPPC_FP128TypeKind => false,
+        _ => {
+            leaves.push(Leaf {
+                offset,
+                size: target_data.get_abi_size(&basic_type),
+                kind,
+                ty,
+            });
+
+            true
+        },
+    }
+}
+
+fn is_float_kind(kind: LLVMTypeKind) -> bool {
+    matches!(kind, LLVMTypeKind::LLVMHalfTypeKind | LLVMTypeKind::LLVMFloatTypeKind | LLVMTypeKind::LLVMDoubleTypeKind)
+}
+
+fn classify_sysv(target_data: &TargetData, ty: BasicTypeEnum, size: u64) -> Option<Vec<SysVClass>> {
+    if size > 16 {
+        return None;
+    }
+
+    let mut leaves = Vec::new();
+
+    if !collect_leaves(target_data, ty.as_type_ref(), 0, &mut leaves) {
+        return None;
+    }
+
+    let num_eightbytes = ((size + 7) / 8) as usize;
+    let mut classes = vec![SysVClass::Sse; num_eightbytes];
+
+    for leaf in &leaves {
+        let eightbyte = (leaf.offset / 8) as usize;
+
+        // Leaves straddling two eightbytes are only possible in packed structs
+        if leaf.size > 0 && (leaf.offset + leaf.size - 1) / 8 != leaf.offset / 8 {
+            return None;
+        }
+
+        if !is_float_kind(leaf.kind) {
+            classes[eightbyte] = SysVClass::Integer;
+        }
+    }
+
+    // Padding only eightbytes are passed as integers
+    for (i, class) in classes.iter_mut().enumerate() {
+        if !leaves.iter().any(|leaf| (leaf.offset / 8) as usize == i) {
+            *class = SysVClass::Integer;
+        }
+    }
+
+    Some(classes)
+}
+
+fn coerce_sysv<'ctx>(target_data: &TargetData, ty: BasicTypeEnum<'ctx>, size: u64, classes: &[SysVClass]) -> Vec<BasicTypeEnum<'ctx>> {
+    let context = context_of(ty);
+    let mut leaves = Vec::new();
+
+    collect_leaves(target_data, ty.as_type_ref(), 0, &mut leaves);
+
+    classes.iter().enumerate().map(|(i, class)| {
+        let start = i as u64 * 8;
+        let bytes = (size - start).min(8);
+
+        match class {
+            SysVClass::Integer => int_type(context, bytes * 8),
+            SysVClass::Sse => {
+                let floats: Vec<_> = leaves.iter().filter(|leaf| leaf.offset / 8 == i as u64).collect();
+
+                unsafe {
+                    match (floats.len(), floats[0].kind) {
+                        (1, LLVMTypeKind::LLVMDoubleTypeKind) => BasicTypeEnum::new(LLVMDoubleTypeInContext(context)),
+                        (1, LLVMTypeKind::LLVMFloatTypeKind) => BasicTypeEnum::new(LLVMFloatTypeInContext(context)),
+                        (1, _) if bytes <= floats[0].size => BasicTypeEnum::new(floats[0].ty),
+                        _ if floats.iter().all(|leaf| leaf.kind == LLVMTypeKind::LLVMFloatTypeKind) => {
+                            BasicTypeEnum::new(LLVMVectorType(LLVMFloatTypeInContext(context), 2))
+                        },
+                        _ => BasicTypeEnum::new(LLVMDoubleTypeInContext(context)),
+                    }
+                }
+            },
+        }
+    }).collect()
+}
+
+// An aggregate of one to four floats of the same type, with nothing else in it.
+fn homogeneous_float_aggregate(target_data: &TargetData, ty: BasicTypeEnum) -> Option<(LLVMTypeRef, u32)> {
+    let size = target_data.get_abi_size(&ty);
+    let mut leaves = Vec::new();
+
+    // Four doubles are the largest possible homogeneous aggregate of supported floats
+    if size > 32 || !collect_leaves(target_data, ty.as_type_ref(), 0, &mut leaves) || leaves.is_empty() || leaves.len() > 4 {
+        return None;
+    }
+
+    let first = leaves[0];
+
+    if !is_float_kind(first.kind) || leaves.iter().any(|leaf| leaf.kind != first.kind) || size != first.size * leaves.len() as u64 {
+        return None;
+    }
+
+    Some((first.ty, leaves.len() as u32))
+}
+
+fn coerce_aarch64<'ctx>(ty: BasicTypeEnum<'ctx>, size: u64) -> Option<ArgAbi<'ctx>> {
+    let context = context_of(ty);
+
+    match size {
+        0..=8 => Some(ArgAbi::Direct(int_type(context, 64))),
+        9..=16 => unsafe {
+            Some(ArgAbi::Direct(array_type(LLVMIntTypeInContext(context, 64), 2)))
+        },
+        _ => None,
+    }
+}
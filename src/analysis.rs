@@ -0,0 +1,670 @@
+//! Lightweight analyses of the control flow of a `FunctionValue`, such as dominance and
+//! natural loops, computed from its `BasicBlock`s and their terminators. The LLVM C API
+//! does not expose LLVM's own analyses.
+//!
+//! These are snapshots: they are not updated when the function is modified afterwards, and
+//! should be recomputed instead.
+
+use llvm_sys::core::{LLVMGetNumSuccessors, LLVMGetSuccessor};
+
+use std::collections::HashMap;
+
+use crate::basic_block::BasicBlock;
+use crate::values::{AsValueRef, FunctionValue, InstructionValue};
+
+/// The control flow graph of a function, whose nodes are its `BasicBlock`s and whose edges
+/// lead from each block to the successors named by its terminator.
+#[derive(Debug)]
+pub struct ControlFlowGraph<'ctx> {
+    blocks: Vec<BasicBlock<'ctx>>,
+    indices: HashMap<BasicBlock<'ctx>, usize>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl<'ctx> ControlFlowGraph<'ctx> {
+    /// Computes the control flow graph of `function`. Blocks without a terminator are
+    /// treated as having no successors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::analysis::ControlFlowGraph;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_module");
+    /// let builder = context.create_builder();
+    /// let function = module.add_function("f", context.void_type().fn_type(&[], false), None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let exit = context.append_basic_block(function, "exit");
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_unconditional_branch(exit);
+    /// builder.position_at_end(exit);
+    /// builder.build_return(None);
+    ///
+    /// let cfg = ControlFlowGraph::new(function);
+    ///
+    /// assert_eq!(cfg.get_successors(entry), vec![exit]);
+    /// assert_eq!(cfg.get_predecessors(exit), vec![entry]);
+    /// ```
+    pub fn new(function: FunctionValue<'ctx>) -> Self {
+        let blocks = function.get_basic_blocks();
+        let indices: HashMap<_, _> = blocks.iter().enumerate().map(|(i, &block)| (block, i)).collect();
+        let mut successors = vec![Vec::new(); blocks.len()];
+        let mut predecessors = vec![Vec::new(); blocks.len()];
+
+        for (i, block) in blocks.iter().enumerate() {
+            let terminator = match block.get_terminator() {
+                Some(terminator) => terminator,
+                None => continue,
+            };
+            let num_successors = unsafe {
+                LLVMGetNumSuccessors(terminator.as_value_ref())
+            };
+
+            for j in 0..num_successors {
+                let successor = unsafe {
+                    BasicBlock::new(LLVMGetSuccessor(terminator.as_value_ref(), j))
+                };
+                let successor = match successor.and_then(|successor| indices.get(&successor)) {
+                    Some(&successor) => successor,
+                    None => continue,
+                };
+
+                // Switches may name the same successor more than once
+                if !successors[i].contains(&successor) {
+                    successors[i].push(successor);
+                    predecessors[successor].push(i);
+                }
+            }
+        }
+
+        ControlFlowGraph {
+            blocks,
+            indices,
+            successors,
+            predecessors,
+        }
+    }
+
+    /// Gets the blocks of the function, in layout order. The first is the entry block.
+    pub fn get_blocks(&self) -> &[BasicBlock<'ctx>] {
+        &self.blocks
+    }
+
+    /// Gets the distinct successors of `block`, in the order its terminator names them.
+    /// This is empty for blocks not in the function.
+    pub fn get_successors(&self, block: BasicBlock<'ctx>) -> Vec<BasicBlock<'ctx>> {
+        self.map_blocks(block, &self.successors)
+    }
+
+    /// Gets the distinct predecessors of `block`, in layout order.
+    /// This is empty for blocks not in the function.
+    pub fn get_predecessors(&self, block: BasicBlock<'ctx>) -> Vec<BasicBlock<'ctx>> {
+        self.map_blocks(block, &self.predecessors)
+    }
+
+    fn map_blocks(&self, block: BasicBlock<'ctx>, edges: &[Vec<usize>]) -> Vec<BasicBlock<'ctx>> {
+        match self.indices.get(&block) {
+            Some(&i) => edges[i].iter().map(|&j| self.blocks[j]).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// The immediate dominators of a graph's nodes, by index, along with a reverse post order
+/// of the nodes reachable from the root.
+#[derive(Debug)]
+struct Dominators {
+    idoms: Vec<Option<usize>>,
+    reverse_post_order: Vec<usize>,
+    rpo_numbers: Vec<usize>,
+}
+
+impl Dominators {
+    // Computes dominators with the iterative algorithm of Cooper, Harvey and Kennedy,
+    // "A Simple, Fast Dominance Algorithm". The root is its own immediate dominator.
+    fn compute(root: usize, successors: &[Vec<usize>], predecessors: &[Vec<usize>]) -> Self {
+        let num_nodes = successors.len();
+        let mut post_order = Vec::with_capacity(num_nodes);
+        let mut visited = vec![false; num_nodes];
+        let mut stack = vec![(root, 0)];
+
+        visited[root] = true;
+
+        while let Some((node, next_edge)) = stack.pop() {
+            if let Some(&successor) = successors[node].get(next_edge) {
+                stack.push((node, next_edge + 1));
+
+                if !visited[successor] {
+                    visited[successor] = true;
+                    stack.push((successor, 0));
+                }
+            } else {
+                post_order.push(node);
+            }
+        }
+
+        let reverse_post_order: Vec<usize> = post_order.into_iter().rev().collect();
+        let mut rpo_numbers = vec![std::usize::MAX; num_nodes];
+
+        for (number, &node) in reverse_post_order.iter().enumerate() {
+            rpo_numbers[node] = number;
+        }
+
+        let mut idoms = vec![None; num_nodes];
+        let mut changed = true;
+
+        idoms[root] = Some(root);
+
+        while changed {
+            changed = false;
+
+            for &node in &reverse_post_order[1..] {
+                let mut new_idom = None;
+
+                for &predecessor in &predecessors[node] {
+                    if idoms[predecessor].is_none() {
+                        continue;
+                    }
+
+                    new_idom = Some(match new_idom {
+                        None => predecessor,
+                        Some(new_idom) => Dominators::intersect(&idoms, &rpo_numbers, predecessor, new_idom),
+                    });
+                }
+
+                if new_idom.is_some() && idoms[node] != new_idom {
+                    idoms[node] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        Dominators {
+            idoms,
+            reverse_post_order,
+            rpo_numbers,
+        }
+    }
+
+    fn intersect(idoms: &[Option<usize>], rpo_numbers: &[usize], mut a: usize, mut b: usize) -> usize {
+        while a != b {
+            while rpo_numbers[a] > rpo_numbers[b] {
+                a = idoms[a].expect("Processed nodes to have an immediate dominator");
+            }
+
+            while rpo_numbers[b] > rpo_numbers[a] {
+                b = idoms[b].expect("Processed nodes to have an immediate dominator");
+            }
+        }
+
+        a
+    }
+
+    fn is_reachable(&self, node: usize) -> bool {
+        self.idoms[node].is_some()
+    }
+
+    fn get_immediate_dominator(&self, node: usize) -> Option<usize> {
+        self.idoms[node].filter(|&idom| idom != node)
+    }
+
+    // As in LLVM, every node dominates an unreachable one, which dominates nothing else
+    fn dominates(&self, a: usize, mut b: usize) -> bool {
+        if !self.is_reachable(b) {
+            return true;
+        }
+
+        if !self.is_reachable(a) {
+            return false;
+        }
+
+        // Ancestors have lower reverse post order numbers, so stop climbing past `a`'s
+        while self.rpo_numbers[b] > self.rpo_numbers[a] {
+            b = self.idoms[b].expect("Reachable nodes to have an immediate dominator");
+        }
+
+        a == b
+    }
+}
+
+/// The dominator tree of a function. A block `a` dominates a block `b` if every path from
+/// the entry block to `b` passes through `a`. Every block dominates itself.
+#[derive(Debug)]
+pub struct DominatorTree<'ctx> {
+    cfg: ControlFlowGraph<'ctx>,
+    dominators: Dominators,
+}
+
+impl<'ctx> DominatorTree<'ctx> {
+    /// Computes the dominator tree of `function`, which must have a body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::analysis::DominatorTree;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_module");
+    /// let builder = context.create_builder();
+    /// let bool_type = context.bool_type();
+    /// let function = module.add_function("f", context.void_type().fn_type(&[bool_type.into()], false), None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let then_block = context.append_basic_block(function, "then");
+    /// let exit = context.append_basic_block(function, "exit");
+    /// let cond = function.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_conditional_branch(cond, then_block, exit);
+    /// builder.position_at_end(then_block);
+    /// builder.build_unconditional_branch(exit);
+    /// builder.position_at_end(exit);
+    /// builder.build_return(None);
+    ///
+    /// let dom_tree = DominatorTree::new(function);
+    ///
+    /// assert!(dom_tree.dominates(entry, exit));
+    /// assert!(!dom_tree.dominates(then_block, exit));
+    /// assert_eq!(dom_tree.get_immediate_dominator(exit), Some(entry));
+    /// ```
+    pub fn new(function: FunctionValue<'ctx>) -> Self {
+        DominatorTree::from_cfg(ControlFlowGraph::new(function))
+    }
+
+    /// Computes the dominator tree from an existing `ControlFlowGraph`.
+    pub fn from_cfg(cfg: ControlFlowGraph<'ctx>) -> Self {
+        assert!(!cfg.blocks.is_empty(), "Cannot compute the dominators of a function without a body.");
+
+        let dominators = Dominators::compute(0, &cfg.successors, &cfg.predecessors);
+
+        DominatorTree {
+            cfg,
+            dominators,
+        }
+    }
+
+    /// Gets the `ControlFlowGraph` the tree was computed from.
+    pub fn get_cfg(&self) -> &ControlFlowGraph<'ctx> {
+        &self.cfg
+    }
+
+    /// Determines whether `block` can be reached from the entry block.
+    pub fn is_reachable(&self, block: BasicBlock<'ctx>) -> bool {
+        self.cfg.indices.get(&block).map_or(false, |&i| self.dominators.is_reachable(i))
+    }
+
+    /// Gets the immediate dominator of `block`, which is its parent in the tree. This is
+    /// `None` for the entry block, and for blocks which are unreachable or not in the function.
+    pub fn get_immediate_dominator(&self, block: BasicBlock<'ctx>) -> Option<BasicBlock<'ctx>> {
+        let i = *self.cfg.indices.get(&block)?;
+
+        self.dominators.get_immediate_dominator(i).map(|idom| self.cfg.blocks[idom])
+    }
+
+    /// Gets the blocks `block` immediately dominates, which are its children in the tree.
+    pub fn get_children(&self, block: BasicBlock<'ctx>) -> Vec<BasicBlock<'ctx>> {
+        let i = match self.cfg.indices.get(&block) {
+            Some(&i) => i,
+            None => return Vec::new(),
+        };
+
+        self.dominators.reverse_post_order.iter()
+            .filter(|&&j| self.dominators.get_immediate_dominator(j) == Some(i))
+            .map(|&j| self.cfg.blocks[j])
+            .collect()
+    }
+
+    /// Determines whether block `a` dominates block `b`. As in LLVM, blocks which are
+    /// unreachable from the entry block are considered to be dominated by every block,
+    /// but dominate no block other than themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either block is not in the function.
+    pub fn dominates(&self, a: BasicBlock<'ctx>, b: BasicBlock<'ctx>) -> bool {
+        self.dominators.dominates(self.cfg.indices[&a], self.cfg.indices[&b])
+    }
+
+    /// Determines whether block `a` dominates block `b` and is not `b` itself.
+    pub fn properly_dominates(&self, a: BasicBlock<'ctx>, b: BasicBlock<'ctx>) -> bool {
+        a != b && self.dominates(a, b)
+    }
+
+    /// Gets the blocks reachable from the entry block in reverse post order, in which
+    /// every block comes after its dominators.
+    pub fn get_reverse_post_order(&self) -> Vec<BasicBlock<'ctx>> {
+        self.dominators.reverse_post_order.iter().map(|&i| self.cfg.blocks[i]).collect()
+    }
+
+    /// Determines whether instruction `a` dominates instruction `b`, meaning that every
+    /// path from the entry block to `b` executes `a` first. An instruction does not
+    /// dominate itself. Code inserted right after `a` can safely use any value available
+    /// at `a` as long as `a` dominates the inserted code's users.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either instruction is not in the function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::analysis::DominatorTree;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_module");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let function = module.add_function("f", i32_type.fn_type(&[i32_type.into()], false), None);
+    /// let param = function.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(context.append_basic_block(function, "entry"));
+    ///
+    /// let sum = builder.build_int_add(param, param, "sum");
+    /// let ret = builder.build_return(Some(&sum));
+    /// let add = sum.as_instruction().unwrap();
+    /// let dom_tree = DominatorTree::new(function);
+    ///
+    /// assert!(dom_tree.dominates_instruction(add, ret));
+    /// assert!(!dom_tree.dominates_instruction(ret, add));
+    /// ```
+    pub fn dominates_instruction(&self, a: InstructionValue<'ctx>, b: InstructionValue<'ctx>) -> bool {
+        let a_block = a.get_parent().expect("Instruction to be in a block");
+        let b_block = b.get_parent().expect("Instruction to be in a block");
+
+        if a_block != b_block {
+            return self.dominates(a_block, b_block);
+        }
+
+        comes_before(a, b)
+    }
+}
+
+// Determines whether `a` comes before `b` in the block they are both in
+fn comes_before(a: InstructionValue, b: InstructionValue) -> bool {
+    let mut instruction = a.get_next_instruction();
+
+    while let Some(next) = instruction {
+        if next == b {
+            return true;
+        }
+
+        instruction = next.get_next_instruction();
+    }
+
+    false
+}
+
+/// The post-dominator tree of a function. A block `a` post-dominates a block `b` if every
+/// path from `b` to an exit of the function, such as a `ret` or `unreachable`, passes
+/// through `a`. Every block post-dominates itself.
+#[derive(Debug)]
+pub struct PostDominatorTree<'ctx> {
+    cfg: ControlFlowGraph<'ctx>,
+    dominators: Dominators,
+}
+
+impl<'ctx> PostDominatorTree<'ctx> {
+    /// Computes the post-dominator tree of `function`.
+    pub fn new(function: FunctionValue<'ctx>) -> Self {
+        PostDominatorTree::from_cfg(ControlFlowGraph::new(function))
+    }
+
+    /// Computes the post-dominator tree from an existing `ControlFlowGraph`.
+    pub fn from_cfg(cfg: ControlFlowGraph<'ctx>) -> Self {
+        // Dominators of the reversed graph, rooted at a virtual node every exit leads to
+        let exit = cfg.blocks.len();
+        let mut successors = cfg.predecessors.clone();
+        let mut predecessors = cfg.successors.clone();
+        let exits: Vec<usize> = (0..exit).filter(|&i| cfg.successors[i].is_empty()).collect();
+
+        for &i in &exits {
+            predecessors[i].push(exit);
+        }
+
+        successors.push(exits);
+        predecessors.push(Vec::new());
+
+        let dominators = Dominators::compute(exit, &successors, &predecessors);
+
+        PostDominatorTree {
+            cfg,
+            dominators,
+        }
+    }
+
+    /// Gets the `ControlFlowGraph` the tree was computed from.
+    pub fn get_cfg(&self) -> &ControlFlowGraph<'ctx> {
+        &self.cfg
+    }
+
+    /// Determines whether an exit of the function can be reached from `block`. This is
+    /// not the case for blocks only leading into infinite loops.
+    pub fn can_reach_exit(&self, block: BasicBlock<'ctx>) -> bool {
+        self.cfg.indices.get(&block).map_or(false, |&i| self.dominators.is_reachable(i))
+    }
+
+    /// Gets the immediate post-dominator of `block`, which is its parent in the tree. This
+    /// is `None` for blocks which are only post-dominated by the function's exits as a
+    /// whole, such as when they are exits themselves or branch to different exits.
+    pub fn get_immediate_post_dominator(&self, block: BasicBlock<'ctx>) -> Option<BasicBlock<'ctx>> {
+        let i = *self.cfg.indices.get(&block)?;
+
+        self.dominators.get_immediate_dominator(i)
+            .filter(|&ipdom| ipdom != self.cfg.blocks.len())
+            .map(|ipdom| self.cfg.blocks[ipdom])
+    }
+
+    /// Determines whether block `a` post-dominates block `b`. Blocks from which no exit
+    /// can be reached are considered to be post-dominated by every block, but post-dominate
+    /// no block other than themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either block is not in the function.
+    pub fn post_dominates(&self, a: BasicBlock<'ctx>, b: BasicBlock<'ctx>) -> bool {
+        self.dominators.dominates(self.cfg.indices[&a], self.cfg.indices[&b])
+    }
+
+    /// Determines whether block `a` post-dominates block `b` and is not `b` itself.
+    pub fn properly_post_dominates(&self, a: BasicBlock<'ctx>, b: BasicBlock<'ctx>) -> bool {
+        a != b && self.post_dominates(a, b)
+    }
+
+    /// Determines whether instruction `a` post-dominates instruction `b`, meaning that
+    /// every path from `b` to an exit of the function executes `a` afterwards. An
+    /// instruction does not post-dominate itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either instruction is not in the function.
+    pub fn post_dominates_instruction(&self, a: InstructionValue<'ctx>, b: InstructionValue<'ctx>) -> bool {
+        let a_block = a.get_parent().expect("Instruction to be in a block");
+        let b_block = b.get_parent().expect("Instruction to be in a block");
+
+        if a_block != b_block {
+            return self.post_dominates(a_block, b_block);
+        }
+
+        comes_before(b, a)
+    }
+}
+
+/// A natural loop, which consists of a header block dominating all of the loop's blocks,
+/// and which is entered through the header only.
+#[derive(Debug)]
+pub struct Loop<'ctx> {
+    header: BasicBlock<'ctx>,
+    blocks: Vec<BasicBlock<'ctx>>,
+    latches: Vec<BasicBlock<'ctx>>,
+    parent: Option<usize>,
+    depth: u32,
+}
+
+impl<'ctx> Loop<'ctx> {
+    /// Gets the header of the loop, which is the target of all of its back edges.
+    pub fn get_header(&self) -> BasicBlock<'ctx> {
+        self.header
+    }
+
+    /// Gets the blocks of the loop, including those of any nested loops, in reverse post
+    /// order. The header comes first.
+    pub fn get_blocks(&self) -> &[BasicBlock<'ctx>] {
+        &self.blocks
+    }
+
+    /// Gets the latches of the loop, which are the blocks with a back edge to the header.
+    pub fn get_latches(&self) -> &[BasicBlock<'ctx>] {
+        &self.latches
+    }
+
+    /// Determines whether `block` is part of this loop or a loop nested in it.
+    pub fn contains(&self, block: BasicBlock<'ctx>) -> bool {
+        self.blocks.contains(&block)
+    }
+
+    /// Gets the nesting depth of the loop, which is 1 for outermost loops.
+    pub fn get_depth(&self) -> u32 {
+        self.depth
+    }
+}
+
+/// The natural loops of a function and how they are nested.
+#[derive(Debug)]
+pub struct LoopInfo<'ctx> {
+    loops: Vec<Loop<'ctx>>,
+    innermost_loops: HashMap<BasicBlock<'ctx>, usize>,
+}
+
+impl<'ctx> LoopInfo<'ctx> {
+    /// Finds the natural loops of the function `dom_tree` was computed for. Loops sharing
+    /// a header are merged into one, as in LLVM.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::analysis::{DominatorTree, LoopInfo};
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_module");
+    /// let builder = context.create_builder();
+    /// let bool_type = context.bool_type();
+    /// let function = module.add_function("f", context.void_type().fn_type(&[bool_type.into()], false), None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let body = context.append_basic_block(function, "body");
+    /// let exit = context.append_basic_block(function, "exit");
+    /// let cond = function.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_unconditional_branch(body);
+    /// builder.position_at_end(body);
+    /// builder.build_conditional_branch(cond, body, exit);
+    /// builder.position_at_end(exit);
+    /// builder.build_return(None);
+    ///
+    /// let loop_info = LoopInfo::new(&DominatorTree::new(function));
+    /// let body_loop = loop_info.get_loop_for(body).unwrap();
+    ///
+    /// assert_eq!(body_loop.get_header(), body);
+    /// assert_eq!(body_loop.get_latches(), &[body]);
+    /// assert!(loop_info.get_loop_for(exit).is_none());
+    /// ```
+    pub fn new(dom_tree: &DominatorTree<'ctx>) -> Self {
+        let cfg = &dom_tree.cfg;
+        let dominators = &dom_tree.dominators;
+        // Loop bodies by header index, as membership flags over all blocks
+        let mut bodies: Vec<(usize, Vec<usize>, Vec<bool>)> = Vec::new();
+
+        for &header in &dominators.reverse_post_order {
+            let latches: Vec<usize> = cfg.predecessors[header].iter()
+                .cloned()
+                .filter(|&predecessor| dominators.is_reachable(predecessor) && dominators.dominates(header, predecessor))
+                .collect();
+
+            if latches.is_empty() {
+                continue;
+            }
+
+            let mut in_loop = vec![false; cfg.blocks.len()];
+            let mut worklist = latches.clone();
+
+            in_loop[header] = true;
+
+            while let Some(block) = worklist.pop() {
+                if in_loop[block] {
+                    continue;
+                }
+
+                in_loop[block] = true;
+                worklist.extend(cfg.predecessors[block].iter().filter(|&&predecessor| dominators.is_reachable(predecessor)));
+            }
+
+            bodies.push((header, latches, in_loop));
+        }
+
+        // Natural loops with distinct headers are either disjoint or nested, so visiting
+        // bigger loops first visits every loop after the loops it is nested in
+        let size = |in_loop: &[bool]| in_loop.iter().filter(|&&member| member).count();
+
+        bodies.sort_by_key(|(_, _, in_loop)| std::cmp::Reverse(size(in_loop)));
+
+        let mut loops: Vec<Loop<'ctx>> = Vec::with_capacity(bodies.len());
+        let mut innermost_loops = HashMap::new();
+
+        for (i, (header, latches, in_loop)) in bodies.iter().enumerate() {
+            let parent = (0..i).rev().find(|&j| bodies[j].2[*header]);
+            let depth = parent.map_or(1, |parent| loops[parent].depth + 1);
+            let blocks = dominators.reverse_post_order.iter()
+                .filter(|&&block| in_loop[block])
+                .map(|&block| cfg.blocks[block])
+                .collect();
+
+            for (block, _) in in_loop.iter().enumerate().filter(|&(_, &member)| member) {
+                innermost_loops.insert(cfg.blocks[block], i);
+            }
+
+            loops.push(Loop {
+                header: cfg.blocks[*header],
+                blocks,
+                latches: latches.iter().map(|&latch| cfg.blocks[latch]).collect(),
+                parent,
+                depth,
+            });
+        }
+
+        LoopInfo {
+            loops,
+            innermost_loops,
+        }
+    }
+
+    /// Gets all loops of the function, with every loop coming after the loops it is nested in.
+    pub fn get_loops(&self) -> &[Loop<'ctx>] {
+        &self.loops
+    }
+
+    /// Gets the innermost loop containing `block`, if any.
+    pub fn get_loop_for(&self, block: BasicBlock<'ctx>) -> Option<&Loop<'ctx>> {
+        self.innermost_loops.get(&block).map(|&i| &self.loops[i])
+    }
+
+    /// Gets the loop `lp` is directly nested in, if any.
+    pub fn get_parent_loop(&self, lp: &Loop<'ctx>) -> Option<&Loop<'ctx>> {
+        lp.parent.map(|parent| &self.loops[parent])
+    }
+
+    /// Gets the number of loops `block` is nested in, which is 0 outside of any loop.
+    pub fn get_loop_depth(&self, block: BasicBlock<'ctx>) -> u32 {
+        self.get_loop_for(block).map_or(0, |lp| lp.depth)
+    }
+
+    /// Determines whether `block` is the header of a loop.
+    pub fn is_loop_header(&self, block: BasicBlock<'ctx>) -> bool {
+        self.get_loop_for(block).map_or(false, |lp| lp.header == block)
+    }
+}
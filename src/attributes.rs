@@ -115,6 +115,38 @@ impl Attribute {
         }
     }
 
+    /// Determines whether or not this `Attribute` is of the kind named `name`. String
+    /// attributes are matched against their key, while enum and type attributes are
+    /// matched against the builtin kind id registered for `name`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::attributes::Attribute;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let kind_id = Attribute::get_named_enum_kind_id("noinline");
+    /// let enum_attribute = context.create_enum_attribute(kind_id, 0);
+    /// let string_attribute = context.create_string_attribute("my_key", "my_val");
+    ///
+    /// assert!(enum_attribute.is_kind_named("noinline"));
+    /// assert!(!enum_attribute.is_kind_named("alwaysinline"));
+    /// assert!(string_attribute.is_kind_named("my_key"));
+    /// ```
+    pub fn is_kind_named(self, name: &str) -> bool {
+        if self.is_string() {
+            return self.get_string_kind_id().to_bytes() == name.as_bytes();
+        }
+
+        // Type attributes are not enum attributes, but still share the enum kind ids
+        let kind_id = unsafe {
+            LLVMGetEnumAttributeKind(self.attribute)
+        };
+
+        kind_id != 0 && kind_id == Attribute::get_named_enum_kind_id(name)
+    }
+
     /// Gets the kind id associated with an enum `Attribute`.
     ///
     /// # Example
@@ -272,4 +304,10 @@ impl AttributeLoc {
             AttributeLoc::Function => u32::max_value(),
         }
     }
+
+    // Every location of a signature with `param_count` params
+    #[llvm_versions(3.9..=latest)]
+    pub(crate) fn all(param_count: u32) -> impl Iterator<Item = AttributeLoc> {
+        vec![AttributeLoc::Function, AttributeLoc::Return].into_iter().chain((0..param_count).map(AttributeLoc::Param))
+    }
 }
@@ -1,6 +1,6 @@
 //! A `BasicBlock` is a container of instructions.
 
-use llvm_sys::core::{LLVMGetBasicBlockParent, LLVMGetBasicBlockTerminator, LLVMGetNextBasicBlock, LLVMIsABasicBlock, LLVMIsConstant, LLVMMoveBasicBlockAfter, LLVMMoveBasicBlockBefore, LLVMPrintTypeToString, LLVMPrintValueToString, LLVMTypeOf, LLVMDeleteBasicBlock, LLVMGetPreviousBasicBlock, LLVMRemoveBasicBlockFromParent, LLVMGetFirstInstruction, LLVMGetLastInstruction, LLVMGetTypeContext, LLVMBasicBlockAsValue, LLVMReplaceAllUsesWith, LLVMGetFirstUse, LLVMBlockAddress};
+use llvm_sys::core::{LLVMGetBasicBlockParent, LLVMGetBasicBlockTerminator, LLVMGetNextBasicBlock, LLVMIsABasicBlock, LLVMIsConstant, LLVMMoveBasicBlockAfter, LLVMMoveBasicBlockBefore, LLVMPrintTypeToString, LLVMPrintValueToString, LLVMTypeOf, LLVMDeleteBasicBlock, LLVMGetPreviousBasicBlock, LLVMRemoveBasicBlockFromParent, LLVMGetFirstInstruction, LLVMGetLastInstruction, LLVMGetNextInstruction, LLVMGetTypeContext, LLVMBasicBlockAsValue, LLVMReplaceAllUsesWith, LLVMGetFirstUse, LLVMBlockAddress};
 #[llvm_versions(3.9..=latest)]
 use llvm_sys::core::LLVMGetBasicBlockName;
 use llvm_sys::prelude::{LLVMValueRef, LLVMBasicBlockRef};
@@ -20,6 +20,7 @@ use std::marker::PhantomData;
 /// instruction. `BasicBlock`s are allowed to be malformed prior to running validation because it may be useful
 /// when constructing or modifying a program.
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct BasicBlock<'ctx> {
     pub(crate) basic_block: LLVMBasicBlockRef,
     _marker: PhantomData<&'ctx ()>,
@@ -280,6 +281,44 @@ impl<'ctx> BasicBlock<'ctx> {
         }
     }
 
+    /// Counts the instructions in this `BasicBlock` by walking them directly, without
+    /// collecting them anywhere.
+    ///
+    /// # Example
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let builder = context.create_builder();
+    /// let module = context.create_module("my_module");
+    /// let void_type = context.void_type();
+    /// let fn_type = void_type.fn_type(&[], false);
+    /// let function = module.add_function("do_nothing", fn_type, None);
+    /// let basic_block = context.append_basic_block(function, "entry");
+    ///
+    /// assert_eq!(basic_block.count_instructions(), 0);
+    ///
+    /// builder.position_at_end(basic_block);
+    /// builder.build_return(None);
+    ///
+    /// assert_eq!(basic_block.count_instructions(), 1);
+    /// ```
+    pub fn count_instructions(self) -> u32 {
+        let mut count = 0;
+        let mut instruction = unsafe {
+            LLVMGetFirstInstruction(self.basic_block)
+        };
+
+        while !instruction.is_null() {
+            count += 1;
+            instruction = unsafe {
+                LLVMGetNextInstruction(instruction)
+            };
+        }
+
+        count
+    }
+
     /// Obtains the terminating `InstructionValue` in this `BasicBlock`, if any. A `BasicBlock` must have a terminating instruction to be valid.
     ///
     /// # Example
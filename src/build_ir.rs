@@ -0,0 +1,132 @@
+//! A small DSL for straight-line IR, see `build_ir!`.
+
+/// Expands a sequence of short IR statements into calls on a `Builder`.
+///
+/// The first argument is the `Builder` (evaluated once), followed by `;` and a list of
+/// statements, each terminated by `;`:
+///
+/// * `let x = op(args...);` builds an instruction and binds it to `x`, also using `"x"` as
+///   the instruction's name. Bindings stay in scope after the macro.
+/// * `op(args...);` builds an instruction without binding it.
+/// * Any other `let x = expr;` or `expr;` is passed through unchanged, so regular builder
+///   calls can be mixed in.
+///
+/// The supported operations are:
+///
+/// | Statement | Builder call |
+/// |-----------|--------------|
+/// | `add`, `sub`, `mul`, `sdiv`, `udiv`, `srem`, `urem` `(a, b)` | `build_int_*` |
+/// | `and`, `or`, `xor`, `shl`, `lshr`, `ashr` `(a, b)` | `build_and`, ..., `build_right_shift` |
+/// | `neg(a)`, `not(a)` | `build_int_neg`, `build_not` |
+/// | `fadd`, `fsub`, `fmul`, `fdiv`, `frem` `(a, b)`, `fneg(a)` | `build_float_*` |
+/// | `icmp(Predicate, a, b)` | `build_int_compare` with an `IntPredicate` |
+/// | `fcmp(Predicate, a, b)` | `build_float_compare` with a `FloatPredicate` |
+/// | `select(cond, a, b)` | `build_select` |
+/// | `phi(type, [(value, block), ...])` | `build_phi` and `add_incoming` |
+/// | `call(function, [args...])` | `build_call` |
+/// | `alloca(type)`, `load(ptr)`, `store(ptr, value)` | `build_alloca`, `build_load`, `build_store` |
+/// | `br(block)`, `cbr(cond, then, else)` | `build_unconditional_branch`, `build_conditional_branch` |
+/// | `ret(value)`, `ret()` | `build_return` |
+/// | `position(block)` | `position_at_end` |
+///
+/// # Example
+///
+/// ```
+/// use inkwell::build_ir;
+/// use inkwell::context::Context;
+///
+/// let context = Context::create();
+/// let module = context.create_module("max");
+/// let builder = context.create_builder();
+/// let i32_type = context.i32_type();
+/// let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+/// let function = module.add_function("max", fn_type, None);
+/// let entry = context.append_basic_block(function, "entry");
+/// let a = function.get_nth_param(0).unwrap().into_int_value();
+/// let b = function.get_nth_param(1).unwrap().into_int_value();
+///
+/// build_ir!(builder;
+///     position(entry);
+///     let is_greater = icmp(SGT, a, b);
+///     let max = select(is_greater, a, b);
+///     ret(max);
+/// );
+///
+/// assert!(function.verify(true));
+/// ```
+#[macro_export]
+macro_rules! build_ir {
+    (@stmts $b:ident;) => {};
+    (@stmts $b:ident; let $name:ident = $op:ident($($args:tt)*); $($rest:tt)*) => {
+        let $name = $crate::build_ir!(@op $b; stringify!($name); $op($($args)*));
+        $crate::build_ir!(@stmts $b; $($rest)*);
+    };
+    (@stmts $b:ident; let $name:ident = $value:expr; $($rest:tt)*) => {
+        let $name = $value;
+        $crate::build_ir!(@stmts $b; $($rest)*);
+    };
+    (@stmts $b:ident; $op:ident($($args:tt)*); $($rest:tt)*) => {
+        $crate::build_ir!(@op $b; ""; $op($($args)*));
+        $crate::build_ir!(@stmts $b; $($rest)*);
+    };
+    (@stmts $b:ident; $value:expr; $($rest:tt)*) => {
+        $value;
+        $crate::build_ir!(@stmts $b; $($rest)*);
+    };
+
+    (@op $b:ident; $n:expr; add($lhs:expr, $rhs:expr)) => { $b.build_int_add($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; sub($lhs:expr, $rhs:expr)) => { $b.build_int_sub($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; mul($lhs:expr, $rhs:expr)) => { $b.build_int_mul($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; sdiv($lhs:expr, $rhs:expr)) => { $b.build_int_signed_div($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; udiv($lhs:expr, $rhs:expr)) => { $b.build_int_unsigned_div($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; srem($lhs:expr, $rhs:expr)) => { $b.build_int_signed_rem($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; urem($lhs:expr, $rhs:expr)) => { $b.build_int_unsigned_rem($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; and($lhs:expr, $rhs:expr)) => { $b.build_and($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; or($lhs:expr, $rhs:expr)) => { $b.build_or($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; xor($lhs:expr, $rhs:expr)) => { $b.build_xor($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; shl($lhs:expr, $rhs:expr)) => { $b.build_left_shift($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; lshr($lhs:expr, $rhs:expr)) => { $b.build_right_shift($lhs, $rhs, false, $n) };
+    (@op $b:ident; $n:expr; ashr($lhs:expr, $rhs:expr)) => { $b.build_right_shift($lhs, $rhs, true, $n) };
+    (@op $b:ident; $n:expr; neg($value:expr)) => { $b.build_int_neg($value, $n) };
+    (@op $b:ident; $n:expr; not($value:expr)) => { $b.build_not($value, $n) };
+    (@op $b:ident; $n:expr; fadd($lhs:expr, $rhs:expr)) => { $b.build_float_add($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; fsub($lhs:expr, $rhs:expr)) => { $b.build_float_sub($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; fmul($lhs:expr, $rhs:expr)) => { $b.build_float_mul($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; fdiv($lhs:expr, $rhs:expr)) => { $b.build_float_div($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; frem($lhs:expr, $rhs:expr)) => { $b.build_float_rem($lhs, $rhs, $n) };
+    (@op $b:ident; $n:expr; fneg($value:expr)) => { $b.build_float_neg($value, $n) };
+    (@op $b:ident; $n:expr; icmp($pred:ident, $lhs:expr, $rhs:expr)) => {
+        $b.build_int_compare($crate::IntPredicate::$pred, $lhs, $rhs, $n)
+    };
+    (@op $b:ident; $n:expr; fcmp($pred:ident, $lhs:expr, $rhs:expr)) => {
+        $b.build_float_compare($crate::FloatPredicate::$pred, $lhs, $rhs, $n)
+    };
+    (@op $b:ident; $n:expr; select($cond:expr, $then:expr, $else_:expr)) => { $b.build_select($cond, $then, $else_, $n) };
+    (@op $b:ident; $n:expr; phi($ty:expr, [$(($value:expr, $block:expr)),* $(,)?])) => {{
+        let phi = $b.build_phi($ty, $n);
+
+        phi.add_incoming(&[$((&$value, $block)),*]);
+
+        phi
+    }};
+    (@op $b:ident; $n:expr; call($function:expr, [$($arg:expr),* $(,)?])) => {
+        $b.build_call($function, &[$($arg.into()),*], $n)
+    };
+    (@op $b:ident; $n:expr; alloca($ty:expr)) => { $b.build_alloca($ty, $n) };
+    (@op $b:ident; $n:expr; load($ptr:expr)) => { $b.build_load($ptr, $n) };
+    (@op $b:ident; $n:expr; store($ptr:expr, $value:expr)) => { $b.build_store($ptr, $value) };
+    (@op $b:ident; $n:expr; br($block:expr)) => { $b.build_unconditional_branch($block) };
+    (@op $b:ident; $n:expr; cbr($cond:expr, $then:expr, $else_:expr)) => {
+        $b.build_conditional_branch($cond, $then, $else_)
+    };
+    (@op $b:ident; $n:expr; ret()) => { $b.build_return(None) };
+    (@op $b:ident; $n:expr; ret($value:expr)) => { $b.build_return(Some(&$value)) };
+    (@op $b:ident; $n:expr; position($block:expr)) => { $b.position_at_end($block) };
+    // Not one of ours, so it is a regular function call
+    (@op $b:ident; $n:expr; $($call:tt)*) => { $($call)* };
+
+    ($builder:expr; $($body:tt)*) => {
+        let __ir_builder = &$builder;
+        $crate::build_ir!(@stmts __ir_builder; $($body)*);
+    };
+}
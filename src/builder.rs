@@ -1,23 +1,37 @@
 //! A `Builder` enables you to build instructions.
 
-use llvm_sys::core::{LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildAnd, LLVMBuildArrayAlloca, LLVMBuildArrayMalloc, LLVMBuildAtomicRMW, LLVMBuildBr, LLVMBuildCall, LLVMBuildCast, LLVMBuildCondBr, LLVMBuildExtractValue, LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv, LLVMBuildFence, LLVMBuildFMul, LLVMBuildFNeg, LLVMBuildFree, LLVMBuildFSub, LLVMBuildGEP, LLVMBuildICmp, LLVMBuildInsertValue, LLVMBuildIsNotNull, LLVMBuildIsNull, LLVMBuildLoad, LLVMBuildMalloc, LLVMBuildMul, LLVMBuildNeg, LLVMBuildNot, LLVMBuildOr, LLVMBuildPhi, LLVMBuildPointerCast, LLVMBuildRet, LLVMBuildRetVoid, LLVMBuildStore, LLVMBuildSub, LLVMBuildUDiv, LLVMBuildUnreachable, LLVMBuildXor, LLVMDisposeBuilder, LLVMGetInsertBlock, LLVMInsertIntoBuilder, LLVMPositionBuilderAtEnd, LLVMBuildExtractElement, LLVMBuildInsertElement, LLVMBuildIntToPtr, LLVMBuildPtrToInt, LLVMInsertIntoBuilderWithName, LLVMClearInsertionPosition, LLVMPositionBuilder, LLVMPositionBuilderBefore, LLVMBuildAggregateRet, LLVMBuildStructGEP, LLVMBuildInBoundsGEP, LLVMBuildPtrDiff, LLVMBuildNSWAdd, LLVMBuildNUWAdd, LLVMBuildNSWSub, LLVMBuildNUWSub, LLVMBuildNSWMul, LLVMBuildNUWMul, LLVMBuildSDiv, LLVMBuildSRem, LLVMBuildURem, LLVMBuildFRem, LLVMBuildNSWNeg, LLVMBuildNUWNeg, LLVMBuildFPToUI, LLVMBuildFPToSI, LLVMBuildSIToFP, LLVMBuildUIToFP, LLVMBuildFPTrunc, LLVMBuildFPExt, LLVMBuildIntCast, LLVMBuildFPCast, LLVMBuildSExtOrBitCast, LLVMBuildZExtOrBitCast, LLVMBuildTruncOrBitCast, LLVMBuildSwitch, LLVMAddCase, LLVMBuildShl, LLVMBuildAShr, LLVMBuildLShr, LLVMBuildGlobalString, LLVMBuildGlobalStringPtr, LLVMBuildExactSDiv, LLVMBuildTrunc, LLVMBuildSExt, LLVMBuildZExt, LLVMBuildSelect, LLVMBuildAddrSpaceCast, LLVMBuildBitCast, LLVMBuildShuffleVector, LLVMBuildVAArg, LLVMBuildIndirectBr, LLVMAddDestination, LLVMBuildInvoke, LLVMBuildResume, LLVMBuildLandingPad, LLVMSetCleanup, LLVMAddClause};
+use llvm_sys::core::{LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildAnd, LLVMBuildArrayAlloca, LLVMBuildArrayMalloc, LLVMBuildAtomicRMW, LLVMBuildBr, LLVMBuildCall, LLVMBuildCast, LLVMBuildCondBr, LLVMBuildExtractValue, LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv, LLVMBuildFence, LLVMBuildFMul, LLVMBuildFNeg, LLVMBuildFree, LLVMBuildFSub, LLVMBuildGEP, LLVMBuildICmp, LLVMBuildInsertValue, LLVMBuildIsNotNull, LLVMBuildIsNull, LLVMBuildLoad, LLVMBuildMalloc, LLVMBuildMul, LLVMBuildNeg, LLVMBuildNot, LLVMBuildOr, LLVMBuildPhi, LLVMBuildPointerCast, LLVMBuildRet, LLVMBuildRetVoid, LLVMBuildStore, LLVMBuildSub, LLVMBuildUDiv, LLVMBuildUnreachable, LLVMBuildXor, LLVMDisposeBuilder, LLVMGetInsertBlock, LLVMInsertIntoBuilder, LLVMPositionBuilderAtEnd, LLVMBuildExtractElement, LLVMBuildInsertElement, LLVMBuildIntToPtr, LLVMBuildPtrToInt, LLVMInsertIntoBuilderWithName, LLVMClearInsertionPosition, LLVMPositionBuilder, LLVMPositionBuilderBefore, LLVMBuildAggregateRet, LLVMBuildStructGEP, LLVMBuildInBoundsGEP, LLVMBuildPtrDiff, LLVMBuildNSWAdd, LLVMBuildNUWAdd, LLVMBuildNSWSub, LLVMBuildNUWSub, LLVMBuildNSWMul, LLVMBuildNUWMul, LLVMBuildSDiv, LLVMBuildSRem, LLVMBuildURem, LLVMBuildFRem, LLVMBuildNSWNeg, LLVMBuildNUWNeg, LLVMBuildFPToUI, LLVMBuildFPToSI, LLVMBuildSIToFP, LLVMBuildUIToFP, LLVMBuildFPTrunc, LLVMBuildFPExt, LLVMBuildIntCast, LLVMBuildFPCast, LLVMBuildSExtOrBitCast, LLVMBuildZExtOrBitCast, LLVMBuildTruncOrBitCast, LLVMBuildSwitch, LLVMAddCase, LLVMBuildShl, LLVMBuildAShr, LLVMBuildLShr, LLVMBuildGlobalString, LLVMBuildGlobalStringPtr, LLVMBuildExactSDiv, LLVMBuildTrunc, LLVMBuildSExt, LLVMBuildZExt, LLVMBuildSelect, LLVMBuildAddrSpaceCast, LLVMBuildBitCast, LLVMBuildShuffleVector, LLVMBuildVAArg, LLVMBuildIndirectBr, LLVMAddDestination, LLVMBuildInvoke, LLVMBuildResume, LLVMBuildLandingPad, LLVMSetCleanup, LLVMAddClause, LLVMGetGlobalParent, LLVMGetNamedFunction, LLVMAddFunction, LLVMGetTypeContext, LLVMGetTypeKind, LLVMDoubleTypeInContext, LLVMInt8TypeInContext, LLVMPointerType, LLVMVoidTypeInContext, LLVMFunctionType, LLVMTypeOf, LLVMInt32TypeInContext, LLVMConstInt, LLVMMDStringInContext, LLVMMDNodeInContext, LLVMGetMDKindIDInContext, LLVMInt1TypeInContext, LLVMStructTypeInContext, LLVMAppendBasicBlockInContext, LLVMInt64TypeInContext, LLVMTypeIsSized, LLVMGetIntTypeWidth, LLVMGetVectorSize, LLVMGetElementType, LLVMGetPointerAddressSpace};
+#[llvm_versions(8.0..=latest)]
+use llvm_sys::core::{LLVMBuildCall2, LLVMSetOperand};
 #[llvm_versions(3.9..=latest)]
 use llvm_sys::core::LLVMBuildAtomicCmpXchg;
 #[llvm_versions(8.0..=latest)]
 use llvm_sys::core::{LLVMBuildMemCpy, LLVMBuildMemMove, LLVMBuildMemSet};
-use llvm_sys::prelude::{LLVMBuilderRef, LLVMValueRef};
+#[llvm_versions(5.0..=latest)]
+use llvm_sys::core::{LLVMGetUndef, LLVMVectorType};
+use llvm_sys::prelude::{LLVMBuilderRef, LLVMTypeRef, LLVMValueRef};
+use llvm_sys::LLVMTypeKind;
 
-use crate::{AtomicOrdering, AtomicRMWBinOp, IntPredicate, FloatPredicate};
+use crate::{AtomicOrdering, AtomicRMWBinOp, CacheType, IntPredicate, FloatPredicate, OverflowArithmetic, PrefetchAccess};
 use crate::basic_block::BasicBlock;
+use crate::module::Module;
 use crate::support::to_c_str;
-use crate::values::{AggregateValue, AggregateValueEnum, AsValueRef, FunctionValue, BasicValue, BasicValueEnum, PhiValue, IntValue, PointerValue, VectorValue, InstructionValue, GlobalValue, IntMathValue, FloatMathValue, PointerMathValue, InstructionOpcode, CallSiteValue, BasicMetadataValueEnum};
+use crate::targets::TargetData;
+use crate::values::{AggregateValue, AggregateValueEnum, AsValueRef, FunctionValue, BasicValue, BasicValueEnum, PhiValue, IntValue, PointerValue, VectorValue, InstructionValue, GlobalValue, IntMathValue, FloatMathValue, PointerMathValue, InstructionOpcode, CallSiteValue, BasicMetadataValueEnum, MetadataValue, BranchValue, ReturnValue, UnreachableValue};
 #[llvm_versions(7.0..=latest)]
 use crate::debug_info::DILocation;
 #[llvm_versions(3.9..=latest)]
 use crate::values::StructValue;
+#[llvm_versions(9.0..=latest)]
+use crate::IntReduction;
+#[llvm_versions(9.0..=latest)]
+use crate::values::FloatValue;
 use crate::values::CallableValue;
-use crate::types::{AsTypeRef, BasicType, IntMathType, FloatMathType, PointerType, PointerMathType};
+use crate::types::{AsTypeRef, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FloatType, FunctionType, IntType, IntMathType, FloatMathType, PointerType, PointerMathType, VoidType};
+#[llvm_versions(5.0..=latest)]
+use crate::types::{AnyType, AnyTypeEnum, VectorType};
 
+use std::convert::TryFrom;
 use std::marker::PhantomData;
 
 #[derive(Debug)]
@@ -155,15 +169,142 @@ impl<'ctx> Builder<'ctx> {
         let mut args: Vec<LLVMValueRef> = args.iter()
                                               .map(|val| val.as_value_ref())
                                               .collect();
+        #[cfg(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                  feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0"))]
         let value = unsafe {
             LLVMBuildCall(self.builder, fn_val_ref, args.as_mut_ptr(), args.len() as u32, c_string.as_ptr())
         };
+        #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                      feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0")))]
+        let value = unsafe {
+            let fn_type = callable_value.get_function_type();
+
+            LLVMBuildCall2(self.builder, fn_type.as_type_ref(), fn_val_ref, args.as_mut_ptr(), args.len() as u32, c_string.as_ptr())
+        };
 
         unsafe {
             CallSiteValue::new(value)
         }
     }
 
+    /// Builds a call through a function pointer, using `function_type` as the type of the
+    /// callee rather than relying on the type of `function_pointer`. The pointer is cast to
+    /// the expected type when needed, so pointers of any type, such as a `void*` loaded from a
+    /// vtable, can be called without first casting them by hand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("indirect");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let callback_type = i32_type.fn_type(&[i32_type.into()], false);
+    /// let opaque_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    /// let fn_type = i32_type.fn_type(&[opaque_ptr_type.into()], false);
+    /// let fn_value = module.add_function("apply", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let callback = fn_value.get_first_param().unwrap().into_pointer_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let ret_val = builder.build_indirect_call(callback_type, callback, &[i32_type.const_int(42, false).into()], "call")
+    ///     .try_as_basic_value()
+    ///     .left()
+    ///     .unwrap();
+    ///
+    /// builder.build_return(Some(&ret_val));
+    /// ```
+    pub fn build_indirect_call(
+        &self,
+        function_type: FunctionType<'ctx>,
+        function_pointer: PointerValue<'ctx>,
+        args: &[BasicMetadataValueEnum<'ctx>],
+        name: &str,
+    ) -> CallSiteValue<'ctx> {
+        let callee_type = function_type.ptr_type(function_pointer.get_type().get_address_space());
+        let callee = if function_pointer.get_type() == callee_type {
+            function_pointer
+        } else {
+            self.build_pointer_cast(function_pointer, callee_type, "")
+        };
+        let callable_value = CallableValue::try_from(callee)
+            .expect("Pointer cast to a function type should point to a function");
+
+        self.build_call(callable_value, args, name)
+    }
+
+    /// Builds a call to a variadic function, such as `printf`. Arguments past the callee's fixed
+    /// parameters go through C's default argument promotions where these don't depend on
+    /// signedness, which means `half` and `float` values are extended to `double`. Integers
+    /// narrower than `int` should be sign or zero extended by the caller.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("printf");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let str_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    /// let printf_type = i32_type.fn_type(&[str_type.into()], true);
+    /// let printf = module.add_function("printf", printf_type, None);
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let fn_value = module.add_function("print_float", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let format = builder.build_global_string_ptr("%f\n", "format");
+    /// let value = context.f32_type().const_float(1.5);
+    ///
+    /// // The f32 is promoted to an f64, as printf expects
+    /// builder.build_vararg_call(printf, &[format.as_pointer_value().into(), value.into()], "printf").unwrap();
+    /// builder.build_return(None);
+    /// ```
+    pub fn build_vararg_call<F>(&self, function: F, args: &[BasicMetadataValueEnum<'ctx>], name: &str) -> Result<CallSiteValue<'ctx>, &'static str>
+    where
+        F: Into<CallableValue<'ctx>>,
+    {
+        let callable_value = function.into();
+        let fn_type = callable_value.get_function_type();
+
+        if !fn_type.is_var_arg() {
+            return Err("Function is not variadic.");
+        }
+
+        let num_fixed = fn_type.count_param_types() as usize;
+
+        if args.len() < num_fixed {
+            return Err("Not enough arguments for the function's fixed parameters.");
+        }
+
+        let f64_type = unsafe {
+            FloatType::new(LLVMDoubleTypeInContext(LLVMGetTypeContext(fn_type.as_type_ref())))
+        };
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = args.iter().enumerate().map(|(i, &arg)| match arg {
+            BasicMetadataValueEnum::FloatValue(float) if i >= num_fixed => {
+                let type_kind = unsafe {
+                    LLVMGetTypeKind(float.get_type().as_type_ref())
+                };
+
+                match type_kind {
+                    LLVMTypeKind::LLVMHalfTypeKind | LLVMTypeKind::LLVMFloatTypeKind => self.build_float_ext(float, f64_type, "").into(),
+                    _ => arg,
+                }
+            },
+            arg => arg,
+        }).collect();
+
+        Ok(self.build_call(callable_value, &args, name))
+    }
+
     /// An invoke is similar to a normal function call, but used to
     /// call functions that may throw an exception, and then respond to the exception.
     ///
@@ -887,6 +1028,62 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
+    /// Builds a volatile [memcpy](http://llvm.org/docs/LangRef.html#llvm-memcpy-intrinsic),
+    /// which LLVM will not remove, merge or reorder with other volatile operations. The
+    /// arguments are the same as for `build_memcpy`.
+    #[llvm_versions(8.0..=latest)]
+    pub fn build_volatile_memcpy(
+        &self,
+        dest: PointerValue<'ctx>,
+        dest_align_bytes: u32,
+        src: PointerValue<'ctx>,
+        src_align_bytes: u32,
+        size: IntValue<'ctx>,
+    ) -> Result<PointerValue<'ctx>, &'static str> {
+        let call = self.build_memcpy(dest, dest_align_bytes, src, src_align_bytes, size)?;
+
+        set_mem_intrinsic_volatile(call.as_value_ref());
+
+        Ok(call)
+    }
+
+    /// Builds a volatile [memmove](http://llvm.org/docs/LangRef.html#llvm-memmove-intrinsic),
+    /// which LLVM will not remove, merge or reorder with other volatile operations. The
+    /// arguments are the same as for `build_memmove`.
+    #[llvm_versions(8.0..=latest)]
+    pub fn build_volatile_memmove(
+        &self,
+        dest: PointerValue<'ctx>,
+        dest_align_bytes: u32,
+        src: PointerValue<'ctx>,
+        src_align_bytes: u32,
+        size: IntValue<'ctx>,
+    ) -> Result<PointerValue<'ctx>, &'static str> {
+        let call = self.build_memmove(dest, dest_align_bytes, src, src_align_bytes, size)?;
+
+        set_mem_intrinsic_volatile(call.as_value_ref());
+
+        Ok(call)
+    }
+
+    /// Builds a volatile [memset](http://llvm.org/docs/LangRef.html#llvm-memset-intrinsics),
+    /// which LLVM will not remove, merge or reorder with other volatile operations. The
+    /// arguments are the same as for `build_memset`.
+    #[llvm_versions(8.0..=latest)]
+    pub fn build_volatile_memset(
+        &self,
+        dest: PointerValue<'ctx>,
+        dest_align_bytes: u32,
+        val: IntValue<'ctx>,
+        size: IntValue<'ctx>,
+    ) -> Result<PointerValue<'ctx>, &'static str> {
+        let call = self.build_memset(dest, dest_align_bytes, val, size)?;
+
+        set_mem_intrinsic_volatile(call.as_value_ref());
+
+        Ok(call)
+    }
+
     // TODOC: Heap allocation
     pub fn build_malloc<T: BasicType<'ctx>>(&self, ty: T, name: &str) -> Result<PointerValue<'ctx>, &'static str> {
         // LLVMBulidMalloc segfaults if ty is unsized
@@ -1646,6 +1843,77 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
+    /// Builds a conditional branch with `branch_weights` profiling metadata attached, which
+    /// tells the optimizer how often each destination is expected to be taken relative to
+    /// the other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("weights");
+    /// let builder = context.create_builder();
+    /// let fn_type = context.void_type().fn_type(&[context.bool_type().into()], false);
+    /// let fn_value = module.add_function("weights", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let hot = context.append_basic_block(fn_value, "hot");
+    /// let cold = context.append_basic_block(fn_value, "cold");
+    /// let condition = fn_value.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_conditional_branch_with_weights(condition, hot, cold, 99, 1);
+    /// ```
+    pub fn build_conditional_branch_with_weights(
+        &self,
+        comparison: IntValue<'ctx>,
+        then_block: BasicBlock<'ctx>,
+        else_block: BasicBlock<'ctx>,
+        then_weight: u32,
+        else_weight: u32,
+    ) -> InstructionValue<'ctx> {
+        let branch = self.build_conditional_branch(comparison, then_block, else_block);
+        let kind = "branch_weights";
+        let prof = "prof";
+
+        unsafe {
+            let context = LLVMGetTypeContext(comparison.get_type().as_type_ref());
+            let i32_type = LLVMInt32TypeInContext(context);
+            let mut operands = [
+                LLVMMDStringInContext(context, kind.as_ptr() as *const ::libc::c_char, kind.len() as u32),
+                LLVMConstInt(i32_type, then_weight as u64, 0),
+                LLVMConstInt(i32_type, else_weight as u64, 0),
+            ];
+            let weights = MetadataValue::new(LLVMMDNodeInContext(context, operands.as_mut_ptr(), operands.len() as u32));
+            let kind_id = LLVMGetMDKindIDInContext(context, prof.as_ptr() as *const ::libc::c_char, prof.len() as u32);
+
+            branch.set_metadata(weights, kind_id).expect("branch_weights should be a metadata node");
+        }
+
+        branch
+    }
+
+    /// Builds a conditional branch which is hinted to usually go to `then_block` when
+    /// `then_is_likely` is true, or to `else_block` otherwise. This uses the same weights
+    /// as Clang does for `__builtin_expect`.
+    pub fn build_conditional_branch_with_likelihood(
+        &self,
+        comparison: IntValue<'ctx>,
+        then_block: BasicBlock<'ctx>,
+        else_block: BasicBlock<'ctx>,
+        then_is_likely: bool,
+    ) -> InstructionValue<'ctx> {
+        const LIKELY_WEIGHT: u32 = 2000;
+        const UNLIKELY_WEIGHT: u32 = 1;
+
+        if then_is_likely {
+            self.build_conditional_branch_with_weights(comparison, then_block, else_block, LIKELY_WEIGHT, UNLIKELY_WEIGHT)
+        } else {
+            self.build_conditional_branch_with_weights(comparison, then_block, else_block, UNLIKELY_WEIGHT, LIKELY_WEIGHT)
+        }
+    }
+
     pub fn build_indirect_branch<BV: BasicValue<'ctx>>(
         &self,
         address: BV,
@@ -1864,6 +2132,112 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
+    /// Builds a chain of extract value instructions which extracts a `BasicValueEnum`
+    /// from a nested struct or array, where each index in `indices` selects a member of the
+    /// previous level. Returns `None`, without building anything, if the path is empty or
+    /// does not exist in the aggregate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("av");
+    /// let i32_type = context.i32_type();
+    /// let pair_type = context.struct_type(&[i32_type.into(), i32_type.into()], false);
+    /// let outer_type = context.struct_type(&[i32_type.into(), pair_type.into()], false);
+    /// let fn_type = i32_type.fn_type(&[outer_type.into()], false);
+    /// let fn_value = module.add_function("av_fn", fn_type, None);
+    /// let builder = context.create_builder();
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let outer = fn_value.get_first_param().unwrap().into_struct_value();
+    /// let inner = builder.build_extract_value_path(outer, &[1, 0], "inner").unwrap();
+    ///
+    /// assert!(builder.build_extract_value_path(outer, &[0, 0], "invalid").is_none());
+    ///
+    /// builder.build_return(Some(&inner));
+    /// ```
+    pub fn build_extract_value_path<AV: AggregateValue<'ctx>>(
+        &self,
+        agg: AV,
+        indices: &[u32],
+        name: &str,
+    ) -> Option<BasicValueEnum<'ctx>> {
+        let (&last, path) = indices.split_last()?;
+
+        if !is_valid_aggregate_path(agg.as_value_ref(), indices) {
+            return None;
+        }
+
+        let mut agg = agg.as_aggregate_value_enum();
+
+        for &index in path {
+            agg = match self.build_extract_value(agg, index, "")? {
+                BasicValueEnum::ArrayValue(av) => av.into(),
+                BasicValueEnum::StructValue(sv) => sv.into(),
+                _ => return None,
+            };
+        }
+
+        self.build_extract_value(agg, last, name)
+    }
+
+    /// Builds the instructions needed to insert a `BasicValue` into a nested struct or array,
+    /// where each index in `indices` selects a member of the previous level, and returns the
+    /// resulting outermost aggregate value. Returns `None`, without building anything, if the
+    /// path is empty or does not exist in the aggregate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("av");
+    /// let i32_type = context.i32_type();
+    /// let pair_type = context.struct_type(&[i32_type.into(), i32_type.into()], false);
+    /// let outer_type = context.struct_type(&[i32_type.into(), pair_type.into()], false);
+    /// let fn_type = outer_type.fn_type(&[], false);
+    /// let fn_value = module.add_function("av_fn", fn_type, None);
+    /// let builder = context.create_builder();
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let value = i32_type.const_int(42, false);
+    /// let outer = builder.build_insert_value_path(outer_type.get_undef(), value, &[1, 0], "outer").unwrap();
+    ///
+    /// builder.build_return(Some(&outer.into_struct_value()));
+    /// ```
+    pub fn build_insert_value_path<AV, BV>(&self, agg: AV, value: BV, indices: &[u32], name: &str) -> Option<AggregateValueEnum<'ctx>>
+    where
+        AV: AggregateValue<'ctx>,
+        BV: BasicValue<'ctx>,
+    {
+        let (&first, rest) = indices.split_first()?;
+
+        if !is_valid_aggregate_path(agg.as_value_ref(), indices) {
+            return None;
+        }
+
+        if rest.is_empty() {
+            return self.build_insert_value(agg, value, first, name);
+        }
+
+        let inner = match self.build_extract_value(agg.as_aggregate_value_enum(), first, "")? {
+            BasicValueEnum::ArrayValue(av) => AggregateValueEnum::from(av),
+            BasicValueEnum::StructValue(sv) => AggregateValueEnum::from(sv),
+            _ => return None,
+        };
+        let inner = self.build_insert_value_path(inner, value, rest, "")?;
+
+        self.build_insert_value(agg, inner, first, name)
+    }
+
     /// Builds an extract element instruction which extracts a `BasicValueEnum`
     /// from a vector.
     /// # Example
@@ -1943,6 +2317,89 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
+    /// Packs a `<N x i1>` `mask` into the low `N` bits of an integer of `int_type`, with
+    /// element `i` of the mask becoming bit `i` of the result. Unlike a `bitcast` of the
+    /// mask, whose bit order depends on the target's endianness, this is the same on every
+    /// target. The mask's elements are combined with a sequence of `extractelement` and `or`
+    /// instructions, which is left to the optimizer and backend to simplify.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::IntPredicate;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("mask");
+    /// let builder = context.create_builder();
+    /// let i8_type = context.i8_type();
+    /// let vec_type = context.i32_type().vec_type(8);
+    /// let fn_type = i8_type.fn_type(&[vec_type.into()], false);
+    /// let fn_value = module.add_function("negative_lanes", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let vector = fn_value.get_first_param().unwrap().into_vector_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let is_negative = builder.build_int_compare(IntPredicate::SLT, vector, vec_type.const_zero(), "is_negative");
+    /// let bits = builder.build_mask_to_int(is_negative, i8_type, "bits").unwrap();
+    ///
+    /// builder.build_return(Some(&bits));
+    /// ```
+    pub fn build_mask_to_int(&self, mask: VectorValue<'ctx>, int_type: IntType<'ctx>, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        let len = mask.get_type().get_size();
+
+        match mask.get_type().get_element_type() {
+            BasicTypeEnum::IntType(element_type) if element_type.get_bit_width() == 1 => {},
+            _ => return Err("The mask must be a vector of i1."),
+        }
+
+        if int_type.get_bit_width() < len {
+            return Err("The integer type is too narrow to hold every element of the mask.");
+        }
+
+        let i32_type = unsafe { IntType::new(LLVMInt32TypeInContext(LLVMGetTypeContext(int_type.as_type_ref()))) };
+        let shifts: Vec<IntValue> = (0..len).map(|i| int_type.const_int(i as u64, false)).collect();
+        let bits = self.build_int_z_extend(mask, int_type.vec_type(len), "");
+        let bits = self.build_left_shift(bits, crate::types::VectorType::const_vector(&shifts), "");
+        let mut packed = self.build_extract_element(bits, i32_type.const_zero(), "").into_int_value();
+
+        for i in 1..len {
+            let bit = self.build_extract_element(bits, i32_type.const_int(i as u64, false), "").into_int_value();
+
+            packed = self.build_or(packed, bit, "");
+        }
+
+        packed.set_name(name);
+
+        Ok(packed)
+    }
+
+    /// Unpacks the low `len` bits of `value` into a `<len x i1>` mask, with bit `i` becoming
+    /// element `i` of the mask. This is the inverse of `build_mask_to_int`, and likewise does
+    /// not depend on the target's endianness.
+    pub fn build_int_to_mask(&self, value: IntValue<'ctx>, len: u32, name: &str) -> Result<VectorValue<'ctx>, &'static str> {
+        let int_type = value.get_type();
+
+        if len == 0 {
+            return Err("The mask must have at least one element.");
+        }
+
+        if int_type.get_bit_width() < len {
+            return Err("The integer type is too narrow to hold every element of the mask.");
+        }
+
+        let i32_type = unsafe { IntType::new(LLVMInt32TypeInContext(LLVMGetTypeContext(int_type.as_type_ref()))) };
+        let wide_type = int_type.vec_type(len);
+        let one = int_type.const_int(1, false);
+        let lane_bits: Vec<IntValue> = (0..len).map(|i| one.const_shl(int_type.const_int(i as u64, false))).collect();
+        let single = self.build_insert_element(wide_type.get_undef(), value, i32_type.const_zero(), "");
+        let splat = self.build_shuffle_vector(single, wide_type.get_undef(), i32_type.vec_type(len).const_zero(), "");
+        let masked = self.build_and(splat, crate::types::VectorType::const_vector(&lane_bits), "");
+
+        Ok(self.build_int_compare(IntPredicate::NE, masked, wide_type.const_zero(), name))
+    }
+
     pub fn build_unreachable(&self) -> InstructionValue<'ctx> {
         let val = unsafe {
             LLVMBuildUnreachable(self.builder)
@@ -2041,6 +2498,34 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
+    /// Builds a `select` instruction, which returns `then` when `condition` is true and `else_`
+    /// otherwise. When `condition` is a vector of `i1`, `then` and `else_` must be vectors of
+    /// the same length, and each element of the result is chosen separately, which allows for
+    /// branch-free code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::IntPredicate;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("select");
+    /// let builder = context.create_builder();
+    /// let vector_type = context.i32_type().vec_type(4);
+    /// let fn_type = vector_type.fn_type(&[vector_type.into(), vector_type.into()], false);
+    /// let fn_value = module.add_function("elementwise_max", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let lhs = fn_value.get_first_param().unwrap().into_vector_value();
+    /// let rhs = fn_value.get_nth_param(1).unwrap().into_vector_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let is_greater = builder.build_int_compare(IntPredicate::SGT, lhs, rhs, "is_greater");
+    /// let max = builder.build_select(is_greater, lhs, rhs, "max");
+    ///
+    /// builder.build_return(Some(&max));
+    /// ```
     // SubTypes: condition can only be IntValue<bool> or VectorValue<IntValue<Bool>>
     pub fn build_select<BV: BasicValue<'ctx>, IMV: IntMathValue<'ctx>>(&self, condition: IMV, then: BV, else_: BV, name: &str) -> BasicValueEnum<'ctx> {
         let c_string = to_c_str(name);
@@ -2103,24 +2588,1296 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
-    /// Builds an atomicrmw instruction. It allows you to atomically modify memory.
+    /// Builds a call to `llvm.va_start`, which initializes the `va_list` pointed to by `list`
+    /// for use by `build_va_arg`. This must be called from within a variadic function.
     ///
     /// # Example
     ///
     /// ```
+    /// use inkwell::AddressSpace;
     /// use inkwell::context::Context;
-    /// use inkwell::{AddressSpace, AtomicOrdering, AtomicRMWBinOp};
+    ///
     /// let context = Context::create();
-    /// let module = context.create_module("rmw");
-    /// let void_type = context.void_type();
+    /// let module = context.create_module("va");
+    /// let builder = context.create_builder();
     /// let i32_type = context.i32_type();
-    /// let i32_seven = i32_type.const_int(7, false);
-    /// let i32_ptr_type = i32_type.ptr_type(AddressSpace::Generic);
-    /// let fn_type = void_type.fn_type(&[i32_ptr_type.into()], false);
-    /// let fn_value = module.add_function("rmw", fn_type, None);
+    /// let fn_type = i32_type.fn_type(&[i32_type.into()], true);
+    /// let fn_value = module.add_function("first_vararg", fn_type, None);
     /// let entry = context.append_basic_block(fn_value, "entry");
-    /// let i32_ptr_param = fn_value.get_first_param().unwrap().into_pointer_value();
-    /// let builder = context.create_builder();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// // The layout of va_list is target specific, this is how it looks on x86-64 SysV
+    /// let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    /// let va_list_type = context.struct_type(&[i32_type.into(), i32_type.into(), i8_ptr_type.into(), i8_ptr_type.into()], false);
+    /// let va_list = builder.build_alloca(va_list_type.array_type(1), "va_list");
+    ///
+    /// builder.build_va_start(va_list).unwrap();
+    ///
+    /// let first = builder.build_va_arg(va_list, i32_type, "first");
+    ///
+    /// builder.build_va_end(va_list).unwrap();
+    /// builder.build_return(Some(&first));
+    /// ```
+    pub fn build_va_start(&self, list: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_va_list_intrinsic("llvm.va_start", &[list])
+    }
+
+    /// Builds a call to `llvm.va_end`, which destroys the `va_list` pointed to by `list`.
+    pub fn build_va_end(&self, list: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_va_list_intrinsic("llvm.va_end", &[list])
+    }
+
+    /// Builds a call to `llvm.va_copy`, which copies the current position of the `va_list`
+    /// pointed to by `src` into the one pointed to by `dest`.
+    pub fn build_va_copy(&self, dest: PointerValue<'ctx>, src: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_va_list_intrinsic("llvm.va_copy", &[dest, src])
+    }
+
+    fn build_va_list_intrinsic(&self, name: &str, lists: &[PointerValue<'ctx>]) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let (i8_ptr_type, void_type) = unsafe {
+            let context = LLVMGetTypeContext(lists[0].get_type().as_type_ref());
+
+            (PointerType::new(LLVMPointerType(LLVMInt8TypeInContext(context), 0)), VoidType::new(LLVMVoidTypeInContext(context)))
+        };
+        let param_types: Vec<BasicMetadataTypeEnum<'ctx>> = lists.iter().map(|_| i8_ptr_type.into()).collect();
+        let intrinsic = self.get_or_insert_intrinsic(name, void_type.fn_type(&param_types, false))?;
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = lists.iter()
+            .map(|&list| self.build_pointer_cast(list, i8_ptr_type, "").into())
+            .collect();
+
+        Ok(self.build_call(intrinsic, &args, ""))
+    }
+
+    /// Builds a call to `llvm.expect`, which hints to the optimizer that `value` is usually
+    /// equal to `expected` and then returns `value` unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("expect");
+    /// let builder = context.create_builder();
+    /// let bool_type = context.bool_type();
+    /// let fn_type = context.void_type().fn_type(&[bool_type.into()], false);
+    /// let fn_value = module.add_function("expect", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let fast = context.append_basic_block(fn_value, "fast");
+    /// let slow = context.append_basic_block(fn_value, "slow");
+    /// let condition = fn_value.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let expected = builder.build_expect(condition, bool_type.const_int(1, false), "expected").unwrap();
+    ///
+    /// builder.build_conditional_branch(expected, fast, slow);
+    /// ```
+    pub fn build_expect(&self, value: IntValue<'ctx>, expected: IntValue<'ctx>, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        let int_type = value.get_type();
+
+        if int_type != expected.get_type() {
+            return Err("The value and its expected value must have the same type.");
+        }
+
+        let intrinsic_name = format!("llvm.expect.i{}", int_type.get_bit_width());
+        let intrinsic = self.get_or_insert_intrinsic(&intrinsic_name, int_type.fn_type(&[int_type.into(), int_type.into()], false))?;
+        let call = self.build_call(intrinsic, &[value.into(), expected.into()], name);
+
+        Ok(call.try_as_basic_value().left().expect("llvm.expect should return an integer").into_int_value())
+    }
+
+    /// Builds a call to `llvm.prefetch`, which hints to the target that the memory pointed to
+    /// by `ptr` is about to be accessed so that it may be moved into the cache ahead of time.
+    /// Targets without software prefetching simply drop the call.
+    ///
+    /// `locality` ranges from 0, meaning the memory has no temporal locality and need not be
+    /// kept in the cache, up to 3, meaning it should be kept in the cache as long as possible.
+    ///
+    /// Prior to LLVM 10, the intrinsic only accepts pointers in the generic address space.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::{AddressSpace, CacheType, PrefetchAccess};
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("prefetch");
+    /// let builder = context.create_builder();
+    /// let f64_ptr_type = context.f64_type().ptr_type(AddressSpace::Generic);
+    /// let fn_type = context.void_type().fn_type(&[f64_ptr_type.into()], false);
+    /// let fn_value = module.add_function("prefetch", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let data = fn_value.get_first_param().unwrap().into_pointer_value();
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_prefetch(data, PrefetchAccess::Read, 3, CacheType::Data).unwrap();
+    /// builder.build_return(None);
+    /// ```
+    pub fn build_prefetch(
+        &self,
+        ptr: PointerValue<'ctx>,
+        access: PrefetchAccess,
+        locality: u32,
+        cache_type: CacheType,
+    ) -> Result<CallSiteValue<'ctx>, &'static str> {
+        if locality > 3 {
+            return Err("The prefetch locality must be between 0 and 3.");
+        }
+
+        let address_space = ptr.get_type().get_address_space() as u32;
+
+        #[cfg(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0",
+                  feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0", feature = "llvm8-0", feature = "llvm9-0"))]
+        let intrinsic = {
+            if address_space != 0 {
+                return Err("Prefetching is only supported in the generic address space prior to LLVM 10.");
+            }
+
+            String::from("llvm.prefetch")
+        };
+        #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0",
+                      feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0", feature = "llvm8-0", feature = "llvm9-0")))]
+        let intrinsic = format!("llvm.prefetch.p{}i8", address_space);
+
+        let (i8_ptr_type, i32_type, void_type) = unsafe {
+            let context = LLVMGetTypeContext(ptr.get_type().as_type_ref());
+
+            (
+                LLVMPointerType(LLVMInt8TypeInContext(context), address_space),
+                LLVMInt32TypeInContext(context),
+                LLVMVoidTypeInContext(context),
+            )
+        };
+        let ptr = self.build_pointer_cast(ptr, unsafe { PointerType::new(i8_ptr_type) }, "");
+
+        unsafe {
+            let args = [
+                ptr.as_value_ref(),
+                LLVMConstInt(i32_type, access as u64, false as i32),
+                LLVMConstInt(i32_type, locality as u64, false as i32),
+                LLVMConstInt(i32_type, cache_type as u64, false as i32),
+            ];
+            let call = self.build_raw_intrinsic_call(&intrinsic, void_type, &args, "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    /// Builds a call to `llvm.sqrt`, which computes the square root of `value`. Vectors of
+    /// floats are supported, in which case the square root of each element is computed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("hypot");
+    /// let builder = context.create_builder();
+    /// let f64_type = context.f64_type();
+    /// let fn_type = f64_type.fn_type(&[f64_type.into(), f64_type.into()], false);
+    /// let fn_value = module.add_function("hypot", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let x = fn_value.get_first_param().unwrap().into_float_value();
+    /// let y = fn_value.get_nth_param(1).unwrap().into_float_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let y_squared = builder.build_float_mul(y, y, "y_squared");
+    /// let sum = builder.build_float_fma(x, x, y_squared, "sum").unwrap();
+    /// let hypot = builder.build_float_sqrt(sum, "hypot").unwrap();
+    ///
+    /// builder.build_return(Some(&hypot));
+    /// ```
+    pub fn build_float_sqrt<T: FloatMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.sqrt", &[value], name)
+    }
+
+    /// Builds a call to `llvm.sin`, which computes the sine of `value` in radians.
+    pub fn build_float_sin<T: FloatMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.sin", &[value], name)
+    }
+
+    /// Builds a call to `llvm.cos`, which computes the cosine of `value` in radians.
+    pub fn build_float_cos<T: FloatMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.cos", &[value], name)
+    }
+
+    /// Builds a call to `llvm.pow`, which raises `base` to the power of `exponent`.
+    pub fn build_float_pow<T: FloatMathValue<'ctx>>(&self, base: T, exponent: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.pow", &[base, exponent], name)
+    }
+
+    /// Builds a call to `llvm.fma`, which computes `a * b + c` without rounding the
+    /// intermediate product.
+    pub fn build_float_fma<T: FloatMathValue<'ctx>>(&self, a: T, b: T, c: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.fma", &[a, b, c], name)
+    }
+
+    /// Builds a call to `llvm.fabs`, which computes the absolute value of `value`.
+    pub fn build_float_abs<T: FloatMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.fabs", &[value], name)
+    }
+
+    /// Builds a call to `llvm.minnum`, which returns the smaller of `lhs` and `rhs`. If only
+    /// one of them is a NaN, the other is returned.
+    pub fn build_float_minnum<T: FloatMathValue<'ctx>>(&self, lhs: T, rhs: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.minnum", &[lhs, rhs], name)
+    }
+
+    /// Builds a call to `llvm.maxnum`, which returns the larger of `lhs` and `rhs`. If only
+    /// one of them is a NaN, the other is returned.
+    pub fn build_float_maxnum<T: FloatMathValue<'ctx>>(&self, lhs: T, rhs: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.maxnum", &[lhs, rhs], name)
+    }
+
+    /// Builds a call to `llvm.copysign`, which returns `magnitude` with the sign of `sign`.
+    pub fn build_float_copysign<T: FloatMathValue<'ctx>>(&self, magnitude: T, sign: T, name: &str) -> Result<T, &'static str> {
+        self.build_float_math_intrinsic("llvm.copysign", &[magnitude, sign], name)
+    }
+
+    // Builds a call to an intrinsic which is overloaded on the type of all of its operands
+    // and its return value, such as the libm-like float intrinsics.
+    fn build_float_math_intrinsic<T: FloatMathValue<'ctx>>(&self, intrinsic: &str, args: &[T], name: &str) -> Result<T, &'static str> {
+        let value_type = args[0].as_basic_value_enum().get_type();
+
+        if args.iter().any(|arg| arg.as_basic_value_enum().get_type() != value_type) {
+            return Err("All operands must have the same type.");
+        }
+
+        let args: Vec<LLVMValueRef> = args.iter().map(|arg| arg.as_value_ref()).collect();
+
+        unsafe {
+            let value = self.build_overloaded_intrinsic_call(intrinsic, &[value_type.as_type_ref()], value_type.as_type_ref(), &args, name)?;
+
+            Ok(T::new(value))
+        }
+    }
+
+    /// Builds a call to `llvm.ctpop`, which counts the number of bits set in `value`. Vectors
+    /// of integers are supported, in which case the bits of each element are counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("bits");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+    /// let fn_value = module.add_function("log2", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let value = fn_value.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let leading_zeros = builder.build_ctlz(value, false, "leading_zeros").unwrap();
+    /// let log2 = builder.build_int_sub(i32_type.const_int(31, false), leading_zeros, "log2");
+    ///
+    /// builder.build_return(Some(&log2));
+    /// ```
+    pub fn build_ctpop<T: IntMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.ctpop", &[value], &[], name)
+    }
+
+    /// Builds a call to `llvm.ctlz`, which counts the number of leading zero bits in `value`.
+    /// When `is_zero_undef` is true, the result is undefined if `value` is zero, which allows
+    /// for faster code on some targets.
+    pub fn build_ctlz<T: IntMathValue<'ctx>>(&self, value: T, is_zero_undef: bool, name: &str) -> Result<T, &'static str> {
+        let flag = const_bool_like(value.as_value_ref(), is_zero_undef);
+
+        self.build_int_math_intrinsic("llvm.ctlz", &[value], &[flag], name)
+    }
+
+    /// Builds a call to `llvm.cttz`, which counts the number of trailing zero bits in `value`.
+    /// When `is_zero_undef` is true, the result is undefined if `value` is zero, which allows
+    /// for faster code on some targets.
+    pub fn build_cttz<T: IntMathValue<'ctx>>(&self, value: T, is_zero_undef: bool, name: &str) -> Result<T, &'static str> {
+        let flag = const_bool_like(value.as_value_ref(), is_zero_undef);
+
+        self.build_int_math_intrinsic("llvm.cttz", &[value], &[flag], name)
+    }
+
+    /// Builds a call to `llvm.bswap`, which reverses the order of the bytes in `value`.
+    /// The bit width of `value`, or of its elements, must be a multiple of 16.
+    pub fn build_bswap<T: IntMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        let bit_width = unsafe {
+            let mut int_type = LLVMTypeOf(value.as_value_ref());
+
+            if LLVMGetTypeKind(int_type) == LLVMTypeKind::LLVMVectorTypeKind {
+                int_type = LLVMGetElementType(int_type);
+            }
+
+            LLVMGetIntTypeWidth(int_type)
+        };
+
+        if bit_width % 16 != 0 {
+            return Err("The bit width of a byte swapped integer must be a multiple of 16.");
+        }
+
+        self.build_int_math_intrinsic("llvm.bswap", &[value], &[], name)
+    }
+
+    /// Builds a call to `llvm.bitreverse`, which reverses the order of the bits in `value`.
+    #[llvm_versions(3.8..=latest)]
+    pub fn build_bitreverse<T: IntMathValue<'ctx>>(&self, value: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.bitreverse", &[value], &[], name)
+    }
+
+    /// Builds a call to `llvm.fshl`, a funnel shift left. It concatenates `high` and `low`,
+    /// shifts the result left by `shift` modulo the bit width and returns the upper half.
+    /// Passing the same value as `high` and `low` rotates it left.
+    #[llvm_versions(7.0..=latest)]
+    pub fn build_fshl<T: IntMathValue<'ctx>>(&self, high: T, low: T, shift: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.fshl", &[high, low, shift], &[], name)
+    }
+
+    /// Builds a call to `llvm.fshr`, a funnel shift right. It concatenates `high` and `low`,
+    /// shifts the result right by `shift` modulo the bit width and returns the lower half.
+    /// Passing the same value as `high` and `low` rotates it right.
+    #[llvm_versions(7.0..=latest)]
+    pub fn build_fshr<T: IntMathValue<'ctx>>(&self, high: T, low: T, shift: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.fshr", &[high, low, shift], &[], name)
+    }
+
+    /// Builds a call to `llvm.smin`, which returns the smaller of `lhs` and `rhs` as signed
+    /// integers. Vectors of integers are supported, in which case each element is compared.
+    #[llvm_versions(12.0..=latest)]
+    pub fn build_smin<T: IntMathValue<'ctx>>(&self, lhs: T, rhs: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.smin", &[lhs, rhs], &[], name)
+    }
+
+    /// Builds a call to `llvm.smax`, which returns the larger of `lhs` and `rhs` as signed
+    /// integers. Vectors of integers are supported, in which case each element is compared.
+    #[llvm_versions(12.0..=latest)]
+    pub fn build_smax<T: IntMathValue<'ctx>>(&self, lhs: T, rhs: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.smax", &[lhs, rhs], &[], name)
+    }
+
+    /// Builds a call to `llvm.umin`, which returns the smaller of `lhs` and `rhs` as unsigned
+    /// integers. Vectors of integers are supported, in which case each element is compared.
+    #[llvm_versions(12.0..=latest)]
+    pub fn build_umin<T: IntMathValue<'ctx>>(&self, lhs: T, rhs: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.umin", &[lhs, rhs], &[], name)
+    }
+
+    /// Builds a call to `llvm.umax`, which returns the larger of `lhs` and `rhs` as unsigned
+    /// integers. Vectors of integers are supported, in which case each element is compared.
+    #[llvm_versions(12.0..=latest)]
+    pub fn build_umax<T: IntMathValue<'ctx>>(&self, lhs: T, rhs: T, name: &str) -> Result<T, &'static str> {
+        self.build_int_math_intrinsic("llvm.umax", &[lhs, rhs], &[], name)
+    }
+
+    // Builds a call to an intrinsic which is overloaded on the type of its integer operands
+    // and its return value. `flags` are passed after the operands and do not take part in
+    // the overloading.
+    fn build_int_math_intrinsic<T: IntMathValue<'ctx>>(&self, intrinsic: &str, args: &[T], flags: &[LLVMValueRef], name: &str) -> Result<T, &'static str> {
+        let value_type = args[0].as_basic_value_enum().get_type();
+
+        if args.iter().any(|arg| arg.as_basic_value_enum().get_type() != value_type) {
+            return Err("All operands must have the same type.");
+        }
+
+        let args: Vec<LLVMValueRef> = args.iter().map(|arg| arg.as_value_ref()).chain(flags.iter().cloned()).collect();
+
+        unsafe {
+            let value = self.build_overloaded_intrinsic_call(intrinsic, &[value_type.as_type_ref()], value_type.as_type_ref(), &args, name)?;
+
+            Ok(T::new(value))
+        }
+    }
+
+    /// Builds a call to one of the `llvm.vector.reduce.*` intrinsics, which combines all of the
+    /// elements of an integer `vector` into a single integer using `op`.
+    ///
+    /// Prior to LLVM 12, these intrinsics are still named `llvm.experimental.vector.reduce.*`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::IntReduction;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("reduce");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = i32_type.fn_type(&[i32_type.vec_type(8).into()], false);
+    /// let fn_value = module.add_function("sum", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let vector = fn_value.get_first_param().unwrap().into_vector_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let sum = builder.build_int_vector_reduce(IntReduction::Add, vector, "sum").unwrap();
+    ///
+    /// builder.build_return(Some(&sum));
+    /// ```
+    #[llvm_versions(9.0..=latest)]
+    pub fn build_int_vector_reduce(&self, op: IntReduction, vector: VectorValue<'ctx>, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        if !vector.get_type().get_element_type().is_int_type() {
+            return Err("Only vectors of integers can be reduced with an integer operation.");
+        }
+
+        unsafe {
+            Ok(IntValue::new(self.build_vector_reduce_intrinsic(op.get_intrinsic_suffix(), None, vector, name)?))
+        }
+    }
+
+    /// Builds a call to `llvm.vector.reduce.fadd`, which adds all of the elements of a float
+    /// `vector` to `start`. Unless the call is given the `reassoc` fast-math flag, the
+    /// additions are performed in order, which usually prevents an efficient lowering.
+    ///
+    /// Prior to LLVM 12, this intrinsic is still named `llvm.experimental.vector.reduce.v2.fadd`.
+    #[llvm_versions(9.0..=latest)]
+    pub fn build_float_vector_reduce_add(&self, start: FloatValue<'ctx>, vector: VectorValue<'ctx>, name: &str) -> Result<FloatValue<'ctx>, &'static str> {
+        self.build_float_vector_reduce("fadd", Some(start), vector, name)
+    }
+
+    /// Builds a call to `llvm.vector.reduce.fmul`, which multiplies `start` with all of the
+    /// elements of a float `vector`. Unless the call is given the `reassoc` fast-math flag,
+    /// the multiplications are performed in order.
+    ///
+    /// Prior to LLVM 12, this intrinsic is still named `llvm.experimental.vector.reduce.v2.fmul`.
+    #[llvm_versions(9.0..=latest)]
+    pub fn build_float_vector_reduce_mul(&self, start: FloatValue<'ctx>, vector: VectorValue<'ctx>, name: &str) -> Result<FloatValue<'ctx>, &'static str> {
+        self.build_float_vector_reduce("fmul", Some(start), vector, name)
+    }
+
+    /// Builds a call to `llvm.vector.reduce.fmax`, which returns the largest element of a
+    /// float `vector`, ignoring NaNs like `llvm.maxnum` does.
+    #[llvm_versions(9.0..=latest)]
+    pub fn build_float_vector_reduce_max(&self, vector: VectorValue<'ctx>, name: &str) -> Result<FloatValue<'ctx>, &'static str> {
+        self.build_float_vector_reduce("fmax", None, vector, name)
+    }
+
+    /// Builds a call to `llvm.vector.reduce.fmin`, which returns the smallest element of a
+    /// float `vector`, ignoring NaNs like `llvm.minnum` does.
+    #[llvm_versions(9.0..=latest)]
+    pub fn build_float_vector_reduce_min(&self, vector: VectorValue<'ctx>, name: &str) -> Result<FloatValue<'ctx>, &'static str> {
+        self.build_float_vector_reduce("fmin", None, vector, name)
+    }
+
+    #[llvm_versions(9.0..=latest)]
+    fn build_float_vector_reduce(&self, op: &str, start: Option<FloatValue<'ctx>>, vector: VectorValue<'ctx>, name: &str) -> Result<FloatValue<'ctx>, &'static str> {
+        let element_type = vector.get_type().get_element_type();
+
+        if !element_type.is_float_type() {
+            return Err("Only vectors of floats can be reduced with a float operation.");
+        }
+
+        if start.map_or(false, |start| start.get_type() != element_type.into_float_type()) {
+            return Err("The start value must have the same type as the vector's elements.");
+        }
+
+        unsafe {
+            Ok(FloatValue::new(self.build_vector_reduce_intrinsic(op, start.map(|start| start.as_value_ref()), vector, name)?))
+        }
+    }
+
+    // The reductions were introduced as experimental intrinsics and lost their extra return
+    // type overload in LLVM 9, apart from fadd and fmul which got a v2 variant taking a start
+    // value. LLVM 12 dropped the experimental prefix along with that overload.
+    #[llvm_versions(9.0..=latest)]
+    unsafe fn build_vector_reduce_intrinsic(&self, op: &str, start: Option<LLVMValueRef>, vector: VectorValue<'ctx>, name: &str) -> Result<LLVMValueRef, &'static str> {
+        let vector_type = vector.get_type().as_type_ref();
+        let element_type = LLVMGetElementType(vector_type);
+        let args: Vec<LLVMValueRef> = start.into_iter().chain(Some(vector.as_value_ref())).collect();
+
+        #[cfg(any(feature = "llvm9-0", feature = "llvm10-0", feature = "llvm11-0"))]
+        let (intrinsic, overloads) = if start.is_some() {
+            (format!("llvm.experimental.vector.reduce.v2.{}", op), vec![element_type, vector_type])
+        } else {
+            (format!("llvm.experimental.vector.reduce.{}", op), vec![vector_type])
+        };
+        #[cfg(not(any(feature = "llvm9-0", feature = "llvm10-0", feature = "llvm11-0")))]
+        let (intrinsic, overloads) = (format!("llvm.vector.reduce.{}", op), vec![vector_type]);
+
+        self.build_overloaded_intrinsic_call(&intrinsic, &overloads, element_type, &args, name)
+    }
+
+    /// Builds a call to `llvm.masked.load`, which loads the vector pointed to by `ptr` but only
+    /// accesses the elements whose bit in `mask` is set. The other elements are taken from
+    /// `passthrough`, or are undefined when it is `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("masked");
+    /// let builder = context.create_builder();
+    /// let vec_type = context.f32_type().vec_type(8);
+    /// let mask_type = context.bool_type().vec_type(8);
+    /// let fn_type = vec_type.fn_type(&[vec_type.ptr_type(AddressSpace::Generic).into(), mask_type.into()], false);
+    /// let fn_value = module.add_function("masked_load", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let ptr = fn_value.get_first_param().unwrap().into_pointer_value();
+    /// let mask = fn_value.get_nth_param(1).unwrap().into_vector_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let loaded = builder.build_masked_load(ptr, 4, mask, Some(vec_type.const_zero()), "loaded").unwrap();
+    ///
+    /// builder.build_return(Some(&loaded));
+    /// ```
+    #[llvm_versions(5.0..=latest)]
+    pub fn build_masked_load(
+        &self,
+        ptr: PointerValue<'ctx>,
+        alignment: u32,
+        mask: VectorValue<'ctx>,
+        passthrough: Option<VectorValue<'ctx>>,
+        name: &str,
+    ) -> Result<VectorValue<'ctx>, &'static str> {
+        let vector_type = match ptr.get_type().get_element_type() {
+            AnyTypeEnum::VectorType(vector_type) => vector_type,
+            _ => return Err("The pointer must point to a vector."),
+        };
+
+        check_masked_operands(vector_type, Some(alignment), mask, passthrough)?;
+
+        unsafe {
+            let passthrough = get_passthrough(vector_type, passthrough);
+            let args = [ptr.as_value_ref(), const_i32_like(mask.as_value_ref(), alignment), mask.as_value_ref(), passthrough];
+            let overloads = [vector_type.as_type_ref(), ptr.get_type().as_type_ref()];
+            let value = self.build_overloaded_intrinsic_call("llvm.masked.load", &overloads, vector_type.as_type_ref(), &args, name)?;
+
+            Ok(VectorValue::new(value))
+        }
+    }
+
+    /// Builds a call to `llvm.masked.store`, which stores `value` to the vector pointed to by
+    /// `ptr`, but only writes the elements whose bit in `mask` is set.
+    #[llvm_versions(5.0..=latest)]
+    pub fn build_masked_store(
+        &self,
+        value: VectorValue<'ctx>,
+        ptr: PointerValue<'ctx>,
+        alignment: u32,
+        mask: VectorValue<'ctx>,
+    ) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let vector_type = value.get_type();
+
+        if ptr.get_type().get_element_type() != vector_type.as_any_type_enum() {
+            return Err("The pointer must point to the type of the stored vector.");
+        }
+
+        check_masked_operands(vector_type, Some(alignment), mask, None)?;
+
+        unsafe {
+            let args = [value.as_value_ref(), ptr.as_value_ref(), const_i32_like(mask.as_value_ref(), alignment), mask.as_value_ref()];
+            let overloads = [vector_type.as_type_ref(), ptr.get_type().as_type_ref()];
+            let call = self.build_overloaded_intrinsic_call("llvm.masked.store", &overloads, void_type_like(value.as_value_ref()), &args, "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    /// Builds a call to `llvm.masked.gather`, which loads each element from the matching pointer
+    /// in the vector `ptrs`, but only for the elements whose bit in `mask` is set. The other
+    /// elements are taken from `passthrough`, or are undefined when it is `None`.
+    #[llvm_versions(5.0..=latest)]
+    pub fn build_masked_gather(
+        &self,
+        ptrs: VectorValue<'ctx>,
+        alignment: u32,
+        mask: VectorValue<'ctx>,
+        passthrough: Option<VectorValue<'ctx>>,
+        name: &str,
+    ) -> Result<VectorValue<'ctx>, &'static str> {
+        let vector_type = get_pointee_vector_type(ptrs)?;
+
+        check_masked_operands(vector_type, Some(alignment), mask, passthrough)?;
+
+        unsafe {
+            let passthrough = get_passthrough(vector_type, passthrough);
+            let args = [ptrs.as_value_ref(), const_i32_like(mask.as_value_ref(), alignment), mask.as_value_ref(), passthrough];
+            let overloads = [vector_type.as_type_ref(), ptrs.get_type().as_type_ref()];
+            let value = self.build_overloaded_intrinsic_call("llvm.masked.gather", &overloads, vector_type.as_type_ref(), &args, name)?;
+
+            Ok(VectorValue::new(value))
+        }
+    }
+
+    /// Builds a call to `llvm.masked.scatter`, which stores each element of `value` to the
+    /// matching pointer in the vector `ptrs`, but only for the elements whose bit in `mask` is set.
+    #[llvm_versions(5.0..=latest)]
+    pub fn build_masked_scatter(
+        &self,
+        value: VectorValue<'ctx>,
+        ptrs: VectorValue<'ctx>,
+        alignment: u32,
+        mask: VectorValue<'ctx>,
+    ) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let vector_type = value.get_type();
+
+        if get_pointee_vector_type(ptrs)? != vector_type {
+            return Err("The pointers must point to the type of the stored elements.");
+        }
+
+        check_masked_operands(vector_type, Some(alignment), mask, None)?;
+
+        unsafe {
+            let args = [value.as_value_ref(), ptrs.as_value_ref(), const_i32_like(mask.as_value_ref(), alignment), mask.as_value_ref()];
+            let overloads = [vector_type.as_type_ref(), ptrs.get_type().as_type_ref()];
+            let call = self.build_overloaded_intrinsic_call("llvm.masked.scatter", &overloads, void_type_like(value.as_value_ref()), &args, "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    /// Builds a call to `llvm.masked.expandload`, which loads consecutive elements starting at
+    /// `ptr` into the elements of a `vector_type` vector whose bit in `mask` is set. The other
+    /// elements are taken from `passthrough`, or are undefined when it is `None`.
+    #[llvm_versions(5.0..=latest)]
+    pub fn build_masked_expand_load(
+        &self,
+        vector_type: VectorType<'ctx>,
+        ptr: PointerValue<'ctx>,
+        mask: VectorValue<'ctx>,
+        passthrough: Option<VectorValue<'ctx>>,
+        name: &str,
+    ) -> Result<VectorValue<'ctx>, &'static str> {
+        if ptr.get_type().get_element_type() != vector_type.get_element_type().as_any_type_enum() {
+            return Err("The pointer must point to the type of the vector's elements.");
+        }
+
+        check_masked_operands(vector_type, None, mask, passthrough)?;
+
+        unsafe {
+            let passthrough = get_passthrough(vector_type, passthrough);
+            let args = [ptr.as_value_ref(), mask.as_value_ref(), passthrough];
+            let value = self.build_overloaded_intrinsic_call("llvm.masked.expandload", &[vector_type.as_type_ref()], vector_type.as_type_ref(), &args, name)?;
+
+            Ok(VectorValue::new(value))
+        }
+    }
+
+    /// Builds a call to `llvm.masked.compressstore`, which stores the elements of `value` whose
+    /// bit in `mask` is set to consecutive memory starting at `ptr`.
+    #[llvm_versions(5.0..=latest)]
+    pub fn build_masked_compress_store(
+        &self,
+        value: VectorValue<'ctx>,
+        ptr: PointerValue<'ctx>,
+        mask: VectorValue<'ctx>,
+    ) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let vector_type = value.get_type();
+
+        if ptr.get_type().get_element_type() != vector_type.get_element_type().as_any_type_enum() {
+            return Err("The pointer must point to the type of the vector's elements.");
+        }
+
+        check_masked_operands(vector_type, None, mask, None)?;
+
+        unsafe {
+            let args = [value.as_value_ref(), ptr.as_value_ref(), mask.as_value_ref()];
+            let call = self.build_overloaded_intrinsic_call("llvm.masked.compressstore", &[vector_type.as_type_ref()], void_type_like(value.as_value_ref()), &args, "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    // Builds a call to an overloaded intrinsic, appending the mangled name of each of the
+    // `overloads` types to its name, as LLVM expects.
+    pub(crate) unsafe fn build_overloaded_intrinsic_call(
+        &self,
+        intrinsic: &str,
+        overloads: &[LLVMTypeRef],
+        return_type: LLVMTypeRef,
+        args: &[LLVMValueRef],
+        name: &str,
+    ) -> Result<LLVMValueRef, &'static str> {
+        let mut intrinsic = intrinsic.to_string();
+
+        for &overload in overloads {
+            intrinsic.push('.');
+            intrinsic.push_str(&get_intrinsic_type_suffix(overload)?);
+        }
+
+        self.build_raw_intrinsic_call(&intrinsic, return_type, args, name)
+    }
+
+    /// Builds an overflow-checked integer operation. When the operation overflows, control
+    /// branches to `on_overflow`, or to a new block which calls `llvm.trap` when it is `None`.
+    /// Otherwise the builder is left positioned at the end of a new block which continues
+    /// with the returned result.
+    ///
+    /// The trap block is created per call, so passing a shared `on_overflow` block can keep
+    /// functions with many checks smaller.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::OverflowArithmetic;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("checked");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    /// let fn_value = module.add_function("checked_add", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let lhs = fn_value.get_first_param().unwrap().into_int_value();
+    /// let rhs = fn_value.get_nth_param(1).unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let sum = builder.build_int_checked_op(OverflowArithmetic::SignedAdd, lhs, rhs, None, "sum").unwrap();
+    ///
+    /// builder.build_return(Some(&sum));
+    /// ```
+    pub fn build_int_checked_op(
+        &self,
+        op: OverflowArithmetic,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+        on_overflow: Option<BasicBlock<'ctx>>,
+        name: &str,
+    ) -> Result<IntValue<'ctx>, &'static str> {
+        let int_type = lhs.get_type();
+
+        if int_type != rhs.get_type() {
+            return Err("The operands must have the same type.");
+        }
+
+        let current_block = self.get_insert_block().ok_or("Builder is not positioned inside of a function.")?;
+        let function = self.get_insert_function()?;
+        let intrinsic_name = format!("{}.i{}", op.get_intrinsic_prefix(), int_type.get_bit_width());
+
+        let (result, overflowed) = unsafe {
+            let context = LLVMGetTypeContext(int_type.as_type_ref());
+            let mut field_types = [int_type.as_type_ref(), LLVMInt1TypeInContext(context)];
+            let return_type = LLVMStructTypeInContext(context, field_types.as_mut_ptr(), field_types.len() as u32, false as i32);
+            let pair = self.build_raw_intrinsic_call(&intrinsic_name, return_type, &[lhs.as_value_ref(), rhs.as_value_ref()], "")?;
+            let c_string = to_c_str(name);
+            let overflowed_string = to_c_str("overflowed");
+            let result = LLVMBuildExtractValue(self.builder, pair, 0, c_string.as_ptr());
+            let overflowed = LLVMBuildExtractValue(self.builder, pair, 1, overflowed_string.as_ptr());
+
+            (IntValue::new(result), IntValue::new(overflowed))
+        };
+
+        let append_block = |block_name: &str| {
+            let c_string = to_c_str(block_name);
+
+            unsafe {
+                let context = LLVMGetTypeContext(int_type.as_type_ref());
+
+                BasicBlock::new(LLVMAppendBasicBlockInContext(context, function.as_value_ref(), c_string.as_ptr())).expect("Appended block should be valid")
+            }
+        };
+
+        let continue_block = append_block("no_overflow");
+        let overflow_block = match on_overflow {
+            Some(block) => block,
+            None => {
+                let trap_block = append_block("overflow");
+
+                self.position_at_end(trap_block);
+
+                self.build_trap()?;
+                self.build_unreachable();
+
+                trap_block
+            },
+        };
+
+        self.position_at_end(current_block);
+        self.build_conditional_branch_with_likelihood(overflowed, overflow_block, continue_block, false);
+        self.position_at_end(continue_block);
+
+        Ok(result)
+    }
+
+    /// Builds a call to `llvm.trap`, which aborts execution in a target specific way, such as
+    /// executing an illegal instruction. As the call does not return, it is usually followed by
+    /// `build_unreachable`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("trap");
+    /// let builder = context.create_builder();
+    /// let fn_value = module.add_function("abort", context.void_type().fn_type(&[], false), None);
+    ///
+    /// builder.position_at_end(context.append_basic_block(fn_value, "entry"));
+    /// builder.build_trap().unwrap();
+    /// builder.build_unreachable();
+    /// ```
+    pub fn build_trap(&self) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_trap_intrinsic("llvm.trap", &[])
+    }
+
+    /// Builds a call to `llvm.debugtrap`, which stops execution in a way a debugger can
+    /// intercept, such as a breakpoint instruction. Unlike `build_trap`, execution may
+    /// continue after the call.
+    pub fn build_debugtrap(&self) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_trap_intrinsic("llvm.debugtrap", &[])
+    }
+
+    /// Builds a call to `llvm.ubsantrap`, which aborts execution like `build_trap` but encodes
+    /// `code` in the trapping instruction where the target supports it, so that the kind of
+    /// failed check can be recovered from a crash.
+    #[llvm_versions(12.0..=latest)]
+    pub fn build_ubsantrap(&self, code: u8) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let code = unsafe {
+            let function = self.get_insert_function()?;
+
+            LLVMConstInt(LLVMInt8TypeInContext(LLVMGetTypeContext(function.get_type().as_type_ref())), code as u64, false as i32)
+        };
+
+        self.build_trap_intrinsic("llvm.ubsantrap", &[code])
+    }
+
+    fn build_trap_intrinsic(&self, intrinsic: &str, args: &[LLVMValueRef]) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let function = self.get_insert_function()?;
+
+        unsafe {
+            let void_type = LLVMVoidTypeInContext(LLVMGetTypeContext(function.get_type().as_type_ref()));
+
+            Ok(CallSiteValue::new(self.build_raw_intrinsic_call(intrinsic, void_type, args, "")?))
+        }
+    }
+
+    /// Builds a call to `llvm.experimental.stackmap`, which records the location of each of
+    /// `live_values` at this point under `id` in the stack map section, without otherwise
+    /// affecting the generated code. `shadow_bytes` bytes following the call are reserved so
+    /// the runtime can later overwrite them, for instance to trigger a deoptimization.
+    ///
+    /// The recorded locations can be read back with `stack_map::StackMap::parse`.
+    pub fn build_stackmap(&self, id: u64, shadow_bytes: u32, live_values: &[BasicValueEnum<'ctx>]) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let function = self.get_insert_function()?;
+
+        unsafe {
+            let context = LLVMGetTypeContext(function.get_type().as_type_ref());
+            let mut args = vec![
+                LLVMConstInt(LLVMInt64TypeInContext(context), id, false as i32),
+                LLVMConstInt(LLVMInt32TypeInContext(context), shadow_bytes as u64, false as i32),
+            ];
+
+            args.extend(live_values.iter().map(|value| value.as_value_ref()));
+
+            let call = self.build_variadic_intrinsic_call("llvm.experimental.stackmap", LLVMVoidTypeInContext(context), 2, &args, "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    /// Builds a call to `llvm.experimental.patchpoint`, which reserves `num_bytes` bytes of
+    /// patchable code at this point and calls `target` with `call_args` from within them. Like
+    /// `build_stackmap`, the locations of `live_values` are recorded under `id`, so the
+    /// runtime can later rewrite the call, for instance to implement an inline cache.
+    ///
+    /// The call returns nothing when `return_type` is `None`, and must otherwise return an `i64`.
+    pub fn build_patchpoint(
+        &self,
+        id: u64,
+        num_bytes: u32,
+        target: PointerValue<'ctx>,
+        call_args: &[BasicValueEnum<'ctx>],
+        live_values: &[BasicValueEnum<'ctx>],
+        return_type: Option<IntType<'ctx>>,
+        name: &str,
+    ) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let function = self.get_insert_function()?;
+
+        if let Some(return_type) = return_type {
+            if return_type.get_bit_width() != 64 {
+                return Err("Patchpoints may only return an i64.");
+            }
+        }
+
+        unsafe {
+            let context = LLVMGetTypeContext(function.get_type().as_type_ref());
+            let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+            let target = self.build_pointer_cast(target, PointerType::new(i8_ptr_type), "");
+            let i32_type = LLVMInt32TypeInContext(context);
+            let (intrinsic, llvm_return_type) = match return_type {
+                Some(return_type) => ("llvm.experimental.patchpoint.i64", return_type.as_type_ref()),
+                None => ("llvm.experimental.patchpoint.void", LLVMVoidTypeInContext(context)),
+            };
+            let mut args = vec![
+                LLVMConstInt(LLVMInt64TypeInContext(context), id, false as i32),
+                LLVMConstInt(i32_type, num_bytes as u64, false as i32),
+                target.as_value_ref(),
+                LLVMConstInt(i32_type, call_args.len() as u64, false as i32),
+            ];
+
+            args.extend(call_args.iter().map(|value| value.as_value_ref()));
+            args.extend(live_values.iter().map(|value| value.as_value_ref()));
+
+            let call = self.build_variadic_intrinsic_call(intrinsic, llvm_return_type, 4, &args, name)?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    // Like `build_raw_intrinsic_call`, but for intrinsics which only fix the types of their
+    // first `fixed_params` parameters and take any further arguments as varargs.
+    unsafe fn build_variadic_intrinsic_call(&self, intrinsic: &str, return_type: LLVMTypeRef, fixed_params: usize, args: &[LLVMValueRef], name: &str) -> Result<LLVMValueRef, &'static str> {
+        let mut param_types: Vec<LLVMTypeRef> = args[..fixed_params].iter().map(|&arg| LLVMTypeOf(arg)).collect();
+        let fn_type = FunctionType::new(LLVMFunctionType(return_type, param_types.as_mut_ptr(), param_types.len() as u32, true as i32));
+        let function = self.get_or_insert_intrinsic(intrinsic, fn_type)?;
+        let c_string = to_c_str(name);
+        let mut args = args.to_vec();
+
+        Ok(LLVMBuildCall(self.builder, function.as_value_ref(), args.as_mut_ptr(), args.len() as u32, c_string.as_ptr()))
+    }
+
+    /// Builds a call to `llvm.lifetime.start`, which marks the start of the region in which
+    /// the memory pointed to by `ptr` is in use. Before this point, and after a matching
+    /// `build_lifetime_end`, LLVM is free to reuse the memory for other stack slots.
+    ///
+    /// `size` is the number of bytes to mark. When it is `None`, the size of the type `ptr`
+    /// points to is computed from the data layout of the module being built. If that type is
+    /// not sized, or `ptr` is an array alloca, which may hold several of them, the whole object
+    /// `ptr` points to is marked instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("lifetimes");
+    /// let builder = context.create_builder();
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let fn_value = module.add_function("lifetimes", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let buffer = builder.build_alloca(context.i8_type().array_type(256), "buffer");
+    ///
+    /// builder.build_lifetime_start(buffer, None).unwrap();
+    /// // Use buffer...
+    /// builder.build_lifetime_end(buffer, None).unwrap();
+    /// builder.build_return(None);
+    ///
+    /// assert!(module.verify().is_ok());
+    /// ```
+    pub fn build_lifetime_start(&self, ptr: PointerValue<'ctx>, size: Option<u64>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_lifetime_intrinsic("llvm.lifetime.start", ptr, size)
+    }
+
+    /// Builds a call to `llvm.lifetime.end`, which marks the end of the region in which the
+    /// memory pointed to by `ptr` is in use. See `build_lifetime_start` for details.
+    pub fn build_lifetime_end(&self, ptr: PointerValue<'ctx>, size: Option<u64>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_lifetime_intrinsic("llvm.lifetime.end", ptr, size)
+    }
+
+    fn build_lifetime_intrinsic(&self, intrinsic: &str, ptr: PointerValue<'ctx>, size: Option<u64>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        unsafe {
+            let context = LLVMGetTypeContext(ptr.get_type().as_type_ref());
+            let address_space = LLVMGetPointerAddressSpace(ptr.get_type().as_type_ref());
+            let i8_ptr = self.build_pointer_cast(ptr, PointerType::new(LLVMPointerType(LLVMInt8TypeInContext(context), address_space)), "");
+            // The pointer type was only overloaded from LLVM 5.0 on
+            #[cfg(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0"))]
+            let intrinsic = intrinsic.to_string();
+            #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0")))]
+            let intrinsic = format!("{}.p{}i8", intrinsic, address_space);
+            // A size of -1 marks the whole object
+            let size = match size {
+                Some(size) => size,
+                None => self.get_lifetime_object_size(ptr)?.unwrap_or(std::u64::MAX),
+            };
+            let size = LLVMConstInt(LLVMInt64TypeInContext(context), size, 0);
+            let args = [size, i8_ptr.as_value_ref()];
+            let call = self.build_raw_intrinsic_call(&intrinsic, LLVMVoidTypeInContext(context), &args, "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    // Computes the size of the object `ptr` points to from the data layout of the module being
+    // built. Unsized pointees and array allocas, which may hold several of them, have no size
+    // which can be computed from the pointee type alone.
+    fn get_lifetime_object_size(&self, ptr: PointerValue<'ctx>) -> Result<Option<u64>, &'static str> {
+        let pointee_type = unsafe { LLVMGetElementType(ptr.get_type().as_type_ref()) };
+
+        if unsafe { LLVMTypeIsSized(pointee_type) } == 0 {
+            return Ok(None);
+        }
+
+        if let Some(instruction) = ptr.as_instruction() {
+            let array_size = instruction.get_operand(0).and_then(|operand| operand.left());
+
+            if instruction.get_opcode() == InstructionOpcode::Alloca
+                && array_size.and_then(|size| size.into_int_value().get_zero_extended_constant()) != Some(1) {
+                return Ok(None);
+            }
+        }
+
+        let function = self.get_insert_function()?;
+        let data_layout = unsafe { Module::get_borrowed_data_layout(LLVMGetGlobalParent(function.as_value_ref())) };
+        let target_data = TargetData::create(&data_layout.as_str().to_string_lossy());
+
+        Ok(Some(target_data.get_abi_size(&unsafe { BasicTypeEnum::new(pointee_type) })))
+    }
+
+    /// Builds a call to `llvm.stacksave`, which captures the current state of the stack so
+    /// that dynamic allocas made after it can be freed by `build_stack_restore`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("vla");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = context.void_type().fn_type(&[i32_type.into()], false);
+    /// let fn_value = module.add_function("vla", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let len = fn_value.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let stack = builder.build_stack_save("stack").unwrap();
+    ///
+    /// builder.build_array_alloca(i32_type, len, "vla");
+    /// builder.build_stack_restore(stack).unwrap();
+    /// builder.build_return(None);
+    /// ```
+    pub fn build_stack_save(&self, name: &str) -> Result<PointerValue<'ctx>, &'static str> {
+        let function = self.get_insert_function()?;
+        let i8_ptr_type = unsafe {
+            PointerType::new(LLVMPointerType(LLVMInt8TypeInContext(LLVMGetTypeContext(function.get_type().as_type_ref())), 0))
+        };
+        let intrinsic = self.get_or_insert_intrinsic("llvm.stacksave", i8_ptr_type.fn_type(&[], false))?;
+        let call = self.build_call(intrinsic, &[], name);
+
+        Ok(call.try_as_basic_value().left().expect("llvm.stacksave should return a pointer").into_pointer_value())
+    }
+
+    /// Builds a call to `llvm.stackrestore`, which frees any dynamic allocas made since
+    /// `stack` was returned by `build_stack_save`.
+    pub fn build_stack_restore(&self, stack: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let void_type = unsafe {
+            VoidType::new(LLVMVoidTypeInContext(LLVMGetTypeContext(stack.get_type().as_type_ref())))
+        };
+        let intrinsic = self.get_or_insert_intrinsic("llvm.stackrestore", void_type.fn_type(&[stack.get_type().into()], false))?;
+
+        Ok(self.build_call(intrinsic, &[stack.into()], ""))
+    }
+
+    /// Runs `scope` between a `build_stack_save` and a matching `build_stack_restore`, so that
+    /// any dynamic allocas built by `scope` are freed once it returns. This mirrors how block
+    /// scoped variable length arrays behave in C.
+    ///
+    /// The restore is built wherever the builder is positioned when `scope` returns, so
+    /// `scope` should leave it in a block which has not yet been terminated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("vla");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = context.void_type().fn_type(&[i32_type.into()], false);
+    /// let fn_value = module.add_function("vla", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let len = fn_value.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_stack_scope(|builder| {
+    ///     let vla = builder.build_array_alloca(i32_type, len, "vla");
+    ///
+    ///     builder.build_store(vla, i32_type.const_zero());
+    /// }).unwrap();
+    /// builder.build_return(None);
+    /// ```
+    pub fn build_stack_scope<F, R>(&self, scope: F) -> Result<R, &'static str>
+    where
+        F: FnOnce(&Self) -> R,
+    {
+        let stack = self.build_stack_save("")?;
+        let result = scope(self);
+
+        self.build_stack_restore(stack)?;
+
+        Ok(result)
+    }
+
+    /// Builds a `ret` instruction like `build_return`, but first checks that the builder is
+    /// positioned in a block which has no terminator yet and that `value` matches the
+    /// function's return type, returning the typed instruction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("ret");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let function = module.add_function("ret", i32_type.fn_type(&[], false), None);
+    /// let one = i32_type.const_int(1, false);
+    ///
+    /// builder.position_at_end(context.append_basic_block(function, "entry"));
+    ///
+    /// assert!(builder.build_return_checked(None).is_err());
+    ///
+    /// let ret = builder.build_return_checked(Some(&one)).unwrap();
+    ///
+    /// assert_eq!(ret.get_return_value(), Some(one.into()));
+    /// assert!(builder.build_return_checked(Some(&one)).is_err());
+    /// ```
+    pub fn build_return_checked(&self, value: Option<&dyn BasicValue<'ctx>>) -> Result<ReturnValue<'ctx>, &'static str> {
+        let function = self.check_can_terminate()?;
+        let return_type = function.get_type().get_return_type();
+
+        if return_type != value.map(|value| value.as_basic_value_enum().get_type()) {
+            return Err("The returned value does not match the function's return type.");
+        }
+
+        unsafe {
+            Ok(ReturnValue::new(self.build_return(value).as_value_ref()))
+        }
+    }
+
+    /// Builds an unconditional `br` instruction like `build_unconditional_branch`, but first
+    /// checks that the builder is positioned in a block which has no terminator yet and that
+    /// the destination belongs to the same function, returning the typed instruction.
+    pub fn build_unconditional_branch_checked(&self, destination_block: BasicBlock<'ctx>) -> Result<BranchValue<'ctx>, &'static str> {
+        let function = self.check_can_terminate()?;
+
+        if destination_block.get_parent() != Some(function) {
+            return Err("The destination block does not belong to the function being built.");
+        }
+
+        unsafe {
+            Ok(BranchValue::new(self.build_unconditional_branch(destination_block).as_value_ref()))
+        }
+    }
+
+    /// Builds a conditional `br` instruction like `build_conditional_branch`, but first
+    /// checks that the builder is positioned in a block which has no terminator yet, that
+    /// the condition is an `i1` and that both destinations belong to the same function,
+    /// returning the typed instruction.
+    pub fn build_conditional_branch_checked(
+        &self,
+        comparison: IntValue<'ctx>,
+        then_block: BasicBlock<'ctx>,
+        else_block: BasicBlock<'ctx>,
+    ) -> Result<BranchValue<'ctx>, &'static str> {
+        let function = self.check_can_terminate()?;
+
+        if comparison.get_type().get_bit_width() != 1 {
+            return Err("The branch condition must be an i1.");
+        }
+
+        if then_block.get_parent() != Some(function) || else_block.get_parent() != Some(function) {
+            return Err("The destination blocks must belong to the function being built.");
+        }
+
+        unsafe {
+            Ok(BranchValue::new(self.build_conditional_branch(comparison, then_block, else_block).as_value_ref()))
+        }
+    }
+
+    /// Builds an `unreachable` instruction like `build_unreachable`, but first checks that the
+    /// builder is positioned in a block which has no terminator yet, returning the typed instruction.
+    pub fn build_unreachable_checked(&self) -> Result<UnreachableValue<'ctx>, &'static str> {
+        self.check_can_terminate()?;
+
+        unsafe {
+            Ok(UnreachableValue::new(self.build_unreachable().as_value_ref()))
+        }
+    }
+
+    // A block may only hold a single terminator, at its end
+    fn check_can_terminate(&self) -> Result<FunctionValue<'ctx>, &'static str> {
+        let function = self.get_insert_function()?;
+        let block = self.get_insert_block().expect("Builder was just found to have an insert block");
+
+        if block.get_terminator().is_some() {
+            return Err("The current block already has a terminator.");
+        }
+
+        Ok(function)
+    }
+
+    pub(crate) fn get_insert_function(&self) -> Result<FunctionValue<'ctx>, &'static str> {
+        self.get_insert_block()
+            .and_then(|block| block.get_parent())
+            .ok_or("Builder is not positioned inside of a function.")
+    }
+
+    // Builds a call to a non-overloaded intrinsic whose parameter types are taken from `args`.
+    // This allows for operands, such as tokens, which have no typed wrapper.
+    pub(crate) unsafe fn build_raw_intrinsic_call(&self, intrinsic: &str, return_type: LLVMTypeRef, args: &[LLVMValueRef], name: &str) -> Result<LLVMValueRef, &'static str> {
+        let mut param_types: Vec<LLVMTypeRef> = args.iter().map(|&arg| LLVMTypeOf(arg)).collect();
+        let fn_type = FunctionType::new(LLVMFunctionType(return_type, param_types.as_mut_ptr(), param_types.len() as u32, false as i32));
+        let function = self.get_or_insert_intrinsic(intrinsic, fn_type)?;
+        let c_string = to_c_str(name);
+        let mut args = args.to_vec();
+
+        Ok(LLVMBuildCall(self.builder, function.as_value_ref(), args.as_mut_ptr(), args.len() as u32, c_string.as_ptr()))
+    }
+
+    // Declares a non-overloaded intrinsic in the module the builder is positioned in,
+    // or reuses the existing declaration if it has the same type.
+    fn get_or_insert_intrinsic(&self, name: &str, fn_type: FunctionType<'ctx>) -> Result<FunctionValue<'ctx>, &'static str> {
+        let function = self.get_insert_function()?;
+        let c_string = to_c_str(name);
+
+        unsafe {
+            let module = LLVMGetGlobalParent(function.as_value_ref());
+            let intrinsic = LLVMGetNamedFunction(module, c_string.as_ptr());
+
+            if intrinsic.is_null() {
+                let intrinsic = LLVMAddFunction(module, c_string.as_ptr(), fn_type.as_type_ref());
+
+                return Ok(FunctionValue::new(intrinsic).expect("Intrinsic should be a function"));
+            }
+
+            let intrinsic = FunctionValue::new(intrinsic).expect("Intrinsic should be a function");
+
+            if intrinsic.get_type() != fn_type {
+                return Err("The intrinsic is already declared with a different type.");
+            }
+
+            Ok(intrinsic)
+        }
+    }
+
+    /// Builds an atomicrmw instruction. It allows you to atomically modify memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    /// use inkwell::{AddressSpace, AtomicOrdering, AtomicRMWBinOp};
+    /// let context = Context::create();
+    /// let module = context.create_module("rmw");
+    /// let void_type = context.void_type();
+    /// let i32_type = context.i32_type();
+    /// let i32_seven = i32_type.const_int(7, false);
+    /// let i32_ptr_type = i32_type.ptr_type(AddressSpace::Generic);
+    /// let fn_type = void_type.fn_type(&[i32_ptr_type.into()], false);
+    /// let fn_value = module.add_function("rmw", fn_type, None);
+    /// let entry = context.append_basic_block(fn_value, "entry");
+    /// let i32_ptr_param = fn_value.get_first_param().unwrap().into_pointer_value();
+    /// let builder = context.create_builder();
     /// builder.position_at_end(entry);
     /// builder.build_atomicrmw(AtomicRMWBinOp::Add, i32_ptr_param, i32_seven, AtomicOrdering::Unordered);
     /// builder.build_return(None);
@@ -2273,6 +4030,133 @@ impl<'ctx> Builder<'ctx> {
     }
 }
 
+// Determines whether every index of `indices` selects a member of the nested aggregate type
+// of `agg`, so that the path helpers never leave a partial chain of instructions behind.
+fn is_valid_aggregate_path(agg: LLVMValueRef, indices: &[u32]) -> bool {
+    let mut ty = unsafe { BasicTypeEnum::new(LLVMTypeOf(agg)) };
+
+    for &index in indices {
+        ty = match ty {
+            BasicTypeEnum::ArrayType(array_type) if index < array_type.len() => array_type.get_element_type(),
+            BasicTypeEnum::StructType(struct_type) => match struct_type.get_field_type_at_index(index) {
+                Some(field_type) => field_type,
+                None => return false,
+            },
+            _ => return false,
+        };
+    }
+
+    true
+}
+
+// The memcpy, memmove and memset intrinsics all take their volatile flag as the fourth operand.
+#[llvm_versions(8.0..=latest)]
+fn set_mem_intrinsic_volatile(call: LLVMValueRef) {
+    unsafe {
+        let context = LLVMGetTypeContext(LLVMTypeOf(call));
+
+        LLVMSetOperand(call, 3, LLVMConstInt(LLVMInt1TypeInContext(context), 1, 0));
+    }
+}
+
+// Creates an i32 constant in the same context as `value`.
+#[llvm_versions(5.0..=latest)]
+fn const_i32_like(value: LLVMValueRef, constant: u32) -> LLVMValueRef {
+    unsafe {
+        LLVMConstInt(LLVMInt32TypeInContext(LLVMGetTypeContext(LLVMTypeOf(value))), constant as u64, false as i32)
+    }
+}
+
+// Gets the void type of the context `value` belongs to.
+#[llvm_versions(5.0..=latest)]
+fn void_type_like(value: LLVMValueRef) -> LLVMTypeRef {
+    unsafe {
+        LLVMVoidTypeInContext(LLVMGetTypeContext(LLVMTypeOf(value)))
+    }
+}
+
+// Gets the vector type a gather loads from, or a scatter stores to, a vector of pointers.
+#[llvm_versions(5.0..=latest)]
+fn get_pointee_vector_type(ptrs: VectorValue) -> Result<VectorType, &'static str> {
+    let ptr_type = match ptrs.get_type().get_element_type() {
+        BasicTypeEnum::PointerType(ptr_type) => ptr_type,
+        _ => return Err("Expected a vector of pointers."),
+    };
+
+    match ptr_type.get_element_type() {
+        AnyTypeEnum::IntType(_) | AnyTypeEnum::FloatType(_) | AnyTypeEnum::PointerType(_) => {},
+        _ => return Err("The pointers must point to a type which can be a vector element."),
+    }
+
+    unsafe {
+        Ok(VectorType::new(LLVMVectorType(ptr_type.get_element_type().as_type_ref(), ptrs.get_type().get_size())))
+    }
+}
+
+// The masked memory intrinsics take an i1 vector mask and a passthrough vector for the
+// disabled elements of loads, both of which must match the length of the accessed vector.
+// Expanding loads and compressing stores have no alignment operand.
+#[llvm_versions(5.0..=latest)]
+fn check_masked_operands(vector_type: VectorType, alignment: Option<u32>, mask: VectorValue, passthrough: Option<VectorValue>) -> Result<(), &'static str> {
+    if alignment.map_or(false, |alignment| !alignment.is_power_of_two()) {
+        return Err("The alignment must be a power of two.");
+    }
+
+    let mask_type = mask.get_type();
+    let is_bool_vector = match mask_type.get_element_type() {
+        BasicTypeEnum::IntType(int_type) => int_type.get_bit_width() == 1,
+        _ => false,
+    };
+
+    if !is_bool_vector || mask_type.get_size() != vector_type.get_size() {
+        return Err("The mask must be a vector of i1s with one element for each accessed element.");
+    }
+
+    if passthrough.map_or(false, |passthrough| passthrough.get_type() != vector_type) {
+        return Err("The passthrough vector must have the same type as the accessed vector.");
+    }
+
+    Ok(())
+}
+
+// Disabled elements are undefined when no passthrough vector is given.
+#[llvm_versions(5.0..=latest)]
+unsafe fn get_passthrough(vector_type: VectorType, passthrough: Option<VectorValue>) -> LLVMValueRef {
+    match passthrough {
+        Some(passthrough) => passthrough.as_value_ref(),
+        None => LLVMGetUndef(vector_type.as_type_ref()),
+    }
+}
+
+// Creates an i1 constant in the same context as `value`.
+fn const_bool_like(value: LLVMValueRef, flag: bool) -> LLVMValueRef {
+    unsafe {
+        LLVMConstInt(LLVMInt1TypeInContext(LLVMGetTypeContext(LLVMTypeOf(value))), flag as u64, false as i32)
+    }
+}
+
+// Mangles a type the same way LLVM does for the name of an overloaded intrinsic.
+unsafe fn get_intrinsic_type_suffix(type_: LLVMTypeRef) -> Result<String, &'static str> {
+    let suffix = match LLVMGetTypeKind(type_) {
+        LLVMTypeKind::LLVMHalfTypeKind => String::from("f16"),
+        LLVMTypeKind::LLVMFloatTypeKind => String::from("f32"),
+        LLVMTypeKind::LLVMDoubleTypeKind => String::from("f64"),
+        LLVMTypeKind::LLVMX86_FP80TypeKind => String::from("f80"),
+        LLVMTypeKind::LLVMFP128TypeKind => String::from("f128"),
+        LLVMTypeKind::LLVMPPC_FP128TypeKind => String::from("ppcf128"),
+        LLVMTypeKind::LLVMIntegerTypeKind => format!("i{}", LLVMGetIntTypeWidth(type_)),
+        LLVMTypeKind::LLVMVectorTypeKind => {
+            format!("v{}{}", LLVMGetVectorSize(type_), get_intrinsic_type_suffix(LLVMGetElementType(type_))?)
+        },
+        LLVMTypeKind::LLVMPointerTypeKind => {
+            format!("p{}{}", LLVMGetPointerAddressSpace(type_), get_intrinsic_type_suffix(LLVMGetElementType(type_))?)
+        },
+        _ => return Err("Intrinsics cannot be overloaded on this type."),
+    };
+
+    Ok(suffix)
+}
+
 /// Used by build_memcpy and build_memmove
 #[llvm_versions(8.0..=latest)]
 fn is_alignment_ok(align: u32) -> bool {
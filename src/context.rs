@@ -9,11 +9,13 @@ use llvm_sys::core::{LLVMCreateEnumAttribute, LLVMCreateStringAttribute};
 use llvm_sys::core::{LLVMConstInlineAsm};
 #[llvm_versions(7.0..=latest)]
 use llvm_sys::core::{LLVMGetInlineAsm};
+#[llvm_versions(9.0..=latest)]
+use llvm_sys::core::{LLVMContextSetDiscardValueNames, LLVMContextShouldDiscardValueNames};
 #[llvm_versions(12.0..=latest)]
-use llvm_sys::core::{LLVMCreateTypeAttribute};
+use llvm_sys::core::{LLVMCreateTypeAttribute, LLVMGetTypeByName2};
 #[llvm_versions(7.0..=latest)]
 use crate::InlineAsmDialect;
-use llvm_sys::prelude::{LLVMContextRef, LLVMTypeRef, LLVMValueRef, LLVMDiagnosticInfoRef};
+use llvm_sys::prelude::{LLVMContextRef, LLVMValueRef, LLVMDiagnosticInfoRef};
 use llvm_sys::ir_reader::LLVMParseIRInContext;
 use llvm_sys::target::{LLVMIntPtrTypeForASInContext, LLVMIntPtrTypeInContext};
 use libc::c_void;
@@ -29,7 +31,7 @@ use crate::memory_buffer::MemoryBuffer;
 use crate::module::Module;
 use crate::support::{to_c_str, LLVMString};
 use crate::targets::TargetData;
-use crate::types::{AnyTypeEnum, BasicTypeEnum, FloatType, IntType, StructType, VoidType, AsTypeRef, FunctionType};
+use crate::types::{AnyTypeEnum, BasicTypeEnum, FloatType, IntType, StructType, VoidType, AsTypeRef, FunctionType, with_type_refs};
 #[llvm_versions(6.0..=latest)]
 use crate::types::MetadataType;
 use crate::values::{AsValueRef, BasicMetadataValueEnum, BasicValueEnum, FunctionValue, StructValue, MetadataValue, VectorValue, PointerValue};
@@ -674,12 +676,9 @@ impl Context {
     /// ```
     // REVIEW: AnyType but VoidType? FunctionType?
     pub fn struct_type(&self, field_types: &[BasicTypeEnum], packed: bool) -> StructType {
-        let mut field_types: Vec<LLVMTypeRef> = field_types.iter()
-                                                           .map(|val| val.as_type_ref())
-                                                           .collect();
-        unsafe {
+        with_type_refs(field_types, |field_types| unsafe {
             StructType::new(LLVMStructTypeInContext(self.context, field_types.as_mut_ptr(), field_types.len() as u32, packed as i32))
-        }
+        })
     }
 
     /// Creates an opaque `StructType` with no type definition yet defined.
@@ -704,6 +703,70 @@ impl Context {
         }
     }
 
+    /// Gets the named `StructType` called `name` from this `Context`, if one has been created.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    ///
+    /// assert!(context.get_struct_type("foo").is_none());
+    ///
+    /// let opaque = context.opaque_struct_type("foo");
+    ///
+    /// assert_eq!(context.get_struct_type("foo"), Some(opaque));
+    /// ```
+    #[llvm_versions(12.0..=latest)]
+    pub fn get_struct_type<'ctx>(&'ctx self, name: &str) -> Option<StructType<'ctx>> {
+        let c_string = to_c_str(name);
+        let ty = unsafe { LLVMGetTypeByName2(self.context, c_string.as_ptr()) };
+
+        if ty.is_null() {
+            return None;
+        }
+
+        unsafe {
+            Some(StructType::new(ty))
+        }
+    }
+
+    /// Gets the named `StructType` called `name`, creating it with the given body if this
+    /// `Context` does not have one yet, or defining the body of an existing opaque one.
+    /// Fails if a struct of that name already has a different body, so that frontends can
+    /// intern their struct types by name without tracking which ones they already emitted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let i32_type = context.i32_type();
+    /// let point = context.get_or_create_named_struct("point", &[i32_type.into(), i32_type.into()], false).unwrap();
+    ///
+    /// assert_eq!(context.get_or_create_named_struct("point", &[i32_type.into(), i32_type.into()], false), Ok(point));
+    /// assert!(context.get_or_create_named_struct("point", &[i32_type.into()], false).is_err());
+    /// ```
+    #[llvm_versions(12.0..=latest)]
+    pub fn get_or_create_named_struct<'ctx>(&'ctx self, name: &str, field_types: &[BasicTypeEnum<'ctx>], packed: bool) -> Result<StructType<'ctx>, &'static str> {
+        let struct_type = match self.get_struct_type(name) {
+            Some(struct_type) => struct_type,
+            None => self.opaque_struct_type(name),
+        };
+
+        if struct_type.set_body(field_types, packed) {
+            return Ok(struct_type);
+        }
+
+        if struct_type.is_packed() != packed || struct_type.get_field_types() != field_types {
+            return Err("A struct with this name already exists with a different body.");
+        }
+
+        Ok(struct_type)
+    }
+
     /// Creates a constant `StructValue` from constant values.
     ///
     /// # Example
@@ -927,6 +990,39 @@ impl Context {
         }
     }
 
+    /// Sets whether this `Context` should discard the names of values other than globals,
+    /// such as instructions, parameters and basic blocks. Names given to those values
+    /// afterwards are silently dropped, which makes building large modules cheaper
+    /// at the cost of less readable IR.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    ///
+    /// assert!(!context.should_discard_value_names());
+    ///
+    /// context.set_discard_value_names(true);
+    ///
+    /// assert!(context.should_discard_value_names());
+    /// ```
+    #[llvm_versions(9.0..=latest)]
+    pub fn set_discard_value_names(&self, discard: bool) {
+        unsafe {
+            LLVMContextSetDiscardValueNames(self.context, discard as i32)
+        }
+    }
+
+    /// Determines whether this `Context` discards the names of values other than globals.
+    #[llvm_versions(9.0..=latest)]
+    pub fn should_discard_value_names(&self) -> bool {
+        unsafe {
+            LLVMContextShouldDiscardValueNames(self.context) == 1
+        }
+    }
+
     // LLVM 3.9+
     // pub fn get_diagnostic_handler(&self) -> DiagnosticHandler {
     //     let handler = unsafe {
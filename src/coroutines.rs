@@ -0,0 +1,283 @@
+//! Helpers for building LLVM's [coroutine intrinsics](https://llvm.org/docs/Coroutines.html).
+//!
+//! A coroutine is written as an ordinary function which uses these intrinsics to mark where its
+//! frame is allocated and where it may suspend. It is then lowered into a ramp function plus
+//! separate resume and destroy functions by running the coroutine passes, either by adding
+//! `add_coroutine_early_pass`, `add_coroutine_split_pass`, `add_coroutine_elide_pass` and
+//! `add_coroutine_cleanup_pass` to a `PassManager` in that order, or by calling
+//! `PassManagerBuilder::add_coroutine_passes_to_extension_points`. From LLVM 13 on, the same
+//! passes can also be run through LLVM's new pass manager with `run_coroutine_passes`.
+
+use llvm_sys::core::{LLVMConstInt, LLVMConstNull, LLVMGetTypeContext, LLVMInt1TypeInContext, LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMInt8TypeInContext, LLVMPointerType, LLVMTokenTypeInContext, LLVMVoidTypeInContext};
+use llvm_sys::prelude::{LLVMContextRef, LLVMValueRef};
+
+use crate::builder::Builder;
+#[llvm_versions(13.0..=latest)]
+use crate::module::Module;
+#[llvm_versions(13.0..=latest)]
+use crate::support::LLVMString;
+#[llvm_versions(13.0..=latest)]
+use crate::targets::TargetMachine;
+use crate::types::{AsTypeRef, PointerType};
+use crate::values::{AsValueRef, CallSiteValue, IntValue, PointerValue};
+
+use std::marker::PhantomData;
+
+/// The token returned by `llvm.coro.id`, which identifies a coroutine to the other intrinsics.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct CoroutineId<'ctx> {
+    id: LLVMValueRef,
+    _marker: PhantomData<&'ctx ()>,
+}
+
+// The pipeline LLVM 13's `PassBuilder` runs to lower coroutines, in which `coro-early`,
+// `coro-elide` and `coro-cleanup` are function passes and `coro-split` is a CGSCC pass.
+#[llvm_versions(13.0..=latest)]
+const COROUTINE_PIPELINE: &str = "function(coro-early),cgscc(coro-split),function(coro-elide,coro-cleanup)";
+
+/// Lowers every coroutine of `module` into its ramp, resume and destroy functions by running
+/// the coroutine passes through LLVM's new pass manager. `target_machine` is only used to
+/// look up target specific information, and may be `None`.
+#[llvm_versions(13.0..=latest)]
+pub fn run_coroutine_passes(module: &Module, target_machine: Option<&TargetMachine>) -> Result<(), LLVMString> {
+    use llvm_sys::error::{LLVMDisposeErrorMessage, LLVMGetErrorMessage};
+    use llvm_sys::transforms::pass_builder::{LLVMCreatePassBuilderOptions, LLVMDisposePassBuilderOptions, LLVMRunPasses};
+
+    use std::ffi::CStr;
+
+    let pipeline = crate::support::to_c_str(COROUTINE_PIPELINE);
+    let target_machine = target_machine.map_or(std::ptr::null_mut(), |target_machine| target_machine.target_machine);
+
+    unsafe {
+        let options = LLVMCreatePassBuilderOptions();
+        let error = LLVMRunPasses(module.module.get(), pipeline.as_ptr(), target_machine, options);
+
+        LLVMDisposePassBuilderOptions(options);
+
+        if error.is_null() {
+            return Ok(());
+        }
+
+        let message = LLVMGetErrorMessage(error);
+        let string = LLVMString::create_from_c_str(CStr::from_ptr(message));
+
+        LLVMDisposeErrorMessage(message);
+
+        Err(string)
+    }
+}
+
+/// Builds calls to the coroutine intrinsics at the position of a `Builder`.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::AddressSpace;
+/// use inkwell::context::Context;
+/// use inkwell::coroutines::CoroutineBuilder;
+///
+/// let context = Context::create();
+/// let module = context.create_module("coro");
+/// let builder = context.create_builder();
+/// let coro_builder = CoroutineBuilder::new(&builder);
+/// let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+/// let malloc_type = i8_ptr_type.fn_type(&[context.i64_type().into()], false);
+/// let malloc = module.add_function("malloc", malloc_type, None);
+/// let fn_value = module.add_function("generator", i8_ptr_type.fn_type(&[], false), None);
+/// let entry = context.append_basic_block(fn_value, "entry");
+/// let suspend = context.append_basic_block(fn_value, "suspend");
+///
+/// builder.position_at_end(entry);
+///
+/// let id = coro_builder.build_id(0, None, "id").unwrap();
+/// let size = coro_builder.build_size("size").unwrap();
+/// let frame = builder.build_call(malloc, &[size.into()], "frame").try_as_basic_value().left().unwrap();
+/// let handle = coro_builder.build_begin(id, frame.into_pointer_value(), "handle").unwrap();
+///
+/// builder.build_unconditional_branch(suspend);
+/// builder.position_at_end(suspend);
+///
+/// coro_builder.build_end(handle, false).unwrap();
+/// builder.build_return(Some(&handle));
+/// ```
+#[derive(Debug)]
+pub struct CoroutineBuilder<'a, 'ctx> {
+    builder: &'a Builder<'ctx>,
+}
+
+impl<'a, 'ctx> CoroutineBuilder<'a, 'ctx> {
+    /// Creates a `CoroutineBuilder` which inserts instructions wherever `builder` is positioned.
+    pub fn new(builder: &'a Builder<'ctx>) -> Self {
+        CoroutineBuilder {
+            builder,
+        }
+    }
+
+    /// Builds a call to `llvm.coro.id`, which identifies the coroutine. `alignment` is the
+    /// alignment of the promise, if any, and may be zero to use the ABI default.
+    pub fn build_id(&self, alignment: u32, promise: Option<PointerValue<'ctx>>, name: &str) -> Result<CoroutineId<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+            let null = LLVMConstNull(i8_ptr_type);
+            let promise = match promise {
+                Some(promise) => self.cast_to_i8_ptr(context, promise).as_value_ref(),
+                None => null,
+            };
+            let args = [LLVMConstInt(LLVMInt32TypeInContext(context), alignment as u64, 0), promise, null, null];
+            let id = self.builder.build_raw_intrinsic_call("llvm.coro.id", LLVMTokenTypeInContext(context), &args, name)?;
+
+            Ok(CoroutineId { id, _marker: PhantomData })
+        }
+    }
+
+    /// Builds a call to `llvm.coro.size.i64`, which gives the size in bytes of the coroutine frame.
+    pub fn build_size(&self, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let size = self.builder.build_raw_intrinsic_call("llvm.coro.size.i64", LLVMInt64TypeInContext(context), &[], name)?;
+
+            Ok(IntValue::new(size))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.alloc`, which gives an `i1` that is true when the frame
+    /// needs to be dynamically allocated, or false if heap allocation was elided.
+    pub fn build_alloc(&self, id: CoroutineId<'ctx>, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let alloc = self.builder.build_raw_intrinsic_call("llvm.coro.alloc", LLVMInt1TypeInContext(context), &[id.id], name)?;
+
+            Ok(IntValue::new(alloc))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.begin`, which gives the coroutine handle given the `memory`
+    /// allocated for its frame.
+    pub fn build_begin(&self, id: CoroutineId<'ctx>, memory: PointerValue<'ctx>, name: &str) -> Result<PointerValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let memory = self.cast_to_i8_ptr(context, memory);
+            let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+            let handle = self.builder.build_raw_intrinsic_call("llvm.coro.begin", i8_ptr_type, &[id.id, memory.as_value_ref()], name)?;
+
+            Ok(PointerValue::new(handle))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.free`, which gives the pointer to the frame memory that
+    /// needs to be freed, or null if heap allocation was elided.
+    pub fn build_free(&self, id: CoroutineId<'ctx>, handle: PointerValue<'ctx>, name: &str) -> Result<PointerValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let handle = self.cast_to_i8_ptr(context, handle);
+            let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+            let memory = self.builder.build_raw_intrinsic_call("llvm.coro.free", i8_ptr_type, &[id.id, handle.as_value_ref()], name)?;
+
+            Ok(PointerValue::new(memory))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.suspend`, which gives an `i8` that is -1 when the
+    /// coroutine suspends, 0 when it is resumed and 1 when it is destroyed. A final
+    /// suspend point must be the last one reached before the coroutine ends.
+    pub fn build_suspend(&self, is_final: bool, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let args = [LLVMConstNull(LLVMTokenTypeInContext(context)), LLVMConstInt(LLVMInt1TypeInContext(context), is_final as u64, 0)];
+            let state = self.builder.build_raw_intrinsic_call("llvm.coro.suspend", LLVMInt8TypeInContext(context), &args, name)?;
+
+            Ok(IntValue::new(state))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.end`, which marks where the coroutine returns to its caller
+    /// after suspending or finishing. `unwind` should be true when this is on an unwind path.
+    pub fn build_end(&self, handle: PointerValue<'ctx>, unwind: bool) -> Result<IntValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let handle = self.cast_to_i8_ptr(context, handle);
+            let args = [handle.as_value_ref(), LLVMConstInt(LLVMInt1TypeInContext(context), unwind as u64, 0)];
+            let in_ramp = self.builder.build_raw_intrinsic_call("llvm.coro.end", LLVMInt1TypeInContext(context), &args, "")?;
+
+            Ok(IntValue::new(in_ramp))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.resume`, which resumes the suspended coroutine `handle`.
+    pub fn build_resume(&self, handle: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_handle_intrinsic("llvm.coro.resume", handle)
+    }
+
+    /// Builds a call to `llvm.coro.destroy`, which destroys the suspended coroutine `handle`.
+    pub fn build_destroy(&self, handle: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        self.build_handle_intrinsic("llvm.coro.destroy", handle)
+    }
+
+    /// Builds a call to `llvm.coro.done`, which gives an `i1` that is true when the coroutine
+    /// `handle` is suspended at its final suspend point.
+    pub fn build_done(&self, handle: PointerValue<'ctx>, name: &str) -> Result<IntValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let handle = self.cast_to_i8_ptr(context, handle);
+            let done = self.builder.build_raw_intrinsic_call("llvm.coro.done", LLVMInt1TypeInContext(context), &[handle.as_value_ref()], name)?;
+
+            Ok(IntValue::new(done))
+        }
+    }
+
+    /// Builds a call to `llvm.coro.promise`, which converts between a coroutine handle and a
+    /// pointer to its promise. When `from_promise` is true, `ptr` is a promise and the handle is
+    /// returned, otherwise `ptr` is a handle and the promise is returned.
+    pub fn build_promise(&self, ptr: PointerValue<'ctx>, alignment: u32, from_promise: bool, name: &str) -> Result<PointerValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let ptr = self.cast_to_i8_ptr(context, ptr);
+            let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+            let args = [
+                ptr.as_value_ref(),
+                LLVMConstInt(LLVMInt32TypeInContext(context), alignment as u64, 0),
+                LLVMConstInt(LLVMInt1TypeInContext(context), from_promise as u64, 0),
+            ];
+            let promise = self.builder.build_raw_intrinsic_call("llvm.coro.promise", i8_ptr_type, &args, name)?;
+
+            Ok(PointerValue::new(promise))
+        }
+    }
+
+    fn build_handle_intrinsic(&self, intrinsic: &str, handle: PointerValue<'ctx>) -> Result<CallSiteValue<'ctx>, &'static str> {
+        let context = self.get_context()?;
+
+        unsafe {
+            let handle = self.cast_to_i8_ptr(context, handle);
+            let call = self.builder.build_raw_intrinsic_call(intrinsic, LLVMVoidTypeInContext(context), &[handle.as_value_ref()], "")?;
+
+            Ok(CallSiteValue::new(call))
+        }
+    }
+
+    fn cast_to_i8_ptr(&self, context: LLVMContextRef, ptr: PointerValue<'ctx>) -> PointerValue<'ctx> {
+        let i8_ptr_type = unsafe {
+            PointerType::new(LLVMPointerType(LLVMInt8TypeInContext(context), 0))
+        };
+
+        self.builder.build_pointer_cast(ptr, i8_ptr_type, "")
+    }
+
+    fn get_context(&self) -> Result<LLVMContextRef, &'static str> {
+        let function = self.builder.get_insert_function()?;
+
+        unsafe {
+            Ok(LLVMGetTypeContext(function.get_type().as_type_ref()))
+        }
+    }
+}
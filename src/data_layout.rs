@@ -35,6 +35,51 @@ impl DataLayout {
             LLVMStringOrRaw::Borrowed(ptr) => ptr,
         }
     }
+
+    /// Gets the name a global called `name` has in the symbol table of an object file built
+    /// with this `DataLayout`, which is the name JITs look symbols up by. This applies the
+    /// same rules as LLVM's `Mangler`: the global prefix of the mangling mode (`m:`) is
+    /// prepended, which is an underscore for MachO and 32 bit Windows (`m:x`) and nothing otherwise.
+    /// Names starting with `\1` are used verbatim without it, as are names starting with
+    /// `?` on Windows, which are already mangled by MSVC's C++ ABI.
+    ///
+    /// This does not need a target or JIT to be initialized, so it can be used to precompute
+    /// symbol names ahead of time. Calling convention based decorations, such as the `@N`
+    /// suffix of `stdcall` functions on 32 bit Windows, are not applied.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::targets::TargetData;
+    ///
+    /// let macho = TargetData::create("e-m:o-i64:64-f80:128-n8:16:32:64-S128");
+    /// let elf = TargetData::create("e-m:e-i64:64-f80:128-n8:16:32:64-S128");
+    ///
+    /// assert_eq!(macho.get_data_layout().get_mangled_name("main"), "_main");
+    /// assert_eq!(elf.get_data_layout().get_mangled_name("main"), "main");
+    /// ```
+    pub fn get_mangled_name(&self, name: &str) -> String {
+        if name.starts_with('\u{1}') {
+            return name[1..].to_string();
+        }
+
+        let mangling_mode = self.as_str()
+            .to_string_lossy()
+            .split('-')
+            .find(|spec| spec.starts_with("m:"))
+            .and_then(|spec| spec[2..].chars().next());
+        let is_windows = mangling_mode == Some('w') || mangling_mode == Some('x');
+        let global_prefix = match mangling_mode {
+            Some('o') | Some('x') => "_",
+            _ => "",
+        };
+
+        if is_windows && name.starts_with('?') {
+            return name.to_string();
+        }
+
+        format!("{}{}", global_prefix, name)
+    }
 }
 
 impl PartialEq for DataLayout {
@@ -0,0 +1,98 @@
+//! A `DeclarationCache` memoizes function declarations, such as intrinsics and
+//! runtime functions, which a frontend looks up over and over while emitting code.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::module::{Linkage, Module};
+use crate::types::FunctionType;
+use crate::values::FunctionValue;
+
+/// Memoizes the functions declared in a `Module` by name, so that repeatedly asking for
+/// the same declaration does not need to search the module each time.
+///
+/// The cache does not notice functions being deleted or renamed behind its back; call
+/// `clear` after doing so.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::declaration_cache::DeclarationCache;
+///
+/// let context = Context::create();
+/// let module = context.create_module("my_mod");
+/// let cache = DeclarationCache::new(&module);
+/// let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+/// let malloc_type = i8_ptr_type.fn_type(&[context.i64_type().into()], false);
+///
+/// let malloc = cache.get_or_declare("malloc", malloc_type).unwrap();
+///
+/// assert_eq!(cache.get_or_declare("malloc", malloc_type), Ok(malloc));
+/// assert_eq!(module.get_function("malloc"), Some(malloc));
+/// ```
+#[derive(Debug)]
+pub struct DeclarationCache<'m, 'ctx> {
+    module: &'m Module<'ctx>,
+    declarations: RefCell<HashMap<String, FunctionValue<'ctx>>>,
+}
+
+impl<'m, 'ctx> DeclarationCache<'m, 'ctx> {
+    /// Creates an empty cache for the functions of `module`.
+    pub fn new(module: &'m Module<'ctx>) -> Self {
+        DeclarationCache {
+            module,
+            declarations: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the `Module` whose functions are cached.
+    pub fn get_module(&self) -> &'m Module<'ctx> {
+        self.module
+    }
+
+    /// Gets the function called `name`, declaring it with external linkage if the module does
+    /// not contain it yet. Fails if a function of that name exists with a different type.
+    pub fn get_or_declare(&self, name: &str, fn_type: FunctionType<'ctx>) -> Result<FunctionValue<'ctx>, &'static str> {
+        if let Some(function) = self.declarations.borrow().get(name) {
+            return check_type(*function, fn_type);
+        }
+
+        let function = match self.module.get_function(name) {
+            Some(function) => check_type(function, fn_type)?,
+            None => self.module.add_function(name, fn_type, Some(Linkage::External)),
+        };
+
+        self.declarations.borrow_mut().insert(name.to_string(), function);
+
+        Ok(function)
+    }
+
+    /// Gets the function called `name` if it has been looked up through this cache before.
+    pub fn get_cached(&self, name: &str) -> Option<FunctionValue<'ctx>> {
+        self.declarations.borrow().get(name).copied()
+    }
+
+    /// Gets the number of functions held by this cache.
+    pub fn len(&self) -> usize {
+        self.declarations.borrow().len()
+    }
+
+    /// Determines whether or not this cache holds any functions.
+    pub fn is_empty(&self) -> bool {
+        self.declarations.borrow().is_empty()
+    }
+
+    /// Forgets every cached function, which is necessary after deleting or renaming any of them.
+    pub fn clear(&self) {
+        self.declarations.borrow_mut().clear()
+    }
+}
+
+fn check_type<'ctx>(function: FunctionValue<'ctx>, fn_type: FunctionType<'ctx>) -> Result<FunctionValue<'ctx>, &'static str> {
+    if function.get_type() != fn_type {
+        return Err("A function with this name has already been declared with a different type.");
+    }
+
+    Ok(function)
+}
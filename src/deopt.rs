@@ -0,0 +1,271 @@
+//! Deoptimization scaffolding for tiered JITs.
+//!
+//! A tiered JIT usually runs a function through a simple baseline implementation first, and
+//! later compiles an optimized version of it under speculative assumptions, such as a value's
+//! type or range. This module provides the pieces needed to back out of such speculation:
+//!
+//! * `build_guard` checks an assumption inside optimized code. When it does not hold,
+//!   execution leaves the optimized code through a *deopt exit* built by `build_deopt_exit`.
+//! * A deopt exit records the live values in the stack map section under an id, so a runtime
+//!   can inspect them through `stack_map::StackMap`, and then continues in a baseline
+//!   *continuation*: a function which takes the live values as its parameters and finishes the
+//!   computation the same way the unoptimized code would have.
+//! * A `TieredFunction` gives a function a stable entry point which calls through a slot. The
+//!   runtime installs optimized code into the slot once it is compiled, and reverts it to the
+//!   baseline when its guards fail too often, without having to relink any callers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use inkwell::OptimizationLevel;
+//! use inkwell::context::Context;
+//! use inkwell::deopt::{build_guard, TieredFunction};
+//! use inkwell::targets::{InitializationConfig, Target};
+//!
+//! Target::initialize_native(&InitializationConfig::default()).unwrap();
+//!
+//! let context = Context::create();
+//! let module = context.create_module("tiered");
+//! let builder = context.create_builder();
+//! let i64_type = context.i64_type();
+//! let fn_type = i64_type.fn_type(&[i64_type.into()], false);
+//!
+//! // The baseline computes x * 2 with overflow checks omitted for brevity
+//! let baseline = module.add_function("double.baseline", fn_type, None);
+//! builder.position_at_end(context.append_basic_block(baseline, "entry"));
+//! let x = baseline.get_first_param().unwrap().into_int_value();
+//! builder.build_return(Some(&builder.build_int_mul(x, i64_type.const_int(2, false), "doubled")));
+//!
+//! // The optimized version speculates that x is small, and deoptimizes otherwise
+//! let optimized = module.add_function("double.optimized", fn_type, None);
+//! builder.position_at_end(context.append_basic_block(optimized, "entry"));
+//! let x = optimized.get_first_param().unwrap().into_int_value();
+//! let is_small = builder.build_int_compare(inkwell::IntPredicate::ULT, x, i64_type.const_int(1 << 16, false), "is_small");
+//! build_guard(&builder, is_small, 1, baseline, &[x.into()]).unwrap();
+//! builder.build_return(Some(&builder.build_left_shift(x, i64_type.const_int(1, false), "doubled")));
+//!
+//! let double = TieredFunction::create(&module, "double", baseline).unwrap();
+//! let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+//!
+//! double.install(&execution_engine, optimized).unwrap();
+//! ```
+
+use llvm_sys::core::{LLVMAppendBasicBlockInContext, LLVMGetTypeContext};
+
+#[llvm_versions(3.8..=latest)]
+use crate::{AddressSpace, AtomicOrdering};
+use crate::basic_block::BasicBlock;
+use crate::builder::Builder;
+#[llvm_versions(3.8..=latest)]
+use crate::execution_engine::ExecutionEngine;
+#[llvm_versions(3.8..=latest)]
+use crate::module::Module;
+use crate::support::to_c_str;
+use crate::types::AsTypeRef;
+use crate::values::{AsValueRef, BasicMetadataValueEnum, BasicValueEnum, FunctionValue, InstructionValue, IntValue};
+#[llvm_versions(3.8..=latest)]
+use crate::values::{CallableValue, GlobalValue};
+
+#[llvm_versions(3.8..=latest)]
+use std::convert::TryFrom;
+#[llvm_versions(3.8..=latest)]
+use std::mem::align_of;
+#[llvm_versions(3.8..=latest)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Leaves the function the builder is positioned in through a deopt exit: the live values are
+/// recorded in a stack map under `id`, after which `continuation` is tail called with them and
+/// its result returned. `continuation` must take exactly the types of `live_values` as its
+/// parameters, and return the same type as the function being left.
+///
+/// Returns the `ret` instruction terminating the current block.
+pub fn build_deopt_exit<'ctx>(builder: &Builder<'ctx>, id: u64, continuation: FunctionValue<'ctx>, live_values: &[BasicValueEnum<'ctx>]) -> Result<InstructionValue<'ctx>, &'static str> {
+    let function = builder.get_insert_function()?;
+
+    check_continuation(function, continuation, live_values)?;
+    builder.build_stackmap(id, 0, live_values)?;
+
+    let args: Vec<BasicMetadataValueEnum> = live_values.iter().map(|&value| value.into()).collect();
+    let call = builder.build_call(continuation, &args, "deopt");
+
+    call.set_tail_call(true);
+
+    let ret = match call.try_as_basic_value().left() {
+        Some(value) => builder.build_return(Some(&value)),
+        None => builder.build_return(None),
+    };
+
+    Ok(ret)
+}
+
+/// Checks that `condition` holds, branching to a new block with a deopt exit built by
+/// `build_deopt_exit` when it doesn't. The builder is left positioned at the end of a new
+/// block in which `condition` is known to be true.
+pub fn build_guard<'ctx>(builder: &Builder<'ctx>, condition: IntValue<'ctx>, id: u64, continuation: FunctionValue<'ctx>, live_values: &[BasicValueEnum<'ctx>]) -> Result<(), &'static str> {
+    if condition.get_type().get_bit_width() != 1 {
+        return Err("Guard condition must be an i1.");
+    }
+
+    let function = builder.get_insert_function()?;
+
+    check_continuation(function, continuation, live_values)?;
+
+    let append_block = |name: &str| {
+        let c_string = to_c_str(name);
+
+        unsafe {
+            let context = LLVMGetTypeContext(condition.get_type().as_type_ref());
+
+            BasicBlock::new(LLVMAppendBasicBlockInContext(context, function.as_value_ref(), c_string.as_ptr())).expect("Appended block should be valid")
+        }
+    };
+    let guarded_block = append_block("guarded");
+    let deopt_block = append_block("deopt");
+
+    builder.build_conditional_branch(condition, guarded_block, deopt_block);
+    builder.position_at_end(deopt_block);
+    build_deopt_exit(builder, id, continuation, live_values)?;
+    builder.position_at_end(guarded_block);
+
+    Ok(())
+}
+
+fn check_continuation<'ctx>(function: FunctionValue<'ctx>, continuation: FunctionValue<'ctx>, live_values: &[BasicValueEnum<'ctx>]) -> Result<(), &'static str> {
+    let continuation_type = continuation.get_type();
+    let param_types = continuation_type.get_param_types();
+
+    if param_types.len() != live_values.len() || param_types.iter().zip(live_values).any(|(ty, value)| *ty != value.get_type()) {
+        return Err("Live values do not match the continuation's parameters.");
+    }
+
+    if continuation_type.get_return_type() != function.get_type().get_return_type() {
+        return Err("Continuation must return the same type as the function being deoptimized.");
+    }
+
+    Ok(())
+}
+
+/// A function whose callers go through a stable entry point, which calls whichever
+/// implementation is currently installed in its slot. Requires LLVM 3.8+, which is needed to
+/// read the slot atomically.
+#[llvm_versions(3.8..=latest)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieredFunction<'ctx> {
+    entry: FunctionValue<'ctx>,
+    slot: GlobalValue<'ctx>,
+    baseline: FunctionValue<'ctx>,
+}
+
+#[llvm_versions(3.8..=latest)]
+impl<'ctx> TieredFunction<'ctx> {
+    /// Defines the entry point `name` in `module`, with the same type as `baseline`, along
+    /// with a `"{name}.slot"` global holding the implementation to call, initially `baseline`.
+    pub fn create(module: &Module<'ctx>, name: &str, baseline: FunctionValue<'ctx>) -> Result<Self, &'static str> {
+        let baseline_name = baseline.get_name().to_str().map_err(|_| "Baseline function name is not valid UTF-8.")?;
+
+        if module.get_function(baseline_name) != Some(baseline) {
+            return Err("Baseline function must belong to the module.");
+        }
+
+        if module.get_function(name).is_some() {
+            return Err("Function is already defined.");
+        }
+
+        let fn_type = baseline.get_type();
+        let slot = module.add_global(fn_type.ptr_type(AddressSpace::Generic), None, &format!("{}.slot", name));
+
+        slot.set_initializer(&baseline.as_global_value().as_pointer_value());
+
+        let entry = module.add_function(name, fn_type, None);
+        let context = module.get_context();
+        let builder = context.create_builder();
+        let block = context.append_basic_block(entry, "entry");
+
+        builder.position_at_end(block);
+
+        // The slot is swapped by the host through an AtomicUsize, so it must be read atomically
+        // too, which also keeps LLVM from hoisting or merging the load
+        let alignment = align_of::<AtomicUsize>() as u32;
+
+        slot.set_alignment(alignment);
+
+        let target = builder.build_load(slot.as_pointer_value(), "target").into_pointer_value();
+        let load = target.as_instruction().expect("Slot should be read by a load");
+
+        load.set_alignment(alignment).expect("Pointer alignment should be valid");
+        load.set_atomic_ordering(AtomicOrdering::Acquire).expect("Acquire should be valid on a load");
+
+        let target = CallableValue::try_from(target).expect("Slot should hold a function pointer");
+        let args: Vec<BasicMetadataValueEnum> = entry.get_param_iter().map(|param| param.into()).collect();
+        let call = builder.build_call(target, &args, "");
+
+        call.set_tail_call(true);
+
+        match call.try_as_basic_value().left() {
+            Some(value) => builder.build_return(Some(&value)),
+            None => builder.build_return(None),
+        };
+
+        Ok(TieredFunction { entry, slot, baseline })
+    }
+
+    /// Gets the entry point which callers should call.
+    pub fn get_entry(self) -> FunctionValue<'ctx> {
+        self.entry
+    }
+
+    /// Gets the global holding the address of the installed implementation.
+    pub fn get_slot(self) -> GlobalValue<'ctx> {
+        self.slot
+    }
+
+    /// Gets the baseline implementation.
+    pub fn get_baseline(self) -> FunctionValue<'ctx> {
+        self.baseline
+    }
+
+    /// Makes the entry point call `function`, compiling it first if necessary. Calls which are
+    /// already running are unaffected.
+    pub fn install(self, execution_engine: &ExecutionEngine<'ctx>, function: FunctionValue<'ctx>) -> Result<(), &'static str> {
+        if function.get_type() != self.baseline.get_type() {
+            return Err("Function type does not match the baseline's.");
+        }
+
+        let name = function.get_name().to_str().map_err(|_| "Function name is not valid UTF-8.")?;
+        let address = execution_engine.get_function_address(name)
+            .map_err(|_| "Function is not compiled by this ExecutionEngine.")?;
+
+        self.install_address(execution_engine, address)
+    }
+
+    /// Makes the entry point call the baseline implementation again, for instance after
+    /// the guards of the installed implementation failed too often.
+    pub fn revert(self, execution_engine: &ExecutionEngine<'ctx>) -> Result<(), &'static str> {
+        self.install(execution_engine, self.baseline)
+    }
+
+    /// Gets the address of the implementation the entry point currently calls.
+    pub fn get_installed_address(self, execution_engine: &ExecutionEngine<'ctx>) -> Result<usize, &'static str> {
+        let slot = self.get_slot_ptr(execution_engine)?;
+
+        unsafe {
+            Ok((*slot).load(Ordering::Acquire))
+        }
+    }
+
+    fn install_address(self, execution_engine: &ExecutionEngine<'ctx>, address: usize) -> Result<(), &'static str> {
+        let slot = self.get_slot_ptr(execution_engine)?;
+
+        unsafe {
+            (*slot).store(address, Ordering::Release);
+        }
+
+        Ok(())
+    }
+
+    fn get_slot_ptr(self, execution_engine: &ExecutionEngine<'ctx>) -> Result<*const AtomicUsize, &'static str> {
+        execution_engine.get_global::<usize>(self.slot)
+            .map(|slot| slot as *const AtomicUsize)
+            .map_err(|_| "Slot is not compiled by this ExecutionEngine.")
+    }
+}
@@ -1,18 +1,30 @@
 use libc::c_int;
-use llvm_sys::execution_engine::{LLVMGetExecutionEngineTargetData, LLVMExecutionEngineRef, LLVMRunFunction, LLVMRunFunctionAsMain, LLVMDisposeExecutionEngine, LLVMGetFunctionAddress, LLVMAddModule, LLVMFindFunction, LLVMLinkInMCJIT, LLVMLinkInInterpreter, LLVMRemoveModule, LLVMGenericValueRef, LLVMFreeMachineCodeForFunction, LLVMAddGlobalMapping, LLVMRunStaticConstructors, LLVMRunStaticDestructors};
+use llvm_sys::execution_engine::{LLVMGetExecutionEngineTargetData, LLVMExecutionEngineRef, LLVMRunFunction, LLVMRunFunctionAsMain, LLVMDisposeExecutionEngine, LLVMGetFunctionAddress, LLVMGetGlobalValueAddress, LLVMAddModule, LLVMFindFunction, LLVMLinkInMCJIT, LLVMLinkInInterpreter, LLVMRemoveModule, LLVMGenericValueRef, LLVMFreeMachineCodeForFunction, LLVMAddGlobalMapping, LLVMRunStaticConstructors, LLVMRunStaticDestructors};
 
+use llvm_sys::core::{LLVMGetGlobalParent, LLVMGetModuleIdentifier};
+
+use crate::abi::{AbiTarget, LoweredSignature};
 use crate::context::Context;
 use crate::module::Module;
 use crate::support::{to_c_str, LLVMString};
-use crate::targets::TargetData;
-use crate::values::{AnyValue, AsValueRef, FunctionValue, GenericValue};
+use crate::targets::{TargetData, TargetMachine};
+use crate::types::{AsTypeRef, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
+use crate::values::{AnyValue, AsValueRef, BasicMetadataValueEnum, FunctionValue, GenericValue, GlobalValue};
+use crate::AddressSpace;
 
+use std::any::Any;
+use std::cell::RefCell;
 use std::error::Error;
+use std::ffi::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::process::abort;
 use std::rc::Rc;
 use std::ops::Deref;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::marker::PhantomData;
-use std::mem::{forget, transmute_copy, size_of, MaybeUninit};
+use std::mem::{forget, transmute_copy, align_of, size_of, MaybeUninit};
+use std::slice;
+use std::time::{Duration, Instant};
 
 static EE_INNER_PANIC: &str = "ExecutionEngineInner should exist until Drop";
 
@@ -20,6 +32,7 @@ static EE_INNER_PANIC: &str = "ExecutionEngineInner should exist until Drop";
 pub enum FunctionLookupError {
     JITNotEnabled,
     FunctionNotFound, // 404!
+    SignatureMismatch,
 }
 
 impl Error for FunctionLookupError {}
@@ -29,6 +42,7 @@ impl FunctionLookupError {
         match self {
             FunctionLookupError::JITNotEnabled => "ExecutionEngine does not have JIT functionality enabled",
             FunctionLookupError::FunctionNotFound => "Function not found in ExecutionEngine",
+            FunctionLookupError::SignatureMismatch => "Function type does not match the requested signature",
         }
     }
 }
@@ -39,6 +53,33 @@ impl Display for FunctionLookupError {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum GlobalLookupError {
+    JITNotEnabled,
+    GlobalNotFound,
+    SizeMismatch,
+    Misaligned,
+}
+
+impl Error for GlobalLookupError {}
+
+impl GlobalLookupError {
+    fn as_str(&self) -> &str {
+        match self {
+            GlobalLookupError::JITNotEnabled => "ExecutionEngine does not have JIT functionality enabled",
+            GlobalLookupError::GlobalNotFound => "Global not found in ExecutionEngine",
+            GlobalLookupError::SizeMismatch => "Global type size does not match the requested type",
+            GlobalLookupError::Misaligned => "Global is not sufficiently aligned for the requested type",
+        }
+    }
+}
+
+impl Display for GlobalLookupError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "GlobalLookupError({})", self.as_str())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum RemoveModuleError {
     ModuleNotOwned,
@@ -87,6 +128,7 @@ pub struct ExecutionEngine<'ctx> {
     execution_engine: Option<ExecEngineInner<'ctx>>,
     target_data: Option<TargetData>,
     jit_mode: bool,
+    lookup_tracer: Option<LookupTracer>,
 }
 
 impl<'ctx> ExecutionEngine<'ctx> {
@@ -100,9 +142,10 @@ impl<'ctx> ExecutionEngine<'ctx> {
         let target_data = LLVMGetExecutionEngineTargetData(*execution_engine);
 
         ExecutionEngine {
-            execution_engine: Some(ExecEngineInner(execution_engine, PhantomData)),
+            execution_engine: Some(ExecEngineInner(execution_engine, PhantomData, HostFunctions::default())),
             target_data: Some(TargetData::new(target_data)),
             jit_mode,
+            lookup_tracer: None,
         }
     }
 
@@ -178,6 +221,100 @@ impl<'ctx> ExecutionEngine<'ctx> {
         }
     }
 
+    /// Exposes a Rust closure to JIT'd code as the function `name` in `module`, so that host
+    /// functionality doesn't need a hand written `extern "C"` wrapper.
+    ///
+    /// The signature is taken from the closure's parameter and return types, which must be
+    /// `JitType`s (or `()` for the return type). If `module` already declares `name`, that
+    /// declaration must have the same type and gets defined; otherwise a new function is added.
+    /// The closure is called through a C ABI shim and stays alive as long as the engine does.
+    /// A panic in the closure aborts the process, since it cannot unwind through JIT'd code.
+    /// `AbiSafe` structs should be taken and returned behind pointers, as the shim is called
+    /// with the types from `JitSignature::fn_type` rather than the C ABI lowering.
+    ///
+    /// `module` must be owned by this `ExecutionEngine` and should not have had any of its
+    /// functions looked up yet, since MCJIT does not compile functions added after that.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use inkwell::OptimizationLevel;
+    /// use inkwell::context::Context;
+    /// use inkwell::targets::{InitializationConfig, Target};
+    ///
+    /// Target::initialize_native(&InitializationConfig::default()).unwrap();
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("host");
+    /// let ee = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    /// let offset = 10;
+    ///
+    /// ee.add_host_function(&module, "add_offset", move |x: u32| x + offset).unwrap();
+    ///
+    /// unsafe {
+    ///     let add_offset = ee.get_function::<unsafe extern "C" fn(u32) -> u32>("add_offset").unwrap();
+    ///
+    ///     assert_eq!(add_offset.call(32), 42);
+    /// }
+    /// ```
+    pub fn add_host_function<Args, F>(&self, module: &Module<'ctx>, name: &str, function: F) -> Result<FunctionValue<'ctx>, &'static str>
+    where
+        F: HostFunction<Args>,
+    {
+        match *module.owned_by_ee.borrow() {
+            Some(ref ee) if Rc::ptr_eq(ee.execution_engine_rc(), self.execution_engine_rc()) => {},
+            _ => return Err("Module is not owned by this ExecutionEngine."),
+        }
+
+        let context = module.get_context();
+        let target_data = self.get_target_data();
+        // The types belong to the module's context and so live as long as 'ctx
+        let fn_type = unsafe { FunctionType::new(F::fn_type(&context, target_data).as_type_ref()) };
+        let wrapper = match module.get_function(name) {
+            Some(wrapper) if wrapper.count_basic_blocks() > 0 => return Err("Function is already defined."),
+            Some(wrapper) if wrapper.get_type() != fn_type => return Err("Function is declared with a different type."),
+            Some(wrapper) => wrapper,
+            None => module.add_function(name, fn_type, None),
+        };
+
+        // The shim takes the closure's environment as an extra first parameter
+        let env_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+        let mut shim_param_types: Vec<BasicMetadataTypeEnum> = vec![env_ptr_type.into()];
+
+        shim_param_types.extend(fn_type.get_param_types().into_iter().map(|ty| ty.into()));
+
+        let shim_type = match fn_type.get_return_type() {
+            Some(return_type) => return_type.fn_type(&shim_param_types, false),
+            None => context.void_type().fn_type(&shim_param_types, false),
+        };
+        let shim_type = unsafe { FunctionType::new(shim_type.as_type_ref()) };
+        let shim = module.add_function(&format!("{}.shim", name), shim_type, None);
+
+        self.add_global_mapping(&shim, F::shim_address());
+
+        let env = Box::new(function);
+        let env_address = &*env as *const F as u64;
+
+        self.execution_engine.as_ref().expect(EE_INNER_PANIC).2.push(env);
+
+        let builder = context.create_builder();
+        let entry = context.append_basic_block(wrapper, "entry");
+        let env_ptr = context.ptr_sized_int_type(target_data, None)
+            .const_int(env_address, false)
+            .const_to_pointer(env_ptr_type);
+        let mut args: Vec<BasicMetadataValueEnum> = vec![env_ptr.into()];
+
+        args.extend(wrapper.get_param_iter().map(|param| param.into()));
+        builder.position_at_end(entry);
+
+        match builder.build_call(shim, &args, "").try_as_basic_value().left() {
+            Some(return_value) => builder.build_return(Some(&return_value)),
+            None => builder.build_return(None),
+        };
+
+        Ok(wrapper)
+    }
+
     /// Adds a module to an `ExecutionEngine`.
     ///
     /// The method will be `Ok(())` if the module does not belong to an `ExecutionEngine` already and `Err(())` otherwise.
@@ -206,6 +343,8 @@ impl<'ctx> ExecutionEngine<'ctx> {
 
         *module.owned_by_ee.borrow_mut() = Some(self.clone());
 
+        instrument_event!("added module {:?} to execution engine", module.get_name());
+
         Ok(())
     }
 
@@ -235,6 +374,8 @@ impl<'ctx> ExecutionEngine<'ctx> {
         module.module.set(new_module);
         *module.owned_by_ee.borrow_mut() = None;
 
+        instrument_event!("removed module {:?} from execution engine", module.get_name());
+
         Ok(())
     }
 
@@ -316,12 +457,137 @@ impl<'ctx> ExecutionEngine<'ctx> {
         })
     }
 
+    /// Loads a function from the execution engine like `get_function`, but first checks that its
+    /// type in the IR matches the LLVM type of `F`, returning `FunctionLookupError::SignatureMismatch`
+    /// otherwise.
+    ///
+    /// On hosts supported by the `abi` module the expected type is the C ABI lowering from
+    /// `JitSignature::lower_signature`, which only differs from `JitSignature::fn_type` when
+    /// `AbiSafe` structs are passed by value. Elsewhere it is `JitSignature::fn_type`. Declaring
+    /// the function with the same method keeps the IR and the Rust signature in sync.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use inkwell::targets::{InitializationConfig, Target};
+    /// # use inkwell::context::Context;
+    /// # use inkwell::OptimizationLevel;
+    /// use inkwell::execution_engine::JitSignature;
+    ///
+    /// # Target::initialize_native(&InitializationConfig::default()).unwrap();
+    /// type AddOne = unsafe extern "C" fn(u64) -> u64;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("test");
+    /// let builder = context.create_builder();
+    /// let ee = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    ///
+    /// let fn_type = AddOne::fn_type(&context, ee.get_target_data());
+    /// let function = module.add_function("add_one", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let x = function.get_first_param().unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_return(Some(&builder.build_int_add(x, context.i64_type().const_int(1, false), "sum")));
+    ///
+    /// unsafe {
+    ///     let add_one = ee.get_checked_function::<AddOne>("add_one").unwrap();
+    ///
+    ///     assert_eq!(add_one.call(41), 42);
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The same as for `get_function`, except that the signature is checked.
+    pub unsafe fn get_checked_function<F>(&self, fn_name: &str) -> Result<JitFunction<'ctx, F>, FunctionLookupError>
+    where
+        F: JitSignature,
+    {
+        let function = self.get_function_value(fn_name)?;
+        let fn_type = function.get_type();
+        let context = fn_type.get_context();
+        let target_data = self.get_target_data();
+        let expected_type = match AbiTarget::from_triple(&TargetMachine::get_default_triple()) {
+            Some(target) => F::lower_signature(&context, target_data, target).get_function_type(),
+            None => F::fn_type(&context, target_data),
+        };
+
+        if fn_type != expected_type {
+            return Err(FunctionLookupError::SignatureMismatch);
+        }
+
+        self.get_function(fn_name)
+    }
+
     /// Attempts to look up a function's address by its name. May return Err if the function cannot be
     /// found or some other unknown error has occurred.
     ///
     /// It is recommended to use `get_function` instead of this method when intending to call the function
     /// pointer so that you don't have to do error-prone transmutes yourself.
     pub fn get_function_address(&self, fn_name: &str) -> Result<usize, FunctionLookupError> {
+        let tracer = match self.lookup_tracer {
+            Some(ref tracer) => tracer,
+            None => return self.find_function_address(fn_name),
+        };
+
+        let start = Instant::now();
+        let result = self.find_function_address(fn_name);
+        let duration = start.elapsed();
+        let module_name = self.get_function_value(fn_name).ok().map(|function| unsafe {
+            let mut len = 0;
+            let module = LLVMGetGlobalParent(function.as_value_ref());
+            let name = LLVMGetModuleIdentifier(module, &mut len);
+
+            String::from_utf8_lossy(slice::from_raw_parts(name as *const u8, len)).into_owned()
+        });
+
+        (tracer.0)(&SymbolLookup {
+            name: fn_name,
+            address: result.as_ref().ok().copied(),
+            duration,
+            module_name: module_name.as_deref(),
+        });
+
+        result
+    }
+
+    /// Calls `tracer` after every lookup of a function address through this `ExecutionEngine`,
+    /// including the lookups made by `get_function`, with the name looked up, the address it
+    /// resolved to, how long resolving it took and which module defined it. As the first
+    /// lookup of a function in a lazily compiling engine triggers its compilation, this is
+    /// useful to diagnose slow symbol resolution. Clones of this `ExecutionEngine` made
+    /// afterwards share the same tracer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use inkwell::OptimizationLevel;
+    /// use inkwell::context::Context;
+    /// use inkwell::targets::{InitializationConfig, Target};
+    ///
+    /// Target::initialize_native(&InitializationConfig::default()).unwrap();
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("traced");
+    /// let mut execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    ///
+    /// execution_engine.set_lookup_tracer(|lookup| {
+    ///     eprintln!("{} -> {:?} in {:?}", lookup.get_name(), lookup.get_address(), lookup.get_duration());
+    /// });
+    ///
+    /// assert!(execution_engine.get_function_address("missing").is_err());
+    /// ```
+    pub fn set_lookup_tracer<F: Fn(&SymbolLookup) + 'static>(&mut self, tracer: F) {
+        self.lookup_tracer = Some(LookupTracer(Rc::new(tracer)));
+    }
+
+    /// Stops calling the tracer set by `set_lookup_tracer`.
+    pub fn clear_lookup_tracer(&mut self) {
+        self.lookup_tracer = None;
+    }
+
+    fn find_function_address(&self, fn_name: &str) -> Result<usize, FunctionLookupError> {
         // LLVMGetFunctionAddress segfaults in llvm 5.0 -> 8.0 when fn_name doesn't exist. This is a workaround
         // to see if it exists and avoid the segfault when it doesn't
         #[cfg(any(feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0", feature = "llvm8-0"))]
@@ -337,12 +603,83 @@ impl<'ctx> ExecutionEngine<'ctx> {
         // initialized (maybe we could figure out which config in particular is the trigger)
         // and if not return an "NoTargetsInitialized" error, instead of not found.
         if address == 0 {
+            instrument_event!("function {:?} not found in execution engine", fn_name);
+
             return Err(FunctionLookupError::FunctionNotFound);
         }
 
+        instrument_event!("resolved function {:?} to {:#x}", fn_name, address);
+
         Ok(address as usize)
     }
 
+    /// Attempts to look up the address of a JIT compiled global variable by its name, compiling
+    /// its module first if necessary.
+    pub fn get_global_address(&self, name: &str) -> Result<*mut u8, GlobalLookupError> {
+        if !self.jit_mode {
+            return Err(GlobalLookupError::JITNotEnabled);
+        }
+
+        let c_string = to_c_str(name);
+        let address = unsafe {
+            LLVMGetGlobalValueAddress(self.execution_engine_inner(), c_string.as_ptr())
+        };
+
+        if address == 0 {
+            instrument_event!("global {:?} not found in execution engine", name);
+
+            return Err(GlobalLookupError::GlobalNotFound);
+        }
+
+        instrument_event!("resolved global {:?} to {:#x}", name, address);
+
+        Ok(address as *mut u8)
+    }
+
+    /// Looks up the JIT compiled storage of `global` as a `T`, checking that the size of the
+    /// global's IR type matches `T` and that its address is suitably aligned, so JIT'd counters
+    /// and tables can be read and written from the host.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use inkwell::OptimizationLevel;
+    /// use inkwell::context::Context;
+    /// use inkwell::targets::{InitializationConfig, Target};
+    ///
+    /// Target::initialize_native(&InitializationConfig::default()).unwrap();
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("counters");
+    /// let i64_type = context.i64_type();
+    /// let counters = module.add_global(i64_type.array_type(4), None, "counters");
+    ///
+    /// counters.set_initializer(&i64_type.array_type(4).const_zero());
+    ///
+    /// let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    /// let counters = execution_engine.get_global::<[u64; 4]>(counters).unwrap();
+    ///
+    /// unsafe {
+    ///     (*counters)[1] += 1;
+    /// }
+    /// ```
+    pub fn get_global<T>(&self, global: GlobalValue<'ctx>) -> Result<*mut T, GlobalLookupError> {
+        let value_type = global.as_pointer_value().get_type().get_element_type();
+
+        if self.get_target_data().get_abi_size(&value_type) != size_of::<T>() as u64 {
+            return Err(GlobalLookupError::SizeMismatch);
+        }
+
+        let name = global.get_name().to_str().map_err(|_| GlobalLookupError::GlobalNotFound)?;
+        let address = self.get_global_address(name)?;
+
+        if address as usize % align_of::<T>() != 0 {
+            return Err(GlobalLookupError::Misaligned);
+        }
+
+        Ok(address as *mut T)
+    }
+
     // REVIEW: Not sure if an EE's target data can change.. if so we might want to update the value
     // when making this call
     pub fn get_target_data(&self) -> &TargetData {
@@ -440,15 +777,97 @@ impl Clone for ExecutionEngine<'_> {
     fn clone(&self) -> Self {
         let execution_engine_rc = self.execution_engine_rc().clone();
 
-        unsafe {
+        let mut execution_engine = unsafe {
             ExecutionEngine::new(execution_engine_rc, self.jit_mode)
-        }
+        };
+
+        execution_engine.lookup_tracer = self.lookup_tracer.clone();
+        execution_engine.execution_engine.as_mut().expect(EE_INNER_PANIC).2 = self.execution_engine.as_ref().expect(EE_INNER_PANIC).2.clone();
+        execution_engine
+    }
+}
+
+/// A single function lookup made through an `ExecutionEngine`, as passed to the
+/// tracer set by `ExecutionEngine::set_lookup_tracer`.
+#[derive(Debug)]
+pub struct SymbolLookup<'a> {
+    name: &'a str,
+    address: Option<usize>,
+    duration: Duration,
+    module_name: Option<&'a str>,
+}
+
+impl<'a> SymbolLookup<'a> {
+    /// Gets the name which was looked up.
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Gets the address the name resolved to, or `None` if the lookup failed.
+    pub fn get_address(&self) -> Option<usize> {
+        self.address
+    }
+
+    /// Gets how long the lookup took, including compiling the function if this
+    /// was its first lookup.
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Gets the identifier of the module which defines the function, if it was found.
+    pub fn get_module_name(&self) -> Option<&'a str> {
+        self.module_name
+    }
+}
+
+#[derive(Clone)]
+struct LookupTracer(Rc<dyn Fn(&SymbolLookup)>);
+
+impl Debug for LookupTracer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("LookupTracer").finish()
     }
 }
 
+impl PartialEq for LookupTracer {
+    fn eq(&self, other: &Self) -> bool {
+        // Only compare the data pointers, as vtable pointers aren't guaranteed to be unique
+        &*self.0 as *const dyn Fn(&SymbolLookup) as *const u8 == &*other.0 as *const dyn Fn(&SymbolLookup) as *const u8
+    }
+}
+
+impl Eq for LookupTracer {}
+
 /// A smart pointer which wraps the `Drop` logic for `LLVMExecutionEngineRef`.
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct ExecEngineInner<'ctx>(Rc<LLVMExecutionEngineRef>, PhantomData<&'ctx Context>);
+struct ExecEngineInner<'ctx>(Rc<LLVMExecutionEngineRef>, PhantomData<&'ctx Context>, HostFunctions);
+
+/// Owns the closures registered through `ExecutionEngine::add_host_function`. It is shared
+/// like the `LLVMExecutionEngineRef` and so is only dropped after the engine is disposed.
+#[derive(Clone, Default)]
+struct HostFunctions(Rc<RefCell<Vec<Box<dyn Any>>>>);
+
+impl HostFunctions {
+    fn push(&self, function: Box<dyn Any>) {
+        self.0.borrow_mut().push(function);
+    }
+}
+
+impl Debug for HostFunctions {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("HostFunctions")
+            .field("count", &self.0.borrow().len())
+            .finish()
+    }
+}
+
+impl PartialEq for HostFunctions {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for HostFunctions {}
 
 impl Drop for ExecEngineInner<'_> {
     fn drop(&mut self) {
@@ -497,6 +916,156 @@ mod private {
 
 impl<F: private::SealedUnsafeFunctionPointer> UnsafeFunctionPointer for F {}
 
+/// A Rust type with a fixed LLVM representation, which can be passed to and returned from
+/// JIT'd functions.
+pub trait JitType {
+    /// Gets the LLVM type of `Self` in `context`. `target_data` is used for pointer sized integers.
+    fn jit_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> BasicTypeEnum<'ctx>;
+}
+
+/// A type which can be returned from a JIT'd function: either a `JitType` or `()`.
+pub trait JitReturnType {
+    /// Gets the LLVM return type of `Self` in `context`, or `None` for `void`.
+    fn jit_return_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> Option<BasicTypeEnum<'ctx>>;
+}
+
+/// A `#[repr(C)]` struct which can be passed to and returned from JIT'd functions, both by
+/// value and behind raw pointers. Every `AbiSafe` type is a `JitType` whose LLVM type is the
+/// literal struct of its `jit_field_types`.
+///
+/// Passing a struct by value is only compatible with Rust's `extern "C"` calls if the JIT'd
+/// function follows the platform's C ABI, so such functions should be declared with
+/// `JitSignature::lower_signature` (and its `apply_attributes`) rather than `JitSignature::fn_type`.
+/// Structs behind pointers have no such requirement.
+///
+/// # Safety
+///
+/// `Self` must be `#[repr(C)]` and not packed, and `jit_field_types` must return the LLVM
+/// types of its fields in declaration order, so that the LLVM struct has the same size,
+/// alignment and field offsets. Nested structs must be `AbiSafe` themselves.
+///
+/// # Example
+///
+/// ```no_run
+/// use inkwell::context::Context;
+/// use inkwell::execution_engine::{AbiSafe, JitType};
+/// use inkwell::targets::TargetData;
+/// use inkwell::types::BasicTypeEnum;
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Complex {
+///     re: f64,
+///     im: f64,
+/// }
+///
+/// unsafe impl AbiSafe for Complex {
+///     fn jit_field_types<'ctx>(context: &'ctx Context, target_data: &TargetData) -> Vec<BasicTypeEnum<'ctx>> {
+///         vec![f64::jit_type(context, target_data), f64::jit_type(context, target_data)]
+///     }
+/// }
+/// ```
+pub unsafe trait AbiSafe: Copy {
+    /// Gets the LLVM types of the fields of `Self` in `context`, in declaration order.
+    fn jit_field_types<'ctx>(context: &'ctx Context, target_data: &TargetData) -> Vec<BasicTypeEnum<'ctx>>;
+}
+
+/// An `unsafe extern "C" fn` whose parameters and return type are all `JitType`s, so that
+/// its LLVM `FunctionType` can be derived from the Rust signature.
+pub trait JitSignature: UnsafeFunctionPointer {
+    /// Gets the LLVM `FunctionType` matching `Self` in `context`, with `AbiSafe` structs
+    /// passed as first class aggregates.
+    fn fn_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> FunctionType<'ctx>;
+
+    /// Lowers `Self` the way a C compiler for `target` would, which is how Rust calls it.
+    fn lower_signature<'ctx>(context: &'ctx Context, target_data: &TargetData, target: AbiTarget) -> LoweredSignature<'ctx>;
+}
+
+/// A Rust closure which can be exposed to JIT'd code with `ExecutionEngine::add_host_function`.
+///
+/// This is implemented for closures of up to 13 `JitType` parameters returning a `JitReturnType`;
+/// `Args` is the tuple of parameter types.
+pub trait HostFunction<Args>: 'static {
+    /// Gets the LLVM `FunctionType` of the closure as seen from JIT'd code.
+    fn fn_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> FunctionType<'ctx>;
+
+    /// Gets the address of the `extern "C"` shim which calls the closure. The shim takes a
+    /// pointer to the closure followed by the closure's own parameters.
+    fn shim_address() -> usize;
+}
+
+macro_rules! impl_jit_type {
+    ($($ty:ty => $method:ident),*) => {
+        $(
+            impl JitType for $ty {
+                fn jit_type<'ctx>(context: &'ctx Context, _target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+                    context.$method().into()
+                }
+            }
+        )*
+    };
+}
+
+impl_jit_type!(
+    i8 => i8_type, u8 => i8_type, i16 => i16_type, u16 => i16_type, i32 => i32_type, u32 => i32_type,
+    i64 => i64_type, u64 => i64_type, i128 => i128_type, u128 => i128_type, f32 => f32_type, f64 => f64_type
+);
+
+impl JitType for isize {
+    fn jit_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        context.ptr_sized_int_type(target_data, None).into()
+    }
+}
+
+impl JitType for usize {
+    fn jit_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        context.ptr_sized_int_type(target_data, None).into()
+    }
+}
+
+impl<T: JitType> JitType for *const T {
+    fn jit_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        T::jit_type(context, target_data).ptr_type(AddressSpace::Generic).into()
+    }
+}
+
+impl<T: JitType> JitType for *mut T {
+    fn jit_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        T::jit_type(context, target_data).ptr_type(AddressSpace::Generic).into()
+    }
+}
+
+// LLVM has no void pointers, so these are represented by i8* like in C
+impl JitType for *const c_void {
+    fn jit_type<'ctx>(context: &'ctx Context, _target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        context.i8_type().ptr_type(AddressSpace::Generic).into()
+    }
+}
+
+impl JitType for *mut c_void {
+    fn jit_type<'ctx>(context: &'ctx Context, _target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        context.i8_type().ptr_type(AddressSpace::Generic).into()
+    }
+}
+
+impl<T: AbiSafe> JitType for T {
+    fn jit_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> BasicTypeEnum<'ctx> {
+        context.struct_type(&T::jit_field_types(context, target_data), false).into()
+    }
+}
+
+impl<T: JitType> JitReturnType for T {
+    fn jit_return_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> Option<BasicTypeEnum<'ctx>> {
+        Some(T::jit_type(context, target_data))
+    }
+}
+
+impl JitReturnType for () {
+    fn jit_return_type<'ctx>(_context: &'ctx Context, _target_data: &TargetData) -> Option<BasicTypeEnum<'ctx>> {
+        None
+    }
+}
+
 macro_rules! impl_unsafe_fn {
     (@recurse $first:ident $( , $rest:ident )*) => {
         impl_unsafe_fn!($( $rest ),*);
@@ -518,6 +1087,46 @@ macro_rules! impl_unsafe_fn {
             }
         }
 
+        impl<Output: JitReturnType, $( $param: JitType ),*> JitSignature for unsafe extern "C" fn($( $param ),*) -> Output {
+            fn fn_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> FunctionType<'ctx> {
+                let param_types: &[BasicMetadataTypeEnum<'ctx>] = &[$( $param::jit_type(context, target_data).into() ),*];
+
+                match Output::jit_return_type(context, target_data) {
+                    Some(return_type) => return_type.fn_type(param_types, false),
+                    None => context.void_type().fn_type(param_types, false),
+                }
+            }
+
+            fn lower_signature<'ctx>(context: &'ctx Context, target_data: &TargetData, target: AbiTarget) -> LoweredSignature<'ctx> {
+                let param_types: &[BasicTypeEnum<'ctx>] = &[$( $param::jit_type(context, target_data) ),*];
+
+                target.lower_signature(context, target_data, Output::jit_return_type(context, target_data), param_types)
+            }
+        }
+
+        impl<HostFn, Output, $( $param ),*> HostFunction<($( $param, )*)> for HostFn
+        where
+            HostFn: Fn($( $param ),*) -> Output + 'static,
+            Output: JitReturnType,
+            $( $param: JitType ),*
+        {
+            fn fn_type<'ctx>(context: &'ctx Context, target_data: &TargetData) -> FunctionType<'ctx> {
+                <unsafe extern "C" fn($( $param ),*) -> Output as JitSignature>::fn_type(context, target_data)
+            }
+
+            fn shim_address() -> usize {
+                #[allow(non_snake_case)]
+                unsafe extern "C" fn shim<HostFn: Fn($( $param ),*) -> Output, Output, $( $param ),*>(env: *const c_void, $( $param: $param ),*) -> Output {
+                    let function = &*(env as *const HostFn);
+
+                    // Unwinding into JIT'd frames is undefined behavior
+                    catch_unwind(AssertUnwindSafe(|| function($( $param ),*))).unwrap_or_else(|_| abort())
+                }
+
+                shim::<HostFn, Output, $( $param ),*> as usize
+            }
+        }
+
         impl_unsafe_fn!(@recurse $( $param ),*);
     };
 }
@@ -17,6 +17,11 @@ extern crate inkwell_internals;
 #[macro_use]
 pub mod support;
 #[deny(missing_docs)]
+pub mod abi;
+#[deny(missing_docs)]
+#[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8")))]
+pub mod analysis;
+#[deny(missing_docs)]
 pub mod attributes;
 #[deny(missing_docs)]
 #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
@@ -24,18 +29,33 @@ pub mod attributes;
 pub mod comdat;
 #[deny(missing_docs)]
 pub mod basic_block;
+mod build_ir;
 pub mod builder;
 #[deny(missing_docs)]
 pub mod context;
+#[deny(missing_docs)]
+#[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+              feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0")))]
+pub mod coroutines;
 pub mod data_layout;
 #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0")))]
 pub mod debug_info;
+#[deny(missing_docs)]
+pub mod declaration_cache;
+#[deny(missing_docs)]
+pub mod deopt;
 pub mod execution_engine;
 pub mod memory_buffer;
 #[deny(missing_docs)]
 pub mod module;
+#[deny(missing_docs)]
+pub mod module_diff;
 pub mod object_file;
 pub mod passes;
+#[deny(missing_docs)]
+pub mod repl;
+#[deny(missing_docs)]
+pub mod stack_map;
 pub mod targets;
 pub mod types;
 pub mod values;
@@ -76,6 +96,7 @@ use llvm_sys::{LLVMIntPredicate, LLVMRealPredicate, LLVMVisibility, LLVMThreadLo
 use llvm_sys::LLVMInlineAsmDialect;
 
 use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
 
 // Thanks to kennytm for coming up with assert_unique_features!
 // which ensures that the LLVM feature flags are mutually exclusive
@@ -182,6 +203,40 @@ pub enum IntPredicate {
     SLE,
 }
 
+impl IntPredicate {
+    /// Gets the predicate which gives the same result when `left` and `right` are swapped.
+    pub fn get_swapped_predicate(self) -> Self {
+        match self {
+            IntPredicate::EQ => IntPredicate::EQ,
+            IntPredicate::NE => IntPredicate::NE,
+            IntPredicate::UGT => IntPredicate::ULT,
+            IntPredicate::UGE => IntPredicate::ULE,
+            IntPredicate::ULT => IntPredicate::UGT,
+            IntPredicate::ULE => IntPredicate::UGE,
+            IntPredicate::SGT => IntPredicate::SLT,
+            IntPredicate::SGE => IntPredicate::SLE,
+            IntPredicate::SLT => IntPredicate::SGT,
+            IntPredicate::SLE => IntPredicate::SGE,
+        }
+    }
+
+    /// Gets the predicate which gives the opposite result for the same `left` and `right`.
+    pub fn get_inverse_predicate(self) -> Self {
+        match self {
+            IntPredicate::EQ => IntPredicate::NE,
+            IntPredicate::NE => IntPredicate::EQ,
+            IntPredicate::UGT => IntPredicate::ULE,
+            IntPredicate::UGE => IntPredicate::ULT,
+            IntPredicate::ULT => IntPredicate::UGE,
+            IntPredicate::ULE => IntPredicate::UGT,
+            IntPredicate::SGT => IntPredicate::SLE,
+            IntPredicate::SGE => IntPredicate::SLT,
+            IntPredicate::SLT => IntPredicate::SGE,
+            IntPredicate::SLE => IntPredicate::SGT,
+        }
+    }
+}
+
 // REVIEW: Maybe this belongs in some sort of prelude?
 /// Defines how to compare a `left` and `right` `FloatValue`.
 #[llvm_enum(LLVMRealPredicate)]
@@ -252,6 +307,46 @@ pub enum FloatPredicate {
     UNO,
 }
 
+impl FloatPredicate {
+    /// Gets the predicate which gives the same result when `left` and `right` are swapped.
+    pub fn get_swapped_predicate(self) -> Self {
+        match self {
+            FloatPredicate::OGE => FloatPredicate::OLE,
+            FloatPredicate::OGT => FloatPredicate::OLT,
+            FloatPredicate::OLE => FloatPredicate::OGE,
+            FloatPredicate::OLT => FloatPredicate::OGT,
+            FloatPredicate::UGE => FloatPredicate::ULE,
+            FloatPredicate::UGT => FloatPredicate::ULT,
+            FloatPredicate::ULE => FloatPredicate::UGE,
+            FloatPredicate::ULT => FloatPredicate::UGT,
+            symmetric => symmetric,
+        }
+    }
+
+    /// Gets the predicate which gives the opposite result for the same `left` and `right`,
+    /// including when either is NaN.
+    pub fn get_inverse_predicate(self) -> Self {
+        match self {
+            FloatPredicate::OEQ => FloatPredicate::UNE,
+            FloatPredicate::OGE => FloatPredicate::ULT,
+            FloatPredicate::OGT => FloatPredicate::ULE,
+            FloatPredicate::OLE => FloatPredicate::UGT,
+            FloatPredicate::OLT => FloatPredicate::UGE,
+            FloatPredicate::ONE => FloatPredicate::UEQ,
+            FloatPredicate::ORD => FloatPredicate::UNO,
+            FloatPredicate::PredicateFalse => FloatPredicate::PredicateTrue,
+            FloatPredicate::PredicateTrue => FloatPredicate::PredicateFalse,
+            FloatPredicate::UEQ => FloatPredicate::ONE,
+            FloatPredicate::UGE => FloatPredicate::OLT,
+            FloatPredicate::UGT => FloatPredicate::OLE,
+            FloatPredicate::ULE => FloatPredicate::OGT,
+            FloatPredicate::ULT => FloatPredicate::OGE,
+            FloatPredicate::UNE => FloatPredicate::OEQ,
+            FloatPredicate::UNO => FloatPredicate::ORD,
+        }
+    }
+}
+
 // REVIEW: Maybe this belongs in some sort of prelude?
 #[llvm_enum(LLVMAtomicOrdering)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -334,6 +429,95 @@ pub enum AtomicRMWBinOp {
     FSub,
 }
 
+/// Defines which integer operation an overflow-checked arithmetic instruction performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OverflowArithmetic {
+    /// Signed addition, using `llvm.sadd.with.overflow`.
+    SignedAdd,
+    /// Unsigned addition, using `llvm.uadd.with.overflow`.
+    UnsignedAdd,
+    /// Signed subtraction, using `llvm.ssub.with.overflow`.
+    SignedSub,
+    /// Unsigned subtraction, using `llvm.usub.with.overflow`.
+    UnsignedSub,
+    /// Signed multiplication, using `llvm.smul.with.overflow`.
+    SignedMul,
+    /// Unsigned multiplication, using `llvm.umul.with.overflow`.
+    UnsignedMul,
+}
+
+impl OverflowArithmetic {
+    pub(crate) fn get_intrinsic_prefix(self) -> &'static str {
+        match self {
+            OverflowArithmetic::SignedAdd => "llvm.sadd.with.overflow",
+            OverflowArithmetic::UnsignedAdd => "llvm.uadd.with.overflow",
+            OverflowArithmetic::SignedSub => "llvm.ssub.with.overflow",
+            OverflowArithmetic::UnsignedSub => "llvm.usub.with.overflow",
+            OverflowArithmetic::SignedMul => "llvm.smul.with.overflow",
+            OverflowArithmetic::UnsignedMul => "llvm.umul.with.overflow",
+        }
+    }
+}
+
+/// Defines how `Builder::build_int_vector_reduce` combines the elements of an integer vector.
+#[llvm_versions(9.0..=latest)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntReduction {
+    /// Wrapping sum of all elements.
+    Add,
+    /// Wrapping product of all elements.
+    Mul,
+    /// Bitwise and of all elements.
+    And,
+    /// Bitwise or of all elements.
+    Or,
+    /// Bitwise xor of all elements.
+    Xor,
+    /// Largest element, treating them as signed.
+    SignedMax,
+    /// Smallest element, treating them as signed.
+    SignedMin,
+    /// Largest element, treating them as unsigned.
+    UnsignedMax,
+    /// Smallest element, treating them as unsigned.
+    UnsignedMin,
+}
+
+#[llvm_versions(9.0..=latest)]
+impl IntReduction {
+    pub(crate) fn get_intrinsic_suffix(self) -> &'static str {
+        match self {
+            IntReduction::Add => "add",
+            IntReduction::Mul => "mul",
+            IntReduction::And => "and",
+            IntReduction::Or => "or",
+            IntReduction::Xor => "xor",
+            IntReduction::SignedMax => "smax",
+            IntReduction::SignedMin => "smin",
+            IntReduction::UnsignedMax => "umax",
+            IntReduction::UnsignedMin => "umin",
+        }
+    }
+}
+
+/// Defines whether a prefetch built with `Builder::build_prefetch` prepares for a read or a write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrefetchAccess {
+    /// The prefetched memory is about to be read.
+    Read  = 0,
+    /// The prefetched memory is about to be written to.
+    Write = 1,
+}
+
+/// Defines which cache a prefetch built with `Builder::build_prefetch` targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CacheType {
+    /// The instruction cache, for code which is about to be executed.
+    Instruction = 0,
+    /// The data cache, for memory which is about to be accessed.
+    Data        = 1,
+}
+
 /// Defines the optimization level used to compile a `Module`.
 ///
 /// # Remarks
@@ -354,13 +538,21 @@ impl Default for OptimizationLevel {
     }
 }
 
+/// Determines whether a global with non-local linkage can be seen and preempted outside of
+/// the shared object it is defined in, for object file formats which support it (ELF, Mach-O).
 #[llvm_enum(LLVMVisibility)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum GlobalVisibility {
+    /// The global is visible outside of its shared object and may be preempted by a
+    /// definition in another one. This is the only visibility allowed with local linkage.
     #[llvm_variant(LLVMDefaultVisibility)]
     Default,
+    /// The global is not visible outside of its shared object, so references to it are
+    /// always resolved locally and need not go through the GOT or PLT.
     #[llvm_variant(LLVMHiddenVisibility)]
     Hidden,
+    /// The global is visible outside of its shared object but cannot be preempted, so
+    /// references from within the shared object are resolved locally.
     #[llvm_variant(LLVMProtectedVisibility)]
     Protected,
 }
@@ -402,13 +594,193 @@ impl ThreadLocalMode {
     }
 }
 
+/// Defines how arguments and return values are passed between a caller and a callee. The
+/// caller and callee must use the same calling convention, otherwise behavior is undefined.
+///
+/// Conventions which LLVM has no variant for here, including ones introduced by newer
+/// LLVM versions, are represented by `Other`. Conventions are compared by their numeric id,
+/// so `Other(8)` is equal to `Fast`.
+#[derive(Clone, Copy, Debug)]
+pub enum CallingConvention {
+    /// The C calling convention of the target, used by default.
+    C,
+    /// `fastcc`: makes calls as fast as possible, e.g. by passing arguments in registers.
+    /// Supports tail call optimization when `GuaranteedTailCallOpt` is enabled.
+    Fast,
+    /// `coldcc`: makes the caller as efficient as possible under the assumption that the
+    /// call is rarely executed, e.g. by preserving most registers.
+    Cold,
+    /// `ghccc`: used by the Glasgow Haskell Compiler, passing everything in registers.
+    /// Every call using it may be tail call optimized.
+    GHC,
+    /// `cc 11`: used by the High-Performance Erlang compiler.
+    HiPE,
+    /// `webkit_jscc`: used by WebKit's JavaScript JIT.
+    WebKitJS,
+    /// `anyregcc`: lets the code generator choose which registers hold the arguments, for
+    /// use with patchpoints.
+    AnyReg,
+    /// `preserve_mostcc`: the callee preserves most general purpose registers.
+    PreserveMost,
+    /// `preserve_allcc`: the callee preserves all general purpose and most floating point
+    /// registers.
+    PreserveAll,
+    /// `swiftcc`: used by the Swift language.
+    Swift,
+    /// `cxx_fast_tlscc`: used for access functions of C++ thread local variables.
+    CxxFastTLS,
+    /// `tailcc`: like `fastcc`, but every tail call is guaranteed to be optimized. Requires LLVM 9.0+.
+    Tail,
+    /// `cfguard_checkcc`: used for the Windows Control Flow Guard check function. Requires LLVM 10.0+.
+    CFGuardCheck,
+    /// `swifttailcc`: like `swiftcc`, but with guaranteed tail calls. Requires LLVM 13.0+.
+    SwiftTail,
+    /// `x86_stdcallcc`: the callee cleans up the stack, as used by the Win32 API.
+    X86StdCall,
+    /// `x86_fastcallcc`: passes the first two integer arguments in `ecx` and `edx`.
+    X86FastCall,
+    /// `arm_apcscc`: the deprecated ARM Procedure Call Standard.
+    ARMAPCS,
+    /// `arm_aapcscc`: the ARM Architecture Procedure Call Standard.
+    ARMAAPCS,
+    /// `arm_aapcs_vfpcc`: AAPCS passing floating point arguments in VFP registers.
+    ARMAAPCSVFP,
+    /// `x86_thiscallcc`: passes `this` in `ecx`, as used by MSVC for member functions.
+    X86ThisCall,
+    /// `ptx_kernel`: marks a PTX kernel entry point.
+    PTXKernel,
+    /// `ptx_device`: a PTX device function.
+    PTXDevice,
+    /// `x86_64_sysvcc`: the System V AMD64 ABI, regardless of the target's default.
+    X86_64SysV,
+    /// `win64cc`: the Windows x64 ABI, regardless of the target's default.
+    Win64,
+    /// `x86_vectorcallcc`: MSVC's `__vectorcall`.
+    X86VectorCall,
+    /// `x86_regcallcc`: Intel's `__regcall`.
+    X86RegCall,
+    /// `aarch64_vector_pcs`: the AArch64 vector function ABI. Requires LLVM 7.0+.
+    AArch64VectorCall,
+    /// Any other calling convention, by its numeric id.
+    Other(u32),
+}
+
+impl CallingConvention {
+    /// Gets the `CallingConvention` with the given numeric id, as used in textual IR (`cc <n>`).
+    pub fn from_id(id: u32) -> Self {
+        match id {
+            0 => CallingConvention::C,
+            8 => CallingConvention::Fast,
+            9 => CallingConvention::Cold,
+            10 => CallingConvention::GHC,
+            11 => CallingConvention::HiPE,
+            12 => CallingConvention::WebKitJS,
+            13 => CallingConvention::AnyReg,
+            14 => CallingConvention::PreserveMost,
+            15 => CallingConvention::PreserveAll,
+            16 => CallingConvention::Swift,
+            17 => CallingConvention::CxxFastTLS,
+            18 => CallingConvention::Tail,
+            19 => CallingConvention::CFGuardCheck,
+            20 => CallingConvention::SwiftTail,
+            64 => CallingConvention::X86StdCall,
+            65 => CallingConvention::X86FastCall,
+            66 => CallingConvention::ARMAPCS,
+            67 => CallingConvention::ARMAAPCS,
+            68 => CallingConvention::ARMAAPCSVFP,
+            70 => CallingConvention::X86ThisCall,
+            71 => CallingConvention::PTXKernel,
+            72 => CallingConvention::PTXDevice,
+            78 => CallingConvention::X86_64SysV,
+            79 => CallingConvention::Win64,
+            80 => CallingConvention::X86VectorCall,
+            92 => CallingConvention::X86RegCall,
+            97 => CallingConvention::AArch64VectorCall,
+            id => CallingConvention::Other(id),
+        }
+    }
+
+    /// Gets the numeric id of this `CallingConvention`, as used in textual IR (`cc <n>`).
+    pub fn get_id(self) -> u32 {
+        match self {
+            CallingConvention::C => 0,
+            CallingConvention::Fast => 8,
+            CallingConvention::Cold => 9,
+            CallingConvention::GHC => 10,
+            CallingConvention::HiPE => 11,
+            CallingConvention::WebKitJS => 12,
+            CallingConvention::AnyReg => 13,
+            CallingConvention::PreserveMost => 14,
+            CallingConvention::PreserveAll => 15,
+            CallingConvention::Swift => 16,
+            CallingConvention::CxxFastTLS => 17,
+            CallingConvention::Tail => 18,
+            CallingConvention::CFGuardCheck => 19,
+            CallingConvention::SwiftTail => 20,
+            CallingConvention::X86StdCall => 64,
+            CallingConvention::X86FastCall => 65,
+            CallingConvention::ARMAPCS => 66,
+            CallingConvention::ARMAAPCS => 67,
+            CallingConvention::ARMAAPCSVFP => 68,
+            CallingConvention::X86ThisCall => 70,
+            CallingConvention::PTXKernel => 71,
+            CallingConvention::PTXDevice => 72,
+            CallingConvention::X86_64SysV => 78,
+            CallingConvention::Win64 => 79,
+            CallingConvention::X86VectorCall => 80,
+            CallingConvention::X86RegCall => 92,
+            CallingConvention::AArch64VectorCall => 97,
+            CallingConvention::Other(id) => id,
+        }
+    }
+}
+
+impl PartialEq for CallingConvention {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+impl Eq for CallingConvention {}
+
+impl Hash for CallingConvention {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get_id().hash(state);
+    }
+}
+
+impl Default for CallingConvention {
+    /// Returns the default value for `CallingConvention`, namely `CallingConvention::C`.
+    fn default() -> Self {
+        CallingConvention::C
+    }
+}
+
+impl From<u32> for CallingConvention {
+    fn from(id: u32) -> Self {
+        CallingConvention::from_id(id)
+    }
+}
+
+impl From<CallingConvention> for u32 {
+    fn from(calling_convention: CallingConvention) -> Self {
+        calling_convention.get_id()
+    }
+}
+
+/// Determines how a global is imported from or exported to a DLL when targeting Windows.
+/// It has no effect on other object file formats.
 #[llvm_enum(LLVMDLLStorageClass)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum DLLStorageClass {
+    /// The global is neither imported nor exported.
     #[llvm_variant(LLVMDefaultStorageClass)]
     Default,
+    /// The global is defined in another DLL and referenced through its import table
+    /// entry (`__imp_` symbol). Only valid on declarations.
     #[llvm_variant(LLVMDLLImportStorageClass)]
     Import,
+    /// The global is made available to other DLLs through this DLL's export table.
     #[llvm_variant(LLVMDLLExportStorageClass)]
     Export,
 }
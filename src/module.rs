@@ -4,20 +4,21 @@ use llvm_sys::analysis::{LLVMVerifyModule, LLVMVerifierFailureAction};
 #[allow(deprecated)]
 use llvm_sys::bit_reader::LLVMParseBitcodeInContext;
 use llvm_sys::bit_writer::{LLVMWriteBitcodeToFile, LLVMWriteBitcodeToMemoryBuffer};
-use llvm_sys::core::{LLVMAddFunction, LLVMAddGlobal, LLVMDumpModule, LLVMGetNamedFunction, LLVMGetTypeByName, LLVMSetDataLayout, LLVMSetTarget, LLVMCloneModule, LLVMDisposeModule, LLVMGetTarget, LLVMGetModuleContext, LLVMGetFirstFunction, LLVMGetLastFunction, LLVMAddGlobalInAddressSpace, LLVMPrintModuleToString, LLVMGetNamedMetadataNumOperands, LLVMAddNamedMetadataOperand, LLVMGetNamedMetadataOperands, LLVMGetFirstGlobal, LLVMGetLastGlobal, LLVMGetNamedGlobal, LLVMPrintModuleToFile};
+use llvm_sys::core::{LLVMAddFunction, LLVMAddGlobal, LLVMDumpModule, LLVMGetNamedFunction, LLVMGetTypeByName, LLVMSetDataLayout, LLVMSetTarget, LLVMCloneModule, LLVMDisposeModule, LLVMGetTarget, LLVMGetModuleContext, LLVMGetFirstFunction, LLVMGetLastFunction, LLVMAddGlobalInAddressSpace, LLVMPrintModuleToString, LLVMGetNamedMetadataNumOperands, LLVMAddNamedMetadataOperand, LLVMGetNamedMetadataOperands, LLVMGetFirstGlobal, LLVMGetLastGlobal, LLVMGetNamedGlobal, LLVMPrintModuleToFile, LLVMArrayType, LLVMConstArray, LLVMConstPointerCast, LLVMGetElementType, LLVMGetInitializer, LLVMGetNumOperands, LLVMGetOperand, LLVMGetTypeKind, LLVMSetInitializer, LLVMTypeOf, LLVMBasicBlockAsValue};
 #[llvm_versions(3.9..=latest)]
 use llvm_sys::core::{LLVMGetModuleIdentifier, LLVMSetModuleIdentifier};
 #[llvm_versions(7.0..=latest)]
 use llvm_sys::core::{LLVMGetModuleFlag, LLVMAddModuleFlag};
 use llvm_sys::execution_engine::{LLVMCreateInterpreterForModule, LLVMCreateJITCompilerForModule, LLVMCreateExecutionEngineForModule};
-use llvm_sys::prelude::{LLVMModuleRef, LLVMValueRef};
-use llvm_sys::LLVMLinkage;
+use llvm_sys::prelude::{LLVMModuleRef, LLVMTypeRef, LLVMValueRef};
+use llvm_sys::{LLVMLinkage, LLVMTypeKind};
 #[llvm_versions(7.0..=latest)]
 use llvm_sys::LLVMModuleFlagBehavior;
 
 use std::cell::{Cell, RefCell, Ref};
 use std::ffi::CStr;
 use std::fs::File;
+use std::iter::FusedIterator;
 use std::marker::PhantomData;
 use std::mem::{forget, MaybeUninit};
 use std::path::Path;
@@ -33,10 +34,11 @@ use crate::data_layout::DataLayout;
 use crate::debug_info::{DebugInfoBuilder, DICompileUnit, DWARFEmissionKind, DWARFSourceLanguage};
 use crate::execution_engine::ExecutionEngine;
 use crate::memory_buffer::MemoryBuffer;
+use crate::module_diff::{diff_modules, ModuleDiff};
 use crate::support::{to_c_str, LLVMString};
 use crate::targets::{InitializationConfig, Target, TargetTriple};
 use crate::types::{AsTypeRef, BasicType, FunctionType, StructType};
-use crate::values::{AsValueRef, FunctionValue, GlobalValue, MetadataValue};
+use crate::values::{set_value_name, AsValueRef, FunctionValue, GlobalValue, MetadataValue, PointerValue};
 #[llvm_versions(7.0..=latest)]
 use crate::values::BasicValue;
 
@@ -268,6 +270,194 @@ impl<'ctx> Module<'ctx> {
         }
     }
 
+    /// Gets an iterator over the `FunctionValue`s in this `Module`, which yields them
+    /// in the order they appear in the module and may also be iterated in reverse.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let first = module.add_function("first", fn_type, None);
+    /// let second = module.add_function("second", fn_type, None);
+    ///
+    /// assert_eq!(module.get_functions().collect::<Vec<_>>(), vec![first, second]);
+    /// assert_eq!(module.get_functions().rev().collect::<Vec<_>>(), vec![second, first]);
+    /// ```
+    pub fn get_functions(&self) -> FunctionIterator<'ctx> {
+        FunctionIterator {
+            front: self.get_first_function(),
+            back: self.get_last_function(),
+        }
+    }
+
+    /// Gets an iterator over the `FunctionValue`s in this `Module` and their names, in the
+    /// order they appear in the module. The names are borrowed from LLVM rather than copied.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    ///
+    /// module.add_function("first", fn_type, None);
+    /// module.add_function("second", fn_type, None);
+    ///
+    /// for (name, function) in module.get_named_functions() {
+    ///     println!("{:?}: {}", name, function.count_basic_blocks());
+    /// }
+    /// ```
+    pub fn get_named_functions(&self) -> NamedFunctionIterator<'_, 'ctx> {
+        NamedFunctionIterator {
+            iter: self.get_functions(),
+            _module: PhantomData,
+        }
+    }
+
+    /// Structurally compares this `Module` against `other`, reporting which functions and
+    /// global variables were added, removed or changed, along with the instructions that
+    /// differ in each changed function. Unlike a textual diff, this is not thrown off by
+    /// unnamed values being renumbered.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("before");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    ///
+    /// module.add_function("kept", fn_type, None);
+    ///
+    /// let other = module.clone();
+    ///
+    /// other.add_function("added", fn_type, None);
+    ///
+    /// let diff = module.diff(&other);
+    ///
+    /// assert_eq!(diff.get_added_functions(), ["added"]);
+    /// assert!(diff.get_changed_functions().is_empty());
+    /// ```
+    pub fn diff(&self, other: &Module<'_>) -> ModuleDiff {
+        diff_modules(self, other)
+    }
+
+    /// Computes a hash of this `Module`'s contents which ignores the names of arguments,
+    /// instructions and basic blocks as well as the module's name and source file name, so
+    /// it can key a cache of compiled objects or detect whether the IR changed at all. The
+    /// hash is taken over the module's bitcode, and so is only stable across runs using the
+    /// same version of LLVM.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use inkwell::context::Context;
+    /// use inkwell::values::BasicValue;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("first");
+    /// let fn_type = context.void_type().fn_type(&[context.i32_type().into()], false);
+    /// let function = module.add_function("f", fn_type, None);
+    ///
+    /// let other = module.clone();
+    ///
+    /// other.set_name("second");
+    /// function.get_first_param().unwrap().set_name("x");
+    ///
+    /// assert_eq!(module.stable_hash(), other.stable_hash());
+    /// ```
+    pub fn stable_hash(&self) -> u64 {
+        let module = self.clone();
+
+        // The module identifier itself is never written to bitcode
+        module.set_source_file_name_for_hash();
+
+        for function in module.get_functions() {
+            for param in function.get_param_iter() {
+                unsafe {
+                    set_value_name(param.as_value_ref(), "");
+                }
+            }
+
+            for block in function.get_basic_blocks() {
+                unsafe {
+                    set_value_name(LLVMBasicBlockAsValue(block.basic_block), "");
+                }
+
+                let mut instruction = block.get_first_instruction();
+
+                while let Some(current) = instruction {
+                    unsafe {
+                        set_value_name(current.as_value_ref(), "");
+                    }
+
+                    instruction = current.get_next_instruction();
+                }
+            }
+        }
+
+        // 64-bit FNV-1a, which unlike the std hashers is guaranteed not to change
+        module.write_bitcode_to_memory()
+            .as_slice()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    #[llvm_versions(7.0..=latest)]
+    fn set_source_file_name_for_hash(&self) {
+        self.set_source_file_name("");
+    }
+
+    // The source file name is only part of the bitcode from LLVM 4.0, but cannot be set
+    // through the C API before 7.0
+    #[llvm_versions(3.6..7.0)]
+    fn set_source_file_name_for_hash(&self) {}
+
+    /// Deletes every function declaration in this `Module` which has no uses, such as those
+    /// left behind after stitching modules together, and returns how many were deleted.
+    /// Any `FunctionValue`s for the deleted declarations must not be used afterwards.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let builder = context.create_builder();
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let used = module.add_function("used", fn_type, None);
+    ///
+    /// module.add_function("unused", fn_type, None);
+    ///
+    /// let caller = module.add_function("caller", fn_type, None);
+    /// let entry = context.append_basic_block(caller, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    /// builder.build_call(used, &[], "");
+    /// builder.build_return(None);
+    ///
+    /// assert_eq!(module.strip_unused_function_declarations(), 1);
+    /// assert!(module.get_function("unused").is_none());
+    /// ```
+    pub fn strip_unused_function_declarations(&self) -> u32 {
+        let declarations: Vec<_> = self.get_functions()
+            .map(|function| function.as_global_value())
+            .filter(|global| global.is_declaration())
+            .collect();
+        let mut num_deleted = 0;
+
+        for declaration in declarations {
+            if unsafe { declaration.delete_if_unused() } {
+                num_deleted += 1;
+            }
+        }
+
+        num_deleted
+    }
+
     /// Gets a `FunctionValue` defined in this `Module` by its name.
     ///
     /// # Example
@@ -570,6 +760,11 @@ impl<'ctx> Module<'ctx> {
 
     /// Writes a `Module` to a `Path`.
     ///
+    /// The bitcode is written without a module summary index, as the C API offers no way of
+    /// computing one. Linkers performing ThinLTO therefore treat the written file as an input
+    /// to regular, monolithic LTO instead, which still links correctly but is not split into
+    /// parallel backend jobs. Running `opt -module-summary` over the file adds the summary.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -664,7 +859,7 @@ impl<'ctx> Module<'ctx> {
         Ok(())
     }
 
-    fn get_borrowed_data_layout(module: LLVMModuleRef) -> DataLayout {
+    pub(crate) fn get_borrowed_data_layout(module: LLVMModuleRef) -> DataLayout {
         #[cfg(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8"))]
         let data_layout = unsafe {
             use llvm_sys::core::LLVMGetDataLayout;
@@ -1010,6 +1205,40 @@ impl<'ctx> Module<'ctx> {
         }
     }
 
+    /// Gets an iterator over the `GlobalValue`s in a module, which yields them in the
+    /// order they appear in the module and may also be iterated in reverse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("mod");
+    /// let i8_type = context.i8_type();
+    /// let first = module.add_global(i8_type, Some(AddressSpace::Const), "first");
+    /// let second = module.add_global(i8_type, Some(AddressSpace::Const), "second");
+    ///
+    /// assert_eq!(module.get_globals().collect::<Vec<_>>(), vec![first, second]);
+    /// assert_eq!(module.get_globals().next_back(), Some(second));
+    /// ```
+    pub fn get_globals(&self) -> GlobalIterator<'ctx> {
+        GlobalIterator {
+            front: self.get_first_global(),
+            back: self.get_last_global(),
+        }
+    }
+
+    /// Gets an iterator over the `GlobalValue`s in a module and their names, in the order
+    /// they appear in the module. The names are borrowed from LLVM rather than copied.
+    pub fn get_named_globals(&self) -> NamedGlobalIterator<'_, 'ctx> {
+        NamedGlobalIterator {
+            iter: self.get_globals(),
+            _module: PhantomData,
+        }
+    }
+
     /// Gets a named `GlobalValue` in a module.
     ///
     /// # Example
@@ -1043,6 +1272,401 @@ impl<'ctx> Module<'ctx> {
         }
     }
 
+    /// Appends globals to the `llvm.used` array, which stops both the optimizer and the
+    /// system linker from removing them even when nothing in the IR references them. Globals
+    /// which are already in the array are not added again.
+    ///
+    /// `llvm.used` is an appending linkage array which cannot be modified in place, so the
+    /// existing array, if any, is replaced with one holding its previous elements followed
+    /// by the new ones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let function = module.add_function("called_from_asm", fn_type, None);
+    ///
+    /// module.append_to_used(&[function.as_global_value()]).unwrap();
+    ///
+    /// assert!(module.get_global("llvm.used").is_some());
+    /// ```
+    pub fn append_to_used(&self, values: &[GlobalValue<'ctx>]) -> Result<(), &'static str> {
+        self.append_to_used_array("llvm.used", values)
+    }
+
+    /// Appends globals to the `llvm.compiler.used` array, which stops the optimizer but not
+    /// the system linker from removing them even when nothing in the IR references them.
+    /// Globals which are already in the array are not added again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let global = module.add_global(context.i32_type(), None, "runtime_flag");
+    ///
+    /// module.append_to_compiler_used(&[global]).unwrap();
+    ///
+    /// assert!(module.get_global("llvm.compiler.used").is_some());
+    /// ```
+    pub fn append_to_compiler_used(&self, values: &[GlobalValue<'ctx>]) -> Result<(), &'static str> {
+        self.append_to_used_array("llvm.compiler.used", values)
+    }
+
+    /// Registers `function` to run when the module is loaded, by adding it to the
+    /// `llvm.global_ctors` array. Constructors with a lower `priority` run first; 65535 is
+    /// the priority used for ordinary C++ static constructors. If `data` is given, the
+    /// constructor is only run if that global is kept in the final binary.
+    ///
+    /// `function` must take no parameters and return void.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let init = module.add_function("init_runtime", fn_type, None);
+    ///
+    /// module.append_global_ctor(65535, init, None).unwrap();
+    ///
+    /// assert!(module.get_global("llvm.global_ctors").is_some());
+    /// ```
+    pub fn append_global_ctor(&self, priority: u32, function: FunctionValue<'ctx>, data: Option<GlobalValue<'ctx>>) -> Result<(), &'static str> {
+        self.append_to_structor_array("llvm.global_ctors", priority, function, data)
+    }
+
+    /// Registers `function` to run when the module is unloaded, by adding it to the
+    /// `llvm.global_dtors` array. Destructors with a lower `priority` run first. If `data`
+    /// is given, the destructor is only run if that global is kept in the final binary.
+    ///
+    /// `function` must take no parameters and return void.
+    pub fn append_global_dtor(&self, priority: u32, function: FunctionValue<'ctx>, data: Option<GlobalValue<'ctx>>) -> Result<(), &'static str> {
+        self.append_to_structor_array("llvm.global_dtors", priority, function, data)
+    }
+
+    // Every supported LLVM accepts the three field `{ i32, void ()*, i8* }` entries; the older
+    // two field form without the associated data is deprecated.
+    fn append_to_structor_array(&self, name: &str, priority: u32, function: FunctionValue<'ctx>, data: Option<GlobalValue<'ctx>>) -> Result<(), &'static str> {
+        let fn_type = function.get_type();
+
+        if fn_type.get_return_type().is_some() || fn_type.count_param_types() != 0 || fn_type.is_var_arg() {
+            return Err("Global constructors and destructors must take no parameters and return void.");
+        }
+
+        let context = self.get_context();
+        let i32_type = context.i32_type();
+        let fn_ptr_type = fn_type.ptr_type(AddressSpace::Generic);
+        let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+        let entry_type = context.struct_type(&[i32_type.into(), fn_ptr_type.into(), i8_ptr_type.into()], false);
+        let mut elements = self.get_global_array_elements(name, entry_type.as_type_ref())?;
+
+        let data = match data {
+            Some(data) => unsafe {
+                PointerValue::new(LLVMConstPointerCast(data.as_value_ref(), i8_ptr_type.as_type_ref()))
+            },
+            None => i8_ptr_type.const_null(),
+        };
+        let entry = entry_type.const_named_struct(&[
+            i32_type.const_int(priority as u64, false).into(),
+            function.as_global_value().as_pointer_value().into(),
+            data.into(),
+        ]);
+
+        elements.push(entry.as_value_ref());
+
+        self.replace_global_array(name, entry_type.as_type_ref(), elements);
+
+        Ok(())
+    }
+
+    fn append_to_used_array(&self, name: &str, values: &[GlobalValue<'ctx>]) -> Result<(), &'static str> {
+        let i8_ptr_type = self.get_context().i8_type().ptr_type(AddressSpace::Generic).as_type_ref();
+        let mut elements = self.get_global_array_elements(name, i8_ptr_type)?;
+
+        for value in values {
+            let element = unsafe {
+                LLVMConstPointerCast(value.as_value_ref(), i8_ptr_type)
+            };
+
+            // Constants are uniqued, so a global already in the array casts to the same element
+            if !elements.contains(&element) {
+                elements.push(element);
+            }
+        }
+
+        if let Some(global) = self.replace_global_array(name, i8_ptr_type, elements) {
+            global.set_section("llvm.metadata");
+        }
+
+        Ok(())
+    }
+
+    // Gets the elements of the appending linkage array global `name`, if it exists
+    fn get_global_array_elements(&self, name: &str, element_type: LLVMTypeRef) -> Result<Vec<LLVMValueRef>, &'static str> {
+        let global = match self.get_global(name) {
+            Some(global) => global,
+            None => return Ok(Vec::new()),
+        };
+
+        let initializer = unsafe {
+            LLVMGetInitializer(global.as_value_ref())
+        };
+
+        if initializer.is_null() {
+            return Ok(Vec::new());
+        }
+
+        unsafe {
+            let array_type = LLVMTypeOf(initializer);
+
+            if LLVMGetTypeKind(array_type) != LLVMTypeKind::LLVMArrayTypeKind || LLVMGetElementType(array_type) != element_type {
+                return Err("The existing global array has an unexpected type.");
+            }
+
+            // A zeroinitializer has no operands, which matches an empty array
+            let num_elements = LLVMGetNumOperands(initializer) as u32;
+
+            Ok((0..num_elements).map(|i| LLVMGetOperand(initializer, i)).collect())
+        }
+    }
+
+    // Replaces the appending linkage array global `name` with one holding `elements`, as the
+    // type of a global can't be changed in place. Nothing is created if there are no elements.
+    fn replace_global_array(&self, name: &str, element_type: LLVMTypeRef, mut elements: Vec<LLVMValueRef>) -> Option<GlobalValue<'ctx>> {
+        if let Some(global) = self.get_global(name) {
+            unsafe {
+                global.delete();
+            }
+        }
+
+        if elements.is_empty() {
+            return None;
+        }
+
+        let c_string = to_c_str(name);
+
+        unsafe {
+            let array_type = LLVMArrayType(element_type, elements.len() as u32);
+            let global = GlobalValue::new(LLVMAddGlobal(self.module.get(), array_type, c_string.as_ptr()));
+
+            LLVMSetInitializer(global.as_value_ref(), LLVMConstArray(element_type, elements.as_mut_ptr(), elements.len() as u32));
+            global.set_linkage(Linkage::Appending);
+
+            Some(global)
+        }
+    }
+
+    /// Adds a function named `name` of type `thunk_type` which forwards all of its arguments
+    /// to `target` through a `musttail` call and returns its result. Unlike a plain `tail`
+    /// call, a `musttail` call is guaranteed to reuse the caller's stack frame, which
+    /// interpreter dispatch loops and dynamic dispatch shims rely upon.
+    ///
+    /// `thunk_type` must be compatible with the type of `target`: both must take the same
+    /// number of parameters, agree on whether they are variadic, and have identical parameter
+    /// and return types, except that pointers may point to different types as long as they
+    /// are in the same address space. Such pointers are bitcast around the call. The thunk
+    /// is given the calling convention and parameter attributes of `target`, as `musttail`
+    /// requires those affecting the ABI to match.
+    ///
+    /// The LLVM C API has no way of marking a call `musttail`, so the thunk is parsed from
+    /// textual IR and linked into this module.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("interpreter");
+    /// let i64_type = context.i64_type();
+    /// let state_ptr_type = context.opaque_struct_type("State").ptr_type(AddressSpace::Generic);
+    /// let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    /// let handler_type = i64_type.fn_type(&[state_ptr_type.into(), i64_type.into()], false);
+    /// let handler = module.add_function("op_add", handler_type, None);
+    /// let erased_type = i64_type.fn_type(&[i8_ptr_type.into(), i64_type.into()], false);
+    ///
+    /// let thunk = module.add_musttail_thunk("op_add_erased", erased_type, handler).unwrap();
+    ///
+    /// assert_eq!(thunk.get_type(), erased_type);
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn add_musttail_thunk(&self, name: &str, thunk_type: FunctionType<'ctx>, target: FunctionValue<'ctx>) -> Result<FunctionValue<'ctx>, LLVMString> {
+        use crate::attributes::AttributeLoc;
+        use crate::values::{CallSiteValue, InstructionOpcode};
+        use llvm_sys::core::LLVMGetGlobalParent;
+        use llvm_sys::ir_reader::LLVMParseIRInContext;
+
+        let target_type = target.get_type();
+
+        if unsafe { LLVMGetGlobalParent(target.as_value_ref()) } != self.module.get() {
+            return Err(LLVMString::create_from_str("The thunk target must be a function in this module.\0"));
+        }
+
+        if self.get_function(name).is_some() || self.get_global(name).is_some() {
+            return Err(LLVMString::create_from_str("A global with the thunk's name already exists.\0"));
+        }
+
+        let thunk_params = thunk_type.get_param_types();
+        let target_params = target_type.get_param_types();
+        let is_compatible = thunk_params.len() == target_params.len()
+            && thunk_type.is_var_arg() == target_type.is_var_arg()
+            && thunk_params.iter().zip(&target_params).all(|(a, b)| is_thunk_compatible(a.as_type_ref(), b.as_type_ref()))
+            && match (thunk_type.get_return_type(), target_type.get_return_type()) {
+                (Some(a), Some(b)) => is_thunk_compatible(a.as_type_ref(), b.as_type_ref()),
+                (None, None) => true,
+                _ => false,
+            };
+
+        if !is_compatible {
+            return Err(LLVMString::create_from_str("The thunk type is not compatible with the type of its target.\0"));
+        }
+
+        let ir = unsafe {
+            self.print_musttail_thunk(name, thunk_type, target)
+        };
+        let buffer = MemoryBuffer::create_from_memory_range_copy(ir.as_bytes(), name);
+        let mut thunk_module = ptr::null_mut();
+        let mut err_string = ptr::null_mut();
+        let code = unsafe {
+            LLVMParseIRInContext(LLVMGetModuleContext(self.module.get()), buffer.memory_buffer, &mut thunk_module, &mut err_string)
+        };
+
+        // Parsing takes ownership of the buffer
+        forget(buffer);
+
+        if code != 0 {
+            unsafe {
+                return Err(LLVMString::new(err_string));
+            }
+        }
+
+        // A declaration of the target in the thunk module would not be linked to a target
+        // with local linkage, so make it visible for the duration of the link
+        let target_linkage = target.get_linkage();
+        let is_local = target_linkage == Linkage::Internal || target_linkage == Linkage::Private;
+
+        if is_local {
+            target.set_linkage(Linkage::External);
+        }
+
+        // The thunk module is in the same context, so its types map back onto ours
+        let linked = self.link_in_module(unsafe { Module::new(thunk_module) });
+
+        if is_local {
+            target.set_linkage(target_linkage);
+        }
+
+        linked?;
+
+        let thunk = self.get_function(name).expect("Thunk to have been linked in");
+        let mut instruction = thunk.get_first_basic_block().and_then(|block| block.get_first_instruction());
+
+        while let Some(call) = instruction {
+            if call.get_opcode() == InstructionOpcode::Call {
+                break;
+            }
+
+            instruction = call.get_next_instruction();
+        }
+
+        let call = unsafe {
+            CallSiteValue::new(instruction.expect("Thunk to contain a call").as_value_ref())
+        };
+        let locs = Some(AttributeLoc::Return).into_iter().chain((0..target_params.len() as u32).map(AttributeLoc::Param));
+
+        thunk.set_call_conventions(target.get_call_conventions());
+        call.set_call_convention(target.get_call_conventions());
+
+        for loc in locs {
+            for attribute in target.attributes(loc) {
+                thunk.add_attribute(loc, attribute);
+                call.add_attribute(loc, attribute);
+            }
+        }
+
+        Ok(thunk)
+    }
+
+    #[llvm_versions(3.9..=latest)]
+    unsafe fn print_musttail_thunk(&self, name: &str, thunk_type: FunctionType<'ctx>, target: FunctionValue<'ctx>) -> String {
+        use std::fmt::Write;
+
+        let target_type = target.get_type();
+        let mut named_structs = Vec::new();
+        let mut ir = String::new();
+
+        collect_named_structs(thunk_type.as_type_ref(), &mut named_structs);
+        collect_named_structs(target_type.as_type_ref(), &mut named_structs);
+
+        let _ = writeln!(ir, "target datalayout = \"{}\"", self.get_data_layout().as_str().to_string_lossy());
+        let _ = writeln!(ir, "target triple = \"{}\"", self.get_triple().as_str().to_string_lossy());
+
+        // Opaque declarations suffice, and are mapped onto the existing definitions when linking
+        for &named_struct in &named_structs {
+            let _ = writeln!(ir, "{} = type opaque", print_type(named_struct));
+        }
+
+        let print_return_type = |ty: FunctionType<'ctx>| ty.get_return_type().map_or_else(|| "void".to_string(), |ty| print_type(ty.as_type_ref()));
+        let print_params = |params: Vec<String>| {
+            let mut params = params;
+
+            if thunk_type.is_var_arg() {
+                params.push("...".to_string());
+            }
+
+            params.join(", ")
+        };
+        let thunk_params: Vec<_> = thunk_type.get_param_types().iter().map(|ty| ty.as_type_ref()).collect();
+        let target_params: Vec<_> = target_type.get_param_types().iter().map(|ty| ty.as_type_ref()).collect();
+        let thunk_return = print_return_type(thunk_type);
+        let target_return = print_return_type(target_type);
+        let target_name = print_global_name(target.get_name().to_bytes());
+
+        let declared_params = target_params.iter().map(|&ty| print_type(ty)).collect();
+        let _ = writeln!(ir, "declare {} {}({})", target_return, target_name, print_params(declared_params));
+
+        let defined_params = thunk_params.iter().enumerate().map(|(i, &ty)| format!("{} %arg{}", print_type(ty), i)).collect();
+        let _ = writeln!(ir, "define {} {}({}) {{", thunk_return, print_global_name(name.as_bytes()), print_params(defined_params));
+
+        let mut args = Vec::with_capacity(target_params.len());
+
+        for (i, (&thunk_param, &target_param)) in thunk_params.iter().zip(&target_params).enumerate() {
+            if thunk_param == target_param {
+                args.push(format!("{} %arg{}", print_type(target_param), i));
+            } else {
+                let _ = writeln!(ir, "  %cast{} = bitcast {} %arg{} to {}", i, print_type(thunk_param), i, print_type(target_param));
+
+                args.push(format!("{} %cast{}", print_type(target_param), i));
+            }
+        }
+
+        let call = format!("musttail call {} {}({})", print_type(target_type.as_type_ref()), target_name, print_params(args));
+
+        if target_type.get_return_type().is_none() {
+            let _ = writeln!(ir, "  {}\n  ret void", call);
+        } else if thunk_return == target_return {
+            let _ = writeln!(ir, "  %ret = {}\n  ret {} %ret", call, target_return);
+        } else {
+            // Only a bitcast of the result may come between a musttail call and the ret
+            let _ = writeln!(ir, "  %ret = {}", call);
+            let _ = writeln!(ir, "  %ret.cast = bitcast {} %ret to {}", target_return, thunk_return);
+            let _ = writeln!(ir, "  ret {} %ret.cast", thunk_return);
+        }
+
+        ir.push_str("}\n");
+        ir
+    }
+
     /// Creates a new `Module` from a `MemoryBuffer`.
     ///
     /// # Example
@@ -1429,6 +2053,101 @@ impl Drop for Module<'_> {
     }
 }
 
+macro_rules! module_value_iterator {
+    ($(#[$meta:meta])* $name:ident, $(#[$named_meta:meta])* $named:ident, $value:ident, $next:ident, $previous:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name<'ctx> {
+            front: Option<$value<'ctx>>,
+            back: Option<$value<'ctx>>,
+        }
+
+        impl<'ctx> Iterator for $name<'ctx> {
+            type Item = $value<'ctx>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let value = self.front?;
+
+                if Some(value) == self.back {
+                    self.front = None;
+                    self.back = None;
+                } else {
+                    self.front = value.$next();
+                }
+
+                Some(value)
+            }
+        }
+
+        impl<'ctx> DoubleEndedIterator for $name<'ctx> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                let value = self.back?;
+
+                if Some(value) == self.front {
+                    self.front = None;
+                    self.back = None;
+                } else {
+                    self.back = value.$previous();
+                }
+
+                Some(value)
+            }
+        }
+
+        impl FusedIterator for $name<'_> {}
+
+        $(#[$named_meta])*
+        #[derive(Debug, Clone)]
+        pub struct $named<'m, 'ctx> {
+            iter: $name<'ctx>,
+            _module: PhantomData<&'m Module<'ctx>>,
+        }
+
+        impl<'m, 'ctx> $named<'m, 'ctx> {
+            fn with_name(value: $value<'ctx>) -> (&'m CStr, $value<'ctx>) {
+                // The name is owned by the value itself, which lives as long as the module it is in.
+                let name = unsafe {
+                    &*(value.get_name() as *const CStr)
+                };
+
+                (name, value)
+            }
+        }
+
+        impl<'m, 'ctx> Iterator for $named<'m, 'ctx> {
+            type Item = (&'m CStr, $value<'ctx>);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next().map(Self::with_name)
+            }
+        }
+
+        impl<'m, 'ctx> DoubleEndedIterator for $named<'m, 'ctx> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back().map(Self::with_name)
+            }
+        }
+
+        impl FusedIterator for $named<'_, '_> {}
+    };
+}
+
+module_value_iterator! {
+    /// Iterates over the functions of a `Module` in the order they appear in it.
+    FunctionIterator,
+    /// Iterates over the functions of a `Module` along with their names, in the order they appear in it.
+    NamedFunctionIterator,
+    FunctionValue, get_next_function, get_previous_function
+}
+
+module_value_iterator! {
+    /// Iterates over the global variables of a `Module` in the order they appear in it.
+    GlobalIterator,
+    /// Iterates over the global variables of a `Module` along with their names, in the order they appear in it.
+    NamedGlobalIterator,
+    GlobalValue, get_next_global, get_previous_global
+}
+
 #[llvm_versions(7.0..=latest)]
 #[llvm_enum(LLVMModuleFlagBehavior)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -1462,3 +2181,92 @@ pub enum FlagBehavior {
     #[llvm_variant(LLVMModuleFlagBehaviorAppendUnique)]
     AppendUnique,
 }
+
+// Prints a type as it appears in textual IR. Named structs are printed by name only,
+// rather than along with their body.
+#[llvm_versions(3.9..=latest)]
+unsafe fn print_type(ty: LLVMTypeRef) -> String {
+    use llvm_sys::core::{LLVMIsLiteralStruct, LLVMPrintTypeToString};
+
+    let printed = LLVMString::new(LLVMPrintTypeToString(ty)).to_string();
+
+    if LLVMGetTypeKind(ty) == LLVMTypeKind::LLVMStructTypeKind && LLVMIsLiteralStruct(ty) == 0 {
+        if let Some(end) = printed.find(" = type ") {
+            return printed[..end].to_string();
+        }
+    }
+
+    printed
+}
+
+// Collects the named structs referenced by a type, without looking into their bodies
+#[llvm_versions(3.9..=latest)]
+unsafe fn collect_named_structs(ty: LLVMTypeRef, named_structs: &mut Vec<LLVMTypeRef>) {
+    use llvm_sys::core::{LLVMCountParamTypes, LLVMCountStructElementTypes, LLVMGetParamTypes, LLVMGetReturnType, LLVMGetStructElementTypes, LLVMIsLiteralStruct};
+
+    match LLVMGetTypeKind(ty) {
+        LLVMTypeKind::LLVMStructTypeKind if LLVMIsLiteralStruct(ty) == 0 => {
+            if !named_structs.contains(&ty) {
+                named_structs.push(ty);
+            }
+        },
+        LLVMTypeKind::LLVMStructTypeKind => {
+            let mut fields = vec![ptr::null_mut(); LLVMCountStructElementTypes(ty) as usize];
+
+            LLVMGetStructElementTypes(ty, fields.as_mut_ptr());
+
+            for field in fields {
+                collect_named_structs(field, named_structs);
+            }
+        },
+        LLVMTypeKind::LLVMFunctionTypeKind => {
+            let mut params = vec![ptr::null_mut(); LLVMCountParamTypes(ty) as usize];
+
+            LLVMGetParamTypes(ty, params.as_mut_ptr());
+            collect_named_structs(LLVMGetReturnType(ty), named_structs);
+
+            for param in params {
+                collect_named_structs(param, named_structs);
+            }
+        },
+        LLVMTypeKind::LLVMPointerTypeKind | LLVMTypeKind::LLVMArrayTypeKind | LLVMTypeKind::LLVMVectorTypeKind => {
+            collect_named_structs(LLVMGetElementType(ty), named_structs);
+        },
+        _ => (),
+    }
+}
+
+// Determines whether a thunk's parameter or return type may be passed on as the target's
+// under musttail, which allows pointers to differ in their pointee type only
+#[llvm_versions(3.9..=latest)]
+fn is_thunk_compatible(thunk_type: LLVMTypeRef, target_type: LLVMTypeRef) -> bool {
+    use llvm_sys::core::LLVMGetPointerAddressSpace;
+
+    if thunk_type == target_type {
+        return true;
+    }
+
+    unsafe {
+        LLVMGetTypeKind(thunk_type) == LLVMTypeKind::LLVMPointerTypeKind
+            && LLVMGetTypeKind(target_type) == LLVMTypeKind::LLVMPointerTypeKind
+            && LLVMGetPointerAddressSpace(thunk_type) == LLVMGetPointerAddressSpace(target_type)
+    }
+}
+
+// Prints a global's name as it appears in textual IR, quoted and with any bytes which
+// can't appear in a quoted name escaped
+#[llvm_versions(3.9..=latest)]
+fn print_global_name(name: &[u8]) -> String {
+    let mut printed = String::from("@\"");
+
+    for &byte in name {
+        if byte == b'"' || byte == b'\\' || !(0x20..0x7f).contains(&byte) {
+            printed.push_str(&format!("\\{:02X}", byte));
+        } else {
+            printed.push(byte as char);
+        }
+    }
+
+    printed.push('"');
+    printed
+}
@@ -0,0 +1,278 @@
+//! A structural comparison of two `Module`s, as produced by `Module::diff`.
+//!
+//! This compares functions and globals by name, and compares function bodies instruction by
+//! instruction, where each instruction is described by its opcode, type and operands. Local
+//! values are referred to by their position in the function regardless of their names:
+//! arguments as `%arg0`, `%arg1`, ..., basic blocks as `%bb0`, `%bb1`, ... and instructions
+//! by their index in the function's body, as `%0`, `%1`, .... Inserting an instruction thus
+//! also changes every later instruction which uses an instruction after it.
+
+use llvm_sys::core::{LLVMBasicBlockAsValue, LLVMGetOperand, LLVMGetNumOperands, LLVMIsABasicBlock, LLVMPrintTypeToString, LLVMPrintValueToString, LLVMTypeOf};
+use llvm_sys::prelude::LLVMValueRef;
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CStr;
+
+use crate::module::Module;
+use crate::support::LLVMString;
+use crate::values::{AsValueRef, FunctionValue, GlobalValue, InstructionValue};
+
+/// The differences between two modules, with every list sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleDiff {
+    added_functions: Vec<String>,
+    removed_functions: Vec<String>,
+    changed_functions: Vec<FunctionDiff>,
+    added_globals: Vec<String>,
+    removed_globals: Vec<String>,
+    changed_globals: Vec<String>,
+}
+
+impl ModuleDiff {
+    /// Gets the names of functions only present in the other module.
+    pub fn get_added_functions(&self) -> &[String] {
+        &self.added_functions
+    }
+
+    /// Gets the names of functions only present in the original module.
+    pub fn get_removed_functions(&self) -> &[String] {
+        &self.removed_functions
+    }
+
+    /// Gets the functions present in both modules which differ.
+    pub fn get_changed_functions(&self) -> &[FunctionDiff] {
+        &self.changed_functions
+    }
+
+    /// Gets the names of global variables only present in the other module.
+    pub fn get_added_globals(&self) -> &[String] {
+        &self.added_globals
+    }
+
+    /// Gets the names of global variables only present in the original module.
+    pub fn get_removed_globals(&self) -> &[String] {
+        &self.removed_globals
+    }
+
+    /// Gets the names of global variables present in both modules whose type, linkage,
+    /// constness or initializer differ.
+    pub fn get_changed_globals(&self) -> &[String] {
+        &self.changed_globals
+    }
+
+    /// Determines whether or not the modules were found to be equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.added_functions.is_empty() && self.removed_functions.is_empty() && self.changed_functions.is_empty() &&
+            self.added_globals.is_empty() && self.removed_globals.is_empty() && self.changed_globals.is_empty()
+    }
+}
+
+/// The differences between two versions of the same function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionDiff {
+    name: String,
+    signature_changed: bool,
+    instruction_changes: Vec<InstructionChange>,
+}
+
+impl FunctionDiff {
+    /// Gets the name of the function.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Determines whether the function's type or linkage changed.
+    pub fn is_signature_changed(&self) -> bool {
+        self.signature_changed
+    }
+
+    /// Gets the instructions which need to be removed from and added to the original
+    /// function's body to arrive at the other's, in the order they appear.
+    pub fn get_instruction_changes(&self) -> &[InstructionChange] {
+        &self.instruction_changes
+    }
+}
+
+/// A single instruction which differs between two versions of a function. Instructions are
+/// described by their opcode, type and operands, such as `Add i32 (i32 %arg0, i32 1)` or
+/// `Br void (i1 %2, label %bb2, label %bb1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionChange {
+    /// An instruction only present in the original function, at the given index of its body.
+    Removed(usize, String),
+    /// An instruction only present in the other function, at the given index of its body.
+    Added(usize, String),
+}
+
+pub(crate) fn diff_modules(original: &Module, other: &Module) -> ModuleDiff {
+    let mut diff = ModuleDiff::default();
+    let original_functions = named_values(original.get_named_functions());
+    let other_functions = named_values(other.get_named_functions());
+
+    for (name, function) in &original_functions {
+        match other_functions.get(name) {
+            Some(other_function) => {
+                if let Some(function_diff) = diff_functions(name, *function, *other_function) {
+                    diff.changed_functions.push(function_diff);
+                }
+            },
+            None => diff.removed_functions.push(name.clone()),
+        }
+    }
+
+    diff.added_functions = other_functions.keys().filter(|name| !original_functions.contains_key(*name)).cloned().collect();
+
+    let original_globals = named_values(original.get_named_globals());
+    let other_globals = named_values(other.get_named_globals());
+
+    for (name, global) in &original_globals {
+        match other_globals.get(name) {
+            Some(other_global) => {
+                if describe_global(*global) != describe_global(*other_global) {
+                    diff.changed_globals.push(name.clone());
+                }
+            },
+            None => diff.removed_globals.push(name.clone()),
+        }
+    }
+
+    diff.added_globals = other_globals.keys().filter(|name| !original_globals.contains_key(*name)).cloned().collect();
+
+    diff
+}
+
+fn named_values<'a, V, I: Iterator<Item = (&'a CStr, V)>>(iter: I) -> BTreeMap<String, V> {
+    iter.map(|(name, value)| (name.to_string_lossy().into_owned(), value)).collect()
+}
+
+fn describe_global(global: GlobalValue) -> (String, String, bool, Option<String>) {
+    let type_string = unsafe {
+        print_type(global.as_value_ref())
+    };
+    let initializer = global.get_initializer().map(|value| unsafe {
+        LLVMString::new(LLVMPrintValueToString(value.as_value_ref())).to_string()
+    });
+
+    (type_string, format!("{:?}", global.get_linkage()), global.is_constant(), initializer)
+}
+
+fn diff_functions(name: &str, original: FunctionValue, other: FunctionValue) -> Option<FunctionDiff> {
+    let signature_changed = unsafe {
+        print_type(original.as_value_ref()) != print_type(other.as_value_ref())
+    } || original.get_linkage() != other.get_linkage();
+    let instruction_changes = diff_sequences(&describe_body(original), &describe_body(other));
+
+    if !signature_changed && instruction_changes.is_empty() {
+        return None;
+    }
+
+    Some(FunctionDiff {
+        name: name.to_string(),
+        signature_changed,
+        instruction_changes,
+    })
+}
+
+fn describe_body(function: FunctionValue) -> Vec<String> {
+    let slots = number_locals(function);
+    let mut instructions = Vec::new();
+    let mut basic_block = function.get_first_basic_block();
+
+    while let Some(block) = basic_block {
+        let mut instruction = block.get_first_instruction();
+
+        while let Some(current) = instruction {
+            instructions.push(unsafe { describe_instruction(current.as_value_ref(), &slots) });
+            instruction = current.get_next_instruction();
+        }
+
+        basic_block = block.get_next_basic_block();
+    }
+
+    instructions
+}
+
+// Numbers the function's arguments, basic blocks and instructions by their position.
+fn number_locals(function: FunctionValue) -> HashMap<LLVMValueRef, String> {
+    let mut slots = HashMap::new();
+
+    for (i, param) in function.get_param_iter().enumerate() {
+        slots.insert(param.as_value_ref(), format!("%arg{}", i));
+    }
+
+    let mut index = 0;
+
+    for (i, block) in function.get_basic_blocks().into_iter().enumerate() {
+        slots.insert(unsafe { LLVMBasicBlockAsValue(block.basic_block) }, format!("%bb{}", i));
+
+        let mut instruction = block.get_first_instruction();
+
+        while let Some(current) = instruction {
+            slots.insert(current.as_value_ref(), format!("%{}", index));
+            index += 1;
+            instruction = current.get_next_instruction();
+        }
+    }
+
+    slots
+}
+
+unsafe fn describe_instruction(instruction: LLVMValueRef, slots: &HashMap<LLVMValueRef, String>) -> String {
+    let opcode = InstructionValue::new(instruction).get_opcode();
+    let operands: Vec<String> = (0..LLVMGetNumOperands(instruction))
+        .map(|i| describe_operand(LLVMGetOperand(instruction, i as u32), slots))
+        .collect();
+
+    format!("{:?} {} ({})", opcode, print_type(instruction), operands.join(", "))
+}
+
+unsafe fn describe_operand(operand: LLVMValueRef, slots: &HashMap<LLVMValueRef, String>) -> String {
+    if operand.is_null() {
+        return String::from("null");
+    }
+
+    match slots.get(&operand) {
+        Some(slot) if !LLVMIsABasicBlock(operand).is_null() => format!("label {}", slot),
+        Some(slot) => format!("{} {}", print_type(operand), slot),
+        None => LLVMString::new(LLVMPrintValueToString(operand)).to_string(),
+    }
+}
+
+unsafe fn print_type(value: LLVMValueRef) -> String {
+    LLVMString::new(LLVMPrintTypeToString(LLVMTypeOf(value))).to_string()
+}
+
+// Finds a shortest edit script between two instruction sequences through their longest
+// common subsequence.
+fn diff_sequences(original: &[String], other: &[String]) -> Vec<InstructionChange> {
+    let (n, m) = (original.len(), other.len());
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if original[i] == other[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < n || j < m {
+        if i < n && j < m && original[i] == other[j] {
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            changes.push(InstructionChange::Added(j, other[j].clone()));
+            j += 1;
+        } else {
+            changes.push(InstructionChange::Removed(i, original[i].clone()));
+            i += 1;
+        }
+    }
+
+    changes
+}
@@ -21,10 +21,16 @@ use llvm_sys::transforms::scalar::LLVMAddConstantPropagationPass;
 use llvm_sys::transforms::scalar::LLVMAddInstructionSimplifyPass;
 
 use crate::OptimizationLevel;
+#[llvm_versions(3.9..=latest)]
+use crate::attributes::{Attribute, AttributeLoc};
 use crate::module::Module;
 #[llvm_versions(3.6..=3.8)]
 use crate::targets::TargetData;
 use crate::values::{AsValueRef, FunctionValue};
+#[llvm_versions(3.9..=latest)]
+use crate::support::to_c_str;
+#[llvm_versions(3.9..=latest)]
+use crate::values::{CallSiteValue, InstructionValue};
 
 use std::borrow::Borrow;
 use std::marker::PhantomData;
@@ -166,6 +172,18 @@ impl PassManagerBuilder {
             LLVMPassManagerBuilderPopulateLTOPassManager(self.pass_manager_builder, pass_manager.pass_manager, internalize as i32, run_inliner as i32)
         }
     }
+
+    /// Registers the coroutine lowering passes at the appropriate points of the pipelines
+    /// this builder populates, so that modules using coroutine intrinsics are lowered as
+    /// part of the usual optimizations.
+    #[llvm_versions(8.0..=latest)]
+    pub fn add_coroutine_passes_to_extension_points(&self) {
+        use llvm_sys::transforms::coroutines::LLVMPassManagerBuilderAddCoroutinePassesToExtensionPoints;
+
+        unsafe {
+            LLVMPassManagerBuilderAddCoroutinePassesToExtensionPoints(self.pass_manager_builder)
+        }
+    }
 }
 
 impl Drop for PassManagerBuilder {
@@ -258,9 +276,13 @@ impl<T: PassManagerSubType> PassManager<T> {
     /// This method returns true if any of the passes modified the function or module
     /// and false otherwise.
     pub fn run_on(&self, input: &T) -> bool {
-        unsafe {
+        let changed = unsafe {
             input.run_in_pass_manager(self)
-        }
+        };
+
+        instrument_event!("ran pass manager, changed: {}", changed);
+
+        changed
     }
 
     #[llvm_versions(3.6..=3.8)]
@@ -1158,6 +1180,394 @@ impl<T: PassManagerSubType> PassManager<T> {
     }
 }
 
+/// Promotes the allocas of a single function to SSA values, which is the cheap cleanup
+/// frontends emitting naive alloca based code usually want before JIT compiling it.
+/// This runs the `mem2reg` pass over `function`, preceded by scalar replacement of
+/// aggregates if `split_aggregates` is true so that allocas of structs and arrays whose
+/// fields are accessed individually can be promoted too.
+///
+/// Returns true if the function was modified.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::passes::promote_allocas;
+///
+/// let context = Context::create();
+/// let module = context.create_module("my_module");
+/// let builder = context.create_builder();
+/// let i32_type = context.i32_type();
+/// let function = module.add_function("identity", i32_type.fn_type(&[i32_type.into()], false), None);
+///
+/// builder.position_at_end(context.append_basic_block(function, "entry"));
+///
+/// let slot = builder.build_alloca(i32_type, "slot");
+///
+/// builder.build_store(slot, function.get_first_param().unwrap());
+/// builder.build_return(Some(&builder.build_load(slot, "value")));
+///
+/// assert!(promote_allocas(function, false));
+/// ```
+pub fn promote_allocas(function: FunctionValue, split_aggregates: bool) -> bool {
+    use llvm_sys::core::LLVMGetGlobalParent;
+
+    let pass_manager: PassManager<FunctionValue> = unsafe {
+        PassManager::new(LLVMCreateFunctionPassManagerForModule(LLVMGetGlobalParent(function.as_value_ref())))
+    };
+
+    if split_aggregates {
+        pass_manager.add_scalar_repl_aggregates_pass();
+    }
+
+    pass_manager.add_promote_memory_to_register_pass();
+    pass_manager.initialize();
+
+    let changed = pass_manager.run_on(&function);
+
+    pass_manager.finalize();
+
+    changed
+}
+
+/// Moves the instructions from `first` up to and including `last`, which must be in the same
+/// basic block, into a new function called `name`, and replaces them with a call to it. Values
+/// the region uses but does not define become parameters of the new function, in the order they
+/// are first used. Values the region defines and which are used after it are returned: directly
+/// if there is a single one, or packed into an anonymous struct if there are several.
+///
+/// This is useful for OpenMP style outlining of a region into a function which can be passed to
+/// a runtime, and for splitting functions which are too large to compile quickly.
+///
+/// The new function has private linkage. The region may not contain phi nodes, landing pads,
+/// or terminators, and allocas in it may not be used after it, as they would then point into
+/// the new function's stack frame. Debug locations of the moved instructions are left as is,
+/// so functions with debug info may need them updated for the module to verify.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::passes::outline_instructions;
+///
+/// let context = Context::create();
+/// let module = context.create_module("my_module");
+/// let builder = context.create_builder();
+/// let i32_type = context.i32_type();
+/// let function = module.add_function("sum_of_squares", i32_type.fn_type(&[i32_type.into(), i32_type.into()], false), None);
+/// let x = function.get_first_param().unwrap().into_int_value();
+/// let y = function.get_nth_param(1).unwrap().into_int_value();
+///
+/// builder.position_at_end(context.append_basic_block(function, "entry"));
+///
+/// let x_squared = builder.build_int_mul(x, x, "x_squared");
+/// let y_squared = builder.build_int_mul(y, y, "y_squared");
+/// let sum = builder.build_int_add(x_squared, y_squared, "sum");
+///
+/// builder.build_return(Some(&sum));
+///
+/// let first = x_squared.as_instruction().unwrap();
+/// let last = sum.as_instruction().unwrap();
+/// let (outlined, _call) = outline_instructions(first, last, "sum_of_squares.outlined").unwrap();
+///
+/// assert_eq!(outlined.count_params(), 2);
+/// assert!(module.verify().is_ok());
+/// ```
+#[llvm_versions(3.9..=latest)]
+pub fn outline_instructions<'ctx>(
+    first: InstructionValue<'ctx>,
+    last: InstructionValue<'ctx>,
+    name: &str,
+) -> Result<(FunctionValue<'ctx>, CallSiteValue<'ctx>), &'static str> {
+    use llvm_sys::LLVMLinkage;
+    use llvm_sys::core::{
+        LLVMAddFunction, LLVMAppendBasicBlockInContext, LLVMBuildCall, LLVMBuildExtractValue, LLVMBuildInsertValue,
+        LLVMBuildRet, LLVMBuildRetVoid, LLVMCreateBuilderInContext, LLVMDisposeBuilder, LLVMFunctionType,
+        LLVMGetFirstUse, LLVMGetGlobalParent, LLVMGetNextUse, LLVMGetNumOperands, LLVMGetOperand, LLVMGetParam,
+        LLVMGetTypeContext, LLVMGetUndef, LLVMGetUser, LLVMInsertIntoBuilder, LLVMInstructionRemoveFromParent,
+        LLVMIsAAllocaInst, LLVMIsAArgument, LLVMIsAInstruction, LLVMIsALandingPadInst, LLVMIsAPHINode,
+        LLVMIsATerminatorInst, LLVMPositionBuilderAtEnd, LLVMPositionBuilderBefore, LLVMSetLinkage, LLVMSetOperand,
+        LLVMStructTypeInContext, LLVMTypeOf, LLVMVoidTypeInContext,
+    };
+    use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+
+    let block = first.get_parent().ok_or("The instructions must be inserted in a basic block.")?;
+    let function = block.get_parent().ok_or("The instructions must be inserted in a function.")?;
+
+    if last.get_parent() != Some(block) {
+        return Err("The first and last instructions must be in the same basic block.");
+    }
+
+    let mut region = vec![first];
+
+    while region[region.len() - 1] != last {
+        let next = region[region.len() - 1].get_next_instruction().ok_or("The last instruction must not come before the first one.")?;
+
+        region.push(next);
+    }
+
+    let region: Vec<LLVMValueRef> = region.iter().map(|instruction| instruction.as_value_ref()).collect();
+    let is_in_region = |value: LLVMValueRef| region.contains(&value);
+
+    unsafe {
+        let is_outlinable = |&instruction: &LLVMValueRef| {
+            LLVMIsAPHINode(instruction).is_null() && LLVMIsALandingPadInst(instruction).is_null() && LLVMIsATerminatorInst(instruction).is_null()
+        };
+
+        if !region.iter().all(is_outlinable) {
+            return Err("The region must not contain phi nodes, landing pads or terminators.");
+        }
+
+        let mut live_ins = Vec::new();
+
+        for &instruction in &region {
+            for index in 0..LLVMGetNumOperands(instruction) {
+                let operand = LLVMGetOperand(instruction, index as u32);
+                let is_local = !LLVMIsAArgument(operand).is_null() || !LLVMIsAInstruction(operand).is_null();
+
+                if is_local && !is_in_region(operand) && !live_ins.contains(&operand) {
+                    live_ins.push(operand);
+                }
+            }
+        }
+
+        let mut live_outs = Vec::new();
+        let mut outside_users = Vec::new();
+
+        for &instruction in &region {
+            let mut value_use = LLVMGetFirstUse(instruction);
+            let mut is_live_out = false;
+
+            while !value_use.is_null() {
+                let user = LLVMGetUser(value_use);
+
+                if !is_in_region(user) {
+                    is_live_out = true;
+
+                    if !outside_users.contains(&user) {
+                        outside_users.push(user);
+                    }
+                }
+
+                value_use = LLVMGetNextUse(value_use);
+            }
+
+            if is_live_out {
+                if !LLVMIsAAllocaInst(instruction).is_null() {
+                    return Err("Allocas in the region must not be used after it.");
+                }
+
+                live_outs.push(instruction);
+            }
+        }
+
+        let context = LLVMGetTypeContext(LLVMTypeOf(function.as_value_ref()));
+        let mut param_types: Vec<LLVMTypeRef> = live_ins.iter().map(|&value| LLVMTypeOf(value)).collect();
+        let mut live_out_types: Vec<LLVMTypeRef> = live_outs.iter().map(|&value| LLVMTypeOf(value)).collect();
+        let return_type = match live_out_types.len() {
+            0 => LLVMVoidTypeInContext(context),
+            1 => live_out_types[0],
+            count => LLVMStructTypeInContext(context, live_out_types.as_mut_ptr(), count as u32, false as i32),
+        };
+        let fn_type = LLVMFunctionType(return_type, param_types.as_mut_ptr(), param_types.len() as u32, false as i32);
+        let c_string = to_c_str(name);
+        let outlined = LLVMAddFunction(LLVMGetGlobalParent(function.as_value_ref()), c_string.as_ptr(), fn_type);
+
+        LLVMSetLinkage(outlined, LLVMLinkage::LLVMPrivateLinkage);
+
+        // Moving the region leaves the instruction after it in place, so the call can be built before it
+        let insert_point = last.get_next_instruction().map(|instruction| instruction.as_value_ref());
+        let builder = LLVMCreateBuilderInContext(context);
+        let entry = LLVMAppendBasicBlockInContext(context, outlined, b"entry\0".as_ptr() as *const _);
+
+        LLVMPositionBuilderAtEnd(builder, entry);
+
+        for &instruction in &region {
+            LLVMInstructionRemoveFromParent(instruction);
+            LLVMInsertIntoBuilder(builder, instruction);
+
+            for index in 0..LLVMGetNumOperands(instruction) {
+                let operand = LLVMGetOperand(instruction, index as u32);
+
+                if let Some(param_index) = live_ins.iter().position(|&live_in| live_in == operand) {
+                    LLVMSetOperand(instruction, index as u32, LLVMGetParam(outlined, param_index as u32));
+                }
+            }
+        }
+
+        match live_outs.len() {
+            0 => { LLVMBuildRetVoid(builder); },
+            1 => { LLVMBuildRet(builder, live_outs[0]); },
+            _ => {
+                let mut aggregate = LLVMGetUndef(return_type);
+
+                for (index, &live_out) in live_outs.iter().enumerate() {
+                    aggregate = LLVMBuildInsertValue(builder, aggregate, live_out, index as u32, b"\0".as_ptr() as *const _);
+                }
+
+                LLVMBuildRet(builder, aggregate);
+            },
+        }
+
+        match insert_point {
+            Some(insert_point) => LLVMPositionBuilderBefore(builder, insert_point),
+            None => LLVMPositionBuilderAtEnd(builder, block.basic_block),
+        }
+
+        let call = LLVMBuildCall(builder, outlined, live_ins.as_mut_ptr(), live_ins.len() as u32, b"\0".as_ptr() as *const _);
+        let results: Vec<LLVMValueRef> = match live_outs.len() {
+            0 => Vec::new(),
+            1 => vec![call],
+            _ => (0..live_outs.len())
+                .map(|index| LLVMBuildExtractValue(builder, call, index as u32, b"\0".as_ptr() as *const _))
+                .collect(),
+        };
+
+        LLVMDisposeBuilder(builder);
+
+        for &user in &outside_users {
+            for index in 0..LLVMGetNumOperands(user) {
+                let operand = LLVMGetOperand(user, index as u32);
+
+                if let Some(live_out_index) = live_outs.iter().position(|&live_out| live_out == operand) {
+                    LLVMSetOperand(user, index as u32, results[live_out_index]);
+                }
+            }
+        }
+
+        let outlined = FunctionValue::new(outlined).expect("Outlined function should be valid");
+
+        Ok((outlined, CallSiteValue::new(call)))
+    }
+}
+
+/// Inlines the function called by `call` into its caller, which partial evaluators and
+/// specializing frontends need to control precisely. As LLVM's inlining utilities themselves
+/// are not exposed through its C API, the call site is marked `alwaysinline` and the always
+/// inliner is run over the module, with the `alwaysinline` attribute temporarily removed from
+/// every other function and call site so that only `call` is inlined. Those attributes are
+/// restored afterwards, though copies of the callee's own call sites made by inlining it do
+/// not carry them.
+///
+/// Returns true if the call was inlined, in which case `call` no longer exists and must not be
+/// used afterwards. Otherwise, for example because LLVM considers the callee impossible to
+/// inline, the call site's inlining attributes are reset to what they were before.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::passes::inline_call_site;
+///
+/// let context = Context::create();
+/// let module = context.create_module("my_module");
+/// let builder = context.create_builder();
+/// let i32_type = context.i32_type();
+/// let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+/// let square = module.add_function("square", fn_type, None);
+/// let caller = module.add_function("caller", fn_type, None);
+///
+/// builder.position_at_end(context.append_basic_block(square, "entry"));
+///
+/// let x = square.get_first_param().unwrap().into_int_value();
+///
+/// builder.build_return(Some(&builder.build_int_mul(x, x, "squared")));
+/// builder.position_at_end(context.append_basic_block(caller, "entry"));
+///
+/// let call = builder.build_call(square, &[caller.get_first_param().unwrap().into()], "call");
+///
+/// builder.build_return(Some(&call.try_as_basic_value().left().unwrap()));
+///
+/// assert!(inline_call_site(call).unwrap());
+/// ```
+#[llvm_versions(3.9..=latest)]
+pub fn inline_call_site(call: CallSiteValue) -> Result<bool, &'static str> {
+    use llvm_sys::core::{LLVMGetBasicBlockParent, LLVMGetCalledValue, LLVMGetFirstBasicBlock, LLVMGetFirstFunction, LLVMGetFirstInstruction, LLVMGetGlobalParent, LLVMGetInstructionParent, LLVMGetNextBasicBlock, LLVMGetNextFunction, LLVMGetNextInstruction, LLVMIsACallInst, LLVMIsAFunction, LLVMIsAInvokeInst, LLVMIsDeclaration};
+
+    unsafe {
+        let block = LLVMGetInstructionParent(call.as_value_ref());
+
+        if block.is_null() || LLVMGetBasicBlockParent(block).is_null() {
+            return Err("The call must be inserted in a function.");
+        }
+
+        let callee = LLVMGetCalledValue(call.as_value_ref());
+
+        if LLVMIsAFunction(callee).is_null() || LLVMIsDeclaration(callee) == 1 {
+            return Err("The call must directly call a function which is defined in the module.");
+        }
+
+        let module = LLVMGetGlobalParent(callee);
+        let kind_id = Attribute::get_named_enum_kind_id("alwaysinline");
+        let mut stripped_functions = Vec::new();
+        let mut stripped_calls = Vec::new();
+        let mut function = LLVMGetFirstFunction(module);
+
+        while !function.is_null() {
+            let fn_value = FunctionValue::new(function).expect("Function should be valid");
+
+            if let Some(attribute) = fn_value.get_enum_attribute(AttributeLoc::Function, kind_id) {
+                fn_value.remove_enum_attribute(AttributeLoc::Function, kind_id);
+                stripped_functions.push((fn_value, attribute));
+            }
+
+            let mut block = LLVMGetFirstBasicBlock(function);
+
+            while !block.is_null() {
+                let mut instruction = LLVMGetFirstInstruction(block);
+
+                while !instruction.is_null() {
+                    let is_call = !LLVMIsACallInst(instruction).is_null() || !LLVMIsAInvokeInst(instruction).is_null();
+
+                    if is_call && instruction != call.as_value_ref() {
+                        let other_call = CallSiteValue::new(instruction);
+
+                        if let Some(attribute) = other_call.get_enum_attribute(AttributeLoc::Function, kind_id) {
+                            other_call.remove_enum_attribute(AttributeLoc::Function, kind_id);
+                            stripped_calls.push((other_call, attribute));
+                        }
+                    }
+
+                    instruction = LLVMGetNextInstruction(instruction);
+                }
+
+                block = LLVMGetNextBasicBlock(block);
+            }
+
+            function = LLVMGetNextFunction(function);
+        }
+
+        let was_always_inline = call.is_always_inline();
+        let was_no_inline = call.is_no_inline();
+
+        call.set_always_inline(true);
+
+        let pass_manager = LLVMCreatePassManager();
+
+        LLVMAddAlwaysInlinerPass(pass_manager);
+
+        // Only `call` is a candidate, so the module only changes if it was inlined
+        let inlined = LLVMRunPassManager(pass_manager, module) == 1;
+
+        LLVMDisposePassManager(pass_manager);
+
+        for (fn_value, attribute) in stripped_functions {
+            fn_value.add_attribute(AttributeLoc::Function, attribute);
+        }
+
+        for (other_call, attribute) in stripped_calls {
+            other_call.add_attribute(AttributeLoc::Function, attribute);
+        }
+
+        if !inlined {
+            call.set_always_inline(was_always_inline);
+            call.set_no_inline(was_no_inline);
+        }
+
+        Ok(inlined)
+    }
+}
+
 impl<T> Drop for PassManager<T> {
     fn drop(&mut self) {
         unsafe {
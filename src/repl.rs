@@ -0,0 +1,197 @@
+//! A `ReplEngine` implements the usual read-eval-print loop pattern on top of a JIT
+//! `ExecutionEngine`, as popularized by LLVM's Kaleidoscope tutorial.
+//!
+//! Definitions, such as functions, are added once and persist for the lifetime of the engine.
+//! Each top level expression is compiled into its own module holding a single anonymous
+//! function, which is run and then removed from the engine again so that its anonymous
+//! function is no longer looked up when resolving symbols.
+//!
+//! This is built on MCJIT, since inkwell has no bindings to LLVM's ORC based JITs. Removing a
+//! module from an MCJIT engine does not free the machine code which was emitted for it, so the
+//! memory used by evaluated expressions is only reclaimed once the `ReplEngine` is dropped.
+
+use crate::OptimizationLevel;
+use crate::context::Context;
+use crate::execution_engine::ExecutionEngine;
+use crate::module::{Linkage, Module};
+use crate::support::LLVMString;
+use crate::types::BasicType;
+use crate::values::FunctionValue;
+
+use std::mem;
+
+/// A single top level expression, consisting of a fresh module and the anonymous function
+/// in it whose body should be built to compute the expression.
+#[derive(Debug)]
+pub struct ReplExpression<'ctx> {
+    module: Module<'ctx>,
+    function: FunctionValue<'ctx>,
+}
+
+impl<'ctx> ReplExpression<'ctx> {
+    /// Gets the module the expression should be built in.
+    pub fn get_module(&self) -> &Module<'ctx> {
+        &self.module
+    }
+
+    /// Gets the anonymous function computing the expression, which takes no parameters
+    /// and has no basic blocks yet.
+    pub fn get_function(&self) -> FunctionValue<'ctx> {
+        self.function
+    }
+}
+
+/// Evaluates top level expressions against a growing set of persistent definitions.
+///
+/// # Example
+///
+/// ```no_run
+/// use inkwell::OptimizationLevel;
+/// use inkwell::context::Context;
+/// use inkwell::repl::ReplEngine;
+/// use inkwell::targets::{InitializationConfig, Target};
+///
+/// Target::initialize_native(&InitializationConfig::default()).unwrap();
+///
+/// let context = Context::create();
+/// let builder = context.create_builder();
+/// let f64_type = context.f64_type();
+/// let mut repl = ReplEngine::new(&context, OptimizationLevel::None).unwrap();
+///
+/// // def two() 2.0
+/// let module = context.create_module("two");
+/// let two = module.add_function("two", f64_type.fn_type(&[], false), None);
+///
+/// builder.position_at_end(context.append_basic_block(two, "entry"));
+/// builder.build_return(Some(&f64_type.const_float(2.0)));
+/// repl.add_definitions(module).unwrap();
+///
+/// // two() + 1.0
+/// let expression = repl.create_expression(f64_type);
+/// let two = repl.declare_persistent_function(expression.get_module(), "two").unwrap();
+///
+/// builder.position_at_end(context.append_basic_block(expression.get_function(), "entry"));
+///
+/// let call = builder.build_call(two, &[], "call").try_as_basic_value().left().unwrap();
+/// let sum = builder.build_float_add(call.into_float_value(), f64_type.const_float(1.0), "sum");
+///
+/// builder.build_return(Some(&sum));
+///
+/// let result: f64 = unsafe { repl.evaluate(expression).unwrap() };
+///
+/// assert_eq!(result, 3.0);
+/// assert_eq!(repl.get_expression_count(), 1);
+/// ```
+#[derive(Debug)]
+pub struct ReplEngine<'ctx> {
+    context: &'ctx Context,
+    execution_engine: ExecutionEngine<'ctx>,
+    // Kept alive for as long as the engine, which owns them once added
+    definitions: Vec<Module<'ctx>>,
+    expression_count: u32,
+}
+
+impl<'ctx> ReplEngine<'ctx> {
+    /// Creates a `ReplEngine` backed by a JIT `ExecutionEngine`. The native target
+    /// needs to have been initialized beforehand.
+    pub fn new(context: &'ctx Context, opt_level: OptimizationLevel) -> Result<Self, LLVMString> {
+        let module = context.create_module("repl");
+        let execution_engine = module.create_jit_execution_engine(opt_level)?;
+
+        Ok(ReplEngine {
+            context,
+            execution_engine,
+            definitions: vec![module],
+            expression_count: 0,
+        })
+    }
+
+    /// Gets the underlying `ExecutionEngine`.
+    pub fn get_execution_engine(&self) -> &ExecutionEngine<'ctx> {
+        &self.execution_engine
+    }
+
+    /// Gets how many expressions have been evaluated so far.
+    pub fn get_expression_count(&self) -> u32 {
+        self.expression_count
+    }
+
+    /// Adds a module of definitions which stay available to all later definitions and
+    /// expressions, which may refer to them through declarations of the same name.
+    pub fn add_definitions(&mut self, module: Module<'ctx>) -> Result<(), &'static str> {
+        self.execution_engine.add_module(&module)
+            .map_err(|_| "Module is already owned by an execution engine.")?;
+
+        self.definitions.push(module);
+
+        Ok(())
+    }
+
+    /// Finds the most recently added persistent definition called `name`.
+    pub fn get_persistent_function(&self, name: &str) -> Option<FunctionValue<'ctx>> {
+        self.definitions.iter()
+            .rev()
+            .filter_map(|module| module.get_function(name))
+            .find(|function| !function.as_global_value().is_declaration())
+    }
+
+    /// Declares the persistent definition called `name` in `module` so that it can be called
+    /// from there, reusing an existing declaration if `module` already has one.
+    pub fn declare_persistent_function(&self, module: &Module<'ctx>, name: &str) -> Option<FunctionValue<'ctx>> {
+        if let Some(function) = module.get_function(name) {
+            return Some(function);
+        }
+
+        let definition = self.get_persistent_function(name)?;
+
+        Some(module.add_function(name, definition.get_type(), Some(Linkage::External)))
+    }
+
+    /// Creates the module and anonymous function for the next expression, which
+    /// will return a value of `return_type`.
+    pub fn create_expression<T: BasicType<'ctx>>(&self, return_type: T) -> ReplExpression<'ctx> {
+        let name = format!("__repl_expr_{}", self.expression_count);
+        let module = self.context.create_module(&name);
+        let function = module.add_function(&name, return_type.fn_type(&[], false), None);
+
+        ReplExpression {
+            module,
+            function,
+        }
+    }
+
+    /// Compiles and runs `expression`, then removes it from the engine again.
+    ///
+    /// # Safety
+    ///
+    /// `R` must match the return type the expression was created with, and running the
+    /// generated code must itself be safe.
+    pub unsafe fn evaluate<R>(&mut self, expression: ReplExpression<'ctx>) -> Result<R, &'static str> {
+        let ReplExpression { module, function } = expression;
+
+        if function.count_basic_blocks() == 0 || !function.verify(false) {
+            return Err("Expression function is not valid.");
+        }
+
+        let name = function.get_name().to_str().map_err(|_| "Expression name is not valid UTF-8.")?.to_string();
+
+        self.execution_engine.add_module(&module)
+            .map_err(|_| "Module is already owned by an execution engine.")?;
+
+        let address = self.execution_engine.get_function_address(&name);
+        let result = address.map(|address| {
+            let function: extern "C" fn() -> R = mem::transmute(address);
+
+            function()
+        });
+
+        self.execution_engine.remove_module(&module)
+            .map_err(|_| "Expression module could not be removed from the execution engine.")?;
+
+        let result = result.map_err(|_| "Expression function was not compiled.")?;
+
+        self.expression_count += 1;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,355 @@
+//! Parses the stack map section LLVM emits for `llvm.experimental.stackmap` and
+//! `llvm.experimental.patchpoint` calls.
+//!
+//! Code generation records where each live value of a stack map or patch point ended up in a
+//! section named `__llvm_stackmaps` (`__LLVM_STACKMAPS,__llvm_stackmaps` on macOS). Runtimes
+//! read it back to reconstruct the state of a frame when deoptimizing, or to find the bytes
+//! reserved by a patch point when patching an inline cache. Versions 1 to 3 of the format are
+//! supported, and the section is expected to be in the host's byte order.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use inkwell::memory_buffer::MemoryBuffer;
+//! use inkwell::stack_map::StackMap;
+//! use std::path::Path;
+//!
+//! let buffer = MemoryBuffer::create_from_file(Path::new("jit.o")).unwrap();
+//! let object_file = buffer.create_object_file().unwrap();
+//!
+//! for section in object_file.get_sections() {
+//!     if section.get_name().map_or(false, |name| name.to_bytes().ends_with(b"__llvm_stackmaps")) {
+//!         let stack_map = StackMap::parse(section.get_contents()).unwrap();
+//!
+//!         for record in stack_map.get_records() {
+//!             println!("{} at {:#x}: {:?}", record.get_id(), record.get_instruction_offset(), record.get_locations());
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::convert::TryInto;
+
+/// A parsed stack map section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackMap {
+    version: u8,
+    functions: Vec<StackSizeRecord>,
+    constants: Vec<u64>,
+    records: Vec<StackMapRecord>,
+}
+
+impl StackMap {
+    /// Parses the contents of a stack map section.
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, offset: 0 };
+        let version = reader.read_u8()?;
+
+        if version < 1 || version > 3 {
+            return Err("Unsupported stack map version.");
+        }
+
+        // Reserved
+        reader.read_u8()?;
+        reader.read_u16()?;
+
+        let num_functions = reader.read_u32()?;
+        let num_constants = reader.read_u32()?;
+        let num_records = reader.read_u32()?;
+        let mut functions = Vec::new();
+        let mut constants = Vec::new();
+        let mut records = Vec::new();
+
+        for _ in 0..num_functions {
+            functions.push(StackSizeRecord {
+                address: reader.read_u64()?,
+                stack_size: reader.read_u64()?,
+                record_count: if version >= 2 { Some(reader.read_u64()?) } else { None },
+            });
+        }
+
+        for _ in 0..num_constants {
+            constants.push(reader.read_u64()?);
+        }
+
+        for _ in 0..num_records {
+            records.push(StackMapRecord::parse(&mut reader, version)?);
+        }
+
+        Ok(StackMap {
+            version,
+            functions,
+            constants,
+            records,
+        })
+    }
+
+    /// Gets the version of the stack map format the section was written in.
+    pub fn get_version(&self) -> u8 {
+        self.version
+    }
+
+    /// Gets the stack size of every function containing a stack map or patch point.
+    pub fn get_functions(&self) -> &[StackSizeRecord] {
+        &self.functions
+    }
+
+    /// Gets the pool of large constants referenced by `Location::ConstantIndex`.
+    pub fn get_constants(&self) -> &[u64] {
+        &self.constants
+    }
+
+    /// Gets the records of every stack map and patch point, in the order the functions
+    /// containing them are listed in.
+    pub fn get_records(&self) -> &[StackMapRecord] {
+        &self.records
+    }
+
+    /// Gets the first record with the given id.
+    pub fn get_record(&self, id: u64) -> Option<&StackMapRecord> {
+        self.records.iter().find(|record| record.id == id)
+    }
+
+    /// Gets the value of a constant location, looking up large constants in the constant pool.
+    pub fn get_constant_value(&self, location: &Location) -> Option<u64> {
+        match *location {
+            Location::Constant(value) => Some(value as i64 as u64),
+            Location::ConstantIndex(index) => self.constants.get(index as usize).copied(),
+            _ => None,
+        }
+    }
+}
+
+/// The stack size of a function containing stack maps or patch points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackSizeRecord {
+    address: u64,
+    stack_size: u64,
+    record_count: Option<u64>,
+}
+
+impl StackSizeRecord {
+    /// Gets the address of the function. Unless the section has been relocated, this is
+    /// usually zero and must be resolved through the relocations of the section.
+    pub fn get_address(&self) -> u64 {
+        self.address
+    }
+
+    /// Gets the size of the function's stack frame, or `std::u64::MAX` if it is dynamically sized.
+    pub fn get_stack_size(&self) -> u64 {
+        self.stack_size
+    }
+
+    /// Gets how many of the records belong to this function. This is not recorded prior to
+    /// version 2 of the format.
+    pub fn get_record_count(&self) -> Option<u64> {
+        self.record_count
+    }
+}
+
+/// The live values recorded for a single stack map or patch point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackMapRecord {
+    id: u64,
+    instruction_offset: u32,
+    locations: Vec<Location>,
+    live_outs: Vec<LiveOut>,
+}
+
+impl StackMapRecord {
+    fn parse(reader: &mut Reader, version: u8) -> Result<Self, &'static str> {
+        let id = reader.read_u64()?;
+        let instruction_offset = reader.read_u32()?;
+
+        // Reserved (record flags)
+        reader.read_u16()?;
+
+        let num_locations = reader.read_u16()?;
+        let mut locations = Vec::with_capacity(num_locations as usize);
+
+        for _ in 0..num_locations {
+            locations.push(Location::parse(reader, version)?);
+        }
+
+        reader.align_to(8)?;
+
+        // Padding
+        reader.read_u16()?;
+
+        let num_live_outs = reader.read_u16()?;
+        let mut live_outs = Vec::with_capacity(num_live_outs as usize);
+
+        for _ in 0..num_live_outs {
+            let dwarf_reg_num = reader.read_u16()?;
+
+            // Reserved
+            reader.read_u8()?;
+
+            live_outs.push(LiveOut {
+                dwarf_reg_num,
+                size: reader.read_u8()?,
+            });
+        }
+
+        reader.align_to(8)?;
+
+        Ok(StackMapRecord {
+            id,
+            instruction_offset,
+            locations,
+            live_outs,
+        })
+    }
+
+    /// Gets the id passed to `build_stackmap` or `build_patchpoint`.
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// Gets the offset of the stack map or patch point from the start of its function.
+    pub fn get_instruction_offset(&self) -> u32 {
+        self.instruction_offset
+    }
+
+    /// Gets the locations of the live values, in the order they were passed in. For patch
+    /// points using the `anyreg` calling convention, the return value and call arguments come
+    /// first.
+    pub fn get_locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    /// Gets the registers which are live across a patch point and must be preserved by any
+    /// code patched into it.
+    pub fn get_live_outs(&self) -> &[LiveOut] {
+        &self.live_outs
+    }
+}
+
+/// Where a live value can be found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// The value is in a register.
+    Register {
+        /// The DWARF number of the register.
+        dwarf_reg_num: u16,
+        /// The size of the value in bytes.
+        size: u16,
+    },
+    /// The value is the address `register + offset`, usually of a stack slot.
+    Direct {
+        /// The DWARF number of the register.
+        dwarf_reg_num: u16,
+        /// The offset added to the register.
+        offset: i32,
+        /// The size of the value in bytes.
+        size: u16,
+    },
+    /// The value is stored in memory at `register + offset`.
+    Indirect {
+        /// The DWARF number of the register.
+        dwarf_reg_num: u16,
+        /// The offset added to the register.
+        offset: i32,
+        /// The size of the value in bytes.
+        size: u16,
+    },
+    /// The value is a constant small enough to be stored inline.
+    Constant(i32),
+    /// The value is a constant stored in the constant pool at the given index.
+    ConstantIndex(u32),
+}
+
+impl Location {
+    fn parse(reader: &mut Reader, version: u8) -> Result<Self, &'static str> {
+        let kind = reader.read_u8()?;
+        let size = if version >= 3 {
+            // Reserved
+            reader.read_u8()?;
+
+            reader.read_u16()?
+        } else {
+            reader.read_u8()? as u16
+        };
+        let dwarf_reg_num = reader.read_u16()?;
+
+        if version >= 3 {
+            // Reserved
+            reader.read_u16()?;
+        }
+
+        let offset = reader.read_i32()?;
+
+        let location = match kind {
+            1 => Location::Register { dwarf_reg_num, size },
+            2 => Location::Direct { dwarf_reg_num, offset, size },
+            3 => Location::Indirect { dwarf_reg_num, offset, size },
+            4 => Location::Constant(offset),
+            5 => Location::ConstantIndex(offset as u32),
+            _ => return Err("Unknown stack map location type."),
+        };
+
+        Ok(location)
+    }
+}
+
+/// A register which is live across a patch point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveOut {
+    dwarf_reg_num: u16,
+    size: u8,
+}
+
+impl LiveOut {
+    /// Gets the DWARF number of the register.
+    pub fn get_dwarf_reg_num(&self) -> u16 {
+        self.dwarf_reg_num
+    }
+
+    /// Gets the size of the register in bytes.
+    pub fn get_size(&self) -> u8 {
+        self.size
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let bytes = self.bytes.get(self.offset..self.offset + len).ok_or("Stack map section is truncated.")?;
+
+        self.offset += len;
+
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, &'static str> {
+        Ok(u16::from_ne_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_ne_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, &'static str> {
+        Ok(i32::from_ne_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, &'static str> {
+        Ok(u64::from_ne_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    fn align_to(&mut self, align: usize) -> Result<(), &'static str> {
+        let padding = (align - self.offset % align) % align;
+
+        self.read_bytes(padding)?;
+
+        Ok(())
+    }
+}
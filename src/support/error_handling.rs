@@ -63,6 +63,10 @@ impl DiagnosticInfo {
 // Assmuptions this handler makes:
 // * A valid *mut *mut i8 is provided as the void_ptr (via context.set_diagnostic_handler)
 //
+// As LLVM calls this handler, a panic would unwind across the FFI boundary, so it must not
+// do anything which can panic. Any future callbacks given to LLVM which run user code need
+// to catch panics with std::panic::catch_unwind instead.
+//
 // https://github.com/llvm-mirror/llvm/blob/master/tools/llvm-c-test/diagnostic.c was super useful
 // for figuring out how to get this to work
 pub(crate) extern "C" fn get_error_str_diagnostic_handler(diagnostic_info: LLVMDiagnosticInfoRef, void_ptr: *mut c_void) {
@@ -1,9 +1,24 @@
+/// Emits a debug level event with the `inkwell` target through the `log` and `tracing`
+/// crates when their respective features are enabled, and compiles to nothing otherwise.
+macro_rules! instrument_event {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::debug!(target: "inkwell", $($arg)+);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "inkwell", $($arg)+);
+    }};
+}
+
 #[deny(missing_docs)]
 pub mod error_handling;
+#[deny(missing_docs)]
+pub mod statistics;
 
 use libc::c_char;
 use llvm_sys::core::{LLVMCreateMessage, LLVMDisposeMessage};
-use llvm_sys::support::LLVMLoadLibraryPermanently;
+use llvm_sys::support::{LLVMLoadLibraryPermanently, LLVMParseCommandLineOptions};
+
+use crate::targets::{TargetMachine, TargetTriple};
 
 use std::borrow::Cow;
 use std::error::Error;
@@ -139,6 +154,148 @@ pub fn load_library_permanently(filename: &str) -> bool {
     }
 }
 
+/// Adds a symbol to the table LLVM searches when resolving external symbols in JIT'd code,
+/// ahead of the symbols of the process and any libraries loaded with `load_library_permanently`.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::support::{add_symbol, search_for_address_of_symbol};
+///
+/// extern "C" fn host_log(value: u64) {
+///     println!("{}", value);
+/// }
+///
+/// add_symbol("host_log", host_log as usize);
+///
+/// assert_eq!(search_for_address_of_symbol("host_log"), Some(host_log as usize));
+/// ```
+#[llvm_versions(3.7..=latest)]
+pub fn add_symbol(name: &str, address: usize) {
+    use llvm_sys::support::LLVMAddSymbol;
+
+    let c_string = to_c_str(name);
+
+    unsafe {
+        LLVMAddSymbol(c_string.as_ptr(), address as *mut _)
+    }
+}
+
+/// Looks up the address LLVM would resolve `name` to in JIT'd code, searching the symbols
+/// added with `add_symbol` first, then the process and libraries loaded into it.
+#[llvm_versions(3.7..=latest)]
+pub fn search_for_address_of_symbol(name: &str) -> Option<usize> {
+    use llvm_sys::support::LLVMSearchForAddressOfSymbol;
+
+    let c_string = to_c_str(name);
+    let address = unsafe {
+        LLVMSearchForAddressOfSymbol(c_string.as_ptr())
+    };
+
+    if address.is_null() {
+        return None;
+    }
+
+    Some(address as usize)
+}
+
+/// Makes the compiler-rt style builtins linked into this program available to JIT'd code.
+///
+/// LLVM lowers some operations, such as 128-bit division and conversions between 128-bit
+/// integers and floats, to calls to these helpers, and stack probes on Windows to `__chkstk`.
+/// They are part of every Rust program but usually not exported from it, so without this
+/// function looking them up fails when the JIT'd code is linked.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::support::{add_compiler_builtin_symbols, search_for_address_of_symbol};
+///
+/// add_compiler_builtin_symbols();
+///
+/// assert!(search_for_address_of_symbol("__udivti3").is_some());
+/// ```
+#[llvm_versions(3.7..=latest)]
+pub fn add_compiler_builtin_symbols() {
+    for (name, address) in builtins::symbols() {
+        add_symbol(name, address);
+    }
+}
+
+// Only their addresses are used, so the declared signatures don't matter
+#[cfg(not(feature = "llvm3-6"))]
+mod builtins {
+    macro_rules! builtin_symbols {
+        ($($(#[$cfg:meta])* $name:ident),* $(,)?) => {
+            extern "C" {
+                $(
+                    $(#[$cfg])*
+                    fn $name();
+                )*
+            }
+
+            pub(super) fn symbols() -> Vec<(&'static str, usize)> {
+                let mut symbols = Vec::new();
+
+                $(
+                    $(#[$cfg])*
+                    symbols.push((stringify!($name), $name as usize));
+                )*
+
+                symbols
+            }
+        };
+    }
+
+    builtin_symbols! {
+        #[cfg(target_pointer_width = "64")] __udivti3,
+        #[cfg(target_pointer_width = "64")] __umodti3,
+        #[cfg(target_pointer_width = "64")] __divti3,
+        #[cfg(target_pointer_width = "64")] __modti3,
+        #[cfg(target_pointer_width = "64")] __multi3,
+        #[cfg(target_pointer_width = "64")] __ashlti3,
+        #[cfg(target_pointer_width = "64")] __ashrti3,
+        #[cfg(target_pointer_width = "64")] __lshrti3,
+        #[cfg(target_pointer_width = "64")] __floattidf,
+        #[cfg(target_pointer_width = "64")] __floattisf,
+        #[cfg(target_pointer_width = "64")] __floatuntidf,
+        #[cfg(target_pointer_width = "64")] __floatuntisf,
+        #[cfg(target_pointer_width = "64")] __fixdfti,
+        #[cfg(target_pointer_width = "64")] __fixsfti,
+        #[cfg(target_pointer_width = "64")] __fixunsdfti,
+        #[cfg(target_pointer_width = "64")] __fixunssfti,
+        __udivdi3,
+        __umoddi3,
+        __divdi3,
+        __moddi3,
+        #[cfg(all(windows, target_env = "msvc"))] __chkstk,
+        #[cfg(all(windows, target_env = "gnu"))] ___chkstk_ms,
+    }
+}
+
+/// Forwards the given arguments to LLVM's command line option parser, as if they were passed to
+/// an LLVM tool such as `opt` or `llc`. The first argument is treated as the program name.
+///
+/// LLVM only expects this to be called once per process; options given more than once may
+/// cause LLVM to report an error and exit.
+///
+/// # Example
+///
+/// ```no_run
+/// use inkwell::support::parse_command_line_options;
+///
+/// parse_command_line_options(&["inkwell", "-time-passes"], "");
+/// ```
+pub fn parse_command_line_options(args: &[&str], overview: &str) {
+    let c_args: Vec<_> = args.iter().map(|arg| to_c_str(arg)).collect();
+    let c_arg_ptrs: Vec<*const c_char> = c_args.iter().map(|arg| arg.as_ptr()).collect();
+    let overview = to_c_str(overview);
+
+    unsafe {
+        LLVMParseCommandLineOptions(c_arg_ptrs.len() as i32, c_arg_ptrs.as_ptr(), overview.as_ptr())
+    }
+}
+
 /// Determines whether or not LLVM has been configured to run in multithreaded mode. (Inkwell currently does
 /// not officially support multithreaded mode)
 pub fn is_multithreaded() -> bool {
@@ -149,6 +306,66 @@ pub fn is_multithreaded() -> bool {
     }
 }
 
+// llvm-sys refuses to build against any LLVM whose major version differs from the one
+// selected through its feature, so the linked LLVM's version is known at compile time.
+#[cfg(feature = "llvm3-6")]
+const LLVM_VERSION: (u32, u32) = (3, 6);
+#[cfg(feature = "llvm3-7")]
+const LLVM_VERSION: (u32, u32) = (3, 7);
+#[cfg(feature = "llvm3-8")]
+const LLVM_VERSION: (u32, u32) = (3, 8);
+#[cfg(feature = "llvm3-9")]
+const LLVM_VERSION: (u32, u32) = (3, 9);
+#[cfg(feature = "llvm4-0")]
+const LLVM_VERSION: (u32, u32) = (4, 0);
+#[cfg(feature = "llvm5-0")]
+const LLVM_VERSION: (u32, u32) = (5, 0);
+#[cfg(feature = "llvm6-0")]
+const LLVM_VERSION: (u32, u32) = (6, 0);
+#[cfg(feature = "llvm7-0")]
+const LLVM_VERSION: (u32, u32) = (7, 0);
+#[cfg(feature = "llvm8-0")]
+const LLVM_VERSION: (u32, u32) = (8, 0);
+#[cfg(feature = "llvm9-0")]
+const LLVM_VERSION: (u32, u32) = (9, 0);
+#[cfg(feature = "llvm10-0")]
+const LLVM_VERSION: (u32, u32) = (10, 0);
+#[cfg(feature = "llvm11-0")]
+const LLVM_VERSION: (u32, u32) = (11, 0);
+#[cfg(feature = "llvm12-0")]
+const LLVM_VERSION: (u32, u32) = (12, 0);
+#[cfg(feature = "llvm13-0")]
+const LLVM_VERSION: (u32, u32) = (13, 0);
+
+/// Gets the `(major, minor)` LLVM version selected by the `llvmX-Y` feature inkwell was
+/// built with.
+///
+/// This is a compile-time constant, not a query of the LLVM library which is actually
+/// linked: the C API of the supported LLVM versions has no way of reporting its version at
+/// runtime. llvm-sys checks the major and minor version of the LLVM it builds against, but
+/// a dynamically linked LLVM may still differ, and the patch version is never known.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::support::llvm_feature_version;
+///
+/// let (major, _minor) = llvm_feature_version();
+///
+/// if major >= 11 {
+///     // Use features only available in newer LLVMs
+/// }
+/// ```
+pub fn llvm_feature_version() -> (u32, u32) {
+    LLVM_VERSION
+}
+
+/// Gets the target triple LLVM was configured to generate code for by default, which is
+/// usually the host's. This is the same as `TargetMachine::get_default_triple`.
+pub fn default_target_triple() -> TargetTriple {
+    TargetMachine::get_default_triple()
+}
+
 pub fn enable_llvm_pretty_stack_trace() {
     #[llvm_versions(3.6..=3.7)]
     use llvm_sys::core::LLVMEnablePrettyStackTrace;
@@ -192,3 +409,11 @@ fn test_to_c_str() {
         panic!();
     }
 }
+
+#[test]
+fn test_llvm_feature_version() {
+    let (major, minor) = llvm_feature_version();
+
+    assert!((3..=13).contains(&major));
+    assert!(major == 3 || minor == 0);
+}
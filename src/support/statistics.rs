@@ -0,0 +1,551 @@
+//! This module provides access to LLVM's internal statistics (`-stats`) and pass timing
+//! (`-time-passes`) reports.
+//!
+//! LLVM has no C API for reading these counters directly, so they are enabled through LLVM's
+//! command line options and appended to a file of your choosing, which can then be loaded with
+//! [`StatisticsReport::read_from_path`]:
+//!
+//! * Pass timings are written as LLVM's plain-text "Pass execution timing report" once the
+//!   timers of the pass manager are destroyed, which happens when the process exits (or, prior
+//!   to LLVM 7, when LLVM is shut down).
+//! * Statistic counters are written as a JSON object (`-stats-json`, which LLVM 4.0 introduced)
+//!   when LLVM is shut down. LLVM only tracks them when it was built with assertions enabled,
+//!   or with `LLVM_FORCE_ENABLE_STATS`; release builds of LLVM only write a notice that
+//!   statistics are disabled, if anything.
+//!
+//! Neither report is available right after `PassManager::run_on` returns. The C API offers no
+//! way to have LLVM print them on demand, so they only exist once the process which ran the
+//! passes has exited, or has called [`shutdown_llvm`](crate::support::shutdown_llvm), after
+//! which LLVM can no longer be used. In practice, the passes are run in a child process and the
+//! report is read by its parent.
+
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Lines;
+
+use crate::support::parse_command_line_options;
+
+/// Configures which of LLVM's performance reports should be collected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatisticsConfig {
+    output_path: PathBuf,
+    statistics: bool,
+    time_passes: bool,
+}
+
+impl StatisticsConfig {
+    /// Creates a config which will have LLVM write its report to `output_path`.
+    /// Statistics are collected by default and pass timing is not.
+    pub fn new<P: AsRef<Path>>(output_path: P) -> Self {
+        StatisticsConfig {
+            output_path: output_path.as_ref().to_path_buf(),
+            statistics: true,
+            time_passes: false,
+        }
+    }
+
+    /// Sets whether LLVM's statistic counters (`-stats`) should be collected.
+    /// LLVM only tracks these counters when it was built with assertions enabled, or with
+    /// `LLVM_FORCE_ENABLE_STATS`, so nothing will be reported by a release build. Prior to
+    /// LLVM 4.0, statistics can't be written as JSON, so this has no effect.
+    pub fn statistics(mut self, enabled: bool) -> Self {
+        self.statistics = enabled;
+        self
+    }
+
+    /// Sets whether the time spent in each pass (`-time-passes`) should be collected.
+    pub fn time_passes(mut self, enabled: bool) -> Self {
+        self.time_passes = enabled;
+        self
+    }
+
+    /// Gets the path LLVM will write its report to.
+    pub fn get_output_path(&self) -> &Path {
+        &self.output_path
+    }
+
+    /// Enables the configured reports in LLVM. This forwards options to LLVM's command line
+    /// parser and so shares its restriction of only being called once per process.
+    ///
+    /// LLVM writes the reports when the process exits, so they can't be read back by the
+    /// process which enabled them. The example below reads a report written by an earlier run.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use inkwell::support::statistics::{StatisticsConfig, StatisticsReport};
+    ///
+    /// let config = StatisticsConfig::new("llvm_stats.txt").time_passes(true);
+    ///
+    /// config.enable();
+    ///
+    /// // Run passes and emit code in a child process, then read the report back once
+    /// // the child has exited...
+    ///
+    /// let report = StatisticsReport::read_from_path(config.get_output_path()).unwrap();
+    ///
+    /// for timing in report.get_pass_timings() {
+    ///     println!("{}: {}s", timing.pass, timing.seconds);
+    /// }
+    /// ```
+    pub fn enable(&self) {
+        let output_file = format!("-info-output-file={}", self.output_path.display());
+        let mut args = vec!["inkwell", output_file.as_str()];
+
+        // LLVM exits the process on unknown options, and only knows -stats-json since 4.0
+        #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9")))]
+        {
+            if self.statistics {
+                args.push("-stats");
+                args.push("-stats-json");
+            }
+        }
+
+        if self.time_passes {
+            args.push("-time-passes");
+        }
+
+        parse_command_line_options(&args, "");
+    }
+}
+
+/// A single counter from LLVM's statistics report, ie `instcombine.NumCombined`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statistic {
+    /// The debug type of the component which owns the counter, ie `instcombine`.
+    pub debug_type: String,
+    /// The name of the counter, ie `NumCombined`.
+    pub name: String,
+    /// The value of the counter.
+    pub value: u64,
+}
+
+/// A single timer from LLVM's pass timing report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassTiming {
+    /// The timer group: its name in JSON reports, ie `pass`, and its description in plain-text
+    /// reports, ie `Pass execution timing report`.
+    pub group: String,
+    /// The timed pass: its command line name in JSON reports, ie `instcombine`, and its
+    /// description in plain-text reports, ie `Combine redundant instructions`.
+    pub pass: String,
+    /// Which clock was measured: `wall`, `user` or `sys`.
+    pub kind: String,
+    /// The measured time in seconds.
+    pub seconds: f64,
+}
+
+/// The structured contents of the reports written by LLVM.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatisticsReport {
+    statistics: Vec<Statistic>,
+    pass_timings: Vec<PassTiming>,
+}
+
+impl StatisticsReport {
+    /// Reads and parses the reports previously written by LLVM.
+    pub fn read_from_path<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let contents = fs::read_to_string(path.as_ref()).map_err(|err| err.to_string())?;
+
+        StatisticsReport::parse(&contents)
+    }
+
+    /// Parses the contents of LLVM's info output file, which holds any number of plain-text
+    /// timer reports and JSON objects written by `-stats-json`. Other text, such as the notice
+    /// a release build of LLVM writes instead of statistics, is ignored.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut report = StatisticsReport::default();
+        let mut lines = contents.lines().peekable();
+
+        while let Some(line) = lines.next() {
+            if line.trim_start().starts_with('{') {
+                let mut json = line.to_string();
+
+                while !json.trim_end().ends_with('}') {
+                    let line = lines.next().ok_or("Statistics report ends inside a JSON object.")?;
+
+                    json.push('\n');
+                    json.push_str(line);
+                }
+
+                report.parse_json(&json)?;
+            } else if is_banner(line) {
+                report.parse_timer_report(&mut lines)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn parse_json(&mut self, json: &str) -> Result<(), String> {
+        for (key, value) in JsonParser::new(json).parse_object()? {
+            if key.starts_with("time.") {
+                let timer = &key[5..];
+                let group_separator = timer.find('.').ok_or_else(|| format!("Malformed timer name: {}", key))?;
+                let kind_separator = timer.rfind('.').filter(|&i| i > group_separator).ok_or_else(|| format!("Malformed timer name: {}", key))?;
+                let kind = &timer[kind_separator + 1..];
+
+                // Memory usage and instruction counts are not times
+                if kind != "wall" && kind != "user" && kind != "sys" {
+                    continue;
+                }
+
+                self.pass_timings.push(PassTiming {
+                    group: timer[..group_separator].to_string(),
+                    pass: timer[group_separator + 1..kind_separator].to_string(),
+                    kind: kind.to_string(),
+                    seconds: value.parse().map_err(|_| format!("Malformed timer value: {}", value))?,
+                });
+            } else {
+                let name_separator = key.find('.').ok_or_else(|| format!("Malformed statistic name: {}", key))?;
+
+                self.statistics.push(Statistic {
+                    debug_type: key[..name_separator].to_string(),
+                    name: key[name_separator + 1..].to_string(),
+                    value: value.parse().map_err(|_| format!("Malformed statistic value: {}", value))?,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Parses a report of the form
+    //
+    // ===-------------------------------------------------------------------------===
+    //                       ... Pass execution timing report ...
+    // ===-------------------------------------------------------------------------===
+    //   Total Execution Time: 0.0040 seconds (0.0040 wall clock)
+    //
+    //    ---User Time---   --System Time--   --User+System--   ---Wall Time---  --- Name ---
+    //    0.0030 ( 75.0%)   0.0000 (  0.0%)   0.0030 ( 75.0%)   0.0030 ( 75.0%)  Combine redundant instructions
+    //    0.0040 (100.0%)   0.0000 (100.0%)   0.0040 (100.0%)   0.0040 (100.0%)  Total
+    //
+    // whose opening banner has already been consumed. Reports without a table of timers, such as
+    // the plain-text statistics report, are skipped.
+    fn parse_timer_report(&mut self, lines: &mut Peekable<Lines>) -> Result<(), String> {
+        let title = match lines.next() {
+            Some(title) => title,
+            None => return Ok(()),
+        };
+
+        if !lines.next().map_or(false, is_banner) {
+            return Ok(());
+        }
+
+        let group = title.trim().trim_start_matches("...").trim_end_matches("...").trim();
+        let columns = loop {
+            let line = match lines.peek() {
+                Some(line) => *line,
+                None => return Ok(()),
+            };
+
+            if is_banner(line) || line.trim_start().starts_with('{') {
+                return Ok(());
+            }
+
+            lines.next();
+
+            if line.contains("--- Name ---") {
+                break parse_timer_columns(line);
+            }
+        };
+
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() {
+                break;
+            }
+
+            let (values, pass) = parse_timer_row(line, &columns).ok_or_else(|| format!("Malformed timer row: {}", line))?;
+
+            if pass == "Total" {
+                continue;
+            }
+
+            for (column, seconds) in columns.iter().zip(values) {
+                let kind = match column {
+                    TimerColumn::User => "user",
+                    TimerColumn::System => "sys",
+                    TimerColumn::Wall => "wall",
+                    TimerColumn::UserSystem | TimerColumn::Mem | TimerColumn::Instr => continue,
+                };
+
+                self.pass_timings.push(PassTiming {
+                    group: group.to_string(),
+                    pass: pass.to_string(),
+                    kind: kind.to_string(),
+                    seconds,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the statistic counters contained in this report.
+    pub fn get_statistics(&self) -> &[Statistic] {
+        &self.statistics
+    }
+
+    /// Gets a statistic counter by its debug type and name, if it was reported.
+    pub fn get_statistic(&self, debug_type: &str, name: &str) -> Option<u64> {
+        self.statistics.iter()
+            .find(|stat| stat.debug_type == debug_type && stat.name == name)
+            .map(|stat| stat.value)
+    }
+
+    /// Gets the pass timings contained in this report.
+    pub fn get_pass_timings(&self) -> &[PassTiming] {
+        &self.pass_timings
+    }
+}
+
+fn is_banner(line: &str) -> bool {
+    line.starts_with("===-")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimerColumn {
+    User,
+    System,
+    UserSystem,
+    Wall,
+    Mem,
+    Instr,
+}
+
+impl TimerColumn {
+    fn has_percentage(self) -> bool {
+        match self {
+            TimerColumn::Mem | TimerColumn::Instr => false,
+            _ => true,
+        }
+    }
+}
+
+// LLVM leaves out the columns of clocks which measured nothing, so the header determines which
+// columns each row has.
+fn parse_timer_columns(header: &str) -> Vec<TimerColumn> {
+    let labels = [
+        ("User Time", TimerColumn::User),
+        ("System Time", TimerColumn::System),
+        ("User+System", TimerColumn::UserSystem),
+        ("Wall Time", TimerColumn::Wall),
+        ("---Mem---", TimerColumn::Mem),
+        ("---Instr---", TimerColumn::Instr),
+    ];
+    let mut columns: Vec<_> = labels.iter()
+        .filter_map(|&(label, column)| header.find(label).map(|position| (position, column)))
+        .collect();
+
+    columns.sort_by_key(|&(position, _)| position);
+    columns.into_iter().map(|(_, column)| column).collect()
+}
+
+// Splits a row into the values of its columns and the timer's description. A column is printed
+// as `-----` instead of a value when its total is zero.
+fn parse_timer_row<'a>(row: &'a str, columns: &[TimerColumn]) -> Option<(Vec<f64>, &'a str)> {
+    let mut rest = row;
+    let mut values = Vec::with_capacity(columns.len());
+
+    for column in columns {
+        rest = rest.trim_start();
+
+        let end = rest.find(char::is_whitespace)?;
+        let value = &rest[..end];
+
+        values.push(if value.starts_with("---") { 0. } else { value.parse().ok()? });
+        rest = rest[end..].trim_start();
+
+        if column.has_percentage() && rest.starts_with('(') {
+            rest = &rest[rest.find(')')? + 1..];
+        }
+    }
+
+    let description = rest.trim();
+
+    if description.is_empty() {
+        return None;
+    }
+
+    Some((values, description))
+}
+
+// A parser for the flat JSON objects written by `-stats-json`, whose values are all numbers.
+struct JsonParser<'a> {
+    json: &'a str,
+    offset: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(json: &'a str) -> Self {
+        JsonParser { json, offset: 0 }
+    }
+
+    // Returns each key along with the text of its number.
+    fn parse_object(mut self) -> Result<Vec<(String, &'a str)>, String> {
+        let mut entries = Vec::new();
+
+        self.expect('{')?;
+
+        if !self.eat('}') {
+            loop {
+                let key = self.parse_string()?;
+
+                self.expect(':')?;
+                entries.push((key, self.parse_number()?));
+
+                if self.eat('}') {
+                    break;
+                }
+
+                self.expect(',')?;
+            }
+        }
+
+        self.skip_whitespace();
+
+        if self.offset != self.json.len() {
+            return Err("Unexpected trailing characters after JSON object.".to_string());
+        }
+
+        Ok(entries)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.json[self.offset..];
+
+        self.offset += rest.len() - rest.trim_start_matches(|c: char| c == ' ' || c == '\t' || c == '\n' || c == '\r').len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.json[self.offset..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.offset += expected.len_utf8();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at offset {} of JSON object.", expected, self.offset))
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+        let mut chars = self.json[self.offset..].char_indices();
+
+        loop {
+            let (i, c) = chars.next().ok_or("Unterminated JSON string.")?;
+
+            match c {
+                '"' => {
+                    self.offset += i + 1;
+
+                    return Ok(string);
+                },
+                '\\' => {
+                    let escaped = match chars.next().ok_or("Unterminated JSON string.")?.1 {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let mut code = 0;
+
+                            for _ in 0..4 {
+                                let digit = chars.next().and_then(|(_, c)| c.to_digit(16)).ok_or("Malformed JSON unicode escape.")?;
+
+                                code = code * 16 + digit;
+                            }
+
+                            std::char::from_u32(code).unwrap_or(std::char::REPLACEMENT_CHARACTER)
+                        },
+                        other => return Err(format!("Unknown JSON escape: \\{}", other)),
+                    };
+
+                    string.push(escaped);
+                },
+                c if (c as u32) < 0x20 => return Err("Unescaped control character in JSON string.".to_string()),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<&'a str, String> {
+        self.skip_whitespace();
+
+        let rest = &self.json[self.offset..];
+        let len = rest.find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E')).unwrap_or(rest.len());
+        let number = &rest[..len];
+
+        if number.is_empty() || number.parse::<f64>().is_err() {
+            return Err(format!("Expected a number at offset {} of JSON object.", self.offset));
+        }
+
+        self.offset += len;
+
+        Ok(number)
+    }
+}
+
+#[test]
+fn test_parse_statistics_report() {
+    let contents = "\
+===-------------------------------------------------------------------------===
+                      ... Pass execution timing report ...
+===-------------------------------------------------------------------------===
+  Total Execution Time: 0.0040 seconds (0.0040 wall clock)
+
+   ---User Time---   --User+System--   ---Wall Time---  --- Name ---
+   0.0030 ( 75.0%)   0.0030 ( 75.0%)   0.0030 ( 75.0%)  Combine redundant instructions
+   0.0010 ( 25.0%)   0.0010 ( 25.0%)   0.0010 ( 25.0%)  Module Verifier
+   0.0040 (100.0%)   0.0040 (100.0%)   0.0040 (100.0%)  Total
+
+Statistics are disabled.  Build with asserts or with -DLLVM_FORCE_ENABLE_STATS
+{
+\t\"instcombine.NumCombined\": 12,
+\t\"time.pass.instcombine.wall\": 1.50000000000000004e-05,
+\t\"time.pass.instcombine.user\": 1.00000000000000008e-05,
+\t\"time.pass.instcombine.mem\": 4096
+}
+";
+    let report = StatisticsReport::parse(contents).unwrap();
+    let timings = report.get_pass_timings();
+
+    assert_eq!(report.get_statistic("instcombine", "NumCombined"), Some(12));
+    assert_eq!(report.get_statistic("instcombine", "NumDeadInst"), None);
+    assert_eq!(timings.len(), 6);
+    assert_eq!(timings[0], PassTiming {
+        group: "Pass execution timing report".to_string(),
+        pass: "Combine redundant instructions".to_string(),
+        kind: "user".to_string(),
+        seconds: 0.003,
+    });
+    assert_eq!(timings[1].kind, "wall");
+    assert_eq!(timings[3].pass, "Module Verifier");
+    assert_eq!(timings[4].group, "pass");
+    assert_eq!(timings[4].pass, "instcombine");
+    assert_eq!(timings[4].kind, "wall");
+    assert_eq!(timings[5].seconds, 1.00000000000000008e-05);
+    assert!(StatisticsReport::parse("{\n\t\"a.b\": 1,\n}\n").is_err());
+    assert!(StatisticsReport::parse("{\n\t\"a.b\": \"1\"\n}\n").is_err());
+    assert_eq!(StatisticsReport::parse("not a report").unwrap(), StatisticsReport::default());
+}
@@ -31,11 +31,13 @@ use crate::types::{AnyType, AsTypeRef, IntType, StructType};
 use crate::values::{AsValueRef, GlobalValue};
 use crate::{AddressSpace, OptimizationLevel};
 
+use std::convert::TryFrom;
 use std::default::Default;
 use std::ffi::CStr;
 use std::fmt;
 use std::mem::MaybeUninit;
 use std::path::Path;
+use std::str::FromStr;
 use std::ptr;
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -71,14 +73,22 @@ impl FileType {
     }
 }
 
-// TODO: Doc: Base gets you TargetMachine support, machine_code gets you asm_backend
+/// Selects which parts of a target are initialized. Each part can be enabled independently,
+/// so that for example a JIT can enable the asm parser needed for inline assembly without
+/// also initializing the disassembler.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct InitializationConfig {
+    /// Parses assembly, which is needed for modules containing inline assembly.
     pub asm_parser: bool,
+    /// Prints assembly and emits object files, which is needed for code generation.
     pub asm_printer: bool,
+    /// The target itself, needed to create a `TargetMachine`.
     pub base: bool,
+    /// Decodes machine code back into instructions.
     pub disassembler: bool,
+    /// The target's description, needed to look it up by name or triple.
     pub info: bool,
+    /// The machine code layer, which provides the target's asm backend.
     pub machine_code: bool,
 }
 
@@ -122,6 +132,100 @@ impl TargetTriple {
     pub fn as_ptr(&self) -> *const ::libc::c_char {
         self.triple.as_ptr()
     }
+
+    /// Gets the architecture component of this triple, such as `x86_64` in `x86_64-pc-linux-gnu`.
+    ///
+    /// The components are taken from the triple as written, so triples which leave some of
+    /// them out, such as `x86_64-linux-gnu`, should be `normalize`d first for the vendor,
+    /// OS and environment to be found in their canonical positions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::targets::TargetTriple;
+    ///
+    /// let triple = TargetTriple::create("x86_64-pc-linux-gnu");
+    ///
+    /// assert_eq!(triple.get_arch(), Some("x86_64"));
+    /// assert_eq!(triple.get_vendor(), Some("pc"));
+    /// assert_eq!(triple.get_os(), Some("linux"));
+    /// assert_eq!(triple.get_environment(), Some("gnu"));
+    /// ```
+    pub fn get_arch(&self) -> Option<&str> {
+        self.get_component(0)
+    }
+
+    /// Gets the vendor component of this triple, such as `pc` in `x86_64-pc-linux-gnu`.
+    pub fn get_vendor(&self) -> Option<&str> {
+        self.get_component(1)
+    }
+
+    /// Gets the operating system component of this triple, such as `linux` in `x86_64-pc-linux-gnu`.
+    pub fn get_os(&self) -> Option<&str> {
+        self.get_component(2)
+    }
+
+    /// Gets the environment component of this triple, such as `gnu` in `x86_64-pc-linux-gnu`,
+    /// if it has one. Anything after the fourth component is included.
+    pub fn get_environment(&self) -> Option<&str> {
+        let triple = self.as_str().to_str().ok()?;
+
+        triple.splitn(4, '-').nth(3).filter(|component| !component.is_empty())
+    }
+
+    fn get_component(&self, index: usize) -> Option<&str> {
+        let triple = self.as_str().to_str().ok()?;
+
+        triple.split('-').nth(index).filter(|component| !component.is_empty())
+    }
+
+    /// Normalizes this triple into the canonical `arch-vendor-os-environment` form,
+    /// filling in components which were left out as `unknown`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::targets::TargetTriple;
+    ///
+    /// let triple = TargetTriple::create("x86_64-linux-gnu").normalize();
+    ///
+    /// assert_eq!(triple.as_str().to_str(), Ok("x86_64-unknown-linux-gnu"));
+    /// assert_eq!(triple.get_os(), Some("linux"));
+    /// ```
+    #[llvm_versions(7.0..=latest)]
+    pub fn normalize(&self) -> TargetTriple {
+        TargetMachine::normalize_triple(self)
+    }
+
+    /// Determines whether this triple and `other` describe the same target once both are
+    /// normalized, so that `x86_64-linux-gnu` is equivalent to `x86_64-unknown-linux-gnu`.
+    /// Unlike `==`, which compares the triples as written.
+    #[llvm_versions(7.0..=latest)]
+    pub fn is_equivalent(&self, other: &TargetTriple) -> bool {
+        self == other || self.normalize() == other.normalize()
+    }
+}
+
+impl TryFrom<&str> for TargetTriple {
+    type Error = &'static str;
+
+    /// Creates a `TargetTriple` from `triple`, failing if it has a null byte anywhere but at its
+    /// end, since the triple would otherwise silently be cut off at that null byte.
+    fn try_from(triple: &str) -> Result<Self, Self::Error> {
+        let without_terminator = if triple.ends_with('\0') { &triple[..triple.len() - 1] } else { triple };
+
+        if without_terminator.contains('\0') {
+            return Err("Target triples may not contain interior null bytes.");
+        }
+
+        Ok(TargetTriple::create(triple))
+    }
+}
+
+impl From<&CStr> for TargetTriple {
+    fn from(triple: &CStr) -> Self {
+        TargetTriple::new(LLVMString::create_from_c_str(triple))
+    }
 }
 
 impl PartialEq for TargetTriple {
@@ -142,6 +246,143 @@ impl fmt::Display for TargetTriple {
     }
 }
 
+/// A set of CPU features to enable or disable, as written in `+sse4.2,-avx` style feature
+/// strings. Each feature appears at most once, with the last toggle of it winning, and
+/// features keep the order they were first mentioned in.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::targets::CpuFeatures;
+///
+/// let mut features: CpuFeatures = "+sse4.2, -avx,+avx".parse().unwrap();
+///
+/// features.disable("sse4.2").enable("bmi2");
+///
+/// assert_eq!(features.is_enabled("avx"), Some(true));
+/// assert_eq!(features.to_string(), "-sse4.2,+avx,+bmi2");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuFeatures {
+    features: Vec<(String, bool)>,
+}
+
+impl CpuFeatures {
+    /// Creates an empty set of features.
+    pub fn new() -> Self {
+        CpuFeatures::default()
+    }
+
+    /// Parses a comma separated feature string. Whitespace around entries and empty
+    /// entries are ignored, but every other entry must start with `+` or `-`.
+    pub fn parse(features: &str) -> Result<Self, &'static str> {
+        let mut cpu_features = CpuFeatures::new();
+
+        for feature in features.split(',').map(str::trim).filter(|feature| !feature.is_empty()) {
+            let (enabled, name) = match feature.as_bytes()[0] {
+                b'+' => (true, &feature[1..]),
+                b'-' => (false, &feature[1..]),
+                _ => return Err("Features must be prefixed with '+' or '-'."),
+            };
+
+            if name.is_empty() {
+                return Err("Feature names may not be empty.");
+            }
+
+            cpu_features.set(name, enabled);
+        }
+
+        Ok(cpu_features)
+    }
+
+    /// Gets the features supported by the host CPU.
+    #[llvm_versions(7.0..=latest)]
+    pub fn host() -> Self {
+        let features = TargetMachine::get_host_cpu_features();
+
+        // LLVM only ever produces well formed feature strings
+        CpuFeatures::parse(&features.to_string()).unwrap_or_default()
+    }
+
+    /// Enables `feature`.
+    pub fn enable(&mut self, feature: &str) -> &mut Self {
+        self.set(feature, true)
+    }
+
+    /// Disables `feature`.
+    pub fn disable(&mut self, feature: &str) -> &mut Self {
+        self.set(feature, false)
+    }
+
+    /// Enables or disables `feature`.
+    pub fn set(&mut self, feature: &str, enabled: bool) -> &mut Self {
+        match self.features.iter_mut().find(|(name, _)| name == feature) {
+            Some(entry) => entry.1 = enabled,
+            None => self.features.push((feature.to_string(), enabled)),
+        }
+
+        self
+    }
+
+    /// Forgets about `feature`, leaving it at the CPU's default. Returns whether or not
+    /// it was part of this set.
+    pub fn remove(&mut self, feature: &str) -> bool {
+        let len = self.features.len();
+
+        self.features.retain(|(name, _)| name != feature);
+
+        self.features.len() != len
+    }
+
+    /// Determines whether `feature` is enabled, disabled, or neither if this set does not mention it.
+    pub fn is_enabled(&self, feature: &str) -> Option<bool> {
+        self.features.iter().find(|(name, _)| name == feature).map(|(_, enabled)| *enabled)
+    }
+
+    /// Iterates over every feature in this set along with whether it is enabled.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.features.iter().map(|(name, enabled)| (name.as_str(), *enabled))
+    }
+
+    /// Gets the number of features in this set.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Determines whether or not this set mentions any features.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Creates a `"target-features"` function attribute enabling and disabling these features.
+    #[llvm_versions(3.9..=latest)]
+    pub fn to_attribute(&self, context: &Context) -> crate::attributes::Attribute {
+        context.create_string_attribute("target-features", &self.to_string())
+    }
+}
+
+impl fmt::Display for CpuFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, enabled)) in self.features.iter().enumerate() {
+            if i != 0 {
+                f.write_str(",")?;
+            }
+
+            write!(f, "{}{}", if *enabled { '+' } else { '-' }, name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for CpuFeatures {
+    type Err = &'static str;
+
+    fn from_str(features: &str) -> Result<Self, Self::Err> {
+        CpuFeatures::parse(features)
+    }
+}
+
 static TARGET_LOCK: Lazy<RwLock<()>> = Lazy::new(|| RwLock::new(()));
 
 // NOTE: Versions verified as target-complete: 3.6, 3.7, 3.8, 3.9, 4.0
@@ -863,11 +1104,51 @@ impl Target {
         Ok(())
     }
 
+    /// Initializes the asm parsers of all targets LLVM was built with, which are needed to
+    /// compile modules containing inline assembly, without initializing anything else.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use inkwell::targets::{InitializationConfig, Target};
+    ///
+    /// let config = InitializationConfig {
+    ///     asm_parser: false,
+    ///     disassembler: false,
+    ///     ..InitializationConfig::default()
+    /// };
+    ///
+    /// Target::initialize_native(&config).unwrap();
+    /// Target::initialize_all_asm_parsers();
+    /// ```
+    pub fn initialize_all_asm_parsers() {
+        use llvm_sys::target::LLVM_InitializeAllAsmParsers;
+
+        let _guard = TARGET_LOCK.write();
+        unsafe { LLVM_InitializeAllAsmParsers() };
+    }
+
+    /// Initializes the asm printers of all targets LLVM was built with, which are needed to
+    /// emit assembly or object files, without initializing anything else.
+    pub fn initialize_all_asm_printers() {
+        use llvm_sys::target::LLVM_InitializeAllAsmPrinters;
+
+        let _guard = TARGET_LOCK.write();
+        unsafe { LLVM_InitializeAllAsmPrinters() };
+    }
+
+    /// Initializes the disassemblers of all targets LLVM was built with, without
+    /// initializing anything else.
+    pub fn initialize_all_disassemblers() {
+        use llvm_sys::target::LLVM_InitializeAllDisassemblers;
+
+        let _guard = TARGET_LOCK.write();
+        unsafe { LLVM_InitializeAllDisassemblers() };
+    }
+
     pub fn initialize_all(config: &InitializationConfig) {
         use llvm_sys::target::{
-            LLVM_InitializeAllAsmParsers, LLVM_InitializeAllAsmPrinters,
-            LLVM_InitializeAllDisassemblers, LLVM_InitializeAllTargetInfos,
-            LLVM_InitializeAllTargetMCs, LLVM_InitializeAllTargets,
+            LLVM_InitializeAllTargetInfos, LLVM_InitializeAllTargetMCs, LLVM_InitializeAllTargets,
         };
 
         if config.base {
@@ -881,18 +1162,15 @@ impl Target {
         }
 
         if config.asm_parser {
-            let _guard = TARGET_LOCK.write();
-            unsafe { LLVM_InitializeAllAsmParsers() };
+            Target::initialize_all_asm_parsers();
         }
 
         if config.asm_printer {
-            let _guard = TARGET_LOCK.write();
-            unsafe { LLVM_InitializeAllAsmPrinters() };
+            Target::initialize_all_asm_printers();
         }
 
         if config.disassembler {
-            let _guard = TARGET_LOCK.write();
-            unsafe { LLVM_InitializeAllDisassemblers() };
+            Target::initialize_all_disassemblers();
         }
 
         if config.machine_code {
@@ -1207,7 +1485,11 @@ impl TargetMachine {
             }
         }
 
-        Ok(MemoryBuffer::new(memory_buffer))
+        let memory_buffer = MemoryBuffer::new(memory_buffer);
+
+        instrument_event!("emitted {:?} of module {:?} to memory ({} bytes)", file_type, module.get_name(), memory_buffer.get_size());
+
+        Ok(memory_buffer)
     }
 
     /// Saves a `TargetMachine` to a file.
@@ -1279,6 +1561,8 @@ impl TargetMachine {
             }
         }
 
+        instrument_event!("emitted {:?} of module {:?} to {}", file_type, module.get_name(), path);
+
         Ok(())
     }
 }
@@ -1295,6 +1579,32 @@ pub enum ByteOrdering {
     LittleEndian,
 }
 
+/// The position of a single field within a `StructLayout`, in bytes.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct FieldLayout {
+    /// The offset of the field from the start of the struct.
+    pub offset: u64,
+    /// The number of bytes the field's value occupies, excluding any tail padding of its own.
+    pub size: u64,
+    /// The ABI alignment of the field's type.
+    pub alignment: u32,
+    /// The number of padding bytes between the end of the previous field and this one.
+    pub padding_before: u64,
+}
+
+/// How a `StructType` is laid out in memory according to a `TargetData`, in bytes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StructLayout {
+    /// The ABI size of the struct, including trailing padding.
+    pub size: u64,
+    /// The ABI alignment of the struct.
+    pub alignment: u32,
+    /// The layout of each field, in declaration order.
+    pub fields: Vec<FieldLayout>,
+    /// The number of padding bytes after the last field needed to round the size up to the alignment.
+    pub trailing_padding: u64,
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct TargetData {
     pub(crate) target_data: LLVMTargetDataRef,
@@ -1427,6 +1737,56 @@ impl TargetData {
             ))
         }
     }
+
+    /// Computes the offset, size and alignment of every field of `struct_type`, along with the
+    /// padding between them. Returns `None` for opaque structs, which have no layout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    /// use inkwell::targets::TargetData;
+    ///
+    /// let context = Context::create();
+    /// let target_data = TargetData::create("e-i64:64-n8:16:32:64-S128");
+    /// let struct_type = context.struct_type(&[context.i8_type().into(), context.i64_type().into()], false);
+    /// let layout = target_data.get_struct_layout(&struct_type).unwrap();
+    ///
+    /// assert_eq!(layout.size, 16);
+    /// assert_eq!(layout.fields[1].offset, 8);
+    /// assert_eq!(layout.fields[1].padding_before, 7);
+    /// ```
+    pub fn get_struct_layout(&self, struct_type: &StructType) -> Option<StructLayout> {
+        if struct_type.is_opaque() {
+            return None;
+        }
+
+        let mut fields = Vec::with_capacity(struct_type.count_fields() as usize);
+        let mut end = 0;
+
+        for (i, field_type) in struct_type.get_field_types().iter().enumerate() {
+            let offset = unsafe { LLVMOffsetOfElement(self.target_data, struct_type.as_type_ref(), i as u32) };
+            let size = self.get_store_size(field_type);
+
+            fields.push(FieldLayout {
+                offset,
+                size,
+                alignment: self.get_abi_alignment(field_type),
+                padding_before: offset.saturating_sub(end),
+            });
+
+            end = offset + size;
+        }
+
+        let size = self.get_abi_size(struct_type);
+
+        Some(StructLayout {
+            size,
+            alignment: self.get_abi_alignment(struct_type),
+            fields,
+            trailing_padding: size.saturating_sub(end),
+        })
+    }
 }
 
 impl Drop for TargetData {
@@ -10,6 +10,7 @@ use crate::types::enums::BasicMetadataTypeEnum;
 
 /// An `ArrayType` is the type of contiguous constants or variables.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct ArrayType<'ctx> {
     array_type: Type<'ctx>,
 }
@@ -235,6 +236,6 @@ impl<'ctx> ArrayType<'ctx> {
 
 impl AsTypeRef for ArrayType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.array_type.ty
+        self.array_type.ty.as_ptr()
     }
 }
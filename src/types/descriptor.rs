@@ -0,0 +1,345 @@
+use llvm_sys::core::{LLVMArrayType, LLVMDoubleTypeInContext, LLVMFP128TypeInContext, LLVMFloatTypeInContext, LLVMFunctionType, LLVMGetPointerAddressSpace, LLVMGetTypeKind, LLVMHalfTypeInContext, LLVMIntTypeInContext, LLVMIsOpaqueStruct, LLVMPPCFP128TypeInContext, LLVMPointerType, LLVMStructCreateNamed, LLVMStructSetBody, LLVMStructTypeInContext, LLVMVectorType, LLVMVoidTypeInContext, LLVMX86FP80TypeInContext};
+#[llvm_versions(11.0..=latest)]
+use llvm_sys::core::LLVMBFloatTypeInContext;
+#[llvm_versions(12.0..=latest)]
+use llvm_sys::core::LLVMGetTypeByName2;
+use llvm_sys::prelude::LLVMTypeRef;
+use llvm_sys::LLVMTypeKind;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+use crate::context::Context;
+use crate::support::to_c_str;
+use crate::types::{AnyType, AnyTypeEnum, AsTypeRef, BasicTypeEnum};
+
+/// A `TypeDescriptor` describes an LLVM type without belonging to any `Context`.
+///
+/// Types created through inkwell are tied to the `Context` they were created in, so a pipeline
+/// which uses one `Context` per thread would otherwise have to rebuild every shared type by hand.
+/// A `TypeDescriptor` can be built once, sent to (or, with the `serde` feature, serialized for)
+/// each worker and turned into a real type with `materialize`.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::types::TypeDescriptor;
+///
+/// let point = TypeDescriptor::NamedStruct {
+///     name: "point".into(),
+///     fields: Some(vec![TypeDescriptor::F64, TypeDescriptor::F64]),
+///     packed: false,
+/// };
+///
+/// let context = Context::create();
+/// let point_type = point.materialize(&context).into_struct_type();
+///
+/// assert_eq!(point_type.count_fields(), 2);
+/// assert_eq!(TypeDescriptor::from_type(point_type), point);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TypeDescriptor {
+    /// The `void` type.
+    Void,
+    /// An integer type of the given bit width.
+    Int(u32),
+    /// The 16 bit `half` type.
+    F16,
+    /// The 16 bit `bfloat` type. Requires LLVM 11.0+ to be materialized.
+    BF16,
+    /// The 32 bit `float` type.
+    F32,
+    /// The 64 bit `double` type.
+    F64,
+    /// The 80 bit `x86_fp80` type.
+    X86F80,
+    /// The 128 bit `fp128` type.
+    F128,
+    /// The 128 bit `ppc_fp128` type.
+    PPCF128,
+    /// A pointer to `pointee` in the given address space.
+    Pointer {
+        /// The type pointed to.
+        pointee: Box<TypeDescriptor>,
+        /// The raw address space of the pointer.
+        address_space: u32,
+    },
+    /// A fixed length array of `element`s.
+    Array {
+        /// The type of each element.
+        element: Box<TypeDescriptor>,
+        /// The number of elements.
+        len: u32,
+    },
+    /// A vector of `element`s.
+    Vector {
+        /// The type of each element.
+        element: Box<TypeDescriptor>,
+        /// The number of elements.
+        len: u32,
+    },
+    /// An anonymous (literal) struct.
+    Struct {
+        /// The types of the fields.
+        fields: Vec<TypeDescriptor>,
+        /// Whether the struct is packed.
+        packed: bool,
+    },
+    /// A named struct. When `fields` is `None` this only refers to the struct by name, which is
+    /// how opaque structs and self referential structs are described.
+    NamedStruct {
+        /// The name of the struct.
+        name: String,
+        /// The types of the fields, if the body is defined here.
+        fields: Option<Vec<TypeDescriptor>>,
+        /// Whether the struct is packed. Ignored when `fields` is `None`.
+        packed: bool,
+    },
+    /// A function type.
+    Function {
+        /// The return type, which may be `Void`.
+        return_type: Box<TypeDescriptor>,
+        /// The types of the parameters.
+        params: Vec<TypeDescriptor>,
+        /// Whether the function takes variadic arguments.
+        is_var_arg: bool,
+    },
+}
+
+impl TypeDescriptor {
+    /// Describes an existing type so that it can be recreated in another `Context`.
+    ///
+    /// The first occurrence of a named struct carries its body, later occurrences (such as a
+    /// pointer back to itself) only refer to it by name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    /// use inkwell::types::TypeDescriptor;
+    ///
+    /// let context = Context::create();
+    /// let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    ///
+    /// assert_eq!(TypeDescriptor::from_type(i8_ptr_type), TypeDescriptor::Pointer {
+    ///     pointee: Box::new(TypeDescriptor::Int(8)),
+    ///     address_space: 0,
+    /// });
+    /// ```
+    pub fn from_type<'ctx, T: AnyType<'ctx>>(ty: T) -> Self {
+        TypeDescriptor::describe(ty.as_any_type_enum(), &mut HashSet::new())
+    }
+
+    fn describe(ty: AnyTypeEnum, seen_structs: &mut HashSet<String>) -> Self {
+        let describe_all = |types: Vec<BasicTypeEnum>, seen_structs: &mut HashSet<String>| -> Vec<TypeDescriptor> {
+            types.into_iter()
+                 .map(|ty| TypeDescriptor::describe(ty.as_any_type_enum(), seen_structs))
+                 .collect()
+        };
+
+        match ty {
+            AnyTypeEnum::VoidType(_) => TypeDescriptor::Void,
+            AnyTypeEnum::IntType(int_type) => TypeDescriptor::Int(int_type.get_bit_width()),
+            AnyTypeEnum::FloatType(float_type) => match unsafe { LLVMGetTypeKind(float_type.as_type_ref()) } {
+                LLVMTypeKind::LLVMHalfTypeKind => TypeDescriptor::F16,
+                LLVMTypeKind::LLVMFloatTypeKind => TypeDescriptor::F32,
+                LLVMTypeKind::LLVMDoubleTypeKind => TypeDescriptor::F64,
+                LLVMTypeKind::LLVMX86_FP80TypeKind => TypeDescriptor::X86F80,
+                LLVMTypeKind::LLVMFP128TypeKind => TypeDescriptor::F128,
+                LLVMTypeKind::LLVMPPC_FP128TypeKind => TypeDescriptor::PPCF128,
+                #[cfg(any(feature = "llvm11-0", feature = "llvm12-0", feature = "llvm13-0"))]
+                LLVMTypeKind::LLVMBFloatTypeKind => TypeDescriptor::BF16,
+                kind => unreachable!("FloatType has non floating point type kind {:?}", kind),
+            },
+            AnyTypeEnum::PointerType(ptr_type) => TypeDescriptor::Pointer {
+                pointee: Box::new(TypeDescriptor::describe(ptr_type.get_element_type(), seen_structs)),
+                address_space: unsafe { LLVMGetPointerAddressSpace(ptr_type.as_type_ref()) },
+            },
+            AnyTypeEnum::ArrayType(array_type) => TypeDescriptor::Array {
+                element: Box::new(TypeDescriptor::describe(array_type.get_element_type().as_any_type_enum(), seen_structs)),
+                len: array_type.len(),
+            },
+            AnyTypeEnum::VectorType(vec_type) => TypeDescriptor::Vector {
+                element: Box::new(TypeDescriptor::describe(vec_type.get_element_type().as_any_type_enum(), seen_structs)),
+                len: vec_type.get_size(),
+            },
+            AnyTypeEnum::StructType(struct_type) => {
+                let name = match struct_type.get_name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => return TypeDescriptor::Struct {
+                        fields: describe_all(struct_type.get_field_types(), seen_structs),
+                        packed: struct_type.is_packed(),
+                    },
+                };
+
+                // Only the first occurrence carries the body so that recursive types terminate
+                let fields = if struct_type.is_opaque() || !seen_structs.insert(name.clone()) {
+                    None
+                } else {
+                    Some(describe_all(struct_type.get_field_types(), seen_structs))
+                };
+
+                TypeDescriptor::NamedStruct {
+                    name,
+                    packed: fields.is_some() && struct_type.is_packed(),
+                    fields,
+                }
+            },
+            AnyTypeEnum::FunctionType(fn_type) => TypeDescriptor::Function {
+                return_type: Box::new(TypeDescriptor::describe(fn_type.get_any_return_type(), seen_structs)),
+                params: describe_all(fn_type.get_param_types(), seen_structs),
+                is_var_arg: fn_type.is_var_arg(),
+            },
+        }
+    }
+
+    /// Creates the described type in `context`.
+    ///
+    /// Named structs which already exist in `context` are reused (on LLVM 12.0+, where they
+    /// can be looked up by name) and have their body filled in if they are still opaque.
+    /// Within a single call, every occurrence of a name resolves to the same struct.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the descriptor does not describe a valid LLVM type, such as a pointer to
+    /// `Void` or a struct with a `Function` field, or if it contains `BF16` prior to LLVM 11.0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    /// use inkwell::types::TypeDescriptor;
+    ///
+    /// let fn_descriptor = TypeDescriptor::Function {
+    ///     return_type: Box::new(TypeDescriptor::Int(32)),
+    ///     params: vec![TypeDescriptor::F32, TypeDescriptor::Int(1)],
+    ///     is_var_arg: false,
+    /// };
+    ///
+    /// let context1 = Context::create();
+    /// let context2 = Context::create();
+    /// let fn_type1 = fn_descriptor.materialize(&context1).into_function_type();
+    /// let fn_type2 = fn_descriptor.materialize(&context2).into_function_type();
+    ///
+    /// assert_eq!(fn_type1.print_to_string(), fn_type2.print_to_string());
+    /// ```
+    pub fn materialize<'ctx>(&self, context: &'ctx Context) -> AnyTypeEnum<'ctx> {
+        let mut named_structs = HashMap::new();
+
+        unsafe {
+            AnyTypeEnum::new(self.materialize_raw(context, &mut named_structs))
+        }
+    }
+
+    unsafe fn materialize_raw(&self, context: &Context, named_structs: &mut HashMap<String, LLVMTypeRef>) -> LLVMTypeRef {
+        let ctx = context.context;
+
+        match self {
+            TypeDescriptor::Void => LLVMVoidTypeInContext(ctx),
+            TypeDescriptor::Int(bits) => LLVMIntTypeInContext(ctx, *bits),
+            TypeDescriptor::F16 => LLVMHalfTypeInContext(ctx),
+            #[cfg(any(feature = "llvm11-0", feature = "llvm12-0", feature = "llvm13-0"))]
+            TypeDescriptor::BF16 => LLVMBFloatTypeInContext(ctx),
+            #[cfg(not(any(feature = "llvm11-0", feature = "llvm12-0", feature = "llvm13-0")))]
+            TypeDescriptor::BF16 => panic!("BF16 requires LLVM 11.0+."),
+            TypeDescriptor::F32 => LLVMFloatTypeInContext(ctx),
+            TypeDescriptor::F64 => LLVMDoubleTypeInContext(ctx),
+            TypeDescriptor::X86F80 => LLVMX86FP80TypeInContext(ctx),
+            TypeDescriptor::F128 => LLVMFP128TypeInContext(ctx),
+            TypeDescriptor::PPCF128 => LLVMPPCFP128TypeInContext(ctx),
+            TypeDescriptor::Pointer { pointee, address_space } => {
+                assert!(**pointee != TypeDescriptor::Void, "Pointers to Void are not valid types.");
+
+                LLVMPointerType(pointee.materialize_raw(context, named_structs), *address_space)
+            },
+            TypeDescriptor::Array { element, len } => {
+                LLVMArrayType(element.materialize_basic(context, named_structs), *len)
+            },
+            TypeDescriptor::Vector { element, len } => {
+                match **element {
+                    TypeDescriptor::Int(_) | TypeDescriptor::Pointer { .. } |
+                    TypeDescriptor::F16 | TypeDescriptor::BF16 | TypeDescriptor::F32 | TypeDescriptor::F64 |
+                    TypeDescriptor::X86F80 | TypeDescriptor::F128 | TypeDescriptor::PPCF128 => {},
+                    _ => panic!("Vector elements must be integers, floats or pointers."),
+                }
+
+                LLVMVectorType(element.materialize_raw(context, named_structs), *len)
+            },
+            TypeDescriptor::Struct { fields, packed } => {
+                let mut field_types = TypeDescriptor::materialize_all_basic(fields, context, named_structs);
+
+                LLVMStructTypeInContext(ctx, field_types.as_mut_ptr(), field_types.len() as u32, *packed as i32)
+            },
+            TypeDescriptor::NamedStruct { name, fields, packed } => {
+                let struct_type = match named_structs.get(name) {
+                    Some(&struct_type) => struct_type,
+                    None => {
+                        let struct_type = TypeDescriptor::get_or_create_named_struct(context, name);
+
+                        named_structs.insert(name.clone(), struct_type);
+
+                        struct_type
+                    },
+                };
+
+                if let Some(fields) = fields {
+                    if LLVMIsOpaqueStruct(struct_type) == 1 {
+                        let mut field_types = TypeDescriptor::materialize_all_basic(fields, context, named_structs);
+
+                        LLVMStructSetBody(struct_type, field_types.as_mut_ptr(), field_types.len() as u32, *packed as i32);
+                    }
+                }
+
+                struct_type
+            },
+            TypeDescriptor::Function { return_type, params, is_var_arg } => {
+                let return_type = match **return_type {
+                    TypeDescriptor::Void => LLVMVoidTypeInContext(ctx),
+                    ref return_type => return_type.materialize_basic(context, named_structs),
+                };
+                let mut param_types = TypeDescriptor::materialize_all_basic(params, context, named_structs);
+
+                LLVMFunctionType(return_type, param_types.as_mut_ptr(), param_types.len() as u32, *is_var_arg as i32)
+            },
+        }
+    }
+
+    unsafe fn materialize_basic(&self, context: &Context, named_structs: &mut HashMap<String, LLVMTypeRef>) -> LLVMTypeRef {
+        match self {
+            TypeDescriptor::Void => panic!("Void is not a valid field, element or parameter type."),
+            TypeDescriptor::Function { .. } => panic!("Function is not a valid field, element or parameter type. Use a pointer to it instead."),
+            _ => self.materialize_raw(context, named_structs),
+        }
+    }
+
+    unsafe fn materialize_all_basic(descriptors: &[TypeDescriptor], context: &Context, named_structs: &mut HashMap<String, LLVMTypeRef>) -> Vec<LLVMTypeRef> {
+        descriptors.iter()
+                   .map(|descriptor| descriptor.materialize_basic(context, named_structs))
+                   .collect()
+    }
+
+    #[llvm_versions(12.0..=latest)]
+    unsafe fn get_or_create_named_struct(context: &Context, name: &str) -> LLVMTypeRef {
+        let c_string = to_c_str(name);
+        let struct_type = LLVMGetTypeByName2(context.context, c_string.as_ptr());
+
+        if struct_type.is_null() {
+            LLVMStructCreateNamed(context.context, c_string.as_ptr())
+        } else {
+            struct_type
+        }
+    }
+
+    // Named types can only be looked up through a module before LLVM 12.0, so each
+    // call to `materialize` creates its own named structs
+    #[llvm_versions(3.6..=11.0)]
+    unsafe fn get_or_create_named_struct(context: &Context, name: &str) -> LLVMTypeRef {
+        let c_string = to_c_str(name);
+
+        LLVMStructCreateNamed(context.context, c_string.as_ptr())
+    }
+}
@@ -11,6 +11,7 @@ use crate::types::enums::BasicMetadataTypeEnum;
 
 /// A `FloatType` is the type of a floating point constant or variable.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct FloatType<'ctx> {
     float_type: Type<'ctx>,
 }
@@ -89,7 +90,7 @@ impl<'ctx> FloatType<'ctx> {
     /// ```
     pub fn const_float(self, value: f64) -> FloatValue<'ctx> {
         unsafe {
-            FloatValue::new(LLVMConstReal(self.float_type.ty, value))
+            FloatValue::new(LLVMConstReal(self.float_type.ty.as_ptr(), value))
         }
     }
 
@@ -272,6 +273,6 @@ impl<'ctx> FloatType<'ctx> {
 
 impl AsTypeRef for FloatType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.float_type.ty
+        self.float_type.ty.as_ptr()
     }
 }
@@ -1,17 +1,20 @@
 use llvm_sys::LLVMTypeKind;
-use llvm_sys::core::{LLVMGetParamTypes, LLVMIsFunctionVarArg, LLVMCountParamTypes, LLVMGetReturnType, LLVMGetTypeKind};
+use llvm_sys::core::{LLVMGetParamTypes, LLVMIsFunctionVarArg, LLVMCountParamTypes, LLVMGetReturnType, LLVMGetTypeKind, LLVMFunctionType, LLVMGetTypeContext, LLVMVoidTypeInContext};
 use llvm_sys::prelude::LLVMTypeRef;
 
 use std::fmt;
+use std::marker::PhantomData;
 use std::mem::forget;
+use std::ptr;
 
 use crate::AddressSpace;
 use crate::context::ContextRef;
 use crate::types::traits::AsTypeRef;
-use crate::types::{AnyType, PointerType, Type, BasicTypeEnum};
+use crate::types::{AnyType, AnyTypeEnum, BasicMetadataTypeEnum, PointerType, Type, BasicTypeEnum, INLINE_TYPE_REFS};
 
 /// A `FunctionType` is the type of a function variable.
 #[derive(PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct FunctionType<'ctx> {
     fn_type: Type<'ctx>,
 }
@@ -192,6 +195,125 @@ impl<'ctx> FunctionType<'ctx> {
         }
     }
 
+    /// Gets the return type of this `FunctionType`, including when it is `void`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let void_type = context.void_type();
+    /// let fn_type = void_type.fn_type(&[], false);
+    ///
+    /// assert_eq!(fn_type.get_any_return_type().into_void_type(), void_type);
+    /// ```
+    pub fn get_any_return_type(self) -> AnyTypeEnum<'ctx> {
+        unsafe {
+            AnyTypeEnum::new(LLVMGetReturnType(self.as_type_ref()))
+        }
+    }
+
+    /// Gets an iterator over the param types of this `FunctionType`. LLVM only hands out
+    /// param types all at once, so they are copied up front into a buffer kept inside the
+    /// iterator, which only allocates for functions with unusually many params.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let f32_type = context.f32_type();
+    /// let i8_type = context.i8_type();
+    /// let fn_type = f32_type.fn_type(&[f32_type.into(), i8_type.into()], false);
+    /// let mut param_types = fn_type.get_param_type_iter();
+    ///
+    /// assert_eq!(param_types.len(), 2);
+    /// assert_eq!(param_types.next().unwrap().into_float_type(), f32_type);
+    /// assert_eq!(param_types.next().unwrap().into_int_type(), i8_type);
+    /// assert!(param_types.next().is_none());
+    /// ```
+    pub fn get_param_type_iter(self) -> ParamTypeIter<'ctx> {
+        let count = self.count_param_types() as usize;
+        let mut inline_types = [ptr::null_mut(); INLINE_TYPE_REFS];
+        let mut heap_types = Vec::new();
+
+        unsafe {
+            if count <= INLINE_TYPE_REFS {
+                LLVMGetParamTypes(self.as_type_ref(), inline_types.as_mut_ptr());
+            } else {
+                heap_types = vec![ptr::null_mut(); count];
+
+                LLVMGetParamTypes(self.as_type_ref(), heap_types.as_mut_ptr());
+            }
+        }
+
+        ParamTypeIter {
+            inline_types,
+            heap_types,
+            front: 0,
+            back: count,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a `FunctionType` with the same return type and variadicness as this one,
+    /// but taking `param_types` instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let f32_type = context.f32_type();
+    /// let i8_type = context.i8_type();
+    /// let fn_type = f32_type.fn_type(&[f32_type.into()], true);
+    /// let wrapper_type = fn_type.with_params_replaced(&[i8_type.into(), f32_type.into()]);
+    ///
+    /// assert_eq!(wrapper_type, f32_type.fn_type(&[i8_type.into(), f32_type.into()], true));
+    /// ```
+    pub fn with_params_replaced(self, param_types: &[BasicMetadataTypeEnum<'ctx>]) -> FunctionType<'ctx> {
+        let mut param_types: Vec<LLVMTypeRef> = param_types.iter().map(|val| val.as_type_ref()).collect();
+
+        unsafe {
+            FunctionType::new(LLVMFunctionType(LLVMGetReturnType(self.as_type_ref()), param_types.as_mut_ptr(), param_types.len() as u32, self.is_var_arg() as i32))
+        }
+    }
+
+    /// Creates a `FunctionType` with the same params and variadicness as this one,
+    /// but returning `return_type` instead, or `void` if it is `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let f32_type = context.f32_type();
+    /// let fn_type = f32_type.fn_type(&[f32_type.into()], false);
+    /// let void_fn_type = fn_type.with_return_replaced(None);
+    ///
+    /// assert_eq!(void_fn_type, context.void_type().fn_type(&[f32_type.into()], false));
+    /// assert_eq!(void_fn_type.with_return_replaced(Some(f32_type.into())), fn_type);
+    /// ```
+    pub fn with_return_replaced(self, return_type: Option<BasicTypeEnum<'ctx>>) -> FunctionType<'ctx> {
+        let count = self.count_param_types() as usize;
+        let mut param_types: Vec<LLVMTypeRef> = vec![ptr::null_mut(); count];
+
+        unsafe {
+            LLVMGetParamTypes(self.as_type_ref(), param_types.as_mut_ptr());
+
+            let return_type = match return_type {
+                Some(return_type) => return_type.as_type_ref(),
+                None => LLVMVoidTypeInContext(LLVMGetTypeContext(self.as_type_ref())),
+            };
+
+            FunctionType::new(LLVMFunctionType(return_type, param_types.as_mut_ptr(), count as u32, self.is_var_arg() as i32))
+        }
+    }
+
     // REVIEW: Can you do undef for functions?
     // Seems to "work" - no UB or SF so far but fails
     // LLVMIsAFunction() check. Commenting out for further research
@@ -200,6 +322,62 @@ impl<'ctx> FunctionType<'ctx> {
     // }
 }
 
+/// An iterator over the param types of a `FunctionType`.
+#[derive(Debug)]
+pub struct ParamTypeIter<'ctx> {
+    inline_types: [LLVMTypeRef; INLINE_TYPE_REFS],
+    heap_types: Vec<LLVMTypeRef>,
+    front: usize,
+    back: usize,
+    _marker: PhantomData<&'ctx ()>,
+}
+
+impl ParamTypeIter<'_> {
+    fn get(&self, index: usize) -> LLVMTypeRef {
+        if self.heap_types.is_empty() {
+            self.inline_types[index]
+        } else {
+            self.heap_types[index]
+        }
+    }
+}
+
+impl<'ctx> Iterator for ParamTypeIter<'ctx> {
+    type Item = BasicTypeEnum<'ctx>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        let ty = self.get(self.front);
+
+        self.front += 1;
+
+        Some(unsafe { BasicTypeEnum::new(ty) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for ParamTypeIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+
+        Some(unsafe { BasicTypeEnum::new(self.get(self.back)) })
+    }
+}
+
+impl ExactSizeIterator for ParamTypeIter<'_> {}
+
 impl fmt::Debug for FunctionType<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let llvm_type = self.print_to_string();
@@ -214,6 +392,6 @@ impl fmt::Debug for FunctionType<'_> {
 
 impl AsTypeRef for FunctionType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.fn_type.ty
+        self.fn_type.ty.as_ptr()
     }
 }
@@ -66,6 +66,7 @@ impl StringRadix {
 
 /// An `IntType` is the type of an integer constant or variable.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct IntType<'ctx> {
     int_type: Type<'ctx>,
 }
@@ -385,6 +386,6 @@ impl<'ctx> IntType<'ctx> {
 
 impl AsTypeRef for IntType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.int_type.ty
+        self.int_type.ty.as_ptr()
     }
 }
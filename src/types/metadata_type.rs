@@ -8,6 +8,7 @@ use crate::types::enums::BasicMetadataTypeEnum;
 
 /// A `MetadataType` is the type of a metadata.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct MetadataType<'ctx> {
     metadata_type: Type<'ctx>,
 }
@@ -59,7 +60,7 @@ impl<'ctx> MetadataType<'ctx> {
 impl AsTypeRef for MetadataType<'_> {
     #[llvm_versions(6.0..=latest)]
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.metadata_type.ty
+        self.metadata_type.ty.as_ptr()
     }
 
     #[llvm_versions(3.6..6.0)]
@@ -2,6 +2,8 @@
 
 #[deny(missing_docs)]
 mod array_type;
+#[deny(missing_docs)]
+mod descriptor;
 mod enums;
 #[deny(missing_docs)]
 mod float_type;
@@ -23,9 +25,10 @@ mod vec_type;
 mod void_type;
 
 pub use crate::types::array_type::ArrayType;
+pub use crate::types::descriptor::TypeDescriptor;
 pub use crate::types::enums::{AnyTypeEnum, BasicTypeEnum, BasicMetadataTypeEnum};
 pub use crate::types::float_type::FloatType;
-pub use crate::types::fn_type::FunctionType;
+pub use crate::types::fn_type::{FunctionType, ParamTypeIter};
 pub use crate::types::int_type::{IntType, StringRadix};
 pub use crate::types::metadata_type::MetadataType;
 pub use crate::types::ptr_type::PointerType;
@@ -35,7 +38,7 @@ pub use crate::types::vec_type::VectorType;
 pub use crate::types::void_type::VoidType;
 pub(crate) use crate::types::traits::AsTypeRef;
 
-use llvm_sys::LLVMTypeKind;
+use llvm_sys::{LLVMType, LLVMTypeKind};
 #[llvm_versions(3.7..=4.0)]
 use llvm_sys::core::LLVMDumpType;
 use llvm_sys::core::{LLVMAlignOf, LLVMGetTypeContext, LLVMFunctionType, LLVMArrayType, LLVMGetUndef, LLVMPointerType, LLVMPrintTypeToString, LLVMTypeIsSized, LLVMSizeOf, LLVMVectorType, LLVMGetElementType, LLVMConstNull, LLVMGetTypeKind, LLVMConstPointerNull};
@@ -45,6 +48,7 @@ use static_alloc::Bump;
 
 use std::fmt;
 use std::marker::PhantomData;
+use std::ptr::NonNull;
 
 use crate::AddressSpace;
 use crate::context::ContextRef;
@@ -54,8 +58,9 @@ use crate::values::IntValue;
 // Worth noting that types seem to be singletons. At the very least, primitives are.
 // Though this is likely only true per thread since LLVM claims to not be very thread-safe.
 #[derive(PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 struct Type<'ctx> {
-    ty: LLVMTypeRef,
+    ty: NonNull<LLVMType>,
     _marker: PhantomData<&'ctx ()>,
 }
 
@@ -64,7 +69,7 @@ impl<'ctx> Type<'ctx> {
         assert!(!ty.is_null());
 
         Type {
-            ty,
+            ty: NonNull::new_unchecked(ty),
             _marker: PhantomData,
         }
     }
@@ -76,22 +81,22 @@ impl<'ctx> Type<'ctx> {
     #[llvm_versions(3.7..=4.0)]
     fn print_to_stderr(self) {
         unsafe {
-            LLVMDumpType(self.ty);
+            LLVMDumpType(self.ty.as_ptr());
         }
     }
 
     fn const_zero(self) -> LLVMValueRef {
         unsafe {
-            match LLVMGetTypeKind(self.ty) {
-                LLVMTypeKind::LLVMMetadataTypeKind => LLVMConstPointerNull(self.ty),
-                _ => LLVMConstNull(self.ty)
+            match LLVMGetTypeKind(self.ty.as_ptr()) {
+                LLVMTypeKind::LLVMMetadataTypeKind => LLVMConstPointerNull(self.ty.as_ptr()),
+                _ => LLVMConstNull(self.ty.as_ptr())
             }
         }
     }
 
     fn ptr_type(self, address_space: AddressSpace) -> PointerType<'ctx> {
         unsafe {
-            PointerType::new(LLVMPointerType(self.ty, address_space as u32))
+            PointerType::new(LLVMPointerType(self.ty.as_ptr(), address_space as u32))
         }
     }
 
@@ -100,18 +105,15 @@ impl<'ctx> Type<'ctx> {
         // -- https://llvm.org/docs/LangRef.html#vector-type
 
         unsafe {
-            VectorType::new(LLVMVectorType(self.ty, size))
+            VectorType::new(LLVMVectorType(self.ty.as_ptr(), size))
         }
     }
 
     #[cfg(not(feature = "experimental"))]
     fn fn_type(self, param_types: &[BasicMetadataTypeEnum<'ctx>], is_var_args: bool) -> FunctionType<'ctx> {
-        let mut param_types: Vec<LLVMTypeRef> = param_types.iter()
-                                                           .map(|val| val.as_type_ref())
-                                                           .collect();
-        unsafe {
-            FunctionType::new(LLVMFunctionType(self.ty, param_types.as_mut_ptr(), param_types.len() as u32, is_var_args as i32))
-        }
+        with_type_refs(param_types, |param_types| unsafe {
+            FunctionType::new(LLVMFunctionType(self.ty.as_ptr(), param_types.as_mut_ptr(), param_types.len() as u32, is_var_args as i32))
+        })
     }
 
     #[cfg(feature = "experimental")]
@@ -128,31 +130,31 @@ impl<'ctx> Type<'ctx> {
         }
 
         unsafe {
-            FunctionType::new(LLVMFunctionType(self.ty, pool_start.unwrap_or(std::ptr::null_mut()), param_types.len() as u32, is_var_args as i32))
+            FunctionType::new(LLVMFunctionType(self.ty.as_ptr(), pool_start.unwrap_or(std::ptr::null_mut()), param_types.len() as u32, is_var_args as i32))
         }
     }
 
     fn array_type(self, size: u32) -> ArrayType<'ctx> {
         unsafe {
-            ArrayType::new(LLVMArrayType(self.ty, size))
+            ArrayType::new(LLVMArrayType(self.ty.as_ptr(), size))
         }
     }
 
     fn get_undef(self) -> LLVMValueRef {
         unsafe {
-            LLVMGetUndef(self.ty)
+            LLVMGetUndef(self.ty.as_ptr())
         }
     }
 
     fn get_alignment(self) -> IntValue<'ctx> {
         unsafe {
-            IntValue::new(LLVMAlignOf(self.ty))
+            IntValue::new(LLVMAlignOf(self.ty.as_ptr()))
         }
     }
 
     fn get_context(self) -> ContextRef<'ctx> {
         unsafe {
-            ContextRef::new(LLVMGetTypeContext(self.ty))
+            ContextRef::new(LLVMGetTypeContext(self.ty.as_ptr()))
         }
     }
 
@@ -161,7 +163,7 @@ impl<'ctx> Type<'ctx> {
     // enum has only sized types for example)
     fn is_sized(self) -> bool {
         unsafe {
-            LLVMTypeIsSized(self.ty) == 1
+            LLVMTypeIsSized(self.ty.as_ptr()) == 1
         }
     }
 
@@ -171,22 +173,45 @@ impl<'ctx> Type<'ctx> {
         }
 
         unsafe {
-            Some(IntValue::new(LLVMSizeOf(self.ty)))
+            Some(IntValue::new(LLVMSizeOf(self.ty.as_ptr())))
         }
     }
 
     fn print_to_string(self) -> LLVMString {
         unsafe {
-            LLVMString::new(LLVMPrintTypeToString(self.ty))
+            LLVMString::new(LLVMPrintTypeToString(self.ty.as_ptr()))
         }
     }
 
     pub fn get_element_type(self) -> AnyTypeEnum<'ctx> {
         unsafe {
-            AnyTypeEnum::new(LLVMGetElementType(self.ty))
+            AnyTypeEnum::new(LLVMGetElementType(self.ty.as_ptr()))
+        }
+    }
+
+}
+
+/// The number of type refs `with_type_refs` and `ParamTypeIter` can hold without allocating.
+pub(crate) const INLINE_TYPE_REFS: usize = 16;
+
+/// Calls `f` with the raw refs of `types`, which are kept on the stack rather than in a
+/// fresh `Vec` when there are few enough of them, as is almost always the case for the
+/// params of a function or the fields of a struct. Type construction is hot in frontends
+/// generating large modules, so this avoids an allocation per type built.
+pub(crate) fn with_type_refs<T: AsTypeRef, R>(types: &[T], f: impl FnOnce(&mut [LLVMTypeRef]) -> R) -> R {
+    if types.len() <= INLINE_TYPE_REFS {
+        let mut refs = [std::ptr::null_mut(); INLINE_TYPE_REFS];
+
+        for (type_ref, ty) in refs.iter_mut().zip(types) {
+            *type_ref = ty.as_type_ref();
         }
+
+        return f(&mut refs[..types.len()]);
     }
 
+    let mut refs: Vec<LLVMTypeRef> = types.iter().map(|ty| ty.as_type_ref()).collect();
+
+    f(&mut refs)
 }
 
 impl fmt::Debug for Type<'_> {
@@ -12,6 +12,7 @@ use crate::types::enums::BasicMetadataTypeEnum;
 
 /// A `PointerType` is the type of a pointer constant or variable.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct PointerType<'ctx> {
     ptr_type: Type<'ctx>,
 }
@@ -293,6 +294,6 @@ impl<'ctx> PointerType<'ctx> {
 
 impl AsTypeRef for PointerType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.ptr_type.ty
+        self.ptr_type.ty.as_ptr()
     }
 }
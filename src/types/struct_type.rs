@@ -9,12 +9,13 @@ use std::mem::forget;
 use crate::AddressSpace;
 use crate::context::ContextRef;
 use crate::types::traits::AsTypeRef;
-use crate::types::{ArrayType, BasicTypeEnum, PointerType, FunctionType, Type};
+use crate::types::{ArrayType, BasicTypeEnum, PointerType, FunctionType, Type, with_type_refs};
 use crate::values::{ArrayValue, BasicValueEnum, StructValue, IntValue, AsValueRef};
 use crate::types::enums::BasicMetadataTypeEnum;
 
 /// A `StructType` is the type of a heterogeneous container of types.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct StructType<'ctx> {
     struct_type: Type<'ctx>,
 }
@@ -371,13 +372,11 @@ impl<'ctx> StructType<'ctx> {
     /// ```
     pub fn set_body(self, field_types: &[BasicTypeEnum<'ctx>], packed: bool) -> bool {
         let is_opaque = self.is_opaque();
-        let mut field_types: Vec<LLVMTypeRef> = field_types.iter()
-                                                           .map(|val| val.as_type_ref())
-                                                           .collect();
+
         if is_opaque {
-            unsafe {
+            with_type_refs(field_types, |field_types| unsafe {
                 LLVMStructSetBody(self.as_type_ref(), field_types.as_mut_ptr(), field_types.len() as u32, packed as i32);
-            }
+            });
         }
 
         is_opaque
@@ -410,6 +409,6 @@ impl<'ctx> StructType<'ctx> {
 
 impl AsTypeRef for StructType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.struct_type.ty
+        self.struct_type.ty.as_ptr()
     }
 }
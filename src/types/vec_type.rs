@@ -9,6 +9,7 @@ use crate::types::enums::BasicMetadataTypeEnum;
 
 /// A `VectorType` is the type of a multiple value SIMD constant or variable.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct VectorType<'ctx> {
     vec_type: Type<'ctx>,
 }
@@ -275,6 +276,6 @@ impl<'ctx> VectorType<'ctx> {
 
 impl AsTypeRef for VectorType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.vec_type.ty
+        self.vec_type.ty.as_ptr()
     }
 }
@@ -8,6 +8,7 @@ use crate::types::enums::BasicMetadataTypeEnum;
 /// A `VoidType` is a special type with no possible direct instances. It's only
 /// useful as a function return type.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct VoidType<'ctx> {
     void_type: Type<'ctx>,
 }
@@ -81,6 +82,6 @@ impl<'ctx> VoidType<'ctx> {
 
 impl AsTypeRef for VoidType<'_> {
     fn as_type_ref(&self) -> LLVMTypeRef {
-        self.void_type.ty
+        self.void_type.ty.as_ptr()
     }
 }
@@ -10,6 +10,7 @@ use crate::values::{Value, InstructionValue};
 
 /// An `ArrayValue` is a block of contiguous constants or variables.
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct ArrayValue<'ctx> {
     array_value: Value<'ctx>,
 }
@@ -83,7 +84,7 @@ impl<'ctx> ArrayValue<'ctx> {
 
 impl AsValueRef for ArrayValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.array_value.value
+        self.array_value.value.as_ptr()
     }
 }
 
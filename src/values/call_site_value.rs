@@ -3,10 +3,13 @@ use llvm_sys::LLVMTypeKind;
 use llvm_sys::core::{LLVMIsTailCall, LLVMSetTailCall, LLVMGetTypeKind, LLVMTypeOf, LLVMSetInstructionCallConv, LLVMGetInstructionCallConv, LLVMSetInstrParamAlignment};
 use llvm_sys::prelude::LLVMValueRef;
 
+use crate::CallingConvention;
 #[llvm_versions(3.9..=latest)]
 use crate::attributes::{Attribute};
 use crate::attributes::AttributeLoc;
 use crate::support::LLVMString;
+#[llvm_versions(12.0..=latest)]
+use crate::types::AnyTypeEnum;
 use crate::values::{AsValueRef, BasicValueEnum, InstructionValue, Value};
 #[llvm_versions(3.9..=latest)]
 use crate::values::FunctionValue;
@@ -15,6 +18,7 @@ use crate::values::FunctionValue;
 ///
 /// This struct may be removed in the future in favor of an `InstructionValue<CallSite>` type.
 #[derive(Debug, PartialEq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct CallSiteValue<'ctx>(Value<'ctx>);
 
 impl<'ctx> CallSiteValue<'ctx> {
@@ -300,6 +304,123 @@ impl<'ctx> CallSiteValue<'ctx> {
         }
     }
 
+    /// Gets all `Attribute`s on this `CallSiteValue` at an index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::attributes::AttributeLoc;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let builder = context.create_builder();
+    /// let module = context.create_module("my_mod");
+    /// let void_type = context.void_type();
+    /// let fn_type = void_type.fn_type(&[], false);
+    /// let fn_value = module.add_function("my_fn", fn_type, None);
+    /// let string_attribute = context.create_string_attribute("my_key", "my_val");
+    /// let enum_attribute = context.create_enum_attribute(1, 1);
+    /// let entry_bb = context.append_basic_block(fn_value, "entry");
+    ///
+    /// builder.position_at_end(entry_bb);
+    ///
+    /// let call_site_value = builder.build_call(fn_value, &[], "my_fn");
+    ///
+    /// call_site_value.add_attribute(AttributeLoc::Return, string_attribute);
+    /// call_site_value.add_attribute(AttributeLoc::Return, enum_attribute);
+    ///
+    /// assert_eq!(call_site_value.attributes(AttributeLoc::Return).len(), 2);
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn attributes(self, loc: AttributeLoc) -> Vec<Attribute> {
+        use llvm_sys::core::LLVMGetCallSiteAttributes;
+
+        let count = self.count_attributes(loc);
+        let mut attributes = vec![std::ptr::null_mut(); count as usize];
+
+        unsafe {
+            LLVMGetCallSiteAttributes(self.as_value_ref(), loc.get_index(), attributes.as_mut_ptr())
+        }
+
+        attributes.into_iter().map(|attribute| unsafe { Attribute::new(attribute) }).collect()
+    }
+
+    /// Copies every `Attribute` of `other`, on the call itself, its return value and each of
+    /// its arguments, onto this `CallSiteValue`.
+    #[llvm_versions(3.9..=latest)]
+    pub fn copy_attributes_from(self, other: CallSiteValue<'ctx>) -> Result<(), &'static str> {
+        if self.count_arguments() != other.count_arguments() {
+            return Err("Call sites have a different number of arguments.");
+        }
+
+        for loc in AttributeLoc::all(other.count_arguments()) {
+            for attribute in other.attributes(loc) {
+                self.add_attribute(loc, attribute);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies every `Attribute` of `function`, on the function itself, its return value and
+    /// each of its params, onto this `CallSiteValue`, such as when a thunk forwards its params
+    /// to the function it wraps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::attributes::AttributeLoc;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let builder = context.create_builder();
+    /// let fn_type = context.void_type().fn_type(&[context.i32_type().into()], false);
+    /// let original = module.add_function("original", fn_type, None);
+    /// let thunk = module.add_function("thunk", fn_type, None);
+    ///
+    /// original.add_attribute(AttributeLoc::Param(0), context.create_string_attribute("my_key", "my_val"));
+    /// thunk.copy_attributes_from(original).unwrap();
+    /// builder.position_at_end(context.append_basic_block(thunk, "entry"));
+    ///
+    /// let call = builder.build_call(original, &[thunk.get_first_param().unwrap().into()], "");
+    ///
+    /// call.copy_attributes_from_function(original).unwrap();
+    /// builder.build_return(None);
+    ///
+    /// assert_eq!(call.attributes(AttributeLoc::Param(0)), original.attributes(AttributeLoc::Param(0)));
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn copy_attributes_from_function(self, function: FunctionValue<'ctx>) -> Result<(), &'static str> {
+        if self.count_arguments() != function.count_params() {
+            return Err("Call site and function have a different number of arguments.");
+        }
+
+        for loc in AttributeLoc::all(function.count_params()) {
+            for attribute in function.attributes(loc) {
+                self.add_attribute(loc, attribute);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the type held by a type `Attribute` on this `CallSiteValue` at an index and kind id, if any.
+    #[llvm_versions(12.0..=latest)]
+    pub fn get_type_attribute_value(self, loc: AttributeLoc, kind_id: u32) -> Option<AnyTypeEnum<'ctx>> {
+        use llvm_sys::core::LLVMGetTypeAttributeValue;
+
+        let attribute = self.get_enum_attribute(loc, kind_id)?;
+
+        if !attribute.is_type() {
+            return None;
+        }
+
+        unsafe {
+            Some(AnyTypeEnum::new(LLVMGetTypeAttributeValue(attribute.attribute)))
+        }
+    }
+
     /// Removes an enum `Attribute` on this `CallSiteValue` at an index and kind id.
     ///
     /// # Example
@@ -412,6 +533,7 @@ impl<'ctx> CallSiteValue<'ctx> {
     /// # Example
     ///
     /// ```no_run
+    /// use inkwell::CallingConvention;
     /// use inkwell::context::Context;
     ///
     /// let context = Context::create();
@@ -426,12 +548,14 @@ impl<'ctx> CallSiteValue<'ctx> {
     ///
     /// let call_site_value = builder.build_call(fn_value, &[], "my_fn");
     ///
-    /// assert_eq!(call_site_value.get_call_convention(), 0);
+    /// assert_eq!(call_site_value.get_call_convention(), CallingConvention::C);
     /// ```
-    pub fn get_call_convention(self) -> u32 {
-        unsafe {
+    pub fn get_call_convention(self) -> CallingConvention {
+        let conv = unsafe {
             LLVMGetInstructionCallConv(self.as_value_ref())
-        }
+        };
+
+        CallingConvention::from_id(conv)
     }
 
     /// Sets the calling convention for this `CallSiteValue`.
@@ -439,6 +563,7 @@ impl<'ctx> CallSiteValue<'ctx> {
     /// # Example
     ///
     /// ```no_run
+    /// use inkwell::CallingConvention;
     /// use inkwell::context::Context;
     ///
     /// let context = Context::create();
@@ -453,13 +578,13 @@ impl<'ctx> CallSiteValue<'ctx> {
     ///
     /// let call_site_value = builder.build_call(fn_value, &[], "my_fn");
     ///
-    /// call_site_value.set_call_convention(2);
+    /// call_site_value.set_call_convention(CallingConvention::Fast);
     ///
-    /// assert_eq!(call_site_value.get_call_convention(), 2);
+    /// assert_eq!(call_site_value.get_call_convention(), CallingConvention::Fast);
     /// ```
-    pub fn set_call_convention(self, conv: u32) {
+    pub fn set_call_convention(self, conv: CallingConvention) {
         unsafe {
-            LLVMSetInstructionCallConv(self.as_value_ref(), conv)
+            LLVMSetInstructionCallConv(self.as_value_ref(), conv.get_id())
         }
     }
 
@@ -497,6 +622,88 @@ impl<'ctx> CallSiteValue<'ctx> {
         }
     }
 
+    /// Marks this call site `alwaysinline`, so that the always inliner inlines it even if the
+    /// called function is not marked `alwaysinline` itself, or removes that mark. As the two
+    /// conflict, marking a call site `alwaysinline` removes any `noinline` mark from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let builder = context.create_builder();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let callee = module.add_function("callee", fn_type, None);
+    /// let caller = module.add_function("caller", fn_type, None);
+    ///
+    /// builder.position_at_end(context.append_basic_block(caller, "entry"));
+    ///
+    /// let call_site_value = builder.build_call(callee, &[], "call");
+    ///
+    /// call_site_value.set_always_inline(true);
+    ///
+    /// assert!(call_site_value.is_always_inline());
+    ///
+    /// call_site_value.set_no_inline(true);
+    ///
+    /// assert!(!call_site_value.is_always_inline());
+    /// assert!(call_site_value.is_no_inline());
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn set_always_inline(self, always_inline: bool) {
+        if always_inline {
+            self.set_function_enum_attribute("noinline", false);
+        }
+
+        self.set_function_enum_attribute("alwaysinline", always_inline);
+    }
+
+    /// Determines whether this call site is marked `alwaysinline`. This does not take the
+    /// attributes of the called function into account.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_always_inline(self) -> bool {
+        self.get_enum_attribute(AttributeLoc::Function, Attribute::get_named_enum_kind_id("alwaysinline")).is_some()
+    }
+
+    /// Marks this call site `noinline`, so that no inliner inlines it, or removes that mark.
+    /// As the two conflict, marking a call site `noinline` removes any `alwaysinline` mark from it.
+    #[llvm_versions(3.9..=latest)]
+    pub fn set_no_inline(self, no_inline: bool) {
+        if no_inline {
+            self.set_function_enum_attribute("alwaysinline", false);
+        }
+
+        self.set_function_enum_attribute("noinline", no_inline);
+    }
+
+    /// Determines whether this call site is marked `noinline`. This does not take the
+    /// attributes of the called function into account.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_no_inline(self) -> bool {
+        self.get_enum_attribute(AttributeLoc::Function, Attribute::get_named_enum_kind_id("noinline")).is_some()
+    }
+
+    #[llvm_versions(3.9..=latest)]
+    fn set_function_enum_attribute(self, name: &str, enabled: bool) {
+        use llvm_sys::core::{LLVMCreateEnumAttribute, LLVMGetTypeContext};
+
+        let kind_id = Attribute::get_named_enum_kind_id(name);
+
+        if enabled {
+            let attribute = unsafe {
+                let context = LLVMGetTypeContext(LLVMTypeOf(self.as_value_ref()));
+
+                Attribute::new(LLVMCreateEnumAttribute(context, kind_id, 0))
+            };
+
+            self.add_attribute(AttributeLoc::Function, attribute);
+        } else {
+            self.remove_enum_attribute(AttributeLoc::Function, kind_id);
+        }
+    }
+
     /// Prints the definition of a `CallSiteValue` to a `LLVMString`.
     pub fn print_to_string(self) -> LLVMString {
         self.0.print_to_string()
@@ -505,6 +712,6 @@ impl<'ctx> CallSiteValue<'ctx> {
 
 impl AsValueRef for CallSiteValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.0.value
+        self.0.value.as_ptr()
     }
 }
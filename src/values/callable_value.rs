@@ -1,6 +1,7 @@
 use std::convert::TryFrom;
 use either::Either;
 
+use crate::types::FunctionType;
 use crate::values::AsValueRef;
 use crate::values::{FunctionValue, PointerValue, AnyValue};
 
@@ -73,7 +74,7 @@ use llvm_sys::LLVMTypeKind;
 ///
 /// builder.build_return(Some(&ret_val));
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct CallableValue<'ctx>(Either<FunctionValue<'ctx>, PointerValue<'ctx>>);
 
 impl<'ctx> AsValueRef for CallableValue<'ctx> {
@@ -97,6 +98,12 @@ impl<'ctx> CallableValue<'ctx> {
 
         matches!(return_type, LLVMTypeKind::LLVMVoidTypeKind)
     }
+
+    pub(crate) fn get_function_type(&self) -> FunctionType<'ctx> {
+        unsafe {
+            FunctionType::new(LLVMGetElementType(LLVMTypeOf(self.as_value_ref())))
+        }
+    }
 }
 
 impl<'ctx> From<FunctionValue<'ctx>> for CallableValue<'ctx> {
@@ -9,6 +9,7 @@ use crate::values::traits::AsValueRef;
 use crate::values::{InstructionValue, IntValue, Value};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct FloatValue<'ctx> {
     float_value: Value<'ctx>,
 }
@@ -175,6 +176,6 @@ impl<'ctx> FloatValue<'ctx> {
 
 impl AsValueRef for FloatValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.float_value.value
+        self.float_value.value.as_ptr()
     }
 }
@@ -1,18 +1,24 @@
 use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyFunction, LLVMViewFunctionCFG, LLVMViewFunctionCFGOnly};
-use llvm_sys::core::{LLVMIsAFunction, LLVMIsConstant, LLVMGetLinkage, LLVMGetPreviousFunction, LLVMGetNextFunction, LLVMGetParam, LLVMCountParams, LLVMGetLastParam, LLVMCountBasicBlocks, LLVMGetFirstParam, LLVMGetNextParam, LLVMGetBasicBlocks, LLVMDeleteFunction, LLVMGetLastBasicBlock, LLVMGetFirstBasicBlock, LLVMGetIntrinsicID, LLVMGetFunctionCallConv, LLVMSetFunctionCallConv, LLVMGetGC, LLVMSetGC, LLVMSetLinkage, LLVMSetParamAlignment, LLVMGetParams};
+use llvm_sys::core::{LLVMIsAFunction, LLVMIsConstant, LLVMGetLinkage, LLVMGetPreviousFunction, LLVMGetNextFunction, LLVMGetParam, LLVMCountParams, LLVMGetLastParam, LLVMCountBasicBlocks, LLVMGetFirstParam, LLVMGetNextParam, LLVMGetBasicBlocks, LLVMDeleteFunction, LLVMGetLastBasicBlock, LLVMGetFirstBasicBlock, LLVMGetEntryBasicBlock, LLVMGetIntrinsicID, LLVMGetFunctionCallConv, LLVMSetFunctionCallConv, LLVMGetGC, LLVMSetGC, LLVMSetLinkage, LLVMSetParamAlignment, LLVMGetParams};
 #[llvm_versions(3.7..=latest)]
 use llvm_sys::core::{LLVMGetPersonalityFn, LLVMSetPersonalityFn};
 #[llvm_versions(3.9..=latest)]
 use llvm_sys::core::{LLVMAddAttributeAtIndex, LLVMGetAttributeCountAtIndex, LLVMGetEnumAttributeAtIndex, LLVMGetStringAttributeAtIndex, LLVMRemoveEnumAttributeAtIndex, LLVMRemoveStringAttributeAtIndex};
 use llvm_sys::prelude::{LLVMValueRef, LLVMBasicBlockRef};
+#[llvm_versions(3.9..=latest)]
+use llvm_sys::prelude::LLVMAttributeRef;
 #[llvm_versions(7.0..=latest)]
 use llvm_sys::debuginfo::{LLVMGetSubprogram, LLVMSetSubprogram};
 
 use std::ffi::CStr;
 use std::marker::PhantomData;
+use std::iter::FusedIterator;
 use std::mem::forget;
 use std::fmt;
+#[llvm_versions(3.9..=latest)]
+use std::ptr;
 
+use crate::CallingConvention;
 #[llvm_versions(3.9..=latest)]
 use crate::attributes::{Attribute, AttributeLoc};
 use crate::basic_block::BasicBlock;
@@ -21,10 +27,13 @@ use crate::debug_info::DISubprogram;
 use crate::module::Linkage;
 use crate::support::to_c_str;
 use crate::types::{AnyType, FunctionType, PointerType};
+#[llvm_versions(12.0..=latest)]
+use crate::types::AnyTypeEnum;
 use crate::values::traits::{AnyValue, AsValueRef};
 use crate::values::{BasicValueEnum, GlobalValue, Value};
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct FunctionValue<'ctx> {
     fn_value: Value<'ctx>,
 }
@@ -73,13 +82,12 @@ impl<'ctx> FunctionValue<'ctx> {
         };
 
         let code = unsafe {
-            LLVMVerifyFunction(self.fn_value.value, action)
+            LLVMVerifyFunction(self.fn_value.value.as_ptr(), action)
         };
 
         code != 1
     }
 
-    // REVIEW: If there's a demand, could easily create a module.get_functions() -> Iterator
     pub fn get_next_function(self) -> Option<Self> {
         unsafe {
             FunctionValue::new(LLVMGetNextFunction(self.as_value_ref()))
@@ -140,7 +148,7 @@ impl<'ctx> FunctionValue<'ctx> {
 
     pub fn count_params(self) -> u32 {
         unsafe {
-            LLVMCountParams(self.fn_value.value)
+            LLVMCountParams(self.fn_value.value.as_ptr())
         }
     }
 
@@ -168,8 +176,9 @@ impl<'ctx> FunctionValue<'ctx> {
 
     pub fn get_param_iter(self) -> ParamValueIter<'ctx> {
         ParamValueIter {
-            param_iter_value: self.fn_value.value,
+            param_iter_value: self.fn_value.value.as_ptr(),
             start: true,
+            remaining: self.count_params(),
             _marker: PhantomData,
         }
     }
@@ -192,8 +201,94 @@ impl<'ctx> FunctionValue<'ctx> {
 
     pub fn get_last_basic_block(self) -> Option<BasicBlock<'ctx>> {
         unsafe {
-            BasicBlock::new(LLVMGetLastBasicBlock(self.fn_value.value))
+            BasicBlock::new(LLVMGetLastBasicBlock(self.fn_value.value.as_ptr()))
+        }
+    }
+
+    /// Gets the entry block of this function, which is the first block in its layout and
+    /// the one executed when it is called. This is `None` for function declarations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let function = module.add_function("my_fn", fn_type, None);
+    ///
+    /// assert!(function.get_entry_block().is_none());
+    ///
+    /// let entry = context.append_basic_block(function, "entry");
+    ///
+    /// context.append_basic_block(function, "next");
+    ///
+    /// assert_eq!(function.get_entry_block(), Some(entry));
+    /// ```
+    pub fn get_entry_block(self) -> Option<BasicBlock<'ctx>> {
+        // LLVMGetEntryBasicBlock assumes the function has a body
+        if self.count_basic_blocks() == 0 {
+            return None;
+        }
+
+        unsafe {
+            BasicBlock::new(LLVMGetEntryBasicBlock(self.as_value_ref()))
+        }
+    }
+
+    /// Lays out the blocks of this function in the order given, which must contain every
+    /// block of the function exactly once. The first block becomes the entry block, which
+    /// must not have any predecessors for the function to be valid.
+    ///
+    /// Block layout determines which successor of a branch can be reached by falling
+    /// through, which matters for code built without optimizations. New blocks can be
+    /// placed explicitly with `Context::insert_basic_block_after` and `Context::prepend_basic_block`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let function = module.add_function("my_fn", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let cold = context.append_basic_block(function, "cold");
+    /// let hot = context.append_basic_block(function, "hot");
+    ///
+    /// function.reorder_blocks(&[entry, hot, cold]).unwrap();
+    ///
+    /// assert_eq!(function.get_basic_blocks(), vec![entry, hot, cold]);
+    /// ```
+    pub fn reorder_blocks(self, blocks: &[BasicBlock<'ctx>]) -> Result<(), &'static str> {
+        if blocks.len() != self.count_basic_blocks() as usize {
+            return Err("Every block of the function must be given exactly once.");
         }
+
+        for (i, block) in blocks.iter().enumerate() {
+            if block.get_parent() != Some(self) || blocks[..i].contains(block) {
+                return Err("Every block of the function must be given exactly once.");
+            }
+        }
+
+        let mut previous = match blocks.first() {
+            Some(&first) => first,
+            None => return Ok(()),
+        };
+        let entry = self.get_entry_block().expect("Function with blocks to have an entry block");
+
+        if previous != entry {
+            previous.move_before(entry).expect("Blocks to be in the function");
+        }
+
+        for &block in &blocks[1..] {
+            block.move_after(previous).expect("Blocks to be in the function");
+            previous = block;
+        }
+
+        Ok(())
     }
 
     /// Gets the name of a `FunctionValue`.
@@ -277,15 +372,36 @@ impl<'ctx> FunctionValue<'ctx> {
         }
     }
 
-    pub fn get_call_conventions(self) -> u32 {
-        unsafe {
+    /// Gets the `CallingConvention` of this function.
+    pub fn get_call_conventions(self) -> CallingConvention {
+        let call_conventions = unsafe {
             LLVMGetFunctionCallConv(self.as_value_ref())
-        }
+        };
+
+        CallingConvention::from_id(call_conventions)
     }
 
-    pub fn set_call_conventions(self, call_conventions: u32) {
+    /// Sets the `CallingConvention` of this function. Calls to it must use the same
+    /// convention, see `CallSiteValue::set_call_convention`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::CallingConvention;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let fn_value = module.add_function("my_fn", fn_type, None);
+    ///
+    /// fn_value.set_call_conventions(CallingConvention::Fast);
+    ///
+    /// assert_eq!(fn_value.get_call_conventions(), CallingConvention::Fast);
+    /// ```
+    pub fn set_call_conventions(self, call_conventions: CallingConvention) {
         unsafe {
-            LLVMSetFunctionCallConv(self.as_value_ref(), call_conventions)
+            LLVMSetFunctionCallConv(self.as_value_ref(), call_conventions.get_id())
         }
     }
 
@@ -333,6 +449,43 @@ impl<'ctx> FunctionValue<'ctx> {
         }
     }
 
+    /// Copies every `Attribute` of `other`, on the function itself, its return value and each
+    /// of its params, onto this `FunctionValue`, as needed to give a thunk the exact signature
+    /// of the function it wraps. Attributes this `FunctionValue` already has are kept, unless
+    /// `other` has an enum attribute of the same kind.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::attributes::AttributeLoc;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[context.i32_type().into()], false);
+    /// let original = module.add_function("original", fn_type, None);
+    /// let thunk = module.add_function("thunk", fn_type, None);
+    ///
+    /// original.add_attribute(AttributeLoc::Param(0), context.create_string_attribute("my_key", "my_val"));
+    /// thunk.copy_attributes_from(original).unwrap();
+    ///
+    /// assert_eq!(thunk.attributes(AttributeLoc::Param(0)), original.attributes(AttributeLoc::Param(0)));
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn copy_attributes_from(self, other: FunctionValue<'ctx>) -> Result<(), &'static str> {
+        if self.count_params() != other.count_params() {
+            return Err("Functions have a different number of params.");
+        }
+
+        for loc in AttributeLoc::all(other.count_params()) {
+            for attribute in other.attributes(loc) {
+                self.add_attribute(loc, attribute);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Counts the number of `Attribute`s belonging to the specified location in this `FunctionValue`.
     ///
     /// # Example
@@ -481,6 +634,84 @@ impl<'ctx> FunctionValue<'ctx> {
         }
     }
 
+    /// Gets all `Attribute`s belonging to the specified location in this `FunctionValue`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::attributes::AttributeLoc;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let void_type = context.void_type();
+    /// let fn_type = void_type.fn_type(&[], false);
+    /// let fn_value = module.add_function("my_fn", fn_type, None);
+    /// let string_attribute = context.create_string_attribute("my_key", "my_val");
+    /// let enum_attribute = context.create_enum_attribute(1, 1);
+    ///
+    /// fn_value.add_attribute(AttributeLoc::Function, string_attribute);
+    /// fn_value.add_attribute(AttributeLoc::Function, enum_attribute);
+    ///
+    /// let attributes = fn_value.attributes(AttributeLoc::Function);
+    ///
+    /// assert_eq!(attributes.len(), 2);
+    /// assert!(attributes.contains(&string_attribute));
+    /// assert!(attributes.contains(&enum_attribute));
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn attributes(self, loc: AttributeLoc) -> Vec<Attribute> {
+        use llvm_sys::core::LLVMGetAttributesAtIndex;
+
+        let count = self.count_attributes(loc);
+        let mut attributes: Vec<LLVMAttributeRef> = vec![ptr::null_mut(); count as usize];
+
+        unsafe {
+            LLVMGetAttributesAtIndex(self.as_value_ref(), loc.get_index(), attributes.as_mut_ptr())
+        }
+
+        attributes.into_iter().map(|attribute| unsafe { Attribute::new(attribute) }).collect()
+    }
+
+    /// Gets the type held by a type `Attribute` belonging to the specified location in this
+    /// `FunctionValue`, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::attributes::{Attribute, AttributeLoc};
+    /// use inkwell::context::Context;
+    /// use inkwell::types::AnyType;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let void_type = context.void_type();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = void_type.fn_type(&[i32_type.ptr_type(AddressSpace::Generic).into()], false);
+    /// let fn_value = module.add_function("my_fn", fn_type, None);
+    /// let kind_id = Attribute::get_named_enum_kind_id("sret");
+    /// let type_attribute = context.create_type_attribute(kind_id, i32_type.as_any_type_enum());
+    ///
+    /// fn_value.add_attribute(AttributeLoc::Param(0), type_attribute);
+    ///
+    /// assert_eq!(fn_value.get_type_attribute_value(AttributeLoc::Param(0), kind_id), Some(i32_type.as_any_type_enum()));
+    /// ```
+    #[llvm_versions(12.0..=latest)]
+    pub fn get_type_attribute_value(self, loc: AttributeLoc, kind_id: u32) -> Option<AnyTypeEnum<'ctx>> {
+        use llvm_sys::core::LLVMGetTypeAttributeValue;
+
+        let attribute = self.get_enum_attribute(loc, kind_id)?;
+
+        if !attribute.is_type() {
+            return None;
+        }
+
+        unsafe {
+            Some(AnyTypeEnum::new(LLVMGetTypeAttributeValue(attribute.attribute)))
+        }
+    }
+
     pub fn set_param_alignment(self, param_index: u32, alignment: u32) {
         if let Some(param) = self.get_nth_param(param_index) {
             unsafe {
@@ -522,7 +753,7 @@ impl<'ctx> FunctionValue<'ctx> {
 
 impl AsValueRef for FunctionValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.fn_value.value
+        self.fn_value.value.as_ptr()
     }
 }
 
@@ -532,7 +763,7 @@ impl fmt::Debug for FunctionValue<'_> {
         let llvm_type = self.get_type();
         let name = self.get_name();
         let is_const = unsafe {
-            LLVMIsConstant(self.fn_value.value) == 1
+            LLVMIsConstant(self.fn_value.value.as_ptr()) == 1
         };
         let is_null = self.is_null();
 
@@ -551,6 +782,7 @@ impl fmt::Debug for FunctionValue<'_> {
 pub struct ParamValueIter<'ctx> {
     param_iter_value: LLVMValueRef,
     start: bool,
+    remaining: u32,
     _marker: PhantomData<&'ctx ()>,
 }
 
@@ -568,6 +800,7 @@ impl<'ctx> Iterator for ParamValueIter<'ctx> {
             }
 
             self.start = false;
+            self.remaining -= 1;
 
             self.param_iter_value = first_value;
 
@@ -583,9 +816,18 @@ impl<'ctx> Iterator for ParamValueIter<'ctx> {
         }
 
         self.param_iter_value = next_value;
+        self.remaining -= 1;
 
         unsafe {
             Some(Self::Item::new(next_value))
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
 }
+
+impl ExactSizeIterator for ParamValueIter<'_> {}
+
+impl FusedIterator for ParamValueIter<'_> {}
@@ -1,8 +1,8 @@
 use llvm_sys::LLVMThreadLocalMode;
 #[llvm_versions(3.6..8.0)]
-use llvm_sys::core::{LLVMGetVisibility, LLVMSetVisibility, LLVMGetSection, LLVMSetSection, LLVMIsExternallyInitialized, LLVMSetExternallyInitialized, LLVMDeleteGlobal, LLVMIsGlobalConstant, LLVMSetGlobalConstant, LLVMGetPreviousGlobal, LLVMGetNextGlobal, LLVMIsThreadLocal, LLVMSetThreadLocal, LLVMGetThreadLocalMode, LLVMSetThreadLocalMode, LLVMGetInitializer, LLVMSetInitializer, LLVMIsDeclaration, LLVMGetDLLStorageClass, LLVMSetDLLStorageClass, LLVMGetAlignment, LLVMSetAlignment, LLVMGetLinkage, LLVMSetLinkage};
+use llvm_sys::core::{LLVMGetVisibility, LLVMSetVisibility, LLVMGetSection, LLVMSetSection, LLVMIsExternallyInitialized, LLVMSetExternallyInitialized, LLVMDeleteGlobal, LLVMIsGlobalConstant, LLVMSetGlobalConstant, LLVMGetPreviousGlobal, LLVMGetNextGlobal, LLVMIsThreadLocal, LLVMSetThreadLocal, LLVMGetThreadLocalMode, LLVMSetThreadLocalMode, LLVMGetInitializer, LLVMSetInitializer, LLVMIsDeclaration, LLVMGetDLLStorageClass, LLVMSetDLLStorageClass, LLVMGetAlignment, LLVMSetAlignment, LLVMGetLinkage, LLVMSetLinkage, LLVMGetFirstUse, LLVMIsAFunction, LLVMIsAGlobalVariable, LLVMDeleteFunction, LLVMTypeOf};
 #[llvm_versions(8.0..=latest)]
-use llvm_sys::core::{LLVMGetVisibility, LLVMSetVisibility, LLVMGetSection, LLVMSetSection, LLVMIsExternallyInitialized, LLVMSetExternallyInitialized, LLVMDeleteGlobal, LLVMIsGlobalConstant, LLVMSetGlobalConstant, LLVMGetPreviousGlobal, LLVMGetNextGlobal, LLVMIsThreadLocal, LLVMSetThreadLocal, LLVMGetThreadLocalMode, LLVMSetThreadLocalMode, LLVMGetInitializer, LLVMSetInitializer, LLVMIsDeclaration, LLVMGetDLLStorageClass, LLVMSetDLLStorageClass, LLVMGetAlignment, LLVMSetAlignment, LLVMGetLinkage, LLVMSetLinkage};
+use llvm_sys::core::{LLVMGetVisibility, LLVMSetVisibility, LLVMGetSection, LLVMSetSection, LLVMIsExternallyInitialized, LLVMSetExternallyInitialized, LLVMDeleteGlobal, LLVMIsGlobalConstant, LLVMSetGlobalConstant, LLVMGetPreviousGlobal, LLVMGetNextGlobal, LLVMIsThreadLocal, LLVMSetThreadLocal, LLVMGetThreadLocalMode, LLVMSetThreadLocalMode, LLVMGetInitializer, LLVMSetInitializer, LLVMIsDeclaration, LLVMGetDLLStorageClass, LLVMSetDLLStorageClass, LLVMGetAlignment, LLVMSetAlignment, LLVMGetLinkage, LLVMSetLinkage, LLVMGetFirstUse, LLVMIsAFunction, LLVMIsAGlobalVariable, LLVMDeleteFunction};
 #[llvm_versions(3.6..=6.0)]
 use llvm_sys::core::{LLVMHasUnnamedAddr, LLVMSetUnnamedAddr};
 #[llvm_versions(7.0..=latest)]
@@ -10,7 +10,11 @@ use llvm_sys::core::{LLVMGetUnnamedAddress, LLVMSetUnnamedAddress};
 #[llvm_versions(7.0..=latest)]
 use llvm_sys::LLVMUnnamedAddr;
 #[llvm_versions(8.0..=latest)]
-use llvm_sys::core::LLVMGlobalSetMetadata;
+use llvm_sys::core::{LLVMGlobalSetMetadata, LLVMGlobalEraseMetadata, LLVMGlobalClearMetadata, LLVMGlobalCopyAllMetadata, LLVMGetTypeContext, LLVMTypeOf, LLVMConstInt, LLVMInt64TypeInContext, LLVMMDStringInContext, LLVMMDNodeInContext, LLVMGetMDKindIDInContext};
+use llvm_sys::core::{LLVMGetOperand, LLVMGetNumOperands, LLVMIsAConstantStruct, LLVMIsAConstantArray, LLVMIsAConstantVector, LLVMIsAConstantDataSequential, LLVMIsAConstantExpr, LLVMGetConstOpcode, LLVMGetElementAsConstant, LLVMGetElementType, LLVMGetTypeKind, LLVMGetArrayLength, LLVMGetVectorSize, LLVMConstNamedStruct, LLVMConstArray, LLVMConstVector, LLVMConstBitCast, LLVMConstPtrToInt, LLVMConstIntToPtr, LLVMConstAddrSpaceCast};
+#[llvm_versions(5.0..=latest)]
+use llvm_sys::core::{LLVMIsInBounds, LLVMConstGEP, LLVMConstInBoundsGEP};
+use llvm_sys::{LLVMOpcode, LLVMTypeKind};
 use llvm_sys::prelude::LLVMValueRef;
 
 use std::ffi::CStr;
@@ -24,10 +28,13 @@ use crate::values::traits::AsValueRef;
 use crate::values::{BasicValueEnum, BasicValue, PointerValue, Value};
 #[llvm_versions(8.0..=latest)]
 use crate::values::MetadataValue;
+#[llvm_versions(8.0..=latest)]
+use crate::values::metadata_value::take_metadata_entries;
 
 // REVIEW: GlobalValues are always PointerValues. With SubTypes, we should
 // compress this into a PointerValue<Global> type
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct GlobalValue<'ctx> {
     global_value: Value<'ctx>,
 }
@@ -74,6 +81,8 @@ impl<'ctx> GlobalValue<'ctx> {
         }
     }
 
+    /// Gets the `DLLStorageClass` of this global, which determines whether it is imported
+    /// from or exported to a DLL on Windows.
     pub fn get_dll_storage_class(self) -> DLLStorageClass {
         let dll_storage_class = unsafe {
             LLVMGetDLLStorageClass(self.as_value_ref())
@@ -82,6 +91,24 @@ impl<'ctx> GlobalValue<'ctx> {
         DLLStorageClass::new(dll_storage_class)
     }
 
+    /// Sets the `DLLStorageClass` of this global. As COFF import stubs are only used for
+    /// declarations, `DLLStorageClass::Import` should only be set on globals without a definition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::DLLStorageClass;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let imported = module.add_function("imported_fn", fn_type, None);
+    ///
+    /// imported.as_global_value().set_dll_storage_class(DLLStorageClass::Import);
+    ///
+    /// assert_eq!(imported.as_global_value().get_dll_storage_class(), DLLStorageClass::Import);
+    /// ```
     pub fn set_dll_storage_class(self, dll_storage_class: DLLStorageClass) {
         unsafe {
             LLVMSetDLLStorageClass(self.as_value_ref(), dll_storage_class.into())
@@ -109,6 +136,62 @@ impl<'ctx> GlobalValue<'ctx> {
         }
     }
 
+    /// Replaces every occurrence of the constant `from` in this global's initializer with `to`,
+    /// rebuilding the constant structs, arrays, vectors and pointer casts or GEPs that contain
+    /// it. Returns whether anything was replaced.
+    ///
+    /// This is useful for backpatching the real address of a symbol into a constant table
+    /// before it is handed to a JIT. Only explicit elements are visited, so a
+    /// `zeroinitializer` is left untouched.
+    ///
+    /// Constants are uniqued by LLVM, so any element equal to `from` is replaced, including
+    /// ones which only happen to have the same value. A placeholder should therefore be unique,
+    /// such as the address of a dedicated external global, rather than an integer which may
+    /// also appear in the table as a regular value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("table");
+    /// let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    /// let placeholder = module.add_global(context.i8_type(), None, "placeholder").as_pointer_value();
+    /// let table = module.add_global(i8_ptr_type.array_type(2), None, "table");
+    ///
+    /// table.set_initializer(&i8_ptr_type.const_array(&[placeholder, i8_ptr_type.const_null()]));
+    ///
+    /// let address = context.i64_type().const_int(0x1000, false).const_to_pointer(i8_ptr_type);
+    ///
+    /// assert_eq!(table.replace_in_initializer(placeholder.into(), address.into()), Ok(true));
+    /// ```
+    pub fn replace_in_initializer(self, from: BasicValueEnum<'ctx>, to: BasicValueEnum<'ctx>) -> Result<bool, &'static str> {
+        if from.get_type() != to.get_type() {
+            return Err("Replacement constant must have the same type.");
+        }
+
+        let initializer = match self.get_initializer() {
+            Some(initializer) => initializer.as_value_ref(),
+            None => return Ok(false),
+        };
+
+        let rewritten = unsafe {
+            replace_constant(initializer, from.as_value_ref(), to.as_value_ref())?
+        };
+
+        if rewritten == initializer {
+            return Ok(false);
+        }
+
+        unsafe {
+            LLVMSetInitializer(self.as_value_ref(), rewritten);
+        }
+
+        Ok(true)
+    }
+
     pub fn is_thread_local(self) -> bool {
         unsafe {
             LLVMIsThreadLocal(self.as_value_ref()) == 1
@@ -227,12 +310,35 @@ impl<'ctx> GlobalValue<'ctx> {
         }
     }
 
+    /// Sets the `GlobalVisibility` of this global. Functions can be given a visibility through
+    /// `FunctionValue::as_global_value`.
+    ///
+    /// LLVM treats globals with hidden or protected visibility, as well as those with local
+    /// linkage, as `dso_local`, which is what lets position-independent code reference them
+    /// directly. The LLVM C API has no way of marking other globals `dso_local` explicitly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::GlobalVisibility;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let function = module.add_function("internal_helper", fn_type, None);
+    ///
+    /// function.as_global_value().set_visibility(GlobalVisibility::Hidden);
+    ///
+    /// assert_eq!(function.as_global_value().get_visibility(), GlobalVisibility::Hidden);
+    /// ```
     pub fn set_visibility(self, visibility: GlobalVisibility) {
         unsafe {
             LLVMSetVisibility(self.as_value_ref(), visibility.into())
         }
     }
 
+    /// Gets the `GlobalVisibility` of this global.
     pub fn get_visibility(self) -> GlobalVisibility {
         let visibility = unsafe {
             LLVMGetVisibility(self.as_value_ref())
@@ -259,6 +365,45 @@ impl<'ctx> GlobalValue<'ctx> {
         LLVMDeleteGlobal(self.as_value_ref())
     }
 
+    /// Deletes this global variable or function if nothing in its module refers to it,
+    /// returning whether or not it was deleted. Aliases and ifuncs are never deleted.
+    ///
+    /// # Safety
+    ///
+    /// No other handle to this global, such as a `FunctionValue` for it, may be used
+    /// after it has been deleted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("dce");
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let fn_value = module.add_function("unused", fn_type, None);
+    ///
+    /// assert!(unsafe { fn_value.as_global_value().delete_if_unused() });
+    /// assert!(module.get_function("unused").is_none());
+    /// ```
+    pub unsafe fn delete_if_unused(self) -> bool {
+        let value = self.as_value_ref();
+
+        if !LLVMGetFirstUse(value).is_null() {
+            return false;
+        }
+
+        if !LLVMIsAFunction(value).is_null() {
+            LLVMDeleteFunction(value);
+        } else if !LLVMIsAGlobalVariable(value).is_null() {
+            LLVMDeleteGlobal(value);
+        } else {
+            return false;
+        }
+
+        true
+    }
+
     pub fn as_pointer_value(self) -> PointerValue<'ctx> {
         unsafe {
             PointerValue::new(self.as_value_ref())
@@ -278,6 +423,25 @@ impl<'ctx> GlobalValue<'ctx> {
     }
 
     /// Sets a metadata of the given type on the GlobalValue
+    ///
+    /// This replaces any metadata previously attached at `kind_id`. The metadata must be a
+    /// node, as is created by `Context::metadata_node`. For example, an `!associated` node
+    /// naming another global keeps this one alive for as long as that global is:
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("metadata");
+    /// let i32_type = context.i32_type();
+    /// let function = module.add_function("instrumented", context.void_type().fn_type(&[], false), None);
+    /// let counters = module.add_global(i32_type.array_type(4), None, "counters");
+    /// let associated = context.metadata_node(&[function.as_global_value().as_pointer_value().into()]);
+    ///
+    /// counters.set_metadata(associated, context.get_kind_id("associated"));
+    ///
+    /// assert_eq!(counters.get_metadata(context.get_kind_id("associated")), Some(associated));
+    /// ```
     #[llvm_versions(8.0..=latest)]
     pub fn set_metadata(self, metadata: MetadataValue<'ctx>, kind_id: u32) {
         unsafe {
@@ -285,6 +449,91 @@ impl<'ctx> GlobalValue<'ctx> {
         }
     }
 
+    /// Gets the metadata attached to this `GlobalValue` at a specific `kind_id`, if any. When
+    /// several nodes are attached at the same kind, as can happen with `!type`, the first is returned.
+    #[llvm_versions(8.0..=latest)]
+    pub fn get_metadata(self, kind_id: u32) -> Option<MetadataValue<'ctx>> {
+        self.get_all_metadata()
+            .into_iter()
+            .find(|&(entry_kind_id, _)| entry_kind_id == kind_id)
+            .map(|(_, metadata)| metadata)
+    }
+
+    /// Gets all metadata attached to this `GlobalValue` along with their kind ids.
+    #[llvm_versions(8.0..=latest)]
+    pub fn get_all_metadata(self) -> Vec<(u32, MetadataValue<'ctx>)> {
+        let mut count = 0;
+
+        unsafe {
+            let entries = LLVMGlobalCopyAllMetadata(self.as_value_ref(), &mut count);
+            let context = LLVMGetTypeContext(LLVMTypeOf(self.as_value_ref()));
+
+            take_metadata_entries(context, entries, count)
+        }
+    }
+
+    /// Removes all metadata attached to this `GlobalValue` at a specific `kind_id`.
+    #[llvm_versions(8.0..=latest)]
+    pub fn erase_metadata(self, kind_id: u32) {
+        unsafe {
+            LLVMGlobalEraseMetadata(self.as_value_ref(), kind_id)
+        }
+    }
+
+    /// Removes all metadata attached to this `GlobalValue`.
+    #[llvm_versions(8.0..=latest)]
+    pub fn clear_metadata(self) {
+        unsafe {
+            LLVMGlobalClearMetadata(self.as_value_ref())
+        }
+    }
+
+    /// Attaches `!type` metadata to this `GlobalValue`, which declares that the address at
+    /// `offset` bytes into it is compatible with the type identified by `type_id`, as matched
+    /// by `llvm.type.test` calls.
+    ///
+    /// LLVM allows several `!type` nodes per global, and control flow integrity checks and
+    /// whole program devirtualization need one per compatible type, such as for every base
+    /// class of a vtable's class. The C API can however only replace the metadata of a kind,
+    /// so this can attach a single `!type` node and is not enough to describe vtables of class
+    /// hierarchies. An `Err` is returned, without changing the global, if a `!type` node is
+    /// already attached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("cfi");
+    /// let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+    /// let vtable = module.add_global(i8_ptr_type.array_type(3), None, "vtable");
+    ///
+    /// assert!(vtable.set_type_metadata(8, "_ZTS6Shape").is_ok());
+    /// assert!(vtable.set_type_metadata(8, "_ZTS6Circle").is_err());
+    /// ```
+    #[llvm_versions(8.0..=latest)]
+    pub fn set_type_metadata(self, offset: u64, type_id: &str) -> Result<(), &'static str> {
+        unsafe {
+            let context = LLVMGetTypeContext(LLVMTypeOf(self.as_value_ref()));
+            let kind_id = LLVMGetMDKindIDInContext(context, "type".as_ptr() as *const _, "type".len() as u32);
+
+            if self.get_metadata(kind_id).is_some() {
+                return Err("A !type node is already attached, and the C API cannot attach several.");
+            }
+
+            let mut operands = [
+                LLVMConstInt(LLVMInt64TypeInContext(context), offset, false as i32),
+                LLVMMDStringInContext(context, type_id.as_ptr() as *const _, type_id.len() as u32),
+            ];
+            let node = MetadataValue::new(LLVMMDNodeInContext(context, operands.as_mut_ptr(), operands.len() as u32));
+
+            self.set_metadata(node, kind_id);
+
+            Ok(())
+        }
+    }
+
     /// Gets a `Comdat` assigned to this `GlobalValue`, if any.
     #[llvm_versions(7.0..=latest)]
     pub fn get_comdat(self) -> Option<Comdat> {
@@ -350,7 +599,7 @@ impl<'ctx> GlobalValue<'ctx> {
 
 impl AsValueRef for GlobalValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.global_value.value
+        self.global_value.value.as_ptr()
     }
 }
 
@@ -371,3 +620,94 @@ pub enum UnnamedAddress {
     #[llvm_variant(LLVMGlobalUnnamedAddr)]
     Global,
 }
+
+/// Rebuilds `value` with every occurrence of `from` replaced by `to`, returning `value` itself
+/// when nothing changed.
+unsafe fn replace_constant(value: LLVMValueRef, from: LLVMValueRef, to: LLVMValueRef) -> Result<LLVMValueRef, &'static str> {
+    if value == from {
+        return Ok(to);
+    }
+
+    let ty = LLVMTypeOf(value);
+
+    if !LLVMIsAConstantDataSequential(value).is_null() {
+        let len = match LLVMGetTypeKind(ty) {
+            LLVMTypeKind::LLVMArrayTypeKind => LLVMGetArrayLength(ty),
+            _ => LLVMGetVectorSize(ty),
+        };
+        let elements = (0..len).map(|i| LLVMGetElementAsConstant(value, i)).collect::<Vec<_>>();
+        let mut new_elements = Vec::with_capacity(elements.len());
+
+        for &element in &elements {
+            new_elements.push(replace_constant(element, from, to)?);
+        }
+
+        if new_elements == elements {
+            return Ok(value);
+        }
+
+        return Ok(match LLVMGetTypeKind(ty) {
+            LLVMTypeKind::LLVMArrayTypeKind => LLVMConstArray(LLVMGetElementType(ty), new_elements.as_mut_ptr(), len),
+            _ => LLVMConstVector(new_elements.as_mut_ptr(), len),
+        });
+    }
+
+    let is_aggregate = !LLVMIsAConstantStruct(value).is_null()
+        || !LLVMIsAConstantArray(value).is_null()
+        || !LLVMIsAConstantVector(value).is_null();
+    let is_expr = !LLVMIsAConstantExpr(value).is_null();
+
+    if !is_aggregate && !is_expr {
+        return Ok(value);
+    }
+
+    let num_operands = LLVMGetNumOperands(value) as u32;
+    let operands = (0..num_operands).map(|i| LLVMGetOperand(value, i)).collect::<Vec<_>>();
+    let mut new_operands = Vec::with_capacity(operands.len());
+
+    for &operand in &operands {
+        new_operands.push(replace_constant(operand, from, to)?);
+    }
+
+    if new_operands == operands {
+        return Ok(value);
+    }
+
+    if !LLVMIsAConstantStruct(value).is_null() {
+        return Ok(LLVMConstNamedStruct(ty, new_operands.as_mut_ptr(), num_operands));
+    }
+
+    if !LLVMIsAConstantArray(value).is_null() {
+        return Ok(LLVMConstArray(LLVMGetElementType(ty), new_operands.as_mut_ptr(), num_operands));
+    }
+
+    if !LLVMIsAConstantVector(value).is_null() {
+        return Ok(LLVMConstVector(new_operands.as_mut_ptr(), num_operands));
+    }
+
+    match LLVMGetConstOpcode(value) {
+        LLVMOpcode::LLVMBitCast => Ok(LLVMConstBitCast(new_operands[0], ty)),
+        LLVMOpcode::LLVMPtrToInt => Ok(LLVMConstPtrToInt(new_operands[0], ty)),
+        LLVMOpcode::LLVMIntToPtr => Ok(LLVMConstIntToPtr(new_operands[0], ty)),
+        LLVMOpcode::LLVMAddrSpaceCast => Ok(LLVMConstAddrSpaceCast(new_operands[0], ty)),
+        LLVMOpcode::LLVMGetElementPtr => replace_gep_operands(value, &mut new_operands),
+        _ => Err("Unsupported constant expression in initializer."),
+    }
+}
+
+#[llvm_versions(5.0..=latest)]
+unsafe fn replace_gep_operands(value: LLVMValueRef, operands: &mut [LLVMValueRef]) -> Result<LLVMValueRef, &'static str> {
+    let num_indices = (operands.len() - 1) as u32;
+    let indices = operands[1..].as_mut_ptr();
+
+    if LLVMIsInBounds(value) == 1 {
+        Ok(LLVMConstInBoundsGEP(operands[0], indices, num_indices))
+    } else {
+        Ok(LLVMConstGEP(operands[0], indices, num_indices))
+    }
+}
+
+#[llvm_versions(3.6..=4.0)]
+unsafe fn replace_gep_operands(_value: LLVMValueRef, _operands: &mut [LLVMValueRef]) -> Result<LLVMValueRef, &'static str> {
+    Err("Rewriting constant GEPs requires LLVM 5.0 or later.")
+}
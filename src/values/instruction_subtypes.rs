@@ -0,0 +1,369 @@
+//! Typed views of specific kinds of `InstructionValue`, exposing accessors which
+//! only make sense for that kind of instruction.
+
+use llvm_sys::core::{LLVMGetAlignment, LLVMGetNumOperands, LLVMGetOperand, LLVMGetVolatile, LLVMSetVolatile};
+#[llvm_versions(3.8..=latest)]
+use llvm_sys::core::LLVMGetOrdering;
+#[llvm_versions(3.9..=latest)]
+use llvm_sys::core::{LLVMGetCmpXchgFailureOrdering, LLVMGetCmpXchgSuccessOrdering, LLVMGetCondition, LLVMGetNumSuccessors, LLVMGetSuccessor, LLVMGetWeak, LLVMIsAtomicSingleThread, LLVMIsConditional, LLVMIsInBounds, LLVMSetAtomicSingleThread, LLVMSetIsInBounds, LLVMSetWeak};
+use llvm_sys::prelude::LLVMValueRef;
+
+#[llvm_versions(3.9..=latest)]
+use crate::basic_block::BasicBlock;
+#[llvm_versions(3.8..=latest)]
+use crate::AtomicOrdering;
+use crate::values::{AsValueRef, BasicValueEnum, InstructionValue, PointerValue, Value};
+#[llvm_versions(3.9..=latest)]
+use crate::values::IntValue;
+
+fn get_operand<'ctx, V: AsValueRef>(value: V, index: u32) -> BasicValueEnum<'ctx> {
+    unsafe {
+        BasicValueEnum::new(LLVMGetOperand(value.as_value_ref(), index))
+    }
+}
+
+macro_rules! instruction_subtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+        #[repr(transparent)]
+        pub struct $name<'ctx>(Value<'ctx>);
+
+        impl<'ctx> $name<'ctx> {
+            pub(crate) unsafe fn new(value: LLVMValueRef) -> Self {
+                debug_assert!(!value.is_null());
+
+                $name(Value::new(value))
+            }
+
+            /// Gets the underlying `InstructionValue`.
+            pub fn as_instruction(self) -> InstructionValue<'ctx> {
+                unsafe {
+                    InstructionValue::new(self.as_value_ref())
+                }
+            }
+        }
+
+        impl AsValueRef for $name<'_> {
+            fn as_value_ref(&self) -> LLVMValueRef {
+                self.0.value.as_ptr()
+            }
+        }
+
+        impl<'ctx> From<$name<'ctx>> for InstructionValue<'ctx> {
+            fn from(value: $name<'ctx>) -> Self {
+                value.as_instruction()
+            }
+        }
+    };
+}
+
+instruction_subtype! {
+    /// A `load` instruction.
+    LoadValue
+}
+
+instruction_subtype! {
+    /// A `store` instruction.
+    StoreValue
+}
+
+instruction_subtype! {
+    /// A `getelementptr` instruction.
+    GEPValue
+}
+
+instruction_subtype! {
+    /// A `cmpxchg` instruction.
+    CmpXchgValue
+}
+
+instruction_subtype! {
+    /// A `br` instruction, which may be either conditional or unconditional.
+    BranchValue
+}
+
+instruction_subtype! {
+    /// A `ret` instruction.
+    ReturnValue
+}
+
+instruction_subtype! {
+    /// An `unreachable` instruction.
+    UnreachableValue
+}
+
+impl<'ctx> LoadValue<'ctx> {
+    /// Gets the pointer being loaded from.
+    pub fn get_pointer_operand(self) -> PointerValue<'ctx> {
+        get_operand(self, 0).into_pointer_value()
+    }
+
+    /// Determines whether or not this load is volatile.
+    pub fn is_volatile(self) -> bool {
+        unsafe {
+            LLVMGetVolatile(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Sets whether or not this load is volatile.
+    pub fn set_volatile(self, volatile: bool) {
+        unsafe {
+            LLVMSetVolatile(self.as_value_ref(), volatile as i32)
+        }
+    }
+
+    /// Gets the alignment of this load.
+    pub fn get_alignment(self) -> u32 {
+        unsafe {
+            LLVMGetAlignment(self.as_value_ref())
+        }
+    }
+
+    /// Sets the alignment of this load.
+    pub fn set_alignment(self, alignment: u32) -> Result<(), &'static str> {
+        self.as_instruction().set_alignment(alignment)
+    }
+
+    /// Determines whether or not this load carries `!nontemporal` metadata.
+    pub fn is_nontemporal(self) -> bool {
+        self.as_instruction().is_nontemporal().expect("load should support nontemporal metadata")
+    }
+
+    /// Adds or removes `!nontemporal` metadata on this load.
+    pub fn set_nontemporal(self, nontemporal: bool) {
+        self.as_instruction().set_nontemporal(nontemporal).expect("load should support nontemporal metadata")
+    }
+
+    /// Gets the atomic ordering of this load.
+    #[llvm_versions(3.8..=latest)]
+    pub fn get_atomic_ordering(self) -> AtomicOrdering {
+        unsafe {
+            LLVMGetOrdering(self.as_value_ref()).into()
+        }
+    }
+}
+
+impl<'ctx> StoreValue<'ctx> {
+    /// Gets the value being stored.
+    pub fn get_value_operand(self) -> BasicValueEnum<'ctx> {
+        get_operand(self, 0)
+    }
+
+    /// Gets the pointer being stored to.
+    pub fn get_pointer_operand(self) -> PointerValue<'ctx> {
+        get_operand(self, 1).into_pointer_value()
+    }
+
+    /// Determines whether or not this store is volatile.
+    pub fn is_volatile(self) -> bool {
+        unsafe {
+            LLVMGetVolatile(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Sets whether or not this store is volatile.
+    pub fn set_volatile(self, volatile: bool) {
+        unsafe {
+            LLVMSetVolatile(self.as_value_ref(), volatile as i32)
+        }
+    }
+
+    /// Gets the alignment of this store.
+    pub fn get_alignment(self) -> u32 {
+        unsafe {
+            LLVMGetAlignment(self.as_value_ref())
+        }
+    }
+
+    /// Sets the alignment of this store.
+    pub fn set_alignment(self, alignment: u32) -> Result<(), &'static str> {
+        self.as_instruction().set_alignment(alignment)
+    }
+
+    /// Determines whether or not this store carries `!nontemporal` metadata.
+    pub fn is_nontemporal(self) -> bool {
+        self.as_instruction().is_nontemporal().expect("store should support nontemporal metadata")
+    }
+
+    /// Adds or removes `!nontemporal` metadata on this store.
+    pub fn set_nontemporal(self, nontemporal: bool) {
+        self.as_instruction().set_nontemporal(nontemporal).expect("store should support nontemporal metadata")
+    }
+
+    /// Gets the atomic ordering of this store.
+    #[llvm_versions(3.8..=latest)]
+    pub fn get_atomic_ordering(self) -> AtomicOrdering {
+        unsafe {
+            LLVMGetOrdering(self.as_value_ref()).into()
+        }
+    }
+}
+
+impl<'ctx> GEPValue<'ctx> {
+    /// Gets the base pointer being indexed into.
+    pub fn get_pointer_operand(self) -> BasicValueEnum<'ctx> {
+        // This may be a vector of pointers, so it isn't always a PointerValue.
+        get_operand(self, 0)
+    }
+
+    /// Gets the indices of this GEP, not including the base pointer.
+    pub fn get_indices(self) -> Vec<BasicValueEnum<'ctx>> {
+        let num_operands = self.as_instruction().get_num_operands();
+
+        (1..num_operands).map(|i| get_operand(self, i)).collect()
+    }
+
+    /// Determines whether or not this GEP is marked `inbounds`.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_in_bounds(self) -> bool {
+        unsafe {
+            LLVMIsInBounds(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Sets whether or not this GEP is marked `inbounds`.
+    #[llvm_versions(3.9..=latest)]
+    pub fn set_in_bounds(self, in_bounds: bool) {
+        unsafe {
+            LLVMSetIsInBounds(self.as_value_ref(), in_bounds as i32)
+        }
+    }
+}
+
+impl<'ctx> CmpXchgValue<'ctx> {
+    /// Gets the pointer being operated on.
+    pub fn get_pointer_operand(self) -> PointerValue<'ctx> {
+        get_operand(self, 0).into_pointer_value()
+    }
+
+    /// Gets the value the pointee is compared against.
+    pub fn get_compare_operand(self) -> BasicValueEnum<'ctx> {
+        get_operand(self, 1)
+    }
+
+    /// Gets the value stored should the comparison succeed.
+    pub fn get_new_value_operand(self) -> BasicValueEnum<'ctx> {
+        get_operand(self, 2)
+    }
+
+    /// Determines whether or not this cmpxchg is volatile.
+    #[llvm_versions(10.0..=latest)]
+    pub fn is_volatile(self) -> bool {
+        unsafe {
+            LLVMGetVolatile(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Determines whether or not this cmpxchg is allowed to fail spuriously.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_weak(self) -> bool {
+        unsafe {
+            LLVMGetWeak(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Sets whether or not this cmpxchg is allowed to fail spuriously.
+    #[llvm_versions(3.9..=latest)]
+    pub fn set_weak(self, weak: bool) {
+        unsafe {
+            LLVMSetWeak(self.as_value_ref(), weak as i32)
+        }
+    }
+
+    /// Determines whether or not this cmpxchg only synchronizes with the current thread.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_single_thread(self) -> bool {
+        unsafe {
+            LLVMIsAtomicSingleThread(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Sets whether or not this cmpxchg only synchronizes with the current thread.
+    #[llvm_versions(3.9..=latest)]
+    pub fn set_single_thread(self, single_thread: bool) {
+        unsafe {
+            LLVMSetAtomicSingleThread(self.as_value_ref(), single_thread as i32)
+        }
+    }
+
+    /// Gets the alignment of this cmpxchg.
+    #[llvm_versions(13.0..=latest)]
+    pub fn get_alignment(self) -> u32 {
+        unsafe {
+            LLVMGetAlignment(self.as_value_ref())
+        }
+    }
+
+    /// Sets the alignment of this cmpxchg.
+    #[llvm_versions(13.0..=latest)]
+    pub fn set_alignment(self, alignment: u32) -> Result<(), &'static str> {
+        self.as_instruction().set_alignment(alignment)
+    }
+
+    /// Gets the ordering used when the exchange succeeds.
+    #[llvm_versions(3.9..=latest)]
+    pub fn get_success_ordering(self) -> AtomicOrdering {
+        unsafe {
+            LLVMGetCmpXchgSuccessOrdering(self.as_value_ref()).into()
+        }
+    }
+
+    /// Gets the ordering used when the exchange fails.
+    #[llvm_versions(3.9..=latest)]
+    pub fn get_failure_ordering(self) -> AtomicOrdering {
+        unsafe {
+            LLVMGetCmpXchgFailureOrdering(self.as_value_ref()).into()
+        }
+    }
+}
+
+impl<'ctx> BranchValue<'ctx> {
+    /// Determines whether or not this branch has a condition.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_conditional(self) -> bool {
+        unsafe {
+            LLVMIsConditional(self.as_value_ref()) == 1
+        }
+    }
+
+    /// Gets the condition of this branch, if it is conditional.
+    #[llvm_versions(3.9..=latest)]
+    pub fn get_condition(self) -> Option<IntValue<'ctx>> {
+        if !self.is_conditional() {
+            return None;
+        }
+
+        unsafe {
+            Some(IntValue::new(LLVMGetCondition(self.as_value_ref())))
+        }
+    }
+
+    /// Gets the blocks this branch may jump to. For a conditional branch the
+    /// block taken when the condition is true comes first.
+    #[llvm_versions(3.9..=latest)]
+    pub fn get_successors(self) -> Vec<BasicBlock<'ctx>> {
+        let num_successors = unsafe {
+            LLVMGetNumSuccessors(self.as_value_ref())
+        };
+
+        (0..num_successors).map(|i| unsafe {
+            BasicBlock::new(LLVMGetSuccessor(self.as_value_ref(), i)).expect("Branch successor should be a BasicBlock")
+        }).collect()
+    }
+}
+
+impl<'ctx> ReturnValue<'ctx> {
+    /// Gets the value being returned, or `None` for `ret void`.
+    pub fn get_return_value(self) -> Option<BasicValueEnum<'ctx>> {
+        let num_operands = unsafe {
+            LLVMGetNumOperands(self.as_value_ref())
+        };
+
+        if num_operands == 0 {
+            return None;
+        }
+
+        Some(get_operand(self, 0))
+    }
+}
@@ -1,9 +1,11 @@
 use either::{Either, Either::{Left, Right}};
-use llvm_sys::core::{LLVMGetAlignment, LLVMSetAlignment, LLVMGetInstructionOpcode, LLVMIsTailCall, LLVMGetPreviousInstruction, LLVMGetNextInstruction, LLVMGetInstructionParent, LLVMInstructionEraseFromParent, LLVMInstructionClone, LLVMSetVolatile, LLVMGetVolatile, LLVMGetNumOperands, LLVMGetOperand, LLVMGetOperandUse, LLVMSetOperand, LLVMValueAsBasicBlock, LLVMIsABasicBlock, LLVMGetICmpPredicate, LLVMGetFCmpPredicate, LLVMIsAAllocaInst, LLVMIsALoadInst, LLVMIsAStoreInst, LLVMGetMetadata, LLVMHasMetadata, LLVMSetMetadata};
+use llvm_sys::core::{LLVMGetAlignment, LLVMSetAlignment, LLVMGetInstructionOpcode, LLVMIsTailCall, LLVMGetPreviousInstruction, LLVMGetNextInstruction, LLVMGetInstructionParent, LLVMInstructionEraseFromParent, LLVMInstructionClone, LLVMSetVolatile, LLVMGetVolatile, LLVMGetNumOperands, LLVMGetOperand, LLVMGetOperandUse, LLVMSetOperand, LLVMValueAsBasicBlock, LLVMIsABasicBlock, LLVMGetICmpPredicate, LLVMGetFCmpPredicate, LLVMIsAAllocaInst, LLVMIsALoadInst, LLVMIsAStoreInst, LLVMGetMetadata, LLVMHasMetadata, LLVMSetMetadata, LLVMIsConstant, LLVMTypeOf, LLVMGetTypeContext, LLVMReplaceAllUsesWith, LLVMCreateBuilderInContext, LLVMPositionBuilderBefore, LLVMBuildICmp, LLVMBuildFCmp, LLVMDisposeBuilder, LLVMConstInt, LLVMInt32TypeInContext, LLVMMDNodeInContext, LLVMGetMDKindIDInContext};
 #[llvm_versions(3.8..=latest)]
 use llvm_sys::core::{LLVMGetOrdering, LLVMSetOrdering};
 #[llvm_versions(3.9..=latest)]
-use llvm_sys::core::LLVMInstructionRemoveFromParent;
+use llvm_sys::core::{LLVMInstructionRemoveFromParent, LLVMIsAtomicSingleThread, LLVMSetAtomicSingleThread};
+#[llvm_versions(8.0..=latest)]
+use llvm_sys::core::LLVMInstructionGetAllMetadataOtherThanDebugLoc;
 #[llvm_versions(10.0..=latest)]
 use llvm_sys::core::{LLVMIsAAtomicRMWInst, LLVMIsAAtomicCmpXchgInst};
 use llvm_sys::LLVMOpcode;
@@ -11,9 +13,14 @@ use llvm_sys::prelude::LLVMValueRef;
 
 use crate::basic_block::BasicBlock;
 use crate::values::traits::AsValueRef;
-use crate::values::{BasicValue, BasicValueEnum, BasicValueUse, Value, MetadataValue};
+use crate::values::{BasicValue, BasicValueEnum, BasicValueUse, BranchValue, CallSiteValue, CmpXchgValue, GEPValue, LoadValue, ReturnValue, StoreValue, UnreachableValue, Value, MetadataValue};
+#[llvm_versions(8.0..=latest)]
+use crate::values::metadata_value::take_metadata_entries;
 use crate::{AtomicOrdering, IntPredicate, FloatPredicate};
 
+use std::mem;
+use std::ptr;
+
 // REVIEW: Split up into structs for SubTypes on InstructionValues?
 // REVIEW: This should maybe be split up into InstructionOpcode and ConstOpcode?
 // see LLVMGetConstOpcode
@@ -101,6 +108,7 @@ pub enum InstructionOpcode {
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Hash)]
+#[repr(transparent)]
 pub struct InstructionValue<'ctx> {
     instruction_value: Value<'ctx>,
 }
@@ -123,6 +131,15 @@ impl<'ctx> InstructionValue<'ctx> {
     fn is_a_cmpxchg_inst(self) -> bool {
         !unsafe { LLVMIsAAtomicCmpXchgInst(self.as_value_ref()) }.is_null()
     }
+    // The C API only accepts atomicrmw and cmpxchg in LLVMGetAlignment and LLVMSetAlignment from LLVM 13.
+    #[llvm_versions(3.6..=12.0)]
+    fn is_a_aligned_atomic_inst(self) -> bool {
+        false
+    }
+    #[llvm_versions(13.0..=latest)]
+    fn is_a_aligned_atomic_inst(self) -> bool {
+        self.is_a_atomicrmw_inst() || self.is_a_cmpxchg_inst()
+    }
 
     pub(crate) unsafe fn new(instruction_value: LLVMValueRef) -> Self {
         debug_assert!(!instruction_value.is_null());
@@ -260,16 +277,18 @@ impl<'ctx> InstructionValue<'ctx> {
     }
 
     // SubTypes: Only apply to memory access and alloca instructions
-    /// Returns alignment on a memory access instruction or alloca.
+    /// Returns alignment on a memory access instruction or alloca. From LLVM 13
+    /// this also includes atomicrmw and cmpxchg instructions.
     pub fn get_alignment(self) -> Result<u32, &'static str> {
-        if !self.is_a_alloca_inst() && !self.is_a_load_inst() && !self.is_a_store_inst() {
-            return Err("Value is not an alloca, load or store.");
+        if !self.is_a_alloca_inst() && !self.is_a_load_inst() && !self.is_a_store_inst() && !self.is_a_aligned_atomic_inst() {
+            return Err("Value is not an alloca, load, store, atomicrmw or cmpxchg.");
         }
         Ok(unsafe { LLVMGetAlignment(self.as_value_ref()) })
     }
 
     // SubTypes: Only apply to memory access and alloca instructions
-    /// Sets alignment on a memory access instruction or alloca.
+    /// Sets alignment on a memory access instruction or alloca. From LLVM 13
+    /// this also includes atomicrmw and cmpxchg instructions.
     pub fn set_alignment(self, alignment: u32) -> Result<(), &'static str> {
         #[cfg(any(feature = "llvm11-0", feature = "llvm12-0"))]
         {
@@ -281,12 +300,77 @@ impl<'ctx> InstructionValue<'ctx> {
         if !alignment.is_power_of_two() && alignment != 0 {
             return Err("Alignment is not a power of 2!");
         }
-        if !self.is_a_alloca_inst() && !self.is_a_load_inst() && !self.is_a_store_inst() {
-            return Err("Value is not an alloca, load or store.");
+        if !self.is_a_alloca_inst() && !self.is_a_load_inst() && !self.is_a_store_inst() && !self.is_a_aligned_atomic_inst() {
+            return Err("Value is not an alloca, load, store, atomicrmw or cmpxchg.");
         }
         Ok(unsafe { LLVMSetAlignment(self.as_value_ref(), alignment) })
     }
 
+    // SubTypes: Only apply to atomic instructions
+    /// Determines whether an atomic instruction is only synchronized with the current
+    /// thread (`syncscope("singlethread")`) rather than the whole system.
+    ///
+    /// Target specific scopes, such as the `"agent"` and `"workgroup"` scopes used by GPU
+    /// targets, cannot be read or set through LLVM's C API, and are reported as not
+    /// being single threaded.
+    #[llvm_versions(3.9..=latest)]
+    pub fn is_atomic_single_thread(self) -> Result<bool, &'static str> {
+        if !self.is_atomic_access() {
+            return Err("Value is not an atomic load, store, atomicrmw, cmpxchg or fence.");
+        }
+        Ok(unsafe { LLVMIsAtomicSingleThread(self.as_value_ref()) == 1 })
+    }
+
+    // SubTypes: Only apply to atomic instructions
+    /// Sets whether an atomic instruction is only synchronized with the current thread
+    /// (`syncscope("singlethread")`), as is needed when synchronizing with signal handlers,
+    /// or with the whole system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::{AddressSpace, AtomicOrdering};
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("atomics");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = context.void_type().fn_type(&[i32_type.ptr_type(AddressSpace::Generic).into()], false);
+    /// let function = module.add_function("signal_safe", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let ptr = function.get_first_param().unwrap().into_pointer_value();
+    /// let zero = i32_type.const_zero();
+    /// let one = i32_type.const_int(1, false);
+    /// let cmpxchg = builder.build_cmpxchg(ptr, zero, one, AtomicOrdering::SequentiallyConsistent, AtomicOrdering::Monotonic).unwrap();
+    /// let instruction = cmpxchg.as_instruction_value().unwrap();
+    ///
+    /// instruction.set_atomic_single_thread(true).unwrap();
+    ///
+    /// assert!(instruction.is_atomic_single_thread().unwrap());
+    /// ```
+    #[llvm_versions(3.9..=latest)]
+    pub fn set_atomic_single_thread(self, single_thread: bool) -> Result<(), &'static str> {
+        if !self.is_atomic_access() {
+            return Err("Value is not an atomic load, store, atomicrmw, cmpxchg or fence.");
+        }
+        Ok(unsafe { LLVMSetAtomicSingleThread(self.as_value_ref(), single_thread as i32) })
+    }
+
+    #[llvm_versions(3.9..=latest)]
+    fn is_atomic_access(self) -> bool {
+        match self.get_opcode() {
+            InstructionOpcode::AtomicRMW | InstructionOpcode::AtomicCmpXchg | InstructionOpcode::Fence => true,
+            InstructionOpcode::Load | InstructionOpcode::Store => unsafe {
+                AtomicOrdering::from(LLVMGetOrdering(self.as_value_ref())) != AtomicOrdering::NotAtomic
+            },
+            _ => false,
+        }
+    }
+
     // SubTypes: Only apply to memory access instructions
     /// Returns atomic ordering on a memory access instruction.
     #[llvm_versions(3.8..=latest)]
@@ -639,10 +723,138 @@ impl<'ctx> InstructionValue<'ctx> {
         }
     }
 
+    /// Replaces this `ICmp` with one using a different predicate on the same operands,
+    /// returning the replacement. All uses of this instruction are updated and it is then
+    /// erased.
+    ///
+    /// LLVM's C API cannot modify a predicate in place, so the instruction is rebuilt and
+    /// any attached metadata is not carried over.
+    ///
+    /// # Safety
+    ///
+    /// On success, this `InstructionValue` and every copy of it are left dangling, so only the
+    /// returned replacement may be used afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::IntPredicate;
+    /// use inkwell::context::Context;
+    /// use inkwell::values::BasicValue;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = context.bool_type().fn_type(&[i32_type.into(), i32_type.into()], false);
+    /// let function = module.add_function("my_fn", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let lhs = function.get_first_param().unwrap().into_int_value();
+    /// let rhs = function.get_nth_param(1).unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let cmp = builder.build_int_compare(IntPredicate::SLT, lhs, rhs, "cmp");
+    ///
+    /// builder.build_return(Some(&cmp));
+    ///
+    /// let cmp = cmp.as_instruction_value().unwrap();
+    /// let cmp = unsafe { cmp.set_icmp_predicate(IntPredicate::SGE) }.unwrap();
+    ///
+    /// assert_eq!(cmp.get_icmp_predicate(), Some(IntPredicate::SGE));
+    /// ```
+    pub unsafe fn set_icmp_predicate(self, predicate: IntPredicate) -> Result<Self, &'static str> {
+        if self.get_opcode() != InstructionOpcode::ICmp {
+            return Err("Value is not an icmp.");
+        }
+
+        self.rebuild_compare(Left(predicate), false)
+    }
+
+    /// Replaces this `FCmp` with one using a different predicate on the same operands,
+    /// returning the replacement. All uses of this instruction are updated and it is then
+    /// erased, so it must not be used afterwards.
+    ///
+    /// LLVM's C API cannot modify a predicate in place, so the instruction is rebuilt and
+    /// any attached metadata or fast-math flags are not carried over.
+    ///
+    /// # Safety
+    ///
+    /// On success, this `InstructionValue` and every copy of it are left dangling, so only the
+    /// returned replacement may be used afterwards.
+    pub unsafe fn set_fcmp_predicate(self, predicate: FloatPredicate) -> Result<Self, &'static str> {
+        if self.get_opcode() != InstructionOpcode::FCmp {
+            return Err("Value is not an fcmp.");
+        }
+
+        self.rebuild_compare(Right(predicate), false)
+    }
+
+    /// Swaps the operands of an `ICmp` or `FCmp` and updates its predicate so that the
+    /// result is unchanged, ie `icmp slt %a, %b` becomes `icmp sgt %b, %a`. As with
+    /// `set_icmp_predicate`, this instruction is replaced and the replacement is returned.
+    ///
+    /// # Safety
+    ///
+    /// On success, this `InstructionValue` and every copy of it are left dangling, so only the
+    /// returned replacement may be used afterwards.
+    pub unsafe fn swap_operands(self) -> Result<Self, &'static str> {
+        let predicate = if let Some(predicate) = self.get_icmp_predicate() {
+            Left(predicate.get_swapped_predicate())
+        } else if let Some(predicate) = self.get_fcmp_predicate() {
+            Right(predicate.get_swapped_predicate())
+        } else {
+            return Err("Value is not an icmp or fcmp.");
+        };
+
+        self.rebuild_compare(predicate, true)
+    }
+
+    fn rebuild_compare(self, predicate: Either<IntPredicate, FloatPredicate>, swap_operands: bool) -> Result<Self, &'static str> {
+        if self.get_parent().is_none() {
+            return Err("Instruction is not in a basic block.");
+        }
+
+        let value = self.as_value_ref();
+        let (mut lhs, mut rhs) = unsafe {
+            (LLVMGetOperand(value, 0), LLVMGetOperand(value, 1))
+        };
+
+        // Comparing two constants would be folded by the builder rather than producing an instruction.
+        if unsafe { LLVMIsConstant(lhs) == 1 && LLVMIsConstant(rhs) == 1 } {
+            return Err("Cannot rebuild a comparison of two constants.");
+        }
+
+        if swap_operands {
+            mem::swap(&mut lhs, &mut rhs);
+        }
+
+        let name = self.instruction_value.get_name().to_owned();
+
+        self.instruction_value.set_name("");
+
+        unsafe {
+            let builder = LLVMCreateBuilderInContext(LLVMGetTypeContext(LLVMTypeOf(value)));
+
+            LLVMPositionBuilderBefore(builder, value);
+
+            let new_value = match predicate {
+                Left(predicate) => LLVMBuildICmp(builder, predicate.into(), lhs, rhs, name.as_ptr()),
+                Right(predicate) => LLVMBuildFCmp(builder, predicate.into(), lhs, rhs, name.as_ptr()),
+            };
+
+            LLVMDisposeBuilder(builder);
+            LLVMReplaceAllUsesWith(value, new_value);
+            LLVMInstructionEraseFromParent(value);
+
+            Ok(InstructionValue::new(new_value))
+        }
+    }
+
     /// Determines whether or not this `Instruction` has any associated metadata.
     pub fn has_metadata(self) -> bool {
         unsafe {
-            LLVMHasMetadata(self.instruction_value.value) == 1
+            LLVMHasMetadata(self.instruction_value.value.as_ptr()) == 1
         }
     }
 
@@ -650,7 +862,7 @@ impl<'ctx> InstructionValue<'ctx> {
     /// `kind_id`.
     pub fn get_metadata(self, kind_id: u32) -> Option<MetadataValue<'ctx>> {
         let metadata_value = unsafe {
-            LLVMGetMetadata(self.instruction_value.value, kind_id)
+            LLVMGetMetadata(self.instruction_value.value.as_ptr(), kind_id)
         };
 
         if metadata_value.is_null() {
@@ -662,19 +874,273 @@ impl<'ctx> InstructionValue<'ctx> {
         }
     }
 
-    /// Determines whether or not this `Instruction` has any associated metadata
-    /// `kind_id`.
+    /// Attaches a metadata node to this `Instruction` at a specific `kind_id`, replacing
+    /// any node previously attached at it. Kind ids for custom metadata can be obtained
+    /// from `Context::get_kind_id`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("annotated");
+    /// let builder = context.create_builder();
+    /// let function = module.add_function("annotated", context.void_type().fn_type(&[], false), None);
+    ///
+    /// builder.position_at_end(context.append_basic_block(function, "entry"));
+    ///
+    /// let ret = builder.build_return(None);
+    /// let kind_id = context.get_kind_id("my_analysis.cost");
+    /// let cost = context.metadata_node(&[context.i32_type().const_int(3, false).into()]);
+    ///
+    /// ret.set_metadata(cost, kind_id).unwrap();
+    ///
+    /// assert_eq!(ret.get_metadata(kind_id), Some(cost));
+    ///
+    /// ret.remove_metadata(kind_id);
+    ///
+    /// assert!(ret.get_metadata(kind_id).is_none());
+    /// ```
     pub fn set_metadata(self, metadata: MetadataValue<'ctx>, kind_id: u32) -> Result<(), &'static str> {
         if !metadata.is_node() {
             return Err("metadata is expected to be a node.")
         }
 
         unsafe {
-            LLVMSetMetadata(self.instruction_value.value, kind_id, metadata.as_value_ref());
+            LLVMSetMetadata(self.instruction_value.value.as_ptr(), kind_id, metadata.as_value_ref());
         }
 
         Ok(())
     }
+
+    /// Removes the metadata node attached to this `Instruction` at a specific `kind_id`, if any.
+    pub fn remove_metadata(self, kind_id: u32) {
+        unsafe {
+            LLVMSetMetadata(self.instruction_value.value.as_ptr(), kind_id, ptr::null_mut());
+        }
+    }
+
+    /// Gets all metadata nodes attached to this `Instruction` along with their kind ids,
+    /// except for its `!dbg` location, which can still be queried with `get_metadata`.
+    #[llvm_versions(8.0..=latest)]
+    pub fn get_all_metadata(self) -> Vec<(u32, MetadataValue<'ctx>)> {
+        let mut count = 0;
+
+        unsafe {
+            let entries = LLVMInstructionGetAllMetadataOtherThanDebugLoc(self.as_value_ref(), &mut count);
+            let context = LLVMGetTypeContext(LLVMTypeOf(self.as_value_ref()));
+
+            take_metadata_entries(context, entries, count)
+        }
+    }
+
+    // SubTypes: Only apply to memory access instructions
+    /// Determines whether or not a load or store carries `!nontemporal` metadata,
+    /// which hints that the accessed memory is unlikely to be reused soon.
+    pub fn is_nontemporal(self) -> Result<bool, &'static str> {
+        if !self.is_a_load_inst() && !self.is_a_store_inst() {
+            return Err("Value is not a load or store.");
+        }
+
+        let kind_id = self.get_nontemporal_kind_id();
+
+        Ok(unsafe { !LLVMGetMetadata(self.as_value_ref(), kind_id).is_null() })
+    }
+
+    // SubTypes: Only apply to memory access instructions
+    /// Adds or removes `!nontemporal` metadata on a load or store, allowing targets
+    /// to emit streaming accesses which bypass the cache.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("kernel");
+    /// let builder = context.create_builder();
+    /// let f64_type = context.f64_type();
+    /// let fn_type = context.void_type().fn_type(&[f64_type.ptr_type(AddressSpace::Generic).into()], false);
+    /// let function = module.add_function("stream", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let ptr = function.get_first_param().unwrap().into_pointer_value();
+    /// let store = builder.build_store(ptr, f64_type.const_float(1.0));
+    ///
+    /// store.set_nontemporal(true).unwrap();
+    ///
+    /// assert!(store.is_nontemporal().unwrap());
+    /// ```
+    pub fn set_nontemporal(self, nontemporal: bool) -> Result<(), &'static str> {
+        if !self.is_a_load_inst() && !self.is_a_store_inst() {
+            return Err("Value is not a load or store.");
+        }
+
+        let kind_id = self.get_nontemporal_kind_id();
+
+        unsafe {
+            let node = if nontemporal {
+                let context = LLVMGetTypeContext(LLVMTypeOf(self.as_value_ref()));
+                let mut one = LLVMConstInt(LLVMInt32TypeInContext(context), 1, 0);
+
+                LLVMMDNodeInContext(context, &mut one, 1)
+            } else {
+                ptr::null_mut()
+            };
+
+            LLVMSetMetadata(self.as_value_ref(), kind_id, node);
+        }
+
+        Ok(())
+    }
+
+    fn get_nontemporal_kind_id(self) -> u32 {
+        let name = "nontemporal";
+
+        unsafe {
+            let context = LLVMGetTypeContext(LLVMTypeOf(self.as_value_ref()));
+
+            LLVMGetMDKindIDInContext(context, name.as_ptr() as *const ::libc::c_char, name.len() as u32)
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `CallSiteValue`,
+    /// which succeeds for `call` and `invoke` instructions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let builder = context.create_builder();
+    /// let fn_type = context.void_type().fn_type(&[], false);
+    /// let function = module.add_function("my_fn", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let call = builder.build_call(function, &[], "call");
+    /// let ret = builder.build_return(None);
+    /// let instruction = call.try_as_basic_value().right().unwrap();
+    ///
+    /// assert_eq!(instruction.try_into_call_site_value().unwrap(), call);
+    /// assert!(ret.try_into_call_site_value().is_none());
+    /// ```
+    pub fn try_into_call_site_value(self) -> Option<CallSiteValue<'ctx>> {
+        match self.get_opcode() {
+            InstructionOpcode::Call | InstructionOpcode::Invoke => unsafe {
+                Some(CallSiteValue::new(self.as_value_ref()))
+            },
+            _ => None,
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `LoadValue`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::AddressSpace;
+    /// use inkwell::context::Context;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("my_mod");
+    /// let builder = context.create_builder();
+    /// let i32_ptr_type = context.i32_type().ptr_type(AddressSpace::Generic);
+    /// let fn_type = context.void_type().fn_type(&[i32_ptr_type.into()], false);
+    /// let function = module.add_function("my_fn", fn_type, None);
+    /// let entry = context.append_basic_block(function, "entry");
+    /// let ptr = function.get_first_param().unwrap().into_pointer_value();
+    ///
+    /// builder.position_at_end(entry);
+    ///
+    /// let value = builder.build_load(ptr, "value");
+    /// let load = value.as_instruction_value().unwrap().try_into_load_value().unwrap();
+    ///
+    /// assert_eq!(load.get_pointer_operand(), ptr);
+    /// assert!(!load.is_volatile());
+    /// ```
+    pub fn try_into_load_value(self) -> Option<LoadValue<'ctx>> {
+        if !self.is_a_load_inst() {
+            return None;
+        }
+
+        unsafe {
+            Some(LoadValue::new(self.as_value_ref()))
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `StoreValue`.
+    pub fn try_into_store_value(self) -> Option<StoreValue<'ctx>> {
+        if !self.is_a_store_inst() {
+            return None;
+        }
+
+        unsafe {
+            Some(StoreValue::new(self.as_value_ref()))
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `GEPValue`.
+    pub fn try_into_gep_value(self) -> Option<GEPValue<'ctx>> {
+        if self.get_opcode() != InstructionOpcode::GetElementPtr {
+            return None;
+        }
+
+        unsafe {
+            Some(GEPValue::new(self.as_value_ref()))
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `CmpXchgValue`.
+    pub fn try_into_cmpxchg_value(self) -> Option<CmpXchgValue<'ctx>> {
+        if self.get_opcode() != InstructionOpcode::AtomicCmpXchg {
+            return None;
+        }
+
+        unsafe {
+            Some(CmpXchgValue::new(self.as_value_ref()))
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `BranchValue`.
+    pub fn try_into_branch_value(self) -> Option<BranchValue<'ctx>> {
+        if self.get_opcode() != InstructionOpcode::Br {
+            return None;
+        }
+
+        unsafe {
+            Some(BranchValue::new(self.as_value_ref()))
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into a `ReturnValue`.
+    pub fn try_into_return_value(self) -> Option<ReturnValue<'ctx>> {
+        if self.get_opcode() != InstructionOpcode::Return {
+            return None;
+        }
+
+        unsafe {
+            Some(ReturnValue::new(self.as_value_ref()))
+        }
+    }
+
+    /// Attempts to convert this `InstructionValue` into an `UnreachableValue`.
+    pub fn try_into_unreachable_value(self) -> Option<UnreachableValue<'ctx>> {
+        if self.get_opcode() != InstructionOpcode::Unreachable {
+            return None;
+        }
+
+        unsafe {
+            Some(UnreachableValue::new(self.as_value_ref()))
+        }
+    }
 }
 
 impl Clone for InstructionValue<'_> {
@@ -689,6 +1155,6 @@ impl Clone for InstructionValue<'_> {
 
 impl AsValueRef for InstructionValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.instruction_value.value
+        self.instruction_value.value.as_ptr()
     }
 }
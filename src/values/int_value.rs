@@ -11,6 +11,7 @@ use crate::values::traits::AsValueRef;
 use crate::values::{BasicValue, BasicValueEnum, FloatValue, InstructionValue, PointerValue, Value};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct IntValue<'ctx> {
     int_value: Value<'ctx>,
 }
@@ -392,6 +393,6 @@ impl<'ctx> IntValue<'ctx> {
 
 impl AsValueRef for IntValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.int_value.value
+        self.int_value.value.as_ptr()
     }
 }
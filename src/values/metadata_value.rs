@@ -5,6 +5,12 @@ use llvm_sys::prelude::LLVMValueRef;
 use llvm_sys::prelude::LLVMMetadataRef;
 #[llvm_versions(7.0..=latest)]
 use llvm_sys::core::LLVMValueAsMetadata;
+#[llvm_versions(8.0..=latest)]
+use llvm_sys::core::{LLVMDisposeValueMetadataEntries, LLVMMetadataAsValue, LLVMValueMetadataEntriesGetKind, LLVMValueMetadataEntriesGetMetadata};
+#[llvm_versions(8.0..=latest)]
+use llvm_sys::prelude::LLVMContextRef;
+#[llvm_versions(8.0..=latest)]
+use llvm_sys::LLVMValueMetadataEntry;
 
 use crate::support::LLVMString;
 use crate::values::traits::AsValueRef;
@@ -42,6 +48,7 @@ pub const FIRST_CUSTOM_METADATA_KIND_ID: u32 = 31;
 pub const FIRST_CUSTOM_METADATA_KIND_ID: u32 = 31;
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct MetadataValue<'ctx> {
     metadata_value: Value<'ctx>,
 }
@@ -136,9 +143,35 @@ impl<'ctx> MetadataValue<'ctx> {
     }
 }
 
+// Converts and disposes of the metadata entries returned by LLVMGlobalCopyAllMetadata
+// and LLVMInstructionGetAllMetadataOtherThanDebugLoc.
+#[llvm_versions(8.0..=latest)]
+pub(crate) unsafe fn take_metadata_entries<'ctx>(
+    context: LLVMContextRef,
+    entries: *mut LLVMValueMetadataEntry,
+    count: usize,
+) -> Vec<(u32, MetadataValue<'ctx>)> {
+    if entries.is_null() {
+        return Vec::new();
+    }
+
+    let metadata = (0..count as u32)
+        .map(|index| {
+            let kind_id = LLVMValueMetadataEntriesGetKind(entries, index);
+            let node = LLVMMetadataAsValue(context, LLVMValueMetadataEntriesGetMetadata(entries, index));
+
+            (kind_id, MetadataValue::new(node))
+        })
+        .collect();
+
+    LLVMDisposeValueMetadataEntries(entries);
+
+    metadata
+}
+
 impl AsValueRef for MetadataValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.metadata_value.value
+        self.metadata_value.value.as_ptr()
     }
 }
 
@@ -12,10 +12,14 @@ mod fn_value;
 mod generic_value;
 mod global_value;
 mod instruction_value;
+#[deny(missing_docs)]
+mod instruction_subtypes;
 mod int_value;
 mod metadata_value;
 mod phi_value;
 mod ptr_value;
+#[deny(missing_docs)]
+pub mod raw;
 mod struct_value;
 mod traits;
 mod vec_value;
@@ -33,6 +37,7 @@ pub use crate::values::global_value::GlobalValue;
 #[llvm_versions(7.0..=latest)]
 pub use crate::values::global_value::UnnamedAddress;
 pub use crate::values::instruction_value::{InstructionValue, InstructionOpcode};
+pub use crate::values::instruction_subtypes::{BranchValue, CmpXchgValue, GEPValue, LoadValue, ReturnValue, StoreValue, UnreachableValue};
 pub use crate::values::int_value::IntValue;
 pub use crate::values::metadata_value::{MetadataValue, FIRST_CUSTOM_METADATA_KIND_ID};
 pub use crate::values::phi_value::PhiValue;
@@ -43,16 +48,25 @@ pub use crate::values::traits::{AnyValue, AggregateValue, BasicValue, IntMathVal
 pub use crate::values::vec_value::VectorValue;
 pub(crate) use crate::values::traits::AsValueRef;
 
-use llvm_sys::core::{LLVMIsConstant, LLVMIsNull, LLVMIsUndef, LLVMPrintTypeToString, LLVMPrintValueToString, LLVMTypeOf, LLVMDumpValue, LLVMIsAInstruction, LLVMReplaceAllUsesWith, LLVMGetFirstUse};
+use llvm_sys::core::{LLVMIsConstant, LLVMIsNull, LLVMIsUndef, LLVMPrintTypeToString, LLVMPrintValueToString, LLVMTypeOf, LLVMDumpValue, LLVMIsAInstruction, LLVMReplaceAllUsesWith, LLVMGetFirstUse, LLVMIsAArgument, LLVMGetParamParent, LLVMGetFirstParam, LLVMGetNextParam, LLVMGetInstructionParent, LLVMGetBasicBlockParent, LLVMGetFirstBasicBlock, LLVMGetNextBasicBlock, LLVMGetFirstInstruction, LLVMGetNextInstruction, LLVMBasicBlockAsValue, LLVMGetTypeKind};
+use llvm_sys::LLVMTypeKind;
+use llvm_sys::LLVMValue;
 use llvm_sys::prelude::{LLVMValueRef, LLVMTypeRef};
 
 use std::ffi::CStr;
 use std::fmt;
 use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+// Renames any value, including instructions and basic blocks which have no typed setter
+pub(crate) unsafe fn set_value_name(value: LLVMValueRef, name: &str) {
+    Value::new(value).set_name(name)
+}
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 struct Value<'ctx> {
-    value: LLVMValueRef,
+    value: NonNull<LLVMValue>,
     _marker: PhantomData<&'ctx ()>,
 }
 
@@ -61,14 +75,14 @@ impl<'ctx> Value<'ctx> {
         debug_assert!(!value.is_null(), "This should never happen since containing struct should check null ptrs");
 
         Value {
-            value,
+            value: NonNull::new_unchecked(value),
             _marker: PhantomData,
         }
     }
 
     fn is_instruction(self) -> bool {
         unsafe {
-            !LLVMIsAInstruction(self.value).is_null()
+            !LLVMIsAInstruction(self.value.as_ptr()).is_null()
         }
     }
 
@@ -78,19 +92,19 @@ impl<'ctx> Value<'ctx> {
         }
 
         unsafe {
-            Some(InstructionValue::new(self.value))
+            Some(InstructionValue::new(self.value.as_ptr()))
         }
     }
 
     fn is_null(self) -> bool {
         unsafe {
-            LLVMIsNull(self.value) == 1
+            LLVMIsNull(self.value.as_ptr()) == 1
         }
     }
 
     fn is_const(self) -> bool {
         unsafe {
-            LLVMIsConstant(self.value) == 1
+            LLVMIsConstant(self.value.as_ptr()) == 1
         }
     }
 
@@ -110,7 +124,7 @@ impl<'ctx> Value<'ctx> {
             let c_string = to_c_str(name);
 
             unsafe {
-                LLVMSetValueName(self.value, c_string.as_ptr());
+                LLVMSetValueName(self.value.as_ptr(), c_string.as_ptr());
             }
         }
         #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
@@ -119,7 +133,7 @@ impl<'ctx> Value<'ctx> {
             use llvm_sys::core::LLVMSetValueName2;
 
             unsafe {
-                LLVMSetValueName2(self.value, name.as_ptr() as *const ::libc::c_char, name.len())
+                LLVMSetValueName2(self.value.as_ptr(), name.as_ptr() as *const ::libc::c_char, name.len())
             }
         }
     }
@@ -132,7 +146,7 @@ impl<'ctx> Value<'ctx> {
         let ptr = unsafe {
             use llvm_sys::core::LLVMGetValueName;
 
-            LLVMGetValueName(self.value)
+            LLVMGetValueName(self.value.as_ptr())
         };
         #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
                       feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0")))]
@@ -140,7 +154,7 @@ impl<'ctx> Value<'ctx> {
             use llvm_sys::core::LLVMGetValueName2;
             let mut len = 0;
 
-            LLVMGetValueName2(self.value, &mut len)
+            LLVMGetValueName2(self.value.as_ptr(), &mut len)
         };
 
         unsafe {
@@ -150,25 +164,61 @@ impl<'ctx> Value<'ctx> {
 
     fn is_undef(self) -> bool {
         unsafe {
-            LLVMIsUndef(self.value) == 1
+            LLVMIsUndef(self.value.as_ptr()) == 1
         }
     }
 
     fn get_type(self) -> LLVMTypeRef {
         unsafe {
-            LLVMTypeOf(self.value)
+            LLVMTypeOf(self.value.as_ptr())
         }
     }
 
     fn print_to_string(self) -> LLVMString {
         unsafe {
-            LLVMString::new(LLVMPrintValueToString(self.value))
+            LLVMString::new(LLVMPrintValueToString(self.value.as_ptr()))
+        }
+    }
+
+    // Arguments and instructions producing a value are printed as `ty %name`, or `ty %N` when
+    // unnamed, with N computed the same way LLVM's SlotTracker assigns it: unnamed arguments
+    // are numbered first, in order, followed by the unnamed blocks and value-producing
+    // instructions of the body in order. Other values have no local name to refer to them by.
+    fn print_to_string_in_function_context(self) -> LLVMString {
+        unsafe {
+            let function = if !LLVMIsAArgument(self.value.as_ptr()).is_null() {
+                LLVMGetParamParent(self.value.as_ptr())
+            } else if self.is_instruction() && LLVMGetTypeKind(self.get_type()) != LLVMTypeKind::LLVMVoidTypeKind {
+                let block = LLVMGetInstructionParent(self.value.as_ptr());
+
+                if block.is_null() || LLVMGetBasicBlockParent(block).is_null() {
+                    return self.print_to_string();
+                }
+
+                LLVMGetBasicBlockParent(block)
+            } else {
+                return self.print_to_string();
+            };
+
+            let type_string = LLVMString::new(LLVMPrintTypeToString(self.get_type()));
+            let name = self.get_name().to_bytes();
+
+            if !name.is_empty() {
+                return LLVMString::create_from_str(&format!("{} %{}", type_string.to_string_lossy(), print_local_name(name)));
+            }
+
+            let slot = match local_slot(function, self.value.as_ptr()) {
+                Some(slot) => slot,
+                None => return self.print_to_string(),
+            };
+
+            LLVMString::create_from_str(&format!("{} %{}", type_string.to_string_lossy(), slot))
         }
     }
 
     fn print_to_stderr(self) {
         unsafe {
-            LLVMDumpValue(self.value)
+            LLVMDumpValue(self.value.as_ptr())
         }
     }
 
@@ -176,16 +226,16 @@ impl<'ctx> Value<'ctx> {
     // if used incorrectly, which is OK.
     fn replace_all_uses_with(self, other: LLVMValueRef) {
         // LLVM may infinite-loop when they aren't distinct, which is UB in C++.
-        if self.value != other {
+        if self.value.as_ptr() != other {
             unsafe {
-                LLVMReplaceAllUsesWith(self.value, other)
+                LLVMReplaceAllUsesWith(self.value.as_ptr(), other)
             }
         }
     }
 
     pub fn get_first_use(self) -> Option<BasicValueUse<'ctx>> {
         let use_ = unsafe {
-            LLVMGetFirstUse(self.value)
+            LLVMGetFirstUse(self.value.as_ptr())
         };
 
         if use_.is_null() {
@@ -198,11 +248,80 @@ impl<'ctx> Value<'ctx> {
     }
 }
 
+// Finds the slot of the unnamed argument or instruction `value` in `function`.
+unsafe fn local_slot(function: LLVMValueRef, value: LLVMValueRef) -> Option<u32> {
+    let is_unnamed = |value| Value::new(value).get_name().to_bytes().is_empty();
+    let mut slot = 0;
+    let mut param = LLVMGetFirstParam(function);
+
+    while !param.is_null() {
+        if param == value {
+            return Some(slot);
+        }
+
+        if is_unnamed(param) {
+            slot += 1;
+        }
+
+        param = LLVMGetNextParam(param);
+    }
+
+    let mut block = LLVMGetFirstBasicBlock(function);
+
+    while !block.is_null() {
+        if is_unnamed(LLVMBasicBlockAsValue(block)) {
+            slot += 1;
+        }
+
+        let mut instruction = LLVMGetFirstInstruction(block);
+
+        while !instruction.is_null() {
+            if instruction == value {
+                return Some(slot);
+            }
+
+            if is_unnamed(instruction) && LLVMGetTypeKind(LLVMTypeOf(instruction)) != LLVMTypeKind::LLVMVoidTypeKind {
+                slot += 1;
+            }
+
+            instruction = LLVMGetNextInstruction(instruction);
+        }
+
+        block = LLVMGetNextBasicBlock(block);
+    }
+
+    None
+}
+
+// Prints a local name the way LLVM's assembly writer does, quoting and escaping it when it
+// contains characters other than letters, digits, `-`, `.` and `_`, or starts with a digit.
+fn print_local_name(name: &[u8]) -> String {
+    let needs_quotes = name[0].is_ascii_digit() ||
+        name.iter().any(|&c| !c.is_ascii_alphanumeric() && c != b'-' && c != b'.' && c != b'_');
+
+    if !needs_quotes {
+        return String::from_utf8_lossy(name).into_owned();
+    }
+
+    let mut quoted = String::from("\"");
+
+    for &c in name {
+        if c == b'\\' || c == b'"' || !(c.is_ascii_graphic() || c == b' ') {
+            quoted.push_str(&format!("\\{:02X}", c));
+        } else {
+            quoted.push(c as char);
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
 impl fmt::Debug for Value<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let llvm_value = self.print_to_string();
         let llvm_type = unsafe {
-            CStr::from_ptr(LLVMPrintTypeToString(LLVMTypeOf(self.value)))
+            CStr::from_ptr(LLVMPrintTypeToString(LLVMTypeOf(self.value.as_ptr())))
         };
         let name = self.get_name();
         let is_const = self.is_const();
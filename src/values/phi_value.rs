@@ -11,6 +11,7 @@ use crate::values::{BasicValue, BasicValueEnum, InstructionValue, Value};
 /// A Phi Instruction returns a value based on which basic block branched into
 /// the Phi's containing basic block.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct PhiValue<'ctx> {
     phi_value: Value<'ctx>,
 }
@@ -94,6 +95,6 @@ impl<'ctx> PhiValue<'ctx> {
 
 impl AsValueRef for PhiValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.phi_value.value
+        self.phi_value.value.as_ptr()
     }
 }
@@ -7,6 +7,7 @@ use crate::types::{AsTypeRef, IntType, PointerType};
 use crate::values::{AsValueRef, InstructionValue, IntValue, Value};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct PointerValue<'ctx> {
     ptr_value: Value<'ctx>,
 }
@@ -113,6 +114,6 @@ impl<'ctx> PointerValue<'ctx> {
 
 impl AsValueRef for PointerValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.ptr_value.value
+        self.ptr_value.value.as_ptr()
     }
 }
@@ -0,0 +1,92 @@
+//! Zero-cost conversions between slices of raw `LLVMValueRef`s and slices of value wrappers.
+//!
+//! Every value wrapper listed under `TransparentValue` is `#[repr(transparent)]` over a
+//! non null pointer to the value it wraps, so a slice of one can be reinterpreted as a slice
+//! of `LLVMValueRef`s without iterating over or copying it. As the pointer is never null, an
+//! `Option` of a wrapper is no larger than the wrapper itself. This is meant for generators which already hold
+//! large arrays of raw refs obtained from `llvm-sys`, or which need to hand arrays of values
+//! to a C API function inkwell does not wrap.
+
+use llvm_sys::prelude::LLVMValueRef;
+
+use crate::values::{ArrayValue, AsValueRef, BranchValue, CallSiteValue, CmpXchgValue, FloatValue, FunctionValue, GEPValue, GlobalValue, InstructionValue, IntValue, LoadValue, MetadataValue, PhiValue, PointerValue, ReturnValue, StoreValue, StructValue, UnreachableValue, VectorValue};
+
+use std::slice;
+
+/// A value wrapper with exactly the same layout as an `LLVMValueRef`.
+///
+/// This is sealed, so it cannot be implemented outside of inkwell.
+pub unsafe trait TransparentValue: AsValueRef + Copy + private::SealedTransparentValue {}
+
+mod private {
+    /// A sealed trait which ensures nobody outside this crate can implement
+    /// `TransparentValue`.
+    ///
+    /// See https://rust-lang-nursery.github.io/api-guidelines/future-proofing.html
+    pub trait SealedTransparentValue {}
+}
+
+macro_rules! transparent_values {
+    ($($value_type:ident),*) => (
+        $(
+            impl private::SealedTransparentValue for $value_type<'_> {}
+
+            unsafe impl TransparentValue for $value_type<'_> {}
+        )*
+    );
+}
+
+transparent_values! {
+    ArrayValue, BranchValue, CallSiteValue, CmpXchgValue, FloatValue, FunctionValue, GEPValue, GlobalValue, InstructionValue, IntValue,
+    LoadValue, MetadataValue, PhiValue, PointerValue, ReturnValue, StoreValue, StructValue, UnreachableValue, VectorValue
+}
+
+/// Views a slice of values as the raw `LLVMValueRef`s they wrap.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::values::raw::as_value_refs;
+///
+/// let context = Context::create();
+/// let i32_type = context.i32_type();
+/// let values = [i32_type.const_int(1, false), i32_type.const_int(2, false)];
+/// let refs = as_value_refs(&values);
+///
+/// assert_eq!(refs.len(), 2);
+/// ```
+pub fn as_value_refs<V: TransparentValue>(values: &[V]) -> &[LLVMValueRef] {
+    unsafe {
+        slice::from_raw_parts(values.as_ptr() as *const LLVMValueRef, values.len())
+    }
+}
+
+/// Views a slice of raw `LLVMValueRef`s as values of type `V`.
+///
+/// # Safety
+///
+/// Every ref must be non null, must be of the kind of value `V` represents, such as an
+/// integer for `IntValue`, and must belong to a `Context` living at least as long as `'ctx`
+/// in `V`.
+///
+/// # Example
+///
+/// ```
+/// use inkwell::context::Context;
+/// use inkwell::values::IntValue;
+/// use inkwell::values::raw::{as_value_refs, from_value_refs};
+///
+/// let context = Context::create();
+/// let i32_type = context.i32_type();
+/// let values = [i32_type.const_int(1, false), i32_type.const_int(2, false)];
+/// let refs = as_value_refs(&values).to_vec();
+/// let round_trip: &[IntValue] = unsafe { from_value_refs(&refs) };
+///
+/// assert_eq!(round_trip, &values);
+/// ```
+pub unsafe fn from_value_refs<V: TransparentValue>(refs: &[LLVMValueRef]) -> &[V] {
+    debug_assert!(refs.iter().all(|value| !value.is_null()));
+
+    slice::from_raw_parts(refs.as_ptr() as *const V, refs.len())
+}
@@ -7,6 +7,7 @@ use crate::values::traits::AsValueRef;
 use crate::values::{InstructionValue, Value};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct StructValue<'ctx> {
     struct_value: Value<'ctx>,
 }
@@ -55,6 +56,6 @@ impl<'ctx> StructValue<'ctx> {
 
 impl AsValueRef for StructValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.struct_value.value
+        self.struct_value.value.as_ptr()
     }
 }
@@ -3,7 +3,7 @@ use llvm_sys::core::{LLVMConstExtractValue, LLVMConstInsertValue};
 
 use std::fmt::Debug;
 
-use crate::values::{ArrayValue, AggregateValueEnum, BasicValueUse, CallSiteValue, GlobalValue, StructValue, BasicValueEnum, AnyValueEnum, IntValue, FloatValue, PointerValue, PhiValue, VectorValue, FunctionValue, InstructionValue, Value};
+use crate::values::{ArrayValue, AggregateValueEnum, BasicValueUse, CallSiteValue, GlobalValue, StructValue, BasicValueEnum, AnyValueEnum, IntValue, FloatValue, PointerValue, PhiValue, VectorValue, FunctionValue, InstructionValue, Value, LoadValue, StoreValue, GEPValue, CmpXchgValue, BranchValue, ReturnValue, UnreachableValue};
 use crate::types::{IntMathType, FloatMathType, PointerMathType, IntType, FloatType, PointerType, VectorType};
 use crate::support::LLVMString;
 
@@ -139,10 +139,45 @@ pub trait AnyValue<'ctx>: AsValueRef + Debug {
             Value::new(self.as_value_ref()).print_to_string()
         }
     }
+
+    /// Prints a value to a `LLVMString` the way it is referred to as an operand inside its
+    /// function. Arguments and instructions producing a value are printed as `ty %name`, or as
+    /// `ty %N` when unnamed, using the same slot numbers as `Module::print_to_string`. This
+    /// makes it possible to point at values in diagnostics the way the module's IR shows them.
+    ///
+    /// Any other value, such as a constant, a global or an instruction producing no value like
+    /// `store`, is printed the same as by `print_to_string`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use inkwell::context::Context;
+    /// use inkwell::values::AnyValue;
+    ///
+    /// let context = Context::create();
+    /// let module = context.create_module("slots");
+    /// let builder = context.create_builder();
+    /// let i32_type = context.i32_type();
+    /// let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    /// let function = module.add_function("add", fn_type, None);
+    /// let param = function.get_nth_param(1).unwrap().into_int_value();
+    ///
+    /// builder.position_at_end(context.append_basic_block(function, "entry"));
+    ///
+    /// let sum = builder.build_int_add(param, param, "");
+    ///
+    /// assert_eq!(param.print_to_string_in_function_context().to_string(), "i32 %1");
+    /// assert_eq!(sum.print_to_string_in_function_context().to_string(), "i32 %2");
+    /// ```
+    fn print_to_string_in_function_context(&self) -> LLVMString {
+        unsafe {
+            Value::new(self.as_value_ref()).print_to_string_in_function_context()
+        }
+    }
 }
 
 trait_value_set! {AggregateValue: ArrayValue, AggregateValueEnum, StructValue}
-trait_value_set! {AnyValue: AnyValueEnum, BasicValueEnum, AggregateValueEnum, ArrayValue, IntValue, FloatValue, GlobalValue, PhiValue, PointerValue, FunctionValue, StructValue, VectorValue, InstructionValue, CallSiteValue}
+trait_value_set! {AnyValue: AnyValueEnum, BasicValueEnum, AggregateValueEnum, ArrayValue, IntValue, FloatValue, GlobalValue, PhiValue, PointerValue, FunctionValue, StructValue, VectorValue, InstructionValue, CallSiteValue, LoadValue, StoreValue, GEPValue, CmpXchgValue, BranchValue, ReturnValue, UnreachableValue}
 trait_value_set! {BasicValue: ArrayValue, BasicValueEnum, AggregateValueEnum, IntValue, FloatValue, GlobalValue, StructValue, PointerValue, VectorValue}
 math_trait_value_set! {IntMathValue: (IntValue => IntType), (VectorValue => VectorType)}
 math_trait_value_set! {FloatMathValue: (FloatValue => FloatType), (VectorValue => VectorType)}
@@ -8,6 +8,7 @@ use crate::values::traits::AsValueRef;
 use crate::values::{BasicValueEnum, BasicValue, InstructionValue, Value, IntValue};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[repr(transparent)]
 pub struct VectorValue<'ctx> {
     vec_value: Value<'ctx>,
 }
@@ -158,6 +159,6 @@ impl<'ctx> VectorValue<'ctx> {
 
 impl AsValueRef for VectorValue<'_> {
     fn as_value_ref(&self) -> LLVMValueRef {
-        self.vec_value.value
+        self.vec_value.value.as_ptr()
     }
 }
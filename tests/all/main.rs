@@ -4,11 +4,24 @@ extern crate either;
 #[macro_use]
 extern crate inkwell_internals;
 
+#[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8")))]
+mod test_analysis;
 #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8")))]
 mod test_attributes;
 mod test_basic_block;
 mod test_builder;
 mod test_context;
+#[cfg(not(any(
+    feature = "llvm3-6",
+    feature = "llvm3-7",
+    feature = "llvm3-8",
+    feature = "llvm3-9",
+    feature = "llvm4-0",
+    feature = "llvm5-0",
+    feature = "llvm6-0",
+    feature = "llvm7-0"
+)))]
+mod test_coroutines;
 #[cfg(not(any(
     feature = "llvm3-6",
     feature = "llvm3-7",
@@ -0,0 +1,142 @@
+use inkwell::analysis::{ControlFlowGraph, DominatorTree, LoopInfo};
+use inkwell::context::Context;
+
+#[test]
+fn test_dominators_and_loops() {
+    let context = Context::create();
+    let module = context.create_module("analysis");
+    let builder = context.create_builder();
+    let bool_type = context.bool_type();
+    let fn_type = context.void_type().fn_type(&[bool_type.into(), bool_type.into()], false);
+    let function = module.add_function("nested_loops", fn_type, None);
+    let outer_cond = function.get_nth_param(0).unwrap().into_int_value();
+    let inner_cond = function.get_nth_param(1).unwrap().into_int_value();
+
+    let entry = context.append_basic_block(function, "entry");
+    let outer_header = context.append_basic_block(function, "outer_header");
+    let inner_header = context.append_basic_block(function, "inner_header");
+    let outer_latch = context.append_basic_block(function, "outer_latch");
+    let exit = context.append_basic_block(function, "exit");
+    let dead = context.append_basic_block(function, "dead");
+
+    builder.position_at_end(entry);
+    builder.build_unconditional_branch(outer_header);
+    builder.position_at_end(outer_header);
+    builder.build_conditional_branch(outer_cond, inner_header, exit);
+    builder.position_at_end(inner_header);
+    builder.build_conditional_branch(inner_cond, inner_header, outer_latch);
+    builder.position_at_end(outer_latch);
+    builder.build_unconditional_branch(outer_header);
+    builder.position_at_end(exit);
+    builder.build_return(None);
+    builder.position_at_end(dead);
+    builder.build_unconditional_branch(outer_header);
+
+    let cfg = ControlFlowGraph::new(function);
+
+    assert_eq!(cfg.get_blocks().len(), 6);
+    assert_eq!(cfg.get_successors(outer_header), vec![inner_header, exit]);
+    assert_eq!(cfg.get_predecessors(outer_header), vec![entry, outer_latch, dead]);
+    assert!(cfg.get_successors(exit).is_empty());
+
+    let dom_tree = DominatorTree::from_cfg(cfg);
+
+    assert!(dom_tree.is_reachable(exit));
+    assert!(!dom_tree.is_reachable(dead));
+    assert_eq!(dom_tree.get_immediate_dominator(entry), None);
+    assert_eq!(dom_tree.get_immediate_dominator(outer_header), Some(entry));
+    assert_eq!(dom_tree.get_immediate_dominator(outer_latch), Some(inner_header));
+    assert_eq!(dom_tree.get_immediate_dominator(dead), None);
+    let children = dom_tree.get_children(outer_header);
+
+    assert_eq!(children.len(), 2);
+    assert!(children.contains(&inner_header) && children.contains(&exit));
+    assert!(dom_tree.dominates(entry, outer_latch));
+    assert!(dom_tree.dominates(outer_header, outer_header));
+    assert!(!dom_tree.properly_dominates(outer_header, outer_header));
+    assert!(dom_tree.properly_dominates(inner_header, outer_latch));
+    assert!(!dom_tree.dominates(inner_header, exit));
+    assert!(!dom_tree.dominates(dead, outer_header));
+    assert!(dom_tree.dominates(exit, dead));
+    assert_eq!(dom_tree.get_reverse_post_order()[0], entry);
+    assert_eq!(dom_tree.get_reverse_post_order().len(), 5);
+
+    let loop_info = LoopInfo::new(&dom_tree);
+    let loops = loop_info.get_loops();
+
+    assert_eq!(loops.len(), 2);
+    assert_eq!(loops[0].get_header(), outer_header);
+    assert_eq!(loops[0].get_blocks(), &[outer_header, inner_header, outer_latch]);
+    assert_eq!(loops[0].get_latches(), &[outer_latch]);
+    assert_eq!(loops[0].get_depth(), 1);
+    assert!(loop_info.get_parent_loop(&loops[0]).is_none());
+    assert_eq!(loops[1].get_header(), inner_header);
+    assert_eq!(loops[1].get_blocks(), &[inner_header]);
+    assert_eq!(loops[1].get_depth(), 2);
+    assert_eq!(loop_info.get_parent_loop(&loops[1]).unwrap().get_header(), outer_header);
+    assert_eq!(loop_info.get_loop_for(outer_latch).unwrap().get_header(), outer_header);
+    assert_eq!(loop_info.get_loop_depth(inner_header), 2);
+    assert_eq!(loop_info.get_loop_depth(exit), 0);
+    assert_eq!(loop_info.get_loop_depth(dead), 0);
+    assert!(loop_info.is_loop_header(inner_header));
+    assert!(!loop_info.is_loop_header(outer_latch));
+}
+
+#[test]
+fn test_post_dominators_and_instruction_dominance() {
+    use inkwell::analysis::PostDominatorTree;
+
+    let context = Context::create();
+    let module = context.create_module("analysis");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+    let function = module.add_function("diamond", fn_type, None);
+    let param = function.get_first_param().unwrap().into_int_value();
+
+    let entry = context.append_basic_block(function, "entry");
+    let then_block = context.append_basic_block(function, "then");
+    let else_block = context.append_basic_block(function, "else");
+    let merge = context.append_basic_block(function, "merge");
+    let spin = context.append_basic_block(function, "spin");
+
+    builder.position_at_end(entry);
+
+    let double = builder.build_int_add(param, param, "double").as_instruction().unwrap();
+    let cond = builder.build_int_compare(inkwell::IntPredicate::EQ, param, i32_type.const_zero(), "cond");
+
+    builder.build_conditional_branch(cond, then_block, else_block);
+    builder.position_at_end(then_block);
+    builder.build_unconditional_branch(merge);
+    builder.position_at_end(else_block);
+
+    let to_merge = builder.build_unconditional_branch(merge);
+
+    builder.position_at_end(merge);
+
+    let ret = builder.build_return(Some(&param));
+
+    builder.position_at_end(spin);
+    builder.build_unconditional_branch(spin);
+
+    let dom_tree = DominatorTree::new(function);
+
+    assert!(dom_tree.dominates_instruction(double, ret));
+    assert!(dom_tree.dominates_instruction(double, cond.as_instruction().unwrap()));
+    assert!(!dom_tree.dominates_instruction(cond.as_instruction().unwrap(), double));
+    assert!(!dom_tree.dominates_instruction(double, double));
+    assert!(!dom_tree.dominates_instruction(to_merge, ret));
+
+    let post_dom_tree = PostDominatorTree::new(function);
+
+    assert!(post_dom_tree.post_dominates(merge, entry));
+    assert!(post_dom_tree.properly_post_dominates(merge, then_block));
+    assert!(!post_dom_tree.post_dominates(then_block, entry));
+    assert_eq!(post_dom_tree.get_immediate_post_dominator(entry), Some(merge));
+    assert_eq!(post_dom_tree.get_immediate_post_dominator(else_block), Some(merge));
+    assert_eq!(post_dom_tree.get_immediate_post_dominator(merge), None);
+    assert!(post_dom_tree.can_reach_exit(entry));
+    assert!(!post_dom_tree.can_reach_exit(spin));
+    assert!(post_dom_tree.post_dominates_instruction(ret, double));
+    assert!(!post_dom_tree.post_dominates_instruction(to_merge, double));
+}
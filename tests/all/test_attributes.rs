@@ -2,6 +2,7 @@ extern crate inkwell;
 
 use self::inkwell::attributes::{Attribute, AttributeLoc};
 use self::inkwell::context::Context;
+use self::inkwell::values::BasicMetadataValueEnum;
 
 #[test]
 fn test_enum_attribute_kinds() {
@@ -175,3 +176,125 @@ fn test_attributes_on_call_site_values() {
     assert_eq!(call_site_value.count_attributes(AttributeLoc::Return), 1);
     assert!(call_site_value.get_enum_attribute(AttributeLoc::Return, align_attribute).is_some());
 }
+
+#[test]
+fn test_copy_attributes() {
+    let context = Context::create();
+    let builder = context.create_builder();
+    let module = context.create_module("my_mod");
+    let void_type = context.void_type();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    let original = module.add_function("original", fn_type, None);
+    let thunk = module.add_function("thunk", fn_type, None);
+    let other = module.add_function("other", void_type.fn_type(&[], false), None);
+    let string_attribute = context.create_string_attribute("my_key", "my_val");
+    let noinline_attribute = context.create_enum_attribute(Attribute::get_named_enum_kind_id("noinline"), 0);
+    let zeroext_attribute = context.create_enum_attribute(Attribute::get_named_enum_kind_id("zeroext"), 0);
+    let signext_attribute = context.create_enum_attribute(Attribute::get_named_enum_kind_id("signext"), 0);
+
+    original.add_attribute(AttributeLoc::Function, noinline_attribute);
+    original.add_attribute(AttributeLoc::Return, zeroext_attribute);
+    original.add_attribute(AttributeLoc::Param(1), signext_attribute);
+    original.add_attribute(AttributeLoc::Param(1), string_attribute);
+    thunk.add_attribute(AttributeLoc::Param(0), string_attribute);
+
+    assert_eq!(thunk.copy_attributes_from(other), Err("Functions have a different number of params."));
+    assert!(thunk.copy_attributes_from(original).is_ok());
+
+    for loc in &[AttributeLoc::Function, AttributeLoc::Return, AttributeLoc::Param(1)] {
+        assert_eq!(thunk.attributes(*loc), original.attributes(*loc));
+    }
+
+    // Existing attributes are kept
+    assert_eq!(thunk.attributes(AttributeLoc::Param(0)), vec![string_attribute]);
+
+    let entry = context.append_basic_block(thunk, "entry");
+
+    builder.position_at_end(entry);
+
+    let args: Vec<BasicMetadataValueEnum> = thunk.get_param_iter().map(|param| param.into()).collect();
+    let call = builder.build_call(original, &args, "call");
+    let other_call = builder.build_call(original, &args, "other_call");
+    let void_call = builder.build_call(other, &[], "");
+
+    builder.build_return(Some(&call.try_as_basic_value().left().unwrap()));
+
+    assert_eq!(void_call.copy_attributes_from_function(original), Err("Call site and function have a different number of arguments."));
+    assert!(call.copy_attributes_from_function(original).is_ok());
+    assert_eq!(call.attributes(AttributeLoc::Param(1)), original.attributes(AttributeLoc::Param(1)));
+    assert_eq!(call.attributes(AttributeLoc::Return), vec![zeroext_attribute]);
+
+    assert_eq!(other_call.copy_attributes_from(void_call), Err("Call sites have a different number of arguments."));
+    assert!(other_call.copy_attributes_from(call).is_ok());
+    assert_eq!(other_call.attributes(AttributeLoc::Function), call.attributes(AttributeLoc::Function));
+    assert_eq!(other_call.attributes(AttributeLoc::Param(1)), call.attributes(AttributeLoc::Param(1)));
+    assert!(thunk.verify(true));
+}
+
+#[test]
+fn test_attribute_introspection() {
+    let context = Context::create();
+    let builder = context.create_builder();
+    let module = context.create_module("my_mod");
+    let void_type = context.void_type();
+    let i32_type = context.i32_type();
+    let fn_type = void_type.fn_type(&[i32_type.into()], false);
+    let fn_value = module.add_function("my_fn", fn_type, None);
+    let entry_bb = context.append_basic_block(fn_value, "entry");
+    let string_attribute = context.create_string_attribute("my_key", "my_val");
+    let alignstack_attribute = Attribute::get_named_enum_kind_id("alignstack");
+    let enum_attribute = context.create_enum_attribute(alignstack_attribute, 1);
+
+    assert!(string_attribute.is_kind_named("my_key"));
+    assert!(!string_attribute.is_kind_named("alignstack"));
+    assert!(enum_attribute.is_kind_named("alignstack"));
+    assert!(!enum_attribute.is_kind_named("my_key"));
+    assert!(!enum_attribute.is_kind_named("foo"));
+
+    assert!(fn_value.attributes(AttributeLoc::Function).is_empty());
+
+    fn_value.add_attribute(AttributeLoc::Function, string_attribute);
+    fn_value.add_attribute(AttributeLoc::Function, enum_attribute);
+
+    let attributes = fn_value.attributes(AttributeLoc::Function);
+
+    assert_eq!(attributes.len(), 2);
+    assert!(attributes.contains(&string_attribute));
+    assert!(attributes.contains(&enum_attribute));
+    assert!(fn_value.attributes(AttributeLoc::Param(0)).is_empty());
+
+    builder.position_at_end(entry_bb);
+
+    let call_site_value = builder.build_call(fn_value, &[i32_type.const_int(1, false).into()], "my_fn");
+
+    builder.build_return(None);
+
+    call_site_value.add_attribute(AttributeLoc::Param(0), string_attribute);
+
+    assert_eq!(call_site_value.attributes(AttributeLoc::Param(0)), vec![string_attribute]);
+    assert!(call_site_value.attributes(AttributeLoc::Return).is_empty());
+}
+
+#[llvm_versions(12.0..=latest)]
+#[test]
+fn test_type_attribute_values() {
+    use inkwell::types::AnyType;
+    use inkwell::AddressSpace;
+
+    let context = Context::create();
+    let module = context.create_module("my_mod");
+    let void_type = context.void_type();
+    let i32_type = context.i32_type();
+    let fn_type = void_type.fn_type(&[i32_type.ptr_type(AddressSpace::Generic).into()], false);
+    let fn_value = module.add_function("my_fn", fn_type, None);
+    let sret_kind_id = Attribute::get_named_enum_kind_id("sret");
+    let type_attribute = context.create_type_attribute(sret_kind_id, i32_type.as_any_type_enum());
+
+    assert!(type_attribute.is_kind_named("sret"));
+    assert!(fn_value.get_type_attribute_value(AttributeLoc::Param(0), sret_kind_id).is_none());
+
+    fn_value.add_attribute(AttributeLoc::Param(0), type_attribute);
+
+    assert_eq!(fn_value.get_type_attribute_value(AttributeLoc::Param(0), sret_kind_id), Some(i32_type.as_any_type_enum()));
+}
@@ -127,9 +127,12 @@ fn test_get_terminator() {
     assert!(basic_block.get_terminator().is_none());
     assert!(basic_block.get_first_instruction().is_none());
     assert!(basic_block.get_last_instruction().is_none());
+    assert_eq!(basic_block.count_instructions(), 0);
 
     builder.build_return(None);
 
+    assert_eq!(basic_block.count_instructions(), 1);
+
     assert_eq!(basic_block.get_terminator().unwrap().get_opcode(), InstructionOpcode::Return);
     assert_eq!(basic_block.get_first_instruction().unwrap().get_opcode(), InstructionOpcode::Return);
     assert_eq!(basic_block.get_last_instruction().unwrap().get_opcode(), InstructionOpcode::Return);
@@ -215,3 +218,46 @@ fn test_get_address() {
     assert!(unsafe { entry_bb.get_address() }.is_none());
     assert!(unsafe { next_bb.get_address() }.is_some());
 }
+
+#[test]
+fn test_entry_block_and_reordering() {
+    let context = Context::create();
+    let module = context.create_module("my_mod");
+    let builder = context.create_builder();
+    let fn_type = context.void_type().fn_type(&[], false);
+    let function = module.add_function("my_fn", fn_type, None);
+    let other_function = module.add_function("other_fn", fn_type, None);
+
+    assert!(function.get_entry_block().is_none());
+    assert!(function.reorder_blocks(&[]).is_ok());
+
+    let entry = context.append_basic_block(function, "entry");
+    let exit = context.append_basic_block(function, "exit");
+    let middle = context.insert_basic_block_after(entry, "middle");
+    let other_entry = context.append_basic_block(other_function, "entry");
+
+    assert_eq!(function.get_entry_block(), Some(entry));
+    assert_eq!(function.get_basic_blocks(), vec![entry, middle, exit]);
+
+    builder.position_at_end(entry);
+    builder.build_unconditional_branch(middle);
+    builder.position_at_end(middle);
+    builder.build_unconditional_branch(exit);
+    builder.position_at_end(exit);
+    builder.build_return(None);
+
+    assert!(function.reorder_blocks(&[entry, exit]).is_err());
+    assert!(function.reorder_blocks(&[entry, exit, exit]).is_err());
+    assert!(function.reorder_blocks(&[entry, exit, other_entry]).is_err());
+    assert_eq!(function.get_basic_blocks(), vec![entry, middle, exit]);
+
+    function.reorder_blocks(&[entry, exit, middle]).unwrap();
+
+    assert_eq!(function.get_basic_blocks(), vec![entry, exit, middle]);
+    assert!(function.verify(false));
+
+    function.reorder_blocks(&[middle, entry, exit]).unwrap();
+
+    assert_eq!(function.get_entry_block(), Some(middle));
+    assert_eq!(function.get_basic_blocks(), vec![middle, entry, exit]);
+}
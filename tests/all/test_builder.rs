@@ -1,7 +1,8 @@
-use inkwell::{AddressSpace, AtomicOrdering, AtomicRMWBinOp, OptimizationLevel};
+use inkwell::{AddressSpace, AtomicOrdering, AtomicRMWBinOp, CacheType, IntPredicate, OptimizationLevel, OverflowArithmetic, PrefetchAccess};
+use inkwell::build_ir;
 use inkwell::context::Context;
-use inkwell::values::BasicValue;
-use inkwell::values::CallableValue;
+use inkwell::values::{AnyValue, BasicValue};
+use inkwell::values::{CallableValue, InstructionOpcode};
 
 use std::ptr::null;
 use std::convert::TryFrom;
@@ -861,6 +862,615 @@ fn test_insert_value() {
     assert!(module.verify().is_ok());
 }
 
+#[test]
+fn test_vararg_calls() {
+    let context = Context::create();
+    let module = context.create_module("va");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let f32_type = context.f32_type();
+    let f64_type = context.f64_type();
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+
+    let sum_type = f64_type.fn_type(&[i32_type.into()], true);
+    let sum = module.add_function("sum", sum_type, None);
+    let entry = context.append_basic_block(sum, "entry");
+
+    builder.position_at_end(entry);
+
+    let va_list = builder.build_alloca(i8_ptr_type, "va_list");
+    let va_copy = builder.build_alloca(i8_ptr_type, "va_copy");
+
+    assert!(builder.build_va_start(va_list).is_ok());
+    assert!(builder.build_va_copy(va_copy, va_list).is_ok());
+
+    let first = builder.build_va_arg(va_list, f64_type, "first");
+
+    assert!(builder.build_va_end(va_copy).is_ok());
+    assert!(builder.build_va_end(va_list).is_ok());
+
+    builder.build_return(Some(&first));
+
+    let va_start = module.get_function("llvm.va_start").unwrap();
+
+    assert_eq!(va_start.count_params(), 1);
+    assert!(module.get_function("llvm.va_end").is_some());
+    assert_eq!(module.get_function("llvm.va_copy").unwrap().count_params(), 2);
+
+    let caller_type = f64_type.fn_type(&[], false);
+    let caller = module.add_function("caller", caller_type, None);
+    let entry = context.append_basic_block(caller, "entry");
+
+    builder.position_at_end(entry);
+
+    let count = i32_type.const_int(1, false);
+    let value = f32_type.const_float(1.5);
+
+    assert!(builder.build_vararg_call(sum, &[], "call").is_err());
+    assert!(builder.build_vararg_call(caller, &[], "call").is_err());
+
+    let call = builder.build_vararg_call(sum, &[count.into(), value.into()], "call").unwrap();
+    let call_instruction = call.try_as_basic_value().left().unwrap().as_instruction_value().unwrap();
+    let promoted = call_instruction.get_operand(1).unwrap().left().unwrap();
+
+    assert_eq!(promoted.into_float_value().get_type(), f64_type);
+
+    builder.build_return(Some(&call.try_as_basic_value().left().unwrap()));
+
+    assert!(module.verify().is_ok());
+
+    let unpositioned = context.create_builder();
+
+    assert!(unpositioned.build_va_start(va_list).is_err());
+
+    // A declaration of the wrong type is not silently reused
+    let other_module = context.create_module("mismatched");
+    let other_fn_value = other_module.add_function("mismatched", context.void_type().fn_type(&[], false), None);
+
+    other_module.add_function("llvm.va_end", i32_type.fn_type(&[], false), None);
+    builder.position_at_end(context.append_basic_block(other_fn_value, "entry"));
+
+    assert_eq!(builder.build_va_end(va_list).err(), Some("The intrinsic is already declared with a different type."));
+}
+
+#[test]
+fn test_stack_save_restore() {
+    let context = Context::create();
+    let module = context.create_module("vla");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = context.void_type().fn_type(&[i32_type.into()], false);
+    let fn_value = module.add_function("vla", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let len = fn_value.get_first_param().unwrap().into_int_value();
+
+    assert!(builder.build_stack_save("stack").is_err());
+
+    builder.position_at_end(entry);
+
+    let stack = builder.build_stack_save("stack").unwrap();
+
+    builder.build_array_alloca(i32_type, len, "vla");
+
+    assert!(builder.build_stack_restore(stack).is_ok());
+
+    let vla = builder.build_stack_scope(|builder| {
+        let vla = builder.build_array_alloca(i32_type, len, "scoped_vla");
+
+        builder.build_store(vla, i32_type.const_zero());
+
+        vla
+    }).unwrap();
+
+    builder.build_return(None);
+
+    let restore = entry.get_terminator().unwrap().get_previous_instruction().unwrap();
+
+    assert!(vla.as_instruction_value().is_some());
+    assert_eq!(restore.try_into_call_site_value().unwrap().get_called_fn_value(), module.get_function("llvm.stackrestore").unwrap());
+    assert!(module.get_function("llvm.stacksave").is_some());
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_branch_hints() {
+    let context = Context::create();
+    let module = context.create_module("hints");
+    let builder = context.create_builder();
+    let bool_type = context.bool_type();
+    let i32_type = context.i32_type();
+    let fn_type = context.void_type().fn_type(&[bool_type.into(), i32_type.into()], false);
+    let fn_value = module.add_function("hints", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let likely = context.append_basic_block(fn_value, "likely");
+    let unlikely = context.append_basic_block(fn_value, "unlikely");
+    let condition = fn_value.get_first_param().unwrap().into_int_value();
+    let value = fn_value.get_nth_param(1).unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_expect(condition, i32_type.const_zero(), "expected").is_err());
+
+    let expected = builder.build_expect(value, i32_type.const_int(7, false), "expected").unwrap();
+
+    assert_eq!(expected.get_type(), i32_type);
+    assert!(module.get_function("llvm.expect.i32").is_some());
+
+    let condition = builder.build_expect(condition, bool_type.const_int(1, false), "condition").unwrap();
+    let branch = builder.build_conditional_branch_with_likelihood(condition, likely, unlikely, false);
+    let prof = branch.get_metadata(context.get_kind_id("prof")).unwrap();
+
+    assert_eq!(prof.get_node_size(), 3);
+    assert_eq!(prof.get_node_values()[0].into_metadata_value().get_string_value().unwrap().to_str(), Ok("branch_weights"));
+    assert_eq!(prof.get_node_values()[1].into_int_value().get_zero_extended_constant(), Some(1));
+    assert_eq!(prof.get_node_values()[2].into_int_value().get_zero_extended_constant(), Some(2000));
+
+    builder.position_at_end(likely);
+    builder.build_return(None);
+    builder.position_at_end(unlikely);
+    builder.build_return(None);
+
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_checked_int_arithmetic() {
+    let context = Context::create();
+    let module = context.create_module("checked");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let i64_type = context.i64_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    let fn_value = module.add_function("checked", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let handler = context.append_basic_block(fn_value, "handler");
+    let lhs = fn_value.get_first_param().unwrap().into_int_value();
+    let rhs = fn_value.get_nth_param(1).unwrap().into_int_value();
+
+    builder.position_at_end(handler);
+    builder.build_return(Some(&i32_type.const_zero()));
+    builder.position_at_end(entry);
+
+    assert!(builder.build_int_checked_op(OverflowArithmetic::SignedAdd, lhs, i64_type.const_zero(), None, "sum").is_err());
+
+    let sum = builder.build_int_checked_op(OverflowArithmetic::SignedAdd, lhs, rhs, None, "sum").unwrap();
+    let product = builder.build_int_checked_op(OverflowArithmetic::UnsignedMul, sum, rhs, Some(handler), "product").unwrap();
+
+    builder.build_return(Some(&product));
+
+    assert_eq!(sum.get_type(), i32_type);
+    assert!(module.get_function("llvm.sadd.with.overflow.i32").is_some());
+    assert!(module.get_function("llvm.umul.with.overflow.i32").is_some());
+    assert!(module.get_function("llvm.trap").is_some());
+    // entry, handler, the trap block and two continuation blocks
+    assert_eq!(fn_value.count_basic_blocks(), 5);
+    assert_ne!(builder.get_insert_block().unwrap(), entry);
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_lifetime_intrinsics() {
+    let context = Context::create();
+    let module = context.create_module("lifetimes");
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let fn_type = context.void_type().fn_type(&[], false);
+    let fn_value = module.add_function("lifetimes", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+
+    builder.position_at_end(entry);
+
+    let buffer = builder.build_alloca(i64_type.array_type(4), "buffer");
+    let global = module.add_global(i64_type, Some(AddressSpace::Global), "global");
+
+    global.set_initializer(&i64_type.const_zero());
+
+    let array = builder.build_array_alloca(i64_type, i64_type.const_int(4, false), "array");
+    let opaque = builder.build_alloca(context.opaque_struct_type("opaque").ptr_type(AddressSpace::Generic), "opaque");
+    let opaque = builder.build_load(opaque, "").into_pointer_value();
+    let get_size = |call: inkwell::values::CallSiteValue| {
+        call.try_as_basic_value().right().unwrap().get_operand(0).unwrap().left().unwrap().into_int_value().get_sign_extended_constant()
+    };
+
+    assert_eq!(get_size(builder.build_lifetime_start(buffer, Some(16)).unwrap()), Some(16));
+    assert_eq!(get_size(builder.build_lifetime_end(buffer, None).unwrap()), Some(32));
+    assert_eq!(get_size(builder.build_lifetime_start(global.as_pointer_value(), None).unwrap()), Some(8));
+    assert_eq!(get_size(builder.build_lifetime_start(array, None).unwrap()), Some(-1));
+    assert_eq!(get_size(builder.build_lifetime_start(opaque, None).unwrap()), Some(-1));
+
+    builder.build_return(None);
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0")))]
+    {
+        assert!(module.get_function("llvm.lifetime.start.p0i8").is_some());
+        assert!(module.get_function("llvm.lifetime.start.p1i8").is_some());
+    }
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_prefetch() {
+    let context = Context::create();
+    let module = context.create_module("prefetch");
+    let builder = context.create_builder();
+    let f64_ptr_type = context.f64_type().ptr_type(AddressSpace::Generic);
+    let fn_type = context.void_type().fn_type(&[f64_ptr_type.into()], false);
+    let fn_value = module.add_function("prefetch", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let data = fn_value.get_first_param().unwrap().into_pointer_value();
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_prefetch(data, PrefetchAccess::Read, 4, CacheType::Data).is_err());
+
+    let read = builder.build_prefetch(data, PrefetchAccess::Read, 3, CacheType::Data).unwrap();
+    let write = builder.build_prefetch(data, PrefetchAccess::Write, 0, CacheType::Instruction).unwrap();
+
+    builder.build_return(None);
+
+    let read_instruction = read.try_as_basic_value().right().unwrap();
+    let write_instruction = write.try_as_basic_value().right().unwrap();
+    let get_constant = |instruction: inkwell::values::InstructionValue, index| {
+        instruction.get_operand(index).unwrap().left().unwrap().into_int_value().get_zero_extended_constant()
+    };
+
+    assert_eq!(get_constant(read_instruction, 1), Some(0));
+    assert_eq!(get_constant(read_instruction, 2), Some(3));
+    assert_eq!(get_constant(read_instruction, 3), Some(1));
+    assert_eq!(get_constant(write_instruction, 1), Some(1));
+    assert_eq!(get_constant(write_instruction, 2), Some(0));
+    assert_eq!(get_constant(write_instruction, 3), Some(0));
+    assert_eq!(read.get_called_fn_value(), write.get_called_fn_value());
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_float_math_intrinsics() {
+    let context = Context::create();
+    let module = context.create_module("math");
+    let builder = context.create_builder();
+    let f32_type = context.f32_type();
+    let f64_type = context.f64_type();
+    let vec_type = f32_type.vec_type(4);
+    let fn_type = context.void_type().fn_type(&[f64_type.into(), f64_type.into(), vec_type.into(), f32_type.into()], false);
+    let fn_value = module.add_function("math", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let x = fn_value.get_nth_param(0).unwrap().into_float_value();
+    let y = fn_value.get_nth_param(1).unwrap().into_float_value();
+    let v = fn_value.get_nth_param(2).unwrap().into_vector_value();
+    let z = fn_value.get_nth_param(3).unwrap().into_float_value();
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_float_pow(x, z, "mismatched").is_err());
+
+    let sqrt = builder.build_float_sqrt(x, "sqrt").unwrap();
+    let sqrt_vec = builder.build_float_sqrt(v, "sqrt_vec").unwrap();
+
+    builder.build_float_sin(x, "sin").unwrap();
+    builder.build_float_cos(v, "cos").unwrap();
+    builder.build_float_pow(x, y, "pow").unwrap();
+    builder.build_float_fma(v, v, v, "fma").unwrap();
+    builder.build_float_abs(z, "abs").unwrap();
+    builder.build_float_minnum(x, y, "min").unwrap();
+    builder.build_float_maxnum(v, v, "max").unwrap();
+    builder.build_float_copysign(x, y, "copysign").unwrap();
+    builder.build_return(None);
+
+    assert_eq!(sqrt.get_type(), f64_type);
+    assert_eq!(sqrt_vec.get_type(), vec_type);
+
+    for &name in &["llvm.sqrt.f64", "llvm.sqrt.v4f32", "llvm.sin.f64", "llvm.cos.v4f32", "llvm.pow.f64",
+                   "llvm.fma.v4f32", "llvm.fabs.f32", "llvm.minnum.f64", "llvm.maxnum.v4f32", "llvm.copysign.f64"] {
+        assert!(module.get_function(name).is_some(), "{} was not declared", name);
+    }
+
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_vector_select_and_min_max() {
+    let context = Context::create();
+    let module = context.create_module("select");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let vec_type = i32_type.vec_type(4);
+    let fn_type = vec_type.fn_type(&[vec_type.into(), vec_type.into(), i32_type.into(), i32_type.into()], false);
+    let fn_value = module.add_function("select", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let lhs = fn_value.get_nth_param(0).unwrap().into_vector_value();
+    let rhs = fn_value.get_nth_param(1).unwrap().into_vector_value();
+    let x = fn_value.get_nth_param(2).unwrap().into_int_value();
+    let y = fn_value.get_nth_param(3).unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    let is_less = builder.build_int_compare(IntPredicate::SLT, lhs, rhs, "is_less");
+    let min = builder.build_select(is_less, lhs, rhs, "min");
+
+    assert_eq!(is_less.get_type(), context.bool_type().vec_type(4));
+    assert_eq!(min.into_vector_value().get_type(), vec_type);
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                  feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0",
+                  feature = "llvm8-0", feature = "llvm9-0", feature = "llvm10-0", feature = "llvm11-0")))]
+    {
+        let smax = builder.build_smax(lhs, rhs, "smax").unwrap();
+
+        assert_eq!(smax.get_type(), vec_type);
+        assert!(builder.build_smin(x, y, "smin").is_ok());
+        assert!(builder.build_umin(lhs, min.into_vector_value(), "umin").is_ok());
+        assert!(builder.build_umax(x, y, "umax").is_ok());
+        assert!(builder.build_umax(x, context.i64_type().const_zero(), "mismatch").is_err());
+
+        for &name in &["llvm.smax.v4i32", "llvm.smin.i32", "llvm.umin.v4i32", "llvm.umax.i32"] {
+            assert!(module.get_function(name).is_some(), "{} was not declared", name);
+        }
+    }
+
+    let _ = (x, y);
+
+    builder.build_return(Some(&min));
+
+    assert!(fn_value.verify(true));
+}
+
+#[test]
+fn test_mask_int_conversions() {
+    let context = Context::create();
+    let module = context.create_module("masks");
+    let builder = context.create_builder();
+    let i16_type = context.i16_type();
+    let fn_type = i16_type.fn_type(&[i16_type.into()], false);
+    let fn_value = module.add_function("low_bits", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let x = fn_value.get_first_param().unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    assert_eq!(builder.build_int_to_mask(x, 17, "mask").unwrap_err(), "The integer type is too narrow to hold every element of the mask.");
+    assert_eq!(builder.build_int_to_mask(x, 0, "mask").unwrap_err(), "The mask must have at least one element.");
+
+    let mask = builder.build_int_to_mask(x, 12, "mask").unwrap();
+
+    assert_eq!(mask.get_type(), context.bool_type().vec_type(12));
+    assert_eq!(builder.build_mask_to_int(mask, context.i8_type(), "bits").unwrap_err(), "The integer type is too narrow to hold every element of the mask.");
+    assert_eq!(builder.build_mask_to_int(i16_type.vec_type(2).const_zero(), i16_type, "bits").unwrap_err(), "The mask must be a vector of i1.");
+
+    let bits = builder.build_mask_to_int(mask, i16_type, "bits").unwrap();
+
+    builder.build_return(Some(&bits));
+
+    assert!(fn_value.verify(true));
+
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+
+    unsafe {
+        let low_bits = execution_engine.get_function::<unsafe extern "C" fn(u16) -> u16>("low_bits").unwrap();
+
+        assert_eq!(low_bits.call(0xABCD), 0x0BCD);
+        assert_eq!(low_bits.call(0x0001), 0x0001);
+        assert_eq!(low_bits.call(0x0800), 0x0800);
+    }
+}
+
+#[test]
+fn test_bit_manipulation_intrinsics() {
+    let context = Context::create();
+    let module = context.create_module("bits");
+    let builder = context.create_builder();
+    let i8_type = context.i8_type();
+    let i32_type = context.i32_type();
+    let vec_type = i32_type.vec_type(4);
+    let fn_type = context.void_type().fn_type(&[i32_type.into(), vec_type.into(), i8_type.into()], false);
+    let fn_value = module.add_function("bits", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let x = fn_value.get_nth_param(0).unwrap().into_int_value();
+    let v = fn_value.get_nth_param(1).unwrap().into_vector_value();
+    let byte = fn_value.get_nth_param(2).unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_bswap(byte, "bswap").is_err());
+
+    let ctpop = builder.build_ctpop(x, "ctpop").unwrap();
+    let ctlz = builder.build_ctlz(x, true, "ctlz").unwrap();
+
+    builder.build_ctpop(v, "ctpop_vec").unwrap();
+    builder.build_cttz(v, false, "cttz").unwrap();
+    builder.build_bswap(x, "bswap").unwrap();
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7")))]
+    builder.build_bitreverse(byte, "bitreverse").unwrap();
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                  feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0")))]
+    {
+        builder.build_fshl(v, v, v, "fshl").unwrap();
+        builder.build_fshr(x, x, ctlz, "rotr").unwrap();
+    }
+
+    builder.build_return(None);
+
+    let ctlz_instruction = ctlz.as_instruction().unwrap();
+    let is_zero_undef = ctlz_instruction.get_operand(1).unwrap().left().unwrap().into_int_value();
+
+    assert_eq!(ctpop.get_type(), i32_type);
+    assert_eq!(is_zero_undef.get_zero_extended_constant(), Some(1));
+
+    for &name in &["llvm.ctpop.i32", "llvm.ctpop.v4i32", "llvm.ctlz.i32", "llvm.cttz.v4i32", "llvm.bswap.i32"] {
+        assert!(module.get_function(name).is_some(), "{} was not declared", name);
+    }
+
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(9.0..=latest)]
+#[test]
+fn test_vector_reductions() {
+    use inkwell::IntReduction;
+
+    let context = Context::create();
+    let module = context.create_module("reduce");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let f32_type = context.f32_type();
+    let int_vec_type = i32_type.vec_type(4);
+    let float_vec_type = f32_type.vec_type(4);
+    let fn_type = context.void_type().fn_type(&[int_vec_type.into(), float_vec_type.into()], false);
+    let fn_value = module.add_function("reduce", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let ints = fn_value.get_nth_param(0).unwrap().into_vector_value();
+    let floats = fn_value.get_nth_param(1).unwrap().into_vector_value();
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_int_vector_reduce(IntReduction::Add, floats, "sum").is_err());
+    assert!(builder.build_float_vector_reduce_max(ints, "max").is_err());
+    assert!(builder.build_float_vector_reduce_add(context.f64_type().const_zero(), floats, "sum").is_err());
+
+    let sum = builder.build_int_vector_reduce(IntReduction::Add, ints, "sum").unwrap();
+    let umin = builder.build_int_vector_reduce(IntReduction::UnsignedMin, ints, "umin").unwrap();
+    let fsum = builder.build_float_vector_reduce_add(f32_type.const_zero(), floats, "fsum").unwrap();
+    let fmax = builder.build_float_vector_reduce_max(floats, "fmax").unwrap();
+
+    builder.build_float_vector_reduce_mul(f32_type.const_float(1.0), floats, "fproduct").unwrap();
+    builder.build_float_vector_reduce_min(floats, "fmin").unwrap();
+    builder.build_return(None);
+
+    assert_eq!(sum.get_type(), i32_type);
+    assert_eq!(umin.get_type(), i32_type);
+    assert_eq!(fsum.get_type(), f32_type);
+    assert_eq!(fmax.get_type(), f32_type);
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(5.0..=latest)]
+#[test]
+fn test_masked_memory_intrinsics() {
+    let context = Context::create();
+    let module = context.create_module("masked");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let vec_type = i32_type.vec_type(4);
+    let mask_type = context.bool_type().vec_type(4);
+    let i32_ptr_type = i32_type.ptr_type(AddressSpace::Generic);
+    let fn_type = context.void_type().fn_type(&[
+        vec_type.ptr_type(AddressSpace::Generic).into(),
+        i32_ptr_type.vec_type(4).into(),
+        i32_ptr_type.into(),
+        mask_type.into(),
+    ], false);
+    let fn_value = module.add_function("masked", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let vec_ptr = fn_value.get_nth_param(0).unwrap().into_pointer_value();
+    let ptrs = fn_value.get_nth_param(1).unwrap().into_vector_value();
+    let ptr = fn_value.get_nth_param(2).unwrap().into_pointer_value();
+    let mask = fn_value.get_nth_param(3).unwrap().into_vector_value();
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_masked_load(vec_ptr, 3, mask, None, "misaligned").is_err());
+    assert!(builder.build_masked_load(ptr, 4, mask, None, "scalar").is_err());
+    assert!(builder.build_masked_load(vec_ptr, 4, ptrs, None, "bad_mask").is_err());
+
+    let loaded = builder.build_masked_load(vec_ptr, 4, mask, Some(vec_type.const_zero()), "loaded").unwrap();
+    let gathered = builder.build_masked_gather(ptrs, 4, mask, None, "gathered").unwrap();
+    let expanded = builder.build_masked_expand_load(vec_type, ptr, mask, None, "expanded").unwrap();
+
+    builder.build_masked_store(loaded, vec_ptr, 4, mask).unwrap();
+    builder.build_masked_scatter(gathered, ptrs, 4, mask).unwrap();
+    builder.build_masked_compress_store(expanded, ptr, mask).unwrap();
+    builder.build_return(None);
+
+    assert_eq!(loaded.get_type(), vec_type);
+    assert_eq!(gathered.get_type(), vec_type);
+    assert_eq!(expanded.get_type(), vec_type);
+    assert!(module.get_function("llvm.masked.load.v4i32.p0v4i32").is_some());
+    assert!(module.get_function("llvm.masked.gather.v4i32.v4p0i32").is_some());
+    assert!(module.get_function("llvm.masked.expandload.v4i32").is_some());
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_trap_intrinsics() {
+    let context = Context::create();
+    let module = context.create_module("traps");
+    let builder = context.create_builder();
+    let fn_value = module.add_function("traps", context.void_type().fn_type(&[], false), None);
+
+    assert!(builder.build_trap().is_err());
+
+    builder.position_at_end(context.append_basic_block(fn_value, "entry"));
+
+    let debugtrap = builder.build_debugtrap().unwrap();
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0", feature = "llvm5-0",
+                  feature = "llvm6-0", feature = "llvm7-0", feature = "llvm8-0", feature = "llvm9-0", feature = "llvm10-0", feature = "llvm11-0")))]
+    {
+        let ubsantrap = builder.build_ubsantrap(42).unwrap();
+        let code = ubsantrap.try_as_basic_value().right().unwrap().get_operand(0).unwrap().left().unwrap();
+
+        assert_eq!(code.into_int_value().get_zero_extended_constant(), Some(42));
+    }
+
+    let trap = builder.build_trap().unwrap();
+
+    builder.build_unreachable();
+
+    assert_eq!(trap.get_called_fn_value().get_name().to_str(), Ok("llvm.trap"));
+    assert_eq!(debugtrap.get_called_fn_value().get_name().to_str(), Ok("llvm.debugtrap"));
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_nested_aggregate_values() {
+    let context = Context::create();
+    let module = context.create_module("av");
+    let f32_type = context.f32_type();
+    let i32_type = context.i32_type();
+    let inner_type = context.struct_type(&[i32_type.into(), f32_type.into()], false);
+    let array_type = inner_type.array_type(2);
+    let outer_type = context.struct_type(&[i32_type.into(), array_type.into()], false);
+    let fn_type = outer_type.fn_type(&[outer_type.into()], false);
+    let fn_value = module.add_function("av_fn", fn_type, None);
+    let builder = context.create_builder();
+    let entry = context.append_basic_block(fn_value, "entry");
+
+    builder.position_at_end(entry);
+
+    let outer = fn_value.get_first_param().unwrap().into_struct_value();
+    let const_float = f32_type.const_float(1.5);
+
+    assert!(builder.build_extract_value_path(outer, &[1, 1, 1], "extract").unwrap().is_float_value());
+    assert!(builder.build_extract_value_path(outer, &[1, 0], "extract").unwrap().is_struct_value());
+    assert!(builder.build_extract_value_path(outer, &[0], "extract").unwrap().is_int_value());
+
+    let count_instructions = || std::iter::successors(entry.get_first_instruction(), |instruction| instruction.get_next_instruction()).count();
+    let instruction_count = count_instructions();
+
+    assert!(builder.build_extract_value_path(outer, &[1, 2], "extract").is_none());
+    assert!(builder.build_extract_value_path(outer, &[0, 0], "extract").is_none());
+    assert!(builder.build_extract_value_path(outer, &[], "extract").is_none());
+
+    assert!(builder.build_insert_value_path(outer, const_float, &[1, 2, 1], "insert").is_none());
+    assert!(builder.build_insert_value_path(outer, const_float, &[], "insert").is_none());
+
+    // Invalid paths are rejected before any instruction is built
+    assert_eq!(count_instructions(), instruction_count);
+
+    let updated = builder.build_insert_value_path(outer, const_float, &[1, 1, 1], "insert").unwrap().into_struct_value();
+
+    assert_eq!(updated.get_type(), outer_type);
+
+    let first = builder.build_extract_value_path(updated, &[0], "first").unwrap();
+    let second = builder.build_extract_value_path(updated, &[1], "second").unwrap();
+
+    builder.build_aggregate_return(&[first, second]);
+
+    assert!(module.verify().is_ok());
+}
+
 fn is_alignment_ok(align: u32) -> bool {
     // This replicates the assertions LLVM runs.
     //
@@ -1283,3 +1893,206 @@ fn test_safe_struct_gep() {
     assert!(builder.build_struct_gep(struct_ptr, 1, "struct_gep").is_ok());
     assert!(builder.build_struct_gep(struct_ptr, 2, "struct_gep").is_err());
 }
+
+#[llvm_versions(8.0..=latest)]
+#[test]
+fn test_volatile_and_nontemporal_accesses() {
+    let context = Context::create();
+    let module = context.create_module("streaming");
+    let builder = context.create_builder();
+    let f64_type = context.f64_type();
+    let i8_type = context.i8_type();
+    let i64_type = context.i64_type();
+    let f64_ptr_type = f64_type.ptr_type(AddressSpace::Generic);
+    let fn_type = context.void_type().fn_type(&[f64_ptr_type.into(), f64_ptr_type.into()], false);
+    let fn_value = module.add_function("stream", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+
+    builder.position_at_end(entry);
+
+    let dest = fn_value.get_nth_param(0).unwrap().into_pointer_value();
+    let src = fn_value.get_nth_param(1).unwrap().into_pointer_value();
+    let load = builder.build_load(src, "load").as_instruction_value().unwrap();
+    let store = builder.build_store(dest, f64_type.const_float(1.0));
+
+    assert!(!load.is_nontemporal().unwrap());
+    assert!(!store.is_nontemporal().unwrap());
+
+    load.set_nontemporal(true).unwrap();
+    store.try_into_store_value().unwrap().set_nontemporal(true);
+
+    assert!(load.try_into_load_value().unwrap().is_nontemporal());
+    assert!(store.is_nontemporal().unwrap());
+
+    load.set_nontemporal(false).unwrap();
+
+    assert!(!load.is_nontemporal().unwrap());
+
+    let size = i64_type.const_int(64, false);
+    let memcpy = builder.build_volatile_memcpy(dest, 8, src, 8, size).unwrap();
+    let memset = builder.build_volatile_memset(dest, 8, i8_type.const_zero(), size).unwrap();
+    let ret = builder.build_return(None);
+
+    assert!(ret.set_nontemporal(true).is_err());
+
+    let memcpy = memcpy.as_instruction_value().unwrap();
+    let memset = memset.as_instruction_value().unwrap();
+    let is_volatile = |instruction: inkwell::values::InstructionValue| {
+        instruction.get_operand(3).unwrap().left().unwrap().into_int_value().get_zero_extended_constant()
+    };
+
+    assert_eq!(is_volatile(memcpy), Some(1));
+    assert_eq!(is_volatile(memset), Some(1));
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_indirect_call() {
+    let context = Context::create();
+    let module = context.create_module("indirect");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let callback_type = i32_type.fn_type(&[i32_type.into()], false);
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let fn_type = i32_type.fn_type(&[i8_ptr_type.into(), callback_type.ptr_type(AddressSpace::Generic).into()], false);
+    let fn_value = module.add_function("apply", fn_type, None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let opaque_callback = fn_value.get_nth_param(0).unwrap().into_pointer_value();
+    let typed_callback = fn_value.get_nth_param(1).unwrap().into_pointer_value();
+    let arg = i32_type.const_int(42, false);
+
+    builder.position_at_end(entry);
+
+    let first = builder.build_indirect_call(callback_type, opaque_callback, &[arg.into()], "first");
+    let second = builder.build_indirect_call(callback_type, typed_callback, &[arg.into()], "second");
+    let first = first.try_as_basic_value().left().unwrap().into_int_value();
+    let second = second.try_as_basic_value().left().unwrap().into_int_value();
+    let sum = builder.build_int_add(first, second, "sum");
+
+    builder.build_return(Some(&sum));
+
+    // Only the untyped pointer needed a cast
+    assert_eq!(entry.get_first_instruction().unwrap().get_opcode(), InstructionOpcode::BitCast);
+    assert_eq!(first.as_instruction().unwrap().get_opcode(), InstructionOpcode::Call);
+    assert_eq!(first.as_instruction().unwrap().get_previous_instruction().unwrap().get_opcode(), InstructionOpcode::BitCast);
+    assert_eq!(second.as_instruction().unwrap().get_previous_instruction().unwrap().get_opcode(), InstructionOpcode::Call);
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(3.9..=latest)]
+#[test]
+fn test_checked_terminators() {
+    let context = Context::create();
+    let module = context.create_module("terminators");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let bool_type = context.bool_type();
+    let fn_type = i32_type.fn_type(&[bool_type.into(), i32_type.into()], false);
+    let function = module.add_function("select", fn_type, None);
+    let other_function = module.add_function("other", context.void_type().fn_type(&[], false), None);
+    let entry = context.append_basic_block(function, "entry");
+    let then_block = context.append_basic_block(function, "then");
+    let else_block = context.append_basic_block(function, "else");
+    let trap_block = context.append_basic_block(function, "trap");
+    let foreign_block = context.append_basic_block(other_function, "entry");
+    let cond = function.get_nth_param(0).unwrap().into_int_value();
+    let value = function.get_nth_param(1).unwrap().into_int_value();
+
+    assert!(builder.build_unreachable_checked().is_err());
+
+    builder.position_at_end(entry);
+
+    assert!(builder.build_conditional_branch_checked(value, then_block, else_block).is_err());
+    assert!(builder.build_conditional_branch_checked(cond, then_block, foreign_block).is_err());
+
+    let branch = builder.build_conditional_branch_checked(cond, then_block, else_block).unwrap();
+
+    assert!(branch.is_conditional());
+    assert_eq!(branch.get_condition(), Some(cond));
+    assert_eq!(branch.get_successors(), vec![then_block, else_block]);
+    assert_eq!(entry.get_terminator().unwrap().try_into_branch_value(), Some(branch));
+    assert!(builder.build_unreachable_checked().is_err());
+
+    builder.position_at_end(then_block);
+
+    assert!(builder.build_return_checked(None).is_err());
+    assert!(builder.build_return_checked(Some(&cond)).is_err());
+
+    let ret = builder.build_return_checked(Some(&value)).unwrap();
+
+    assert_eq!(ret.get_return_value(), Some(value.into()));
+    assert_eq!(then_block.get_terminator().unwrap().try_into_return_value(), Some(ret));
+
+    builder.position_at_end(else_block);
+
+    assert!(builder.build_unconditional_branch_checked(foreign_block).is_err());
+
+    let branch = builder.build_unconditional_branch_checked(trap_block).unwrap();
+
+    assert!(!branch.is_conditional());
+    assert_eq!(branch.get_successors(), vec![trap_block]);
+
+    builder.position_at_end(trap_block);
+
+    let unreachable = builder.build_unreachable_checked().unwrap();
+
+    assert_eq!(trap_block.get_terminator().unwrap().try_into_unreachable_value(), Some(unreachable));
+    assert_eq!(unreachable.as_any_value_enum().into_instruction_value(), unreachable.as_instruction());
+    assert_eq!(ret.as_any_value_enum().into_instruction_value(), ret.as_instruction());
+    assert!(trap_block.get_terminator().unwrap().try_into_return_value().is_none());
+    assert!(function.verify(false));
+}
+
+#[test]
+fn test_build_ir_macro() {
+    let context = Context::create();
+    let module = context.create_module("build_ir");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    let function = module.add_function("abs_diff", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+    let negate = context.append_basic_block(function, "negate");
+    let done = context.append_basic_block(function, "done");
+    let a = function.get_nth_param(0).unwrap().into_int_value();
+    let b = function.get_nth_param(1).unwrap().into_int_value();
+    let zero = i32_type.const_zero();
+
+    build_ir!(builder;
+        position(entry);
+        let diff = sub(a, b);
+        let is_negative = icmp(SLT, diff, zero);
+        cbr(is_negative, negate, done);
+        position(negate);
+        let negated = neg(diff);
+        br(done);
+        position(done);
+        let result = phi(i32_type, [(diff, entry), (negated, negate)]);
+        ret(result.as_basic_value());
+    );
+
+    assert!(function.verify(false));
+    assert_eq!(result.count_incoming(), 2);
+    assert_eq!(is_negative.get_type(), context.bool_type());
+
+    let printed = function.print_to_string().to_string();
+
+    assert!(printed.contains("%diff = sub i32 %0, %1"));
+    assert!(printed.contains("%negated = sub i32 0, %diff"));
+
+    let caller = module.add_function("caller", fn_type, None);
+    let entry = context.append_basic_block(caller, "entry");
+    let x = caller.get_nth_param(0).unwrap().into_int_value();
+
+    build_ir!(builder;
+        position(entry);
+        let doubled = shl(x, i32_type.const_int(1, false));
+        // Regular builder calls pass through unchanged
+        let tripled = builder.build_int_add(doubled, x, "tripled");
+        let call = call(function, [tripled, x]);
+        ret(call.try_as_basic_value().left().unwrap());
+    );
+
+    assert!(caller.verify(false));
+    assert!(module.verify().is_ok());
+}
@@ -1,5 +1,6 @@
 use inkwell::AddressSpace;
 use inkwell::context::Context;
+use inkwell::types::{BasicType, BasicTypeEnum};
 
 #[test]
 fn test_no_context_double_free() {
@@ -67,3 +68,75 @@ fn test_values_get_context() {
     assert_eq!(*i8_type.get_context(), context);
     assert_eq!(*struct_type.get_context(), context);
 }
+
+#[llvm_versions(9.0..=latest)]
+#[test]
+fn test_discard_value_names() {
+    let context = Context::create();
+    let module = context.create_module("names");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = context.void_type().fn_type(&[], false);
+    let function = module.add_function("kept", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+
+    assert!(!context.should_discard_value_names());
+
+    context.set_discard_value_names(true);
+
+    assert!(context.should_discard_value_names());
+
+    builder.position_at_end(entry);
+
+    let alloca = builder.build_alloca(i32_type, "discarded");
+    let global = module.add_global(i32_type, None, "global");
+
+    assert_eq!(alloca.get_name().to_str(), Ok(""));
+    assert_eq!(global.get_name().to_str(), Ok("global"));
+    assert_eq!(function.get_name().to_str(), Ok("kept"));
+
+    context.set_discard_value_names(false);
+
+    let named = builder.build_alloca(i32_type, "named");
+
+    assert_eq!(named.get_name().to_str(), Ok("named"));
+}
+
+#[llvm_versions(12.0..=latest)]
+#[test]
+fn test_get_or_create_named_struct() {
+    let context = Context::create();
+    let i32_type = context.i32_type();
+    let f64_type = context.f64_type();
+
+    assert!(context.get_struct_type("pair").is_none());
+
+    let pair = context.get_or_create_named_struct("pair", &[i32_type.into(), f64_type.into()], false).unwrap();
+
+    assert_eq!(context.get_struct_type("pair"), Some(pair));
+    assert_eq!(context.get_or_create_named_struct("pair", &[i32_type.into(), f64_type.into()], false), Ok(pair));
+    assert!(context.get_or_create_named_struct("pair", &[i32_type.into(), f64_type.into()], true).is_err());
+    assert!(context.get_or_create_named_struct("pair", &[f64_type.into()], false).is_err());
+
+    let opaque = context.opaque_struct_type("node");
+    let node = context.get_or_create_named_struct("node", &[i32_type.into()], false).unwrap();
+
+    assert_eq!(node, opaque);
+    assert!(!node.is_opaque());
+}
+
+#[test]
+fn test_struct_and_fn_types_with_many_fields() {
+    let context = Context::create();
+    let i8_type = context.i8_type();
+    let few_fields: Vec<BasicTypeEnum> = vec![i8_type.into(); 3];
+    let many_fields: Vec<BasicTypeEnum> = vec![i8_type.into(); 40];
+
+    assert_eq!(context.struct_type(&few_fields, false).get_field_types(), few_fields);
+    assert_eq!(context.struct_type(&many_fields, false).get_field_types(), many_fields);
+
+    let fn_type = i8_type.fn_type(&vec![i8_type.into(); 40], false);
+
+    assert_eq!(fn_type.count_param_types(), 40);
+}
+
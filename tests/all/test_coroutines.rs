@@ -0,0 +1,94 @@
+use inkwell::AddressSpace;
+use inkwell::context::Context;
+use inkwell::coroutines::CoroutineBuilder;
+use inkwell::module::Module;
+use inkwell::passes::PassManager;
+
+// Builds a coroutine `counter` which prints increasing numbers every time it is resumed.
+fn build_counter(context: &Context) -> Module {
+    let module = context.create_module("coro");
+    let builder = context.create_builder();
+    let coro_builder = CoroutineBuilder::new(&builder);
+
+    let void_type = context.void_type();
+    let i8_type = context.i8_type();
+    let i32_type = context.i32_type();
+    let i8_ptr_type = i8_type.ptr_type(AddressSpace::Generic);
+    let malloc = module.add_function("malloc", i8_ptr_type.fn_type(&[context.i64_type().into()], false), None);
+    let free = module.add_function("free", void_type.fn_type(&[i8_ptr_type.into()], false), None);
+    let print = module.add_function("print", void_type.fn_type(&[i32_type.into()], false), None);
+
+    let fn_value = module.add_function("counter", i8_ptr_type.fn_type(&[i32_type.into()], false), None);
+    let entry = context.append_basic_block(fn_value, "entry");
+    let loop_block = context.append_basic_block(fn_value, "loop");
+    let cleanup = context.append_basic_block(fn_value, "cleanup");
+    let suspend = context.append_basic_block(fn_value, "suspend");
+
+    assert!(coro_builder.build_size("size").is_err());
+
+    builder.position_at_end(entry);
+
+    let id = coro_builder.build_id(0, None, "id").unwrap();
+    let size = coro_builder.build_size("size").unwrap();
+    let frame = builder.build_call(malloc, &[size.into()], "frame").try_as_basic_value().left().unwrap();
+    let handle = coro_builder.build_begin(id, frame.into_pointer_value(), "handle").unwrap();
+
+    builder.build_unconditional_branch(loop_block);
+    builder.position_at_end(loop_block);
+
+    let start = fn_value.get_first_param().unwrap().into_int_value();
+    let phi = builder.build_phi(i32_type, "n");
+    let next = builder.build_int_add(phi.as_basic_value().into_int_value(), i32_type.const_int(1, false), "next");
+
+    phi.add_incoming(&[(&start, entry), (&next, loop_block)]);
+    builder.build_call(print, &[phi.as_basic_value().into()], "");
+
+    let state = coro_builder.build_suspend(false, "state").unwrap();
+
+    builder.build_switch(state, suspend, &[(i8_type.const_int(0, false), loop_block), (i8_type.const_int(1, false), cleanup)]);
+    builder.position_at_end(cleanup);
+
+    let memory = coro_builder.build_free(id, handle, "memory").unwrap();
+
+    builder.build_call(free, &[memory.into()], "");
+    builder.build_unconditional_branch(suspend);
+    builder.position_at_end(suspend);
+
+    coro_builder.build_end(handle, false).unwrap();
+    builder.build_return(Some(&handle));
+
+    assert!(module.verify().is_ok());
+
+    module
+}
+
+#[test]
+fn test_coroutine_lowering() {
+    let context = Context::create();
+    let module = build_counter(&context);
+    let pass_manager = PassManager::create(());
+
+    pass_manager.add_coroutine_early_pass();
+    pass_manager.add_coroutine_split_pass();
+    pass_manager.add_coroutine_elide_pass();
+    pass_manager.add_coroutine_cleanup_pass();
+    pass_manager.run_on(&module);
+
+    assert!(module.verify().is_ok());
+    assert!(module.get_function("counter.resume").is_some());
+    assert!(module.get_function("counter.destroy").is_some());
+}
+
+#[llvm_versions(13.0..=latest)]
+#[test]
+fn test_run_coroutine_passes() {
+    use inkwell::coroutines::run_coroutine_passes;
+
+    let context = Context::create();
+    let module = build_counter(&context);
+
+    assert!(run_coroutine_passes(&module, None).is_ok());
+    assert!(module.verify().is_ok());
+    assert!(module.get_function("counter.resume").is_some());
+    assert!(module.get_function("counter.destroy").is_some());
+}
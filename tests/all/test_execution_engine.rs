@@ -1,8 +1,10 @@
 use inkwell::{AddressSpace, OptimizationLevel, IntPredicate};
 use inkwell::context::Context;
-use inkwell::execution_engine::FunctionLookupError;
+use inkwell::abi::AbiTarget;
+use inkwell::execution_engine::{AbiSafe, FunctionLookupError, GlobalLookupError, JitSignature, JitType};
 use inkwell::values::BasicValue;
-use inkwell::targets::{InitializationConfig, Target};
+use inkwell::targets::{InitializationConfig, Target, TargetData};
+use inkwell::types::BasicTypeEnum;
 
 type Thunk = unsafe extern "C" fn();
 
@@ -219,3 +221,447 @@ fn test_add_remove_module() {
 //         module.create_jit_execution_engine(OptimizationLevel::None).unwrap()
 //     };
 // }
+
+#[test]
+fn test_repl_engine() {
+    use inkwell::repl::ReplEngine;
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let mut repl = ReplEngine::new(&context, OptimizationLevel::None).unwrap();
+
+    // A persistent definition: fn double(x) = x * 2
+    let module = context.create_module("double");
+    let double = module.add_function("double", i64_type.fn_type(&[i64_type.into()], false), None);
+    let x = double.get_first_param().unwrap().into_int_value();
+
+    builder.position_at_end(context.append_basic_block(double, "entry"));
+    builder.build_return(Some(&builder.build_int_mul(x, i64_type.const_int(2, false), "mul")));
+    repl.add_definitions(module).unwrap();
+
+    assert!(repl.get_persistent_function("double").is_some());
+    assert!(repl.get_persistent_function("triple").is_none());
+
+    for i in 0..3 {
+        let expression = repl.create_expression(i64_type);
+        let double = repl.declare_persistent_function(expression.get_module(), "double").unwrap();
+
+        assert!(repl.declare_persistent_function(expression.get_module(), "triple").is_none());
+
+        builder.position_at_end(context.append_basic_block(expression.get_function(), "entry"));
+
+        let call = builder.build_call(double, &[i64_type.const_int(i, false).into()], "call");
+
+        builder.build_return(Some(&call.try_as_basic_value().left().unwrap()));
+
+        let result: u64 = unsafe { repl.evaluate(expression).unwrap() };
+
+        assert_eq!(result, i * 2);
+    }
+
+    assert_eq!(repl.get_expression_count(), 3);
+
+    // An invalid expression is rejected without being counted
+    let expression = repl.create_expression(i64_type);
+
+    assert!(unsafe { repl.evaluate::<u64>(expression) }.is_err());
+    assert_eq!(repl.get_expression_count(), 3);
+}
+
+#[test]
+fn test_lookup_tracer() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let module = context.create_module("traced");
+    let builder = context.create_builder();
+    let fn_value = module.add_function("func", context.void_type().fn_type(&[], false), None);
+
+    builder.position_at_end(context.append_basic_block(fn_value, "entry"));
+    builder.build_return(None);
+
+    let mut execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    let lookups = Rc::new(RefCell::new(Vec::new()));
+    let recorded = lookups.clone();
+
+    execution_engine.set_lookup_tracer(move |lookup| {
+        recorded.borrow_mut().push((
+            lookup.get_name().to_string(),
+            lookup.get_address(),
+            lookup.get_module_name().map(str::to_string),
+        ));
+    });
+
+    let address = execution_engine.get_function_address("func").unwrap();
+
+    assert!(execution_engine.get_function_address("missing").is_err());
+    assert!(unsafe { execution_engine.get_function::<Thunk>("func") }.is_ok());
+    assert_eq!(*lookups.borrow(), vec![
+        ("func".to_string(), Some(address), Some("traced".to_string())),
+        ("missing".to_string(), None, None),
+        ("func".to_string(), Some(address), Some("traced".to_string())),
+    ]);
+
+    execution_engine.clear_lookup_tracer();
+    execution_engine.get_function_address("func").unwrap();
+
+    assert_eq!(lookups.borrow().len(), 3);
+}
+
+#[test]
+fn test_get_checked_function() {
+    type Scale = unsafe extern "C" fn(*const f64, usize, f64) -> f64;
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let module = context.create_module("checked");
+    let builder = context.create_builder();
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    let target_data = execution_engine.get_target_data();
+    let fn_type = Scale::fn_type(&context, target_data);
+
+    assert_eq!(fn_type.get_param_types(), vec![
+        context.f64_type().ptr_type(AddressSpace::Generic).into(),
+        context.ptr_sized_int_type(target_data, None).into(),
+        context.f64_type().into(),
+    ]);
+    assert_eq!(<unsafe extern "C" fn()>::fn_type(&context, target_data), context.void_type().fn_type(&[], false));
+
+    // Returns ptr[index] * factor
+    let function = module.add_function("scale", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+    let ptr = function.get_nth_param(0).unwrap().into_pointer_value();
+    let index = function.get_nth_param(1).unwrap().into_int_value();
+    let factor = function.get_nth_param(2).unwrap().into_float_value();
+
+    builder.position_at_end(entry);
+
+    let element_ptr = unsafe { builder.build_gep(ptr, &[index], "element_ptr") };
+    let element = builder.build_load(element_ptr, "element").into_float_value();
+    let product = builder.build_float_mul(element, factor, "product");
+
+    builder.build_return(Some(&product));
+
+    let values = [1.5f64, 2.5, 3.5];
+
+    unsafe {
+        let scale = execution_engine.get_checked_function::<Scale>("scale").unwrap();
+
+        assert_eq!(scale.call(values.as_ptr(), 1, 2.), 5.);
+        assert_eq!(execution_engine.get_checked_function::<unsafe extern "C" fn(u32) -> f64>("scale").unwrap_err(),
+            FunctionLookupError::SignatureMismatch);
+        assert_eq!(execution_engine.get_checked_function::<Scale>("missing").unwrap_err(),
+            FunctionLookupError::FunctionNotFound);
+    }
+}
+
+#[test]
+fn test_add_host_function() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let module = context.create_module("host");
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+
+    // JIT'd code calls a declaration which the host function then defines
+    let record = module.add_function("record", i64_type.fn_type(&[i64_type.into()], false), None);
+    let run = module.add_function("run", i64_type.fn_type(&[], false), None);
+    let entry = context.append_basic_block(run, "entry");
+
+    builder.position_at_end(entry);
+    builder.build_call(record, &[i64_type.const_int(5, false).into()], "first");
+
+    let second = builder.build_call(record, &[i64_type.const_int(7, false).into()], "second");
+
+    builder.build_return(Some(&second.try_as_basic_value().left().unwrap()));
+
+    let total = Rc::new(Cell::new(0u64));
+    let recorded = total.clone();
+
+    let defined = execution_engine.add_host_function(&module, "record", move |value: u64| {
+        recorded.set(recorded.get() + value);
+        recorded.get()
+    }).unwrap();
+
+    assert_eq!(defined, record);
+    assert_eq!(execution_engine.add_host_function(&module, "record", |value: u64| value),
+        Err("Function is already defined."));
+    assert_eq!(execution_engine.add_host_function(&module, "run", |value: u64| value),
+        Err("Function is already defined."));
+
+    let counter = Rc::new(Cell::new(0u32));
+    let calls = counter.clone();
+
+    execution_engine.add_host_function(&module, "tick", move || calls.set(calls.get() + 1)).unwrap();
+    assert!(module.verify().is_ok());
+
+    let other_module = context.create_module("other");
+
+    assert_eq!(execution_engine.add_host_function(&other_module, "record", |value: u64| value),
+        Err("Module is not owned by this ExecutionEngine."));
+
+    unsafe {
+        let run = execution_engine.get_function::<unsafe extern "C" fn() -> u64>("run").unwrap();
+        let tick = execution_engine.get_function::<unsafe extern "C" fn()>("tick").unwrap();
+
+        assert_eq!(run.call(), 12);
+        assert_eq!(total.get(), 12);
+
+        tick.call();
+        tick.call();
+    }
+
+    assert_eq!(counter.get(), 2);
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+unsafe impl AbiSafe for Complex {
+    fn jit_field_types<'ctx>(context: &'ctx Context, target_data: &TargetData) -> Vec<BasicTypeEnum<'ctx>> {
+        vec![f64::jit_type(context, target_data), f64::jit_type(context, target_data)]
+    }
+}
+
+#[test]
+fn test_get_global() {
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let module = context.create_module("counters");
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let counters_type = i64_type.array_type(2);
+    let counters = module.add_global(counters_type, None, "counters");
+    let fn_type = i64_type.fn_type(&[], false);
+    let function = module.add_function("bump", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+
+    counters.set_initializer(&counters_type.const_zero());
+    builder.position_at_end(entry);
+
+    let counter = unsafe {
+        builder.build_in_bounds_gep(counters.as_pointer_value(), &[i64_type.const_zero(), i64_type.const_int(1, false)], "counter")
+    };
+    let value = builder.build_load(counter, "value").into_int_value();
+    let value = builder.build_int_add(value, i64_type.const_int(1, false), "value");
+
+    builder.build_store(counter, value);
+    builder.build_return(Some(&value));
+
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+
+    assert_eq!(execution_engine.get_global::<u64>(counters), Err(GlobalLookupError::SizeMismatch));
+    assert!(execution_engine.get_global_address("counters").is_ok());
+
+    let counters_ptr = execution_engine.get_global::<[u64; 2]>(counters).unwrap();
+
+    unsafe {
+        let bump = execution_engine.get_function::<unsafe extern "C" fn() -> u64>("bump").unwrap();
+
+        (*counters_ptr)[1] = 41;
+
+        assert_eq!(bump.call(), 42);
+        assert_eq!(*counters_ptr, [0, 42]);
+    }
+}
+
+#[llvm_versions(3.8..=latest)]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn test_deopt_tiered_function() {
+    use inkwell::AtomicOrdering;
+    use inkwell::deopt::{build_guard, TieredFunction};
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let module = context.create_module("tiered");
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let fn_type = i64_type.fn_type(&[i64_type.into()], false);
+
+    // Returns x * 2 + 1, so that it can be told apart from the optimized version
+    let baseline = module.add_function("double.baseline", fn_type, None);
+    let entry = context.append_basic_block(baseline, "entry");
+    let x = baseline.get_first_param().unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    let doubled = builder.build_int_mul(x, i64_type.const_int(2, false), "doubled");
+    let result = builder.build_int_add(doubled, i64_type.const_int(1, false), "result");
+
+    builder.build_return(Some(&result));
+
+    let optimized = module.add_function("double.optimized", fn_type, None);
+    let entry = context.append_basic_block(optimized, "entry");
+    let x = optimized.get_first_param().unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    let is_small = builder.build_int_compare(IntPredicate::ULT, x, i64_type.const_int(1 << 16, false), "is_small");
+
+    assert_eq!(build_guard(&builder, x, 1, baseline, &[x.into()]), Err("Guard condition must be an i1."));
+    assert_eq!(build_guard(&builder, is_small, 1, baseline, &[]), Err("Live values do not match the continuation's parameters."));
+    assert!(build_guard(&builder, is_small, 1, baseline, &[x.into()]).is_ok());
+
+    let doubled = builder.build_left_shift(x, i64_type.const_int(1, false), "doubled");
+
+    builder.build_return(Some(&doubled));
+
+    assert!(optimized.verify(true));
+
+    let double = TieredFunction::create(&module, "double", baseline).unwrap();
+
+    assert_eq!(TieredFunction::create(&module, "double", baseline), Err("Function is already defined."));
+    assert_eq!(double.get_baseline(), baseline);
+    assert!(module.verify().is_ok());
+
+    let slot_load = double.get_entry().get_first_basic_block().unwrap().get_first_instruction().unwrap();
+
+    assert_eq!(slot_load.get_atomic_ordering(), Ok(AtomicOrdering::Acquire));
+    assert_eq!(slot_load.get_alignment(), Ok(std::mem::align_of::<usize>() as u32));
+
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    let baseline_address = execution_engine.get_function_address("double.baseline").unwrap();
+
+    assert_eq!(double.get_installed_address(&execution_engine), Ok(baseline_address));
+
+    unsafe {
+        let call_double = execution_engine.get_function::<unsafe extern "C" fn(u64) -> u64>("double").unwrap();
+
+        assert_eq!(call_double.call(3), 7);
+
+        double.install(&execution_engine, optimized).unwrap();
+
+        assert_eq!(call_double.call(3), 6);
+        assert_eq!(call_double.call(1 << 20), (2 << 20) + 1);
+
+        double.revert(&execution_engine).unwrap();
+
+        assert_eq!(call_double.call(3), 7);
+        assert_eq!(double.get_installed_address(&execution_engine), Ok(baseline_address));
+    }
+}
+
+#[test]
+fn test_abi_safe_structs() {
+    type Scale = unsafe extern "C" fn(*mut Complex, f64);
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    let context = Context::create();
+    let f64_type = context.f64_type();
+    let complex_type = context.struct_type(&[f64_type.into(), f64_type.into()], false);
+    let sysv_data = TargetData::create("e-m:e-i64:64-f80:128-n8:16:32:64-S128");
+
+    assert_eq!(Complex::jit_type(&context, &sysv_data), complex_type.into());
+
+    // By value structs are lowered like C would pass them
+    let lowered = <unsafe extern "C" fn(Complex) -> Complex>::lower_signature(&context, &sysv_data, AbiTarget::X86_64SysV);
+
+    assert_eq!(<unsafe extern "C" fn(Complex) -> Complex>::fn_type(&context, &sysv_data), complex_type.fn_type(&[complex_type.into()], false));
+    assert_eq!(lowered.get_function_type(), complex_type.fn_type(&[f64_type.into(), f64_type.into()], false));
+
+    let module = context.create_module("abi_safe");
+    let builder = context.create_builder();
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    let fn_type = Scale::fn_type(&context, execution_engine.get_target_data());
+
+    assert_eq!(fn_type.get_param_types()[0], complex_type.ptr_type(AddressSpace::Generic).into());
+
+    let function = module.add_function("scale", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+    let complex = function.get_nth_param(0).unwrap().into_pointer_value();
+    let factor = function.get_nth_param(1).unwrap().into_float_value();
+
+    builder.position_at_end(entry);
+
+    for index in 0..2 {
+        let field_ptr = builder.build_struct_gep(complex, index, "field_ptr").unwrap();
+        let field = builder.build_load(field_ptr, "field").into_float_value();
+        let scaled = builder.build_float_mul(field, factor, "scaled");
+
+        builder.build_store(field_ptr, scaled);
+    }
+
+    builder.build_return(None);
+
+    let mut value = Complex { re: 1.5, im: -2. };
+
+    unsafe {
+        let scale = execution_engine.get_checked_function::<Scale>("scale").unwrap();
+
+        scale.call(&mut value, 2.);
+    }
+
+    assert_eq!(value, Complex { re: 3., im: -4. });
+}
+
+#[test]
+#[cfg(all(target_pointer_width = "64", not(feature = "llvm3-6")))]
+fn test_compiler_builtin_symbols() {
+    use inkwell::support::{add_compiler_builtin_symbols, add_symbol, search_for_address_of_symbol};
+
+    extern "C" fn host_fn() {}
+
+    add_symbol("inkwell_test_host_fn", host_fn as usize);
+
+    assert_eq!(search_for_address_of_symbol("inkwell_test_host_fn"), Some(host_fn as usize));
+    assert_eq!(search_for_address_of_symbol("inkwell_test_missing_fn"), None);
+
+    add_compiler_builtin_symbols();
+
+    assert!(search_for_address_of_symbol("__udivti3").is_some());
+
+    Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
+
+    // ((hi << 64) | lo) / divisor, which is lowered to a call to __udivti3
+    let context = Context::create();
+    let module = context.create_module("builtins");
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let i128_type = context.i128_type();
+    let fn_type = i64_type.fn_type(&[i64_type.into(), i64_type.into(), i64_type.into()], false);
+    let function = module.add_function("div128", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+    let hi = function.get_nth_param(0).unwrap().into_int_value();
+    let lo = function.get_nth_param(1).unwrap().into_int_value();
+    let divisor = function.get_nth_param(2).unwrap().into_int_value();
+
+    builder.position_at_end(entry);
+
+    let hi = builder.build_int_z_extend(hi, i128_type, "hi");
+    let hi = builder.build_left_shift(hi, i128_type.const_int(64, false), "hi_shifted");
+    let lo = builder.build_int_z_extend(lo, i128_type, "lo");
+    let dividend = builder.build_or(hi, lo, "dividend");
+    let divisor = builder.build_int_z_extend(divisor, i128_type, "divisor");
+    let quotient = builder.build_int_unsigned_div(dividend, divisor, "quotient");
+    let quotient = builder.build_int_truncate(quotient, i64_type, "truncated");
+
+    builder.build_return(Some(&quotient));
+
+    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+
+    unsafe {
+        let div128 = execution_engine.get_function::<unsafe extern "C" fn(u64, u64, u64) -> u64>("div128").unwrap();
+
+        assert_eq!(div128.call(1, 0, 1 << 32), 1 << 32);
+    }
+}
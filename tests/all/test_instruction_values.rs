@@ -499,3 +499,253 @@ fn test_metadata_kinds() {
         md_string.into(),
     ]);
 }
+
+#[test]
+fn test_instruction_metadata() {
+    let context = Context::create();
+    let module = context.create_module("annotated");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let function = module.add_function("annotated", i32_type.fn_type(&[i32_type.into()], false), None);
+
+    builder.position_at_end(context.append_basic_block(function, "entry"));
+
+    let param = function.get_first_param().unwrap().into_int_value();
+    let sum = builder.build_int_add(param, param, "sum").as_instruction_value().unwrap();
+    let cost_kind = context.get_kind_id("my_analysis.cost");
+    let origin_kind = context.get_kind_id("my_analysis.origin");
+    let cost = context.metadata_node(&[i32_type.const_int(3, false).into()]);
+    let origin = context.metadata_node(&[context.metadata_string("frontend").into()]);
+
+    assert_eq!(context.get_kind_id("my_analysis.cost"), cost_kind);
+    assert!(!sum.has_metadata());
+    assert!(sum.set_metadata(context.metadata_string("not a node"), cost_kind).is_err());
+
+    sum.set_metadata(cost, cost_kind).unwrap();
+    sum.set_metadata(origin, origin_kind).unwrap();
+
+    assert!(sum.has_metadata());
+    assert_eq!(sum.get_metadata(cost_kind), Some(cost));
+    assert_eq!(sum.get_metadata(origin_kind), Some(origin));
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                  feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0", feature = "llvm7-0")))]
+    {
+        let mut all_metadata = sum.get_all_metadata();
+
+        all_metadata.sort_by_key(|&(kind_id, _)| kind_id);
+
+        assert_eq!(all_metadata, vec![(cost_kind, cost), (origin_kind, origin)]);
+    }
+
+    sum.remove_metadata(cost_kind);
+
+    assert!(sum.get_metadata(cost_kind).is_none());
+    assert_eq!(sum.get_metadata(origin_kind), Some(origin));
+}
+
+#[llvm_versions(3.9..=latest)]
+#[test]
+fn test_instruction_subtypes() {
+    let context = Context::create();
+    let module = context.create_module("testing");
+    let builder = context.create_builder();
+
+    let i32_type = context.i32_type();
+    let i32_ptr_type = i32_type.ptr_type(AddressSpace::Generic);
+    let fn_type = context.void_type().fn_type(&[i32_ptr_type.into()], false);
+
+    let function = module.add_function("subtypes", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+    let then_block = context.append_basic_block(function, "then");
+    let else_block = context.append_basic_block(function, "else");
+
+    builder.position_at_end(entry);
+
+    let ptr = function.get_first_param().unwrap().into_pointer_value();
+    let zero = i32_type.const_zero();
+    let one = i32_type.const_int(1, false);
+
+    let store = builder.build_store(ptr, one).try_into_store_value().unwrap();
+
+    assert_eq!(store.get_value_operand(), one);
+    assert_eq!(store.get_pointer_operand(), ptr);
+    assert!(!store.is_volatile());
+
+    store.set_volatile(true);
+
+    assert!(store.is_volatile());
+    assert!(store.set_alignment(4).is_ok());
+    assert_eq!(store.get_alignment(), 4);
+    assert_eq!(store.get_atomic_ordering(), AtomicOrdering::NotAtomic);
+
+    let loaded = builder.build_load(ptr, "loaded");
+    let load = loaded.as_instruction_value().unwrap().try_into_load_value().unwrap();
+
+    assert_eq!(load.get_pointer_operand(), ptr);
+    assert!(!load.is_volatile());
+    assert!(load.as_instruction().try_into_store_value().is_none());
+    assert!(load.as_instruction().try_into_gep_value().is_none());
+
+    let gep_ptr = unsafe { builder.build_in_bounds_gep(ptr, &[one], "gep") };
+    let gep = gep_ptr.as_instruction_value().unwrap().try_into_gep_value().unwrap();
+
+    assert_eq!(gep.get_pointer_operand(), ptr);
+    assert_eq!(gep.get_indices(), vec![one.as_basic_value_enum()]);
+    assert!(gep.is_in_bounds());
+
+    gep.set_in_bounds(false);
+
+    assert!(!gep.is_in_bounds());
+
+    let result = builder.build_cmpxchg(ptr, zero, one, AtomicOrdering::AcquireRelease, AtomicOrdering::Monotonic).unwrap();
+    let cmpxchg = result.as_instruction_value().unwrap().try_into_cmpxchg_value().unwrap();
+
+    assert_eq!(cmpxchg.get_pointer_operand(), ptr);
+    assert_eq!(cmpxchg.get_compare_operand(), zero);
+    assert_eq!(cmpxchg.get_new_value_operand(), one);
+    assert!(!cmpxchg.is_weak());
+    assert_eq!(cmpxchg.get_success_ordering(), AtomicOrdering::AcquireRelease);
+    assert_eq!(cmpxchg.get_failure_ordering(), AtomicOrdering::Monotonic);
+
+    let condition = builder.build_int_compare(IntPredicate::EQ, loaded.into_int_value(), zero, "cond");
+    let branch = builder.build_conditional_branch(condition, then_block, else_block).try_into_branch_value().unwrap();
+
+    assert!(branch.is_conditional());
+    assert_eq!(branch.get_condition(), Some(condition));
+    assert_eq!(branch.get_successors(), vec![then_block, else_block]);
+    assert!(branch.as_instruction().try_into_call_site_value().is_none());
+
+    builder.position_at_end(then_block);
+
+    let jump = builder.build_unconditional_branch(else_block).try_into_branch_value().unwrap();
+
+    assert!(!jump.is_conditional());
+    assert!(jump.get_condition().is_none());
+    assert_eq!(jump.get_successors(), vec![else_block]);
+
+    builder.position_at_end(else_block);
+
+    let call = builder.build_call(function, &[ptr.into()], "call");
+    let call_instruction = call.try_as_basic_value().right().unwrap();
+
+    assert_eq!(call_instruction.try_into_call_site_value(), Some(call));
+
+    builder.build_return(None);
+
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_compare_predicate_mutation() {
+    let context = Context::create();
+    let module = context.create_module("testing");
+    let builder = context.create_builder();
+
+    let i32_type = context.i32_type();
+    let f32_type = context.f32_type();
+    let bool_type = context.bool_type();
+    let fn_type = bool_type.fn_type(&[i32_type.into(), i32_type.into(), f32_type.into()], false);
+
+    let function = module.add_function("cmp", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+
+    builder.position_at_end(entry);
+
+    let lhs = function.get_first_param().unwrap().into_int_value();
+    let rhs = function.get_nth_param(1).unwrap().into_int_value();
+    let float = function.get_nth_param(2).unwrap().into_float_value();
+
+    let icmp = builder.build_int_compare(IntPredicate::SLT, lhs, rhs, "icmp");
+    let fcmp = builder.build_float_compare(FloatPredicate::OLT, float, f32_type.const_zero(), "fcmp");
+    let and = builder.build_and(icmp, fcmp, "and");
+
+    builder.build_return(Some(&and));
+
+    let icmp = icmp.as_instruction_value().unwrap();
+    let icmp = unsafe { icmp.set_icmp_predicate(IntPredicate::SGE) }.unwrap();
+
+    assert_eq!(icmp.get_icmp_predicate(), Some(IntPredicate::SGE));
+    assert_eq!(icmp.get_operand(0).unwrap().left().unwrap(), lhs);
+    let and = and.as_instruction_value().unwrap();
+
+    assert_eq!(and.get_operand(0).unwrap().left().unwrap().as_instruction_value(), Some(icmp));
+
+    let icmp = unsafe { icmp.swap_operands() }.unwrap();
+
+    assert_eq!(icmp.get_icmp_predicate(), Some(IntPredicate::SLE));
+    assert_eq!(icmp.get_operand(0).unwrap().left().unwrap(), rhs);
+    assert_eq!(icmp.get_operand(1).unwrap().left().unwrap(), lhs);
+
+    let fcmp = fcmp.as_instruction_value().unwrap();
+    let fcmp = unsafe { fcmp.set_fcmp_predicate(FloatPredicate::UGE) }.unwrap();
+
+    assert_eq!(fcmp.get_fcmp_predicate(), Some(FloatPredicate::UGE));
+    assert_eq!(and.get_operand(1).unwrap().left().unwrap().as_instruction_value(), Some(fcmp));
+    assert!(unsafe { fcmp.set_icmp_predicate(IntPredicate::EQ) }.is_err());
+    assert!(unsafe { and.swap_operands() }.is_err());
+
+    assert_eq!(IntPredicate::ULT.get_swapped_predicate(), IntPredicate::UGT);
+    assert_eq!(IntPredicate::ULT.get_inverse_predicate(), IntPredicate::UGE);
+    assert_eq!(FloatPredicate::OLT.get_swapped_predicate(), FloatPredicate::OGT);
+    assert_eq!(FloatPredicate::OLT.get_inverse_predicate(), FloatPredicate::UGE);
+    assert_eq!(FloatPredicate::UNO.get_swapped_predicate(), FloatPredicate::UNO);
+
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(3.9..=latest)]
+#[test]
+fn test_atomic_sync_scope_and_alignment() {
+    let context = Context::create();
+    let module = context.create_module("atomics");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let i32_ptr_type = i32_type.ptr_type(AddressSpace::Generic);
+    let fn_type = context.void_type().fn_type(&[i32_ptr_type.into()], false);
+    let function = module.add_function("atomics", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+
+    builder.position_at_end(entry);
+
+    let ptr = function.get_first_param().unwrap().into_pointer_value();
+    let zero = i32_type.const_zero();
+    let one = i32_type.const_int(1, false);
+    let rmw = builder.build_atomicrmw(AtomicRMWBinOp::Add, ptr, one, AtomicOrdering::Monotonic).unwrap();
+    let cmpxchg = builder.build_cmpxchg(ptr, zero, one, AtomicOrdering::SequentiallyConsistent, AtomicOrdering::Monotonic).unwrap();
+    let load = builder.build_load(ptr, "load").as_instruction_value().unwrap();
+    let ret = builder.build_return(None);
+
+    let rmw = rmw.as_instruction_value().unwrap();
+    let cmpxchg = cmpxchg.as_instruction_value().unwrap().try_into_cmpxchg_value().unwrap();
+
+    assert!(!rmw.is_atomic_single_thread().unwrap());
+    assert!(!cmpxchg.is_single_thread());
+    assert!(load.is_atomic_single_thread().is_err());
+    assert!(ret.set_atomic_single_thread(true).is_err());
+
+    rmw.set_atomic_single_thread(true).unwrap();
+    cmpxchg.set_single_thread(true);
+    cmpxchg.set_weak(true);
+
+    assert!(rmw.is_atomic_single_thread().unwrap());
+    assert!(cmpxchg.as_instruction().is_atomic_single_thread().unwrap());
+    assert!(cmpxchg.is_weak());
+
+    load.set_atomic_ordering(AtomicOrdering::Acquire).unwrap();
+    load.set_alignment(4).unwrap();
+    load.set_atomic_single_thread(true).unwrap();
+
+    assert!(load.is_atomic_single_thread().unwrap());
+
+    #[cfg(feature = "llvm13-0")]
+    {
+        rmw.set_alignment(8).unwrap();
+        cmpxchg.set_alignment(16).unwrap();
+
+        assert_eq!(rmw.get_alignment().unwrap(), 8);
+        assert_eq!(cmpxchg.get_alignment(), 16);
+    }
+
+    assert!(module.verify().is_ok());
+}
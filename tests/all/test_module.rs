@@ -1,11 +1,12 @@
 extern crate inkwell;
 
-use self::inkwell::OptimizationLevel;
+use self::inkwell::{IntPredicate, OptimizationLevel};
 use self::inkwell::context::Context;
+use self::inkwell::declaration_cache::DeclarationCache;
 use self::inkwell::memory_buffer::MemoryBuffer;
 use self::inkwell::module::Module;
 use self::inkwell::targets::{Target, TargetTriple};
-use self::inkwell::values::AnyValue;
+use self::inkwell::values::{AnyValue, BasicValue};
 
 use std::env::temp_dir;
 use std::fs::{File, remove_file};
@@ -91,6 +92,113 @@ fn test_get_function() {
     assert_eq!(named_fn, some_fn);
 }
 
+#[test]
+fn test_module_iteration_order() {
+    let context = Context::create();
+    let module = context.create_module("my_module");
+    let i8_type = context.i8_type();
+    let fn_type = context.void_type().fn_type(&[], false);
+
+    assert_eq!(module.get_functions().count(), 0);
+    assert_eq!(module.get_named_globals().next_back(), None);
+
+    let a = module.add_function("a", fn_type, None);
+    let b = module.add_function("b", fn_type, None);
+    let c = module.add_function("c", fn_type, None);
+    let x = module.add_global(i8_type, None, "x");
+    let y = module.add_global(i8_type, None, "y");
+
+    assert_eq!(module.get_functions().collect::<Vec<_>>(), vec![a, b, c]);
+    assert_eq!(module.get_functions().rev().collect::<Vec<_>>(), vec![c, b, a]);
+
+    let mut functions = module.get_functions();
+
+    assert_eq!(functions.next(), Some(a));
+    assert_eq!(functions.next_back(), Some(c));
+    assert_eq!(functions.next(), Some(b));
+    assert_eq!(functions.next_back(), None);
+    assert_eq!(functions.next(), None);
+
+    let names: Vec<_> = module.get_named_functions().map(|(name, _)| name.to_str().unwrap()).collect();
+
+    assert_eq!(names, vec!["a", "b", "c"]);
+    assert_eq!(module.get_globals().collect::<Vec<_>>(), vec![x, y]);
+
+    let (name, global) = module.get_named_globals().next_back().unwrap();
+
+    assert_eq!(name.to_str(), Ok("y"));
+    assert_eq!(global, y);
+}
+
+#[test]
+fn test_strip_unused_declarations() {
+    let context = Context::create();
+    let module = context.create_module("my_module");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = context.void_type().fn_type(&[], false);
+    let used = module.add_function("used", fn_type, None);
+
+    module.add_function("unused", fn_type, None);
+
+    let unused_global = module.add_global(i32_type, None, "unused_global");
+    let used_global = module.add_global(i32_type, None, "used_global");
+    let caller = module.add_function("caller", fn_type, None);
+    let entry = context.append_basic_block(caller, "entry");
+
+    builder.position_at_end(entry);
+    builder.build_call(used, &[], "");
+    builder.build_load(used_global.as_pointer_value(), "");
+    builder.build_return(None);
+
+    assert_eq!(module.strip_unused_function_declarations(), 1);
+    assert!(module.get_function("unused").is_none());
+    assert!(module.get_function("used").is_some());
+    // Definitions are kept even when unused
+    assert!(module.get_function("caller").is_some());
+    assert_eq!(module.strip_unused_function_declarations(), 0);
+
+    unsafe {
+        assert!(!used_global.delete_if_unused());
+        assert!(unused_global.delete_if_unused());
+        assert!(!used.as_global_value().delete_if_unused());
+    }
+
+    assert!(module.get_global("unused_global").is_none());
+    assert!(module.get_global("used_global").is_some());
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_declaration_cache() {
+    let context = Context::create();
+    let module = context.create_module("my_module");
+    let cache = DeclarationCache::new(&module);
+    let i32_type = context.i32_type();
+    let abs_type = i32_type.fn_type(&[i32_type.into()], false);
+    let exit_type = context.void_type().fn_type(&[i32_type.into()], false);
+    let existing = module.add_function("exit", exit_type, None);
+
+    assert!(cache.is_empty());
+    assert!(cache.get_cached("abs").is_none());
+
+    let abs = cache.get_or_declare("abs", abs_type).unwrap();
+
+    assert!(abs.as_global_value().is_declaration());
+    assert_eq!(module.get_function("abs"), Some(abs));
+    assert_eq!(cache.get_or_declare("abs", abs_type), Ok(abs));
+    assert_eq!(cache.get_or_declare("exit", exit_type), Ok(existing));
+    assert!(cache.get_or_declare("abs", exit_type).is_err());
+    assert!(cache.get_or_declare("exit", abs_type).is_err());
+    assert_eq!(cache.len(), 2);
+    assert_eq!(module.get_functions().count(), 2);
+
+    cache.clear();
+
+    assert!(cache.get_cached("abs").is_none());
+    assert_eq!(cache.get_or_declare("abs", abs_type), Ok(abs));
+}
+
 #[test]
 fn test_module_owned_data_layout_disposed_safely() {
     let context = Context::create();
@@ -483,3 +591,277 @@ fn test_double_ee_from_same_module() {
 
     assert!(module.create_interpreter_execution_engine().is_err());
 }
+
+#[test]
+fn test_module_diff() {
+    use self::inkwell::module_diff::InstructionChange;
+
+    let context = Context::create();
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+    let build_module = |name: &str, extra_add: bool| {
+        let module = context.create_module(name);
+        let function = module.add_function("compute", fn_type, None);
+        let entry = context.append_basic_block(function, "entry");
+        let param = function.get_first_param().unwrap().into_int_value();
+
+        builder.position_at_end(entry);
+
+        let mut value = builder.build_int_add(param, i32_type.const_int(1, false), "");
+
+        if extra_add {
+            value = builder.build_int_add(value, i32_type.const_int(2, false), "");
+        }
+
+        let value = builder.build_int_mul(value, param, "");
+
+        builder.build_return(Some(&value));
+        module.add_global(i32_type, None, "shared");
+        module
+    };
+
+    let original = build_module("original", false);
+    let same = build_module("same", false);
+    let changed = build_module("changed", true);
+
+    assert!(original.diff(&same).is_empty());
+
+    changed.add_function("helper", fn_type, None);
+    changed.get_global("shared").unwrap().set_constant(true);
+    changed.add_global(i32_type, None, "extra");
+
+    let diff = original.diff(&changed);
+
+    assert!(!diff.is_empty());
+    assert_eq!(diff.get_added_functions(), ["helper"]);
+    assert!(diff.get_removed_functions().is_empty());
+    assert_eq!(diff.get_added_globals(), ["extra"]);
+    assert_eq!(diff.get_changed_globals(), ["shared"]);
+    assert_eq!(diff.get_changed_functions().len(), 1);
+
+    let function_diff = &diff.get_changed_functions()[0];
+
+    assert_eq!(function_diff.get_name(), "compute");
+    assert!(!function_diff.is_signature_changed());
+    assert_eq!(function_diff.get_instruction_changes(), [
+        InstructionChange::Added(1, "Add i32 (i32 %0, i32 2)".to_string()),
+        InstructionChange::Added(2, "Mul i32 (i32 %1, i32 %arg0)".to_string()),
+        InstructionChange::Added(3, "Ret void (i32 %2)".to_string()),
+        InstructionChange::Removed(1, "Mul i32 (i32 %0, i32 %arg0)".to_string()),
+        InstructionChange::Removed(2, "Ret void (i32 %1)".to_string()),
+    ]);
+
+    let reverse = changed.diff(&original);
+
+    assert_eq!(reverse.get_removed_functions(), ["helper"]);
+    assert_eq!(reverse.get_removed_globals(), ["extra"]);
+}
+
+#[test]
+fn test_module_diff_operand_order() {
+    let context = Context::create();
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+    let build_module = |swap_operands: bool, swap_targets: bool| {
+        let module = context.create_module("operands");
+        let function = module.add_function("compute", fn_type, None);
+        let entry = context.append_basic_block(function, "");
+        let then_block = context.append_basic_block(function, "");
+        let else_block = context.append_basic_block(function, "");
+        let param = function.get_first_param().unwrap().into_int_value();
+
+        builder.position_at_end(entry);
+
+        let lhs = builder.build_int_add(param, i32_type.const_int(1, false), "");
+        let rhs = builder.build_int_add(param, i32_type.const_int(2, false), "");
+        let difference = if swap_operands {
+            builder.build_int_sub(rhs, lhs, "")
+        } else {
+            builder.build_int_sub(lhs, rhs, "")
+        };
+        let is_zero = builder.build_int_compare(IntPredicate::EQ, difference, i32_type.const_zero(), "");
+
+        if swap_targets {
+            builder.build_conditional_branch(is_zero, else_block, then_block);
+        } else {
+            builder.build_conditional_branch(is_zero, then_block, else_block);
+        }
+
+        builder.position_at_end(then_block);
+        builder.build_return(Some(&difference));
+        builder.position_at_end(else_block);
+        builder.build_return(Some(&param));
+        module
+    };
+
+    let original = build_module(false, false);
+
+    assert!(original.diff(&build_module(false, false)).is_empty());
+    assert_eq!(original.diff(&build_module(true, false)).get_changed_functions().len(), 1);
+    assert_eq!(original.diff(&build_module(false, true)).get_changed_functions().len(), 1);
+}
+
+#[test]
+fn test_append_to_used() {
+    use self::inkwell::module::Linkage;
+
+    let context = Context::create();
+    let module = context.create_module("used");
+    let fn_type = context.void_type().fn_type(&[], false);
+    let function = module.add_function("keep_me", fn_type, None);
+    let global = module.add_global(context.i32_type(), None, "keep_me_too");
+
+    global.set_initializer(&context.i32_type().const_zero());
+
+    module.append_to_used(&[]).unwrap();
+
+    assert!(module.get_global("llvm.used").is_none());
+
+    module.append_to_used(&[function.as_global_value()]).unwrap();
+    module.append_to_used(&[global, function.as_global_value()]).unwrap();
+    module.append_to_compiler_used(&[global]).unwrap();
+
+    let used = module.get_global("llvm.used").unwrap();
+    let compiler_used = module.get_global("llvm.compiler.used").unwrap();
+    let used_init = used.get_initializer().unwrap().into_array_value();
+
+    assert_eq!(used.get_linkage(), Linkage::Appending);
+    assert_eq!(used.get_section().to_str(), Ok("llvm.metadata"));
+    assert_eq!(used_init.get_type().len(), 2);
+    assert_eq!(compiler_used.get_initializer().unwrap().into_array_value().get_type().len(), 1);
+    assert!(module.verify().is_ok());
+
+    // A malformed existing array is refused rather than clobbered
+    unsafe {
+        compiler_used.delete();
+    }
+
+    let bogus = module.add_global(context.i64_type(), None, "llvm.compiler.used");
+
+    bogus.set_initializer(&context.i64_type().const_zero());
+
+    assert!(module.append_to_compiler_used(&[global]).is_err());
+    assert_eq!(module.get_global("llvm.compiler.used"), Some(bogus));
+}
+
+#[test]
+fn test_append_global_ctor() {
+    let context = Context::create();
+    let module = context.create_module("ctors");
+    let builder = context.create_builder();
+    let void_fn_type = context.void_type().fn_type(&[], false);
+    let ctor = module.add_function("ctor", void_fn_type, None);
+    let dtor = module.add_function("dtor", void_fn_type, None);
+    let not_a_ctor = module.add_function("not_a_ctor", context.i32_type().fn_type(&[], false), None);
+    let data = module.add_global(context.i32_type(), None, "data");
+
+    data.set_initializer(&context.i32_type().const_zero());
+
+    for function in &[ctor, dtor] {
+        builder.position_at_end(context.append_basic_block(*function, "entry"));
+        builder.build_return(None);
+    }
+
+    assert!(module.append_global_ctor(65535, not_a_ctor, None).is_err());
+    assert!(module.get_global("llvm.global_ctors").is_none());
+
+    module.append_global_ctor(65535, ctor, None).unwrap();
+    module.append_global_ctor(101, ctor, Some(data)).unwrap();
+    module.append_global_dtor(65535, dtor, None).unwrap();
+
+    let ctors = module.get_global("llvm.global_ctors").unwrap().get_initializer().unwrap().into_array_value();
+    let dtors = module.get_global("llvm.global_dtors").unwrap().get_initializer().unwrap().into_array_value();
+
+    assert_eq!(ctors.get_type().len(), 2);
+    assert_eq!(dtors.get_type().len(), 1);
+    assert_eq!(ctors.get_type().get_element_type().into_struct_type().count_fields(), 3);
+
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(3.9..=latest)]
+#[test]
+fn test_add_musttail_thunk() {
+    use self::inkwell::{AddressSpace, CallingConvention};
+    use self::inkwell::attributes::{Attribute, AttributeLoc};
+    use self::inkwell::module::Linkage;
+
+    let context = Context::create();
+    let module = context.create_module("thunks");
+    let builder = context.create_builder();
+    let i64_type = context.i64_type();
+    let state_type = context.opaque_struct_type("State");
+    let state_ptr_type = state_type.ptr_type(AddressSpace::Generic);
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let handler_type = state_ptr_type.fn_type(&[state_ptr_type.into(), i64_type.into()], false);
+    let handler = module.add_function("op add", handler_type, Some(Linkage::Internal));
+    let noalias = context.create_enum_attribute(Attribute::get_named_enum_kind_id("noalias"), 0);
+
+    state_type.set_body(&[i64_type.into()], false);
+    handler.set_call_conventions(CallingConvention::Fast);
+    handler.add_attribute(AttributeLoc::Param(0), noalias);
+
+    builder.position_at_end(context.append_basic_block(handler, "entry"));
+    builder.build_return(Some(&handler.get_first_param().unwrap()));
+
+    let wrong_count_type = i64_type.fn_type(&[i8_ptr_type.into()], false);
+    let wrong_param_type = state_ptr_type.fn_type(&[i64_type.into(), i64_type.into()], false);
+
+    assert!(module.add_musttail_thunk("bad", wrong_count_type, handler).is_err());
+    assert!(module.add_musttail_thunk("bad", wrong_param_type, handler).is_err());
+    assert!(module.add_musttail_thunk("op add", handler_type, handler).is_err());
+
+    let erased_type = i8_ptr_type.fn_type(&[i8_ptr_type.into(), i64_type.into()], false);
+    let thunk = module.add_musttail_thunk("op add erased", erased_type, handler).unwrap();
+
+    assert_eq!(thunk.get_type(), erased_type);
+    assert_eq!(thunk.get_call_conventions(), CallingConvention::Fast);
+    assert_eq!(thunk.attributes(AttributeLoc::Param(0)), vec![noalias]);
+    assert_eq!(handler.get_linkage(), Linkage::Internal);
+    assert_eq!(module.get_function("op add"), Some(handler));
+    assert!(thunk.print_to_string().to_string().contains("musttail call"));
+    assert!(module.verify().is_ok());
+
+    // Parameter and return types which are the same are passed along unchanged
+    let same_thunk = module.add_musttail_thunk("op add same", handler_type, handler).unwrap();
+
+    assert_eq!(same_thunk.get_type(), handler_type);
+    assert!(module.verify().is_ok());
+}
+
+#[test]
+fn test_stable_hash() {
+    fn build_increment<'ctx>(context: &'ctx Context, module_name: &str, names: [&str; 3], addend: u64) -> Module<'ctx> {
+        let module = context.create_module(module_name);
+        let builder = context.create_builder();
+        let i32_type = context.i32_type();
+        let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+        let function = module.add_function("increment", fn_type, None);
+        let entry = context.append_basic_block(function, names[0]);
+        let param = function.get_first_param().unwrap().into_int_value();
+
+        param.set_name(names[1]);
+        builder.position_at_end(entry);
+
+        let result = builder.build_int_add(param, i32_type.const_int(addend, false), names[2]);
+
+        builder.build_return(Some(&result));
+
+        module
+    }
+
+    let context = Context::create();
+    let module = build_increment(&context, "first", ["entry", "x", "sum"], 1);
+    let renamed = build_increment(&context, "second", ["", "", ""], 1);
+    let changed = build_increment(&context, "first", ["entry", "x", "sum"], 2);
+
+    assert_eq!(module.stable_hash(), module.stable_hash());
+    assert_eq!(module.stable_hash(), renamed.stable_hash());
+    assert_ne!(module.stable_hash(), changed.stable_hash());
+
+    let param = module.get_function("increment").unwrap().get_first_param().unwrap();
+
+    assert_eq!(param.into_int_value().get_name().to_str(), Ok("x"));
+}
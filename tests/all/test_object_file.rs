@@ -2,12 +2,13 @@ extern crate inkwell;
 
 use self::inkwell::context::Context;
 use self::inkwell::module::Module;
+use self::inkwell::stack_map::{Location, StackMap};
 use self::inkwell::targets::{
     CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
 };
 use self::inkwell::types::IntType;
 use self::inkwell::values::BasicValue;
-use self::inkwell::OptimizationLevel;
+use self::inkwell::{AddressSpace, OptimizationLevel};
 
 #[llvm_versions(7.0..=latest)]
 fn get_host_cpu_name() -> String {
@@ -244,3 +245,54 @@ fn test_section_contains_nul() {
     }
     assert!(has_section_test);
 }
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn test_stack_map_section() {
+    let target_machine = get_native_target_machine();
+
+    let context = Context::create();
+    let mut module = context.create_module("test_stack_map_section");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let i64_type = context.i64_type();
+    let fn_type = context.void_type().fn_type(&[i64_type.into()], false);
+    let function = module.add_function("deopt", fn_type, None);
+    let entry = context.append_basic_block(function, "entry");
+    let param = function.get_first_param().unwrap();
+    let target = context.i8_type().ptr_type(AddressSpace::Generic).const_null();
+
+    builder.position_at_end(entry);
+    builder.build_stackmap(7, 0, &[param, i64_type.const_int(0x1234_5678_9ABC, false).into(), i32_type.const_int(5, false).into()]).unwrap();
+    builder.build_patchpoint(8, 16, target, &[], &[param], None, "").unwrap();
+    assert_eq!(builder.build_patchpoint(9, 16, target, &[], &[], Some(i32_type), ""), Err("Patchpoints may only return an i64."));
+    builder.build_return(None);
+
+    assert!(function.verify(true));
+
+    apply_target_to_module(&target_machine, &module);
+
+    let memory_buffer = target_machine
+        .write_to_memory_buffer(&mut module, FileType::Object)
+        .unwrap();
+    let object_file = memory_buffer.create_object_file().unwrap();
+    let mut sections = object_file.get_sections();
+    let section = sections
+        .find(|section| section.get_name().map_or(false, |name| name.to_bytes().ends_with(b"__llvm_stackmaps")))
+        .expect("Stack map section should have been emitted");
+    let stack_map = StackMap::parse(section.get_contents()).unwrap();
+
+    assert_eq!(stack_map.get_functions().len(), 1);
+    assert_eq!(stack_map.get_records().len(), 2);
+
+    let stackmap = stack_map.get_record(7).unwrap();
+    let locations = stackmap.get_locations();
+
+    assert_eq!(locations.len(), 3);
+    assert_eq!(stack_map.get_constant_value(&locations[1]), Some(0x1234_5678_9ABC));
+    assert_eq!(locations[2], Location::Constant(5));
+    assert_eq!(stack_map.get_constant_value(&locations[2]), Some(5));
+    assert_eq!(stack_map.get_record(8).unwrap().get_locations().len(), 1);
+    assert!(stack_map.get_record(9).is_none());
+    assert!(StackMap::parse(&section.get_contents()[..8]).is_err());
+}
@@ -179,3 +179,221 @@ fn test_pass_registry() {
                   feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0")))]
     pass_registry.initialize_aggressive_inst_combiner();
 }
+
+// Prior to LLVM 7, pass timings are only reported when LLVM is shut down
+#[llvm_versions(7.0..=latest)]
+#[test]
+fn test_statistics_report_from_pass_manager() {
+    use self::inkwell::support::statistics::{StatisticsConfig, StatisticsReport};
+    use std::env;
+    use std::fs;
+    use std::process::Command;
+
+    const OUTPUT_PATH_VAR: &str = "INKWELL_TEST_STATISTICS_OUTPUT";
+
+    // LLVM only writes the pass timing report when the process exits, and its options can only
+    // be set once per process, so the passes are run by a child process running just this test
+    if let Some(output_path) = env::var_os(OUTPUT_PATH_VAR) {
+        StatisticsConfig::new(output_path).time_passes(true).enable();
+
+        let context = Context::create();
+        let module = context.create_module("stats");
+        let builder = context.create_builder();
+        let i32_type = context.i32_type();
+        let function = module.add_function("identity", i32_type.fn_type(&[i32_type.into()], false), None);
+
+        builder.position_at_end(context.append_basic_block(function, "entry"));
+
+        let x = function.get_first_param().unwrap().into_int_value();
+        let sum = builder.build_int_add(x, i32_type.const_zero(), "sum");
+
+        builder.build_return(Some(&sum));
+
+        let pass_manager = PassManager::create(());
+
+        pass_manager.add_instruction_combining_pass();
+        pass_manager.run_on(&module);
+
+        return;
+    }
+
+    let output_path = env::temp_dir().join(format!("inkwell_statistics_{}.txt", std::process::id()));
+    let _ = fs::remove_file(&output_path);
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(&["test_statistics_report_from_pass_manager", "--test-threads=1", "--quiet"])
+        .env(OUTPUT_PATH_VAR, &output_path)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    let report = StatisticsReport::read_from_path(&output_path).unwrap();
+
+    fs::remove_file(&output_path).unwrap();
+
+    assert!(report.get_pass_timings().iter().any(|timing| timing.pass == "Combine redundant instructions" && timing.kind == "wall"));
+}
+
+#[test]
+fn test_promote_allocas() {
+    use self::inkwell::passes::promote_allocas;
+    use self::inkwell::values::{FunctionValue, InstructionOpcode};
+
+    let context = Context::create();
+    let module = context.create_module("my_module");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let pair_type = context.struct_type(&[i32_type.into(), i32_type.into()], false);
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    let count_allocas = |function: FunctionValue| {
+        let mut count = 0;
+        let mut instruction = function.get_first_basic_block().unwrap().get_first_instruction();
+
+        while let Some(inst) = instruction {
+            if inst.get_opcode() == InstructionOpcode::Alloca {
+                count += 1;
+            }
+
+            instruction = inst.get_next_instruction();
+        }
+
+        count
+    };
+
+    let mut functions = Vec::new();
+
+    for name in &["scalar", "aggregate"] {
+        let function = module.add_function(name, fn_type, None);
+        let lhs = function.get_nth_param(0).unwrap().into_int_value();
+        let rhs = function.get_nth_param(1).unwrap().into_int_value();
+
+        builder.position_at_end(context.append_basic_block(function, "entry"));
+
+        let pair = builder.build_alloca(pair_type, "pair");
+        let first = builder.build_struct_gep(pair, 0, "first").unwrap();
+        let second = builder.build_struct_gep(pair, 1, "second").unwrap();
+
+        builder.build_store(first, lhs);
+        builder.build_store(second, rhs);
+
+        let sum = builder.build_int_add(
+            builder.build_load(first, "lhs").into_int_value(),
+            builder.build_load(second, "rhs").into_int_value(),
+            "sum",
+        );
+        let slot = builder.build_alloca(i32_type, "slot");
+
+        builder.build_store(slot, sum);
+        builder.build_return(Some(&builder.build_load(slot, "result")));
+
+        functions.push(function);
+    }
+
+    assert_eq!(count_allocas(functions[0]), 2);
+    assert!(promote_allocas(functions[0], false));
+    // Only the scalar alloca is promoted without splitting the aggregate
+    assert_eq!(count_allocas(functions[0]), 1);
+
+    assert!(promote_allocas(functions[1], true));
+    assert_eq!(count_allocas(functions[1]), 0);
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(3.9..=latest)]
+#[test]
+fn test_outline_instructions() {
+    use self::inkwell::passes::outline_instructions;
+
+    let context = Context::create();
+    let module = context.create_module("my_module");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    let function = module.add_function("outer", fn_type, None);
+    let x = function.get_first_param().unwrap().into_int_value();
+    let y = function.get_nth_param(1).unwrap().into_int_value();
+
+    builder.position_at_end(context.append_basic_block(function, "entry"));
+
+    let doubled = builder.build_int_add(x, x, "doubled");
+    let sum = builder.build_int_add(doubled, y, "sum");
+    let product = builder.build_int_mul(sum, y, "product");
+    let result = builder.build_int_sub(product, sum, "result");
+
+    builder.build_return(Some(&result));
+
+    let first = sum.as_instruction().unwrap();
+    let ret = result.as_instruction().unwrap().get_next_instruction().unwrap();
+
+    assert!(outline_instructions(product.as_instruction().unwrap(), first, "backwards").is_err());
+    assert!(outline_instructions(first, ret, "with_terminator").is_err());
+
+    // Outlining sum and product makes doubled and y parameters and returns both values
+    let (outlined, call) = outline_instructions(first, product.as_instruction().unwrap(), "outer.outlined").unwrap();
+
+    assert_eq!(outlined.count_params(), 2);
+    assert_eq!(outlined.count_basic_blocks(), 1);
+    assert!(outlined.get_type().get_return_type().unwrap().is_struct_type());
+    assert_eq!(call.get_called_fn_value(), outlined);
+    assert_eq!(sum.as_instruction().unwrap().get_parent().unwrap().get_parent(), Some(outlined));
+    assert!(module.verify().is_ok());
+
+    // A single value used after the region is returned directly
+    let (outlined, _) = outline_instructions(doubled.as_instruction().unwrap(), doubled.as_instruction().unwrap(), "doubled.outlined").unwrap();
+
+    assert_eq!(outlined.get_type().get_return_type().unwrap().into_int_type(), i32_type);
+    assert_eq!(outlined.count_params(), 1);
+    assert!(module.verify().is_ok());
+}
+
+#[llvm_versions(3.9..=latest)]
+#[test]
+fn test_inline_call_site() {
+    use self::inkwell::passes::inline_call_site;
+    use self::inkwell::values::InstructionOpcode;
+
+    let context = Context::create();
+    let module = context.create_module("my_module");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into()], false);
+    let square = module.add_function("square", fn_type, None);
+    let external = module.add_function("external", fn_type, None);
+    let caller = module.add_function("caller", fn_type, None);
+
+    builder.position_at_end(context.append_basic_block(square, "entry"));
+
+    let x = square.get_first_param().unwrap().into_int_value();
+
+    builder.build_return(Some(&builder.build_int_mul(x, x, "squared")));
+    builder.position_at_end(context.append_basic_block(caller, "entry"));
+
+    let param = caller.get_first_param().unwrap();
+    let external_call = builder.build_call(external, &[param.into()], "external_call");
+    let call = builder.build_call(square, &[param.into()], "call");
+    let kept_call = builder.build_call(square, &[param.into()], "kept_call");
+    let always_inline_call = builder.build_call(square, &[param.into()], "always_inline_call");
+
+    kept_call.set_no_inline(true);
+    always_inline_call.set_always_inline(true);
+    builder.build_return(Some(&call.try_as_basic_value().left().unwrap()));
+
+    assert!(kept_call.is_no_inline());
+    assert!(inline_call_site(external_call).is_err());
+    assert!(inline_call_site(call).unwrap());
+    assert!(module.verify().is_ok());
+
+    // Other call sites marked alwaysinline are neither inlined nor stripped of their mark
+    assert!(always_inline_call.is_always_inline());
+    assert!(inline_call_site(always_inline_call).unwrap());
+
+    // Only the call marked noinline is left
+    let entry = caller.get_first_basic_block().unwrap();
+    let opcodes: Vec<_> = std::iter::successors(entry.get_first_instruction(), |instruction| instruction.get_next_instruction())
+        .map(|instruction| instruction.get_opcode())
+        .collect();
+
+    assert_eq!(opcodes.iter().filter(|&&opcode| opcode == InstructionOpcode::Call).count(), 2);
+    assert!(opcodes.contains(&InstructionOpcode::Mul));
+}
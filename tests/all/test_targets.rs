@@ -1,10 +1,13 @@
 use inkwell::{AddressSpace, OptimizationLevel};
+use inkwell::abi::{AbiTarget, ArgAbi};
 use inkwell::context::Context;
-use inkwell::targets::{ByteOrdering, CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetData, TargetMachine, TargetTriple};
+use inkwell::targets::{ByteOrdering, CodeModel, CpuFeatures, FieldLayout, FileType, InitializationConfig, RelocMode, Target, TargetData, TargetMachine, TargetTriple};
 
 use regex::Regex;
 
+use std::convert::TryFrom;
 use std::env::temp_dir;
+use std::ffi::CStr;
 use std::fs::{File, remove_file};
 use std::io::Read;
 use std::str::from_utf8;
@@ -161,6 +164,87 @@ fn test_default_triple() {
     // TODO: CFG for other supported major OSes
 }
 
+#[test]
+fn test_target_triple_components() {
+    let triple = TargetTriple::try_from("x86_64-pc-linux-gnu").unwrap();
+
+    assert_eq!(triple, TargetTriple::create("x86_64-pc-linux-gnu"));
+    assert_eq!(TargetTriple::try_from("x86_64-pc-linux-gnu\0"), Ok(TargetTriple::create("x86_64-pc-linux-gnu")));
+    assert!(TargetTriple::try_from("x86_64\0-pc-linux-gnu").is_err());
+    assert_eq!(triple.get_arch(), Some("x86_64"));
+    assert_eq!(triple.get_vendor(), Some("pc"));
+    assert_eq!(triple.get_os(), Some("linux"));
+    assert_eq!(triple.get_environment(), Some("gnu"));
+
+    let triple = TargetTriple::from(CStr::from_bytes_with_nul(b"aarch64-apple-darwin\0").unwrap());
+
+    assert_eq!(triple.get_arch(), Some("aarch64"));
+    assert_eq!(triple.get_os(), Some("darwin"));
+    assert_eq!(triple.get_environment(), None);
+    assert_eq!(TargetTriple::create("").get_arch(), None);
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                  feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0")))]
+    {
+        let short = TargetTriple::create("x86_64-linux-gnu");
+
+        assert_eq!(short.get_vendor(), Some("linux"));
+        assert_eq!(short.normalize().as_str().to_str(), Ok("x86_64-unknown-linux-gnu"));
+        assert_eq!(short.normalize().get_vendor(), Some("unknown"));
+        assert_ne!(short, TargetTriple::create("x86_64-unknown-linux-gnu"));
+        assert!(short.is_equivalent(&TargetTriple::create("x86_64-unknown-linux-gnu")));
+        assert!(!short.is_equivalent(&TargetTriple::create("aarch64-unknown-linux-gnu")));
+    }
+}
+
+#[test]
+fn test_cpu_features() {
+    let mut features = CpuFeatures::parse(" +sse4.2,,-avx , +avx").unwrap();
+
+    assert_eq!(features.len(), 2);
+    assert_eq!(features.is_enabled("sse4.2"), Some(true));
+    assert_eq!(features.is_enabled("avx"), Some(true));
+    assert_eq!(features.is_enabled("bmi2"), None);
+
+    features.disable("sse4.2").enable("bmi2");
+
+    assert_eq!(features.to_string(), "-sse4.2,+avx,+bmi2");
+    assert!(features.remove("avx"));
+    assert!(!features.remove("avx"));
+    assert_eq!(features.iter().collect::<Vec<_>>(), vec![("sse4.2", false), ("bmi2", true)]);
+    assert_eq!("-sse4.2,+bmi2".parse::<CpuFeatures>(), Ok(features));
+
+    assert!(CpuFeatures::parse("sse4.2").is_err());
+    assert!(CpuFeatures::parse("+").is_err());
+    assert!(CpuFeatures::parse("").unwrap().is_empty());
+
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9",
+                  feature = "llvm4-0", feature = "llvm5-0", feature = "llvm6-0")))]
+    {
+        let host = CpuFeatures::host();
+
+        assert_eq!(host.to_string().parse::<CpuFeatures>(), Ok(host));
+    }
+}
+
+#[test]
+fn test_mangled_names() {
+    let mangled_name = |data_layout: &str, name: &str| TargetData::create(data_layout).get_data_layout().get_mangled_name(name);
+
+    assert_eq!(mangled_name("e-m:e-i64:64-n8:16:32:64-S128", "main"), "main");
+    assert_eq!(mangled_name("e-m:o-i64:64-n8:16:32:64-S128", "main"), "_main");
+    assert_eq!(mangled_name("e-m:o-i64:64-n8:16:32:64-S128", "\u{1}verbatim"), "verbatim");
+    assert_eq!(mangled_name("e-i64:64", "main"), "main");
+
+    // The x86 Windows mangling mode was split out of the generic Windows one later on
+    #[cfg(not(any(feature = "llvm3-6", feature = "llvm3-7", feature = "llvm3-8", feature = "llvm3-9", feature = "llvm4-0")))]
+    {
+        assert_eq!(mangled_name("e-m:w-i64:64-n8:16:32:64-S128", "main"), "main");
+        assert_eq!(mangled_name("e-m:x-p:32:32-i64:64-n8:16:32-a:0:32-S32", "main"), "_main");
+        assert_eq!(mangled_name("e-m:x-p:32:32-i64:64-n8:16:32-a:0:32-S32", "?main@@YAHXZ"), "?main@@YAHXZ");
+    }
+}
+
 #[test]
 fn test_target_data() {
     Target::initialize_native(&InitializationConfig::default()).expect("Failed to initialize native target");
@@ -306,6 +390,39 @@ fn test_ptr_sized_int() {
     assert_eq!(int_type4.get_bit_width(), target_data.get_pointer_byte_size(Some(address_space)) * 8);
 }
 
+#[test]
+fn test_struct_layout() {
+    let context = Context::create();
+    let target_data = TargetData::create("e-m:e-i64:64-f80:128-n8:16:32:64-S128");
+    let i8_type = context.i8_type();
+    let i16_type = context.i16_type();
+    let i64_type = context.i64_type();
+    let struct_type = context.struct_type(&[i8_type.into(), i64_type.into(), i16_type.into()], false);
+    let layout = target_data.get_struct_layout(&struct_type).unwrap();
+
+    assert_eq!(layout.size, 24);
+    assert_eq!(layout.alignment, 8);
+    assert_eq!(layout.trailing_padding, 6);
+    assert_eq!(layout.fields, vec![
+        FieldLayout { offset: 0, size: 1, alignment: 1, padding_before: 0 },
+        FieldLayout { offset: 8, size: 8, alignment: 8, padding_before: 7 },
+        FieldLayout { offset: 16, size: 2, alignment: 2, padding_before: 0 },
+    ]);
+
+    let packed_type = context.struct_type(&[i8_type.into(), i64_type.into()], true);
+    let layout = target_data.get_struct_layout(&packed_type).unwrap();
+
+    assert_eq!(layout.size, 9);
+    assert_eq!(layout.alignment, 1);
+    assert_eq!(layout.fields[1].offset, 1);
+    assert_eq!(layout.fields[1].padding_before, 0);
+    assert_eq!(layout.trailing_padding, 0);
+
+    let opaque_type = context.opaque_struct_type("opaque");
+
+    assert!(target_data.get_struct_layout(&opaque_type).is_none());
+}
+
 #[test]
 fn test_write_target_machine_to_file() {
     Target::initialize_x86(&InitializationConfig::default());
@@ -383,3 +500,94 @@ fn test_write_target_machine_to_memory_buffer() {
     assert!(string.contains("my_module"));
     assert!(string.contains(".section"));
 }
+
+#[test]
+fn test_abi_lowering() {
+    let context = Context::create();
+    let i8_type = context.i8_type();
+    let i32_type = context.i32_type();
+    let i64_type = context.i64_type();
+    let f32_type = context.f32_type();
+    let f64_type = context.f64_type();
+    let void_type = context.void_type();
+    let target_data = TargetData::create("e-m:e-i64:64-f80:128-n8:16:32:64-S128");
+
+    assert_eq!(AbiTarget::from_triple(&TargetTriple::create("x86_64-unknown-linux-gnu")), Some(AbiTarget::X86_64SysV));
+    assert_eq!(AbiTarget::from_triple(&TargetTriple::create("x86_64-pc-windows-msvc")), Some(AbiTarget::X86_64Windows));
+    assert_eq!(AbiTarget::from_triple(&TargetTriple::create("arm64-apple-darwin")), Some(AbiTarget::AArch64));
+    assert_eq!(AbiTarget::from_triple(&TargetTriple::create("riscv64-unknown-linux-gnu")), None);
+
+    let small = context.struct_type(&[i32_type.into(), i8_type.into()], false);
+    let floats = context.struct_type(&[f32_type.into(), f32_type.into(), f64_type.into()], false);
+    let big = context.struct_type(&[i64_type.into(), i64_type.into(), i64_type.into()], false);
+    let empty = context.struct_type(&[], false);
+
+    // x86-64 System V
+    let sysv = AbiTarget::X86_64SysV;
+    let lowered = sysv.lower_signature(&context, &target_data, Some(big.into()), &[small.into(), floats.into(), empty.into(), big.into(), i32_type.into()]);
+    let ptr_type = big.ptr_type(AddressSpace::Generic);
+
+    assert_eq!(*lowered.get_return_abi(), ArgAbi::Indirect { byval: false });
+    assert_eq!(lowered.get_arg_abis(), &[
+        ArgAbi::Direct(i64_type.into()),
+        ArgAbi::Split(vec![f32_type.vec_type(2).into(), f64_type.into()]),
+        ArgAbi::Ignore,
+        ArgAbi::Indirect { byval: true },
+        ArgAbi::Direct(i32_type.into()),
+    ]);
+    assert_eq!(lowered.get_param_index(1), Some(2));
+    assert_eq!(lowered.get_param_index(2), None);
+    assert_eq!(lowered.get_param_index(3), Some(4));
+    assert_eq!(lowered.get_function_type(), void_type.fn_type(&[
+        ptr_type.into(),
+        i64_type.into(),
+        f32_type.vec_type(2).into(),
+        f64_type.into(),
+        ptr_type.into(),
+        i32_type.into(),
+    ], false));
+
+    let lowered = sysv.lower_signature(&context, &target_data, Some(floats.into()), &[]);
+    let returned = context.struct_type(&[f32_type.vec_type(2).into(), f64_type.into()], false);
+
+    assert_eq!(lowered.get_function_type(), returned.fn_type(&[], false));
+
+    // Windows x64
+    let win64 = AbiTarget::X86_64Windows;
+    let lowered = win64.lower_signature(&context, &target_data, Some(small.into()), &[floats.into()]);
+
+    assert_eq!(*lowered.get_return_abi(), ArgAbi::Direct(i64_type.into()));
+    assert_eq!(lowered.get_arg_abis(), &[ArgAbi::Indirect { byval: false }]);
+
+    // AArch64
+    let aarch64 = AbiTarget::AArch64;
+    let hfa = context.struct_type(&[f32_type.into(), f32_type.into(), f32_type.into()], false);
+    let lowered = aarch64.lower_signature(&context, &target_data, None, &[hfa.into(), small.into(), floats.into(), big.into()]);
+
+    assert_eq!(lowered.get_arg_abis(), &[
+        ArgAbi::Direct(f32_type.array_type(3).into()),
+        ArgAbi::Direct(i64_type.into()),
+        ArgAbi::Direct(i64_type.array_type(2).into()),
+        ArgAbi::Indirect { byval: false },
+    ]);
+}
+
+#[test]
+fn test_initialize_all_parts() {
+    let config = InitializationConfig {
+        asm_parser: false,
+        asm_printer: false,
+        disassembler: false,
+        ..InitializationConfig::default()
+    };
+
+    Target::initialize_native(&config).expect("Failed to initialize native target");
+    Target::initialize_all_asm_parsers();
+    Target::initialize_all_asm_printers();
+    Target::initialize_all_disassemblers();
+
+    let target = Target::from_triple(&TargetMachine::get_default_triple()).unwrap();
+
+    assert!(target.has_target_machine());
+    assert!(target.has_asm_backend());
+}
@@ -1,7 +1,7 @@
 use inkwell::AddressSpace;
 use inkwell::context::Context;
 use inkwell::values::AnyValue;
-use inkwell::types::BasicType;
+use inkwell::types::{AnyType, BasicMetadataTypeEnum, BasicType, TypeDescriptor};
 
 #[test]
 fn test_struct_type() {
@@ -120,6 +120,43 @@ fn test_function_type() {
     assert_eq!(*fn_type.get_context(), context);
 }
 
+#[test]
+fn test_function_type_derivation() {
+    let context = Context::create();
+    let void_type = context.void_type();
+    let int = context.i8_type();
+    let float = context.f32_type();
+    let fn_type = int.fn_type(&[int.into(), float.into()], true);
+
+    assert_eq!(fn_type.get_any_return_type().into_int_type(), int);
+    assert_eq!(void_type.fn_type(&[], false).get_any_return_type().into_void_type(), void_type);
+
+    let param_types: Vec<_> = fn_type.get_param_type_iter().rev().collect();
+
+    assert_eq!(param_types, vec![float.into(), int.into()]);
+    assert_eq!(fn_type.get_param_type_iter().len(), 2);
+    assert_eq!(void_type.fn_type(&[], false).get_param_type_iter().next(), None);
+
+    // Too many params to be kept inline by the iterator
+    let many_params: Vec<BasicMetadataTypeEnum> = (0..20).map(|i| if i % 2 == 0 { int.into() } else { float.into() }).collect();
+    let mut many_param_types = void_type.fn_type(&many_params, false).get_param_type_iter();
+
+    assert_eq!(many_param_types.len(), 20);
+    assert_eq!(many_param_types.next(), Some(int.into()));
+    assert_eq!(many_param_types.next_back(), Some(float.into()));
+    assert_eq!(many_param_types.len(), 18);
+
+    let replaced_params = fn_type.with_params_replaced(&[float.into()]);
+
+    assert_eq!(replaced_params, int.fn_type(&[float.into()], true));
+
+    let void_fn_type = fn_type.with_return_replaced(None);
+
+    assert_eq!(void_fn_type, void_type.fn_type(&[int.into(), float.into()], true));
+    assert!(void_fn_type.get_return_type().is_none());
+    assert_eq!(void_fn_type.with_return_replaced(Some(float.into())), float.fn_type(&[int.into(), float.into()], true));
+}
+
 #[test]
 fn test_sized_types() {
     unsafe {
@@ -380,3 +417,76 @@ fn test_no_vector_zero() {
     let int = context.i32_type();
     int.vec_type(0);
 }
+
+#[test]
+fn test_type_descriptor() {
+    let node = TypeDescriptor::NamedStruct {
+        name: "node".into(),
+        fields: Some(vec![
+            TypeDescriptor::Int(32),
+            TypeDescriptor::Vector { element: Box::new(TypeDescriptor::F32), len: 4 },
+            TypeDescriptor::Pointer {
+                pointee: Box::new(TypeDescriptor::NamedStruct { name: "node".into(), fields: None, packed: false }),
+                address_space: 0,
+            },
+        ]),
+        packed: false,
+    };
+    let fn_descriptor = TypeDescriptor::Function {
+        return_type: Box::new(TypeDescriptor::Void),
+        params: vec![
+            TypeDescriptor::Pointer { pointee: Box::new(node.clone()), address_space: 1 },
+            TypeDescriptor::Array { element: Box::new(TypeDescriptor::F64), len: 3 },
+            TypeDescriptor::Struct { fields: vec![TypeDescriptor::Int(1), TypeDescriptor::F16], packed: true },
+        ],
+        is_var_arg: true,
+    };
+
+    let context1 = Context::create();
+    let context2 = Context::create();
+    let fn_type1 = fn_descriptor.materialize(&context1).into_function_type();
+    let fn_type2 = fn_descriptor.materialize(&context2).into_function_type();
+
+    assert_eq!(*fn_type1.get_context(), context1);
+    assert_eq!(*fn_type2.get_context(), context2);
+    assert_eq!(fn_type1.print_to_string(), fn_type2.print_to_string());
+    assert!(fn_type1.is_var_arg());
+    assert_eq!(fn_type1.count_param_types(), 3);
+    assert_eq!(TypeDescriptor::from_type(fn_type1), fn_descriptor);
+    assert_eq!(TypeDescriptor::from_type(fn_type2), fn_descriptor);
+
+    #[cfg(any(feature = "llvm12-0", feature = "llvm13-0"))]
+    {
+        // Named structs are reused rather than redefined as "node.0"
+        let node_type = context1.get_struct_type("node").unwrap();
+
+        assert_eq!(node.materialize(&context1).into_struct_type(), node_type);
+        assert_eq!(node_type.get_field_types()[2].into_pointer_type().get_element_type().into_struct_type(), node_type);
+    }
+
+    let opaque = TypeDescriptor::NamedStruct { name: "opaque".into(), fields: None, packed: false };
+    let opaque_type = opaque.materialize(&context1).into_struct_type();
+
+    assert!(opaque_type.is_opaque());
+    assert_eq!(TypeDescriptor::from_type(opaque_type), opaque);
+}
+
+#[test]
+#[should_panic]
+fn test_type_descriptor_void_pointer() {
+    let context = Context::create();
+    let void_ptr = TypeDescriptor::Pointer { pointee: Box::new(TypeDescriptor::Void), address_space: 0 };
+
+    void_ptr.materialize(&context);
+}
+
+#[llvm_versions(11.0..=latest)]
+#[test]
+fn test_type_descriptor_bfloat() {
+    let context = Context::create();
+    let bf16_vector = TypeDescriptor::Vector { element: Box::new(TypeDescriptor::BF16), len: 4 };
+    let bf16_vector_type = bf16_vector.materialize(&context);
+
+    assert_eq!(bf16_vector_type.print_to_string().to_string(), "<4 x bfloat>");
+    assert_eq!(TypeDescriptor::from_type(bf16_vector_type), bf16_vector);
+}
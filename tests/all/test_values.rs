@@ -1,4 +1,4 @@
-use inkwell::{DLLStorageClass, FloatPredicate, GlobalVisibility, ThreadLocalMode, AddressSpace};
+use inkwell::{CallingConvention, DLLStorageClass, FloatPredicate, GlobalVisibility, ThreadLocalMode, AddressSpace};
 use inkwell::attributes::AttributeLoc;
 use inkwell::context::Context;
 use inkwell::module::Linkage::*;
@@ -53,11 +53,23 @@ fn test_call_site() {
 
     assert!(!call_site.is_tail_call());
 
-    assert_eq!(call_site.get_call_convention(), 0);
+    assert_eq!(call_site.get_call_convention(), CallingConvention::C);
 
-    call_site.set_call_convention(2);
+    call_site.set_call_convention(CallingConvention::Fast);
 
-    assert_eq!(call_site.get_call_convention(), 2);
+    assert_eq!(call_site.get_call_convention(), CallingConvention::Fast);
+
+    call_site.set_call_convention(CallingConvention::Other(1234));
+
+    assert_eq!(call_site.get_call_convention(), CallingConvention::Other(1234));
+
+    function.set_call_conventions(CallingConvention::Win64);
+
+    assert_eq!(function.get_call_conventions(), CallingConvention::Win64);
+    assert_eq!(CallingConvention::from(79), CallingConvention::Win64);
+    assert_eq!(u32::from(CallingConvention::Tail), 18);
+    assert_eq!(CallingConvention::Other(8), CallingConvention::Fast);
+    assert_ne!(CallingConvention::Other(9), CallingConvention::Fast);
 
     call_site.set_alignment_attribute(AttributeLoc::Return, 16);
 }
@@ -607,6 +619,23 @@ fn test_floats() {
     assert!(f64_one.const_compare(FloatPredicate::UNO, f64_two).is_null());
 }
 
+#[test]
+fn test_param_iter_size_hint() {
+    let context = Context::create();
+    let module = context.create_module("my_mod");
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
+    let fn_value = module.add_function("three_params", fn_type, None);
+    let mut params = fn_value.get_param_iter();
+
+    assert_eq!(params.len(), 3);
+    assert!(params.next().is_some());
+    assert_eq!(params.size_hint(), (2, Some(2)));
+    assert_eq!(params.by_ref().count(), 2);
+    assert_eq!(params.len(), 0);
+    assert!(params.next().is_none());
+}
+
 #[test]
 fn test_function_value_no_params() {
     let context = Context::create();
@@ -618,6 +647,7 @@ fn test_function_value_no_params() {
     assert_eq!(fn_value.get_type(), fn_type);
     assert_eq!(fn_value.count_params(), 0);
     assert_eq!(fn_value.get_param_iter().collect::<Vec<_>>().len(), 0);
+    assert_eq!(fn_value.get_param_iter().len(), 0);
     assert_eq!(fn_value.get_params().len(), 0);
     assert!(fn_value.get_first_param().is_none());
     assert!(fn_value.get_last_param().is_none());
@@ -685,6 +715,31 @@ fn test_value_copies() {
     assert_eq!(i8_value, i8_value_copy);
 }
 
+#[test]
+fn test_raw_value_slices() {
+    use inkwell::types::IntType;
+    use inkwell::values::{IntValue, PointerValue};
+    use inkwell::values::raw::{as_value_refs, from_value_refs};
+
+    use std::mem::size_of;
+
+    let context = Context::create();
+    let i32_type = context.i32_type();
+    let values: Vec<IntValue> = (0..4).map(|i| i32_type.const_int(i, false)).collect();
+    let refs = as_value_refs(&values);
+
+    assert_eq!(refs.len(), 4);
+    assert_eq!(size_of::<IntValue>(), size_of::<PointerValue>());
+    assert_eq!(size_of::<IntValue>(), size_of::<usize>());
+    assert_eq!(size_of::<Option<IntValue>>(), size_of::<IntValue>());
+    assert_eq!(size_of::<Option<IntType>>(), size_of::<IntType>());
+
+    let round_trip: &[IntValue] = unsafe { from_value_refs(refs) };
+
+    assert_eq!(round_trip, &values[..]);
+    assert_eq!(round_trip[3].get_zero_extended_constant(), Some(3));
+}
+
 #[test]
 fn test_global_byte_array() {
     let context = Context::create();
@@ -859,6 +914,89 @@ fn test_globals() {
     }
 }
 
+#[llvm_versions(8.0..=latest)]
+#[test]
+fn test_global_metadata() {
+    let context = Context::create();
+    let module = context.create_module("my_mod");
+    let i32_type = context.i32_type();
+    let function = module.add_function("instrumented", context.void_type().fn_type(&[], false), None);
+    let vtable = module.add_global(i32_type.array_type(2), None, "vtable");
+    let counters = module.add_global(i32_type.array_type(4), None, "counters");
+    let type_kind = context.get_kind_id("type");
+    let associated_kind = context.get_kind_id("associated");
+    let associated = context.metadata_node(&[function.as_global_value().as_pointer_value().into()]);
+
+    vtable.set_initializer(&i32_type.const_array(&[i32_type.const_zero(), i32_type.const_zero()]));
+    counters.set_initializer(&i32_type.array_type(4).const_zero());
+
+    assert!(vtable.get_metadata(type_kind).is_none());
+    assert!(vtable.get_all_metadata().is_empty());
+
+    assert!(vtable.set_type_metadata(4, "_ZTS6Shape").is_ok());
+    assert!(vtable.set_type_metadata(0, "_ZTS6Circle").is_err());
+    counters.set_metadata(associated, associated_kind);
+
+    let type_node = vtable.get_metadata(type_kind).unwrap();
+    let type_operands = type_node.get_node_values();
+
+    assert_eq!(type_operands.len(), 2);
+    assert_eq!(type_operands[0].into_int_value().get_zero_extended_constant(), Some(4));
+    assert_eq!(type_operands[1].into_metadata_value().get_string_value().unwrap().to_str(), Ok("_ZTS6Shape"));
+    assert_eq!(counters.get_metadata(associated_kind), Some(associated));
+    assert_eq!(counters.get_all_metadata(), vec![(associated_kind, associated)]);
+    assert!(module.verify().is_ok());
+
+    vtable.erase_metadata(type_kind);
+    counters.clear_metadata();
+
+    assert!(vtable.get_metadata(type_kind).is_none());
+    assert!(counters.get_all_metadata().is_empty());
+}
+
+#[test]
+fn test_replace_in_initializer() {
+    let context = Context::create();
+    let module = context.create_module("my_mod");
+    let i8_type = context.i8_type();
+    let i64_type = context.i64_type();
+    let i8_ptr_type = i8_type.ptr_type(AddressSpace::Generic);
+    let struct_type = context.struct_type(&[i64_type.into(), i8_ptr_type.into()], false);
+    let target = module.add_global(i64_type, None, "target");
+    let real_target = module.add_global(i64_type, None, "real_target");
+    let placeholder = i64_type.const_int(0xDEAD, false);
+    let target_ptr = target.as_pointer_value().const_cast(i8_ptr_type);
+    let entry = struct_type.const_named_struct(&[placeholder.into(), target_ptr.into()]);
+    let table = module.add_global(struct_type.array_type(2), None, "table");
+    let empty = module.add_global(i64_type, None, "empty");
+
+    table.set_initializer(&struct_type.const_array(&[entry, entry]));
+
+    let address = i64_type.const_int(0x1000, false);
+
+    assert_eq!(table.replace_in_initializer(placeholder.into(), address.into()), Ok(true));
+    assert_eq!(table.replace_in_initializer(placeholder.into(), address.into()), Ok(false));
+    assert_eq!(table.replace_in_initializer(placeholder.into(), i8_type.const_zero().into()), Err("Replacement constant must have the same type."));
+    assert_eq!(empty.replace_in_initializer(placeholder.into(), address.into()), Ok(false));
+
+    let new_target_ptr = real_target.as_pointer_value().const_cast(i8_ptr_type);
+
+    assert_eq!(table.replace_in_initializer(target.as_pointer_value().into(), real_target.as_pointer_value().into()), Ok(true));
+
+    let new_entry = struct_type.const_named_struct(&[address.into(), new_target_ptr.into()]);
+
+    assert_eq!(table.get_initializer().unwrap(), struct_type.const_array(&[new_entry, new_entry]).into());
+
+    // Constants are uniqued, so an unrelated element with the placeholder's value is replaced too
+    let values = module.add_global(i64_type.array_type(2), None, "values");
+
+    values.set_initializer(&i64_type.const_array(&[placeholder, i64_type.const_int(0xDEAD, false)]));
+
+    assert_eq!(values.replace_in_initializer(placeholder.into(), address.into()), Ok(true));
+    assert_eq!(values.get_initializer().unwrap(), i64_type.const_array(&[address, address]).into());
+    assert!(module.verify().is_ok());
+}
+
 #[test]
 fn test_phi_values() {
     let context = Context::create();
@@ -1211,3 +1349,46 @@ fn test_constant_expression() {
     assert!(expr.is_const());
     assert!(!expr.is_constant_int());
 }
+
+#[test]
+fn test_print_to_string_in_function_context() {
+    let context = Context::create();
+    let module = context.create_module("slots");
+    let builder = context.create_builder();
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
+    let function = module.add_function("add", fn_type, None);
+    let entry = context.append_basic_block(function, "");
+    let named = function.get_nth_param(1).unwrap().into_int_value();
+    let last = function.get_nth_param(2).unwrap().into_int_value();
+
+    named.set_name("named");
+
+    builder.position_at_end(entry);
+
+    let sum = builder.build_int_add(named, last, "");
+
+    assert_eq!(function.get_nth_param(0).unwrap().print_to_string_in_function_context().to_string(), "i32 %0");
+    assert_eq!(named.print_to_string_in_function_context().to_string(), "i32 %named");
+    assert_eq!(last.print_to_string_in_function_context().to_string(), "i32 %1");
+
+    // The unnamed entry block takes slot 2
+    assert_eq!(sum.print_to_string_in_function_context().to_string(), "i32 %3");
+
+    let product = builder.build_int_mul(sum, last, "");
+    let quoted = builder.build_int_sub(product, named, "a b");
+
+    let ret = builder.build_return(Some(&quoted));
+    let module_string = module.print_to_string().to_string();
+
+    assert_eq!(product.print_to_string_in_function_context().to_string(), "i32 %4");
+    assert_eq!(quoted.print_to_string_in_function_context().to_string(), "i32 %\"a b\"");
+    assert!(module_string.contains("%4 = mul i32 %3, %1"));
+    assert!(module_string.contains("%\"a b\" = sub i32 %4, %named"));
+
+    // Constants and instructions producing no value have no local name
+    let constant = i32_type.const_int(7, false);
+
+    assert_eq!(constant.print_to_string_in_function_context().to_string(), constant.print_to_string().to_string());
+    assert_eq!(ret.print_to_string_in_function_context().to_string(), ret.print_to_string().to_string());
+}